@@ -229,7 +229,8 @@ impl pallet_balances::Config for Runtime {
 	type FreezeIdentifier = ();
 	// We allow each account to have holds on it from:
 	//   - `NftFractionalization`: 1
-	type MaxHolds = ConstU32<1>;
+	//   - `AssetConversion`: 1
+	type MaxHolds = ConstU32<2>;
 	type MaxFreezes = ConstU32<0>;
 }
 
@@ -295,6 +296,7 @@ parameter_types! {
 	pub const AllowMultiAssetPools: bool = false;
 	// should be non-zero if AllowMultiAssetPools is true, otherwise can be zero
 	pub const LiquidityWithdrawalFee: Permill = Permill::from_percent(0);
+	pub const LimitOrderDeposit: Balance = deposit(1, 128);
 }
 
 ord_parameter_types! {
@@ -330,6 +332,7 @@ impl pallet_assets::Config<PoolAssetsInstance> for Runtime {
 
 impl pallet_asset_conversion::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type Balance = Balance;
 	type HigherPrecisionBalance = sp_core::U256;
 	type Currency = Balances;
@@ -350,6 +353,8 @@ impl pallet_asset_conversion::Config for Runtime {
 	type PalletId = AssetConversionPalletId;
 	type AllowMultiAssetPools = AllowMultiAssetPools;
 	type MaxSwapPathLength = ConstU32<4>;
+	type MaxLimitOrders = ConstU32<16>;
+	type LimitOrderDeposit = LimitOrderDeposit;
 	type MultiAssetId = Box<MultiLocation>;
 	type MultiAssetIdConverter =
 		MultiLocationConverter<KsmLocation, LocalAndForeignAssetsMultiLocationMatcher>;
@@ -897,7 +902,7 @@ construct_runtime!(
 		NftFractionalization: pallet_nft_fractionalization::{Pallet, Call, Storage, Event<T>, HoldReason} = 54,
 
 		PoolAssets: pallet_assets::<Instance3>::{Pallet, Call, Storage, Event<T>} = 55,
-		AssetConversion: pallet_asset_conversion::{Pallet, Call, Storage, Event<T>} = 56,
+		AssetConversion: pallet_asset_conversion::{Pallet, Call, Storage, Event<T>, HoldReason} = 56,
 
 		#[cfg(feature = "state-trie-version-1")]
 		StateTrieMigration: pallet_state_trie_migration = 70,
@@ -1063,6 +1068,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(2)]
 	impl pallet_asset_conversion::AssetConversionApi<
 		Block,
 		Balance,
@@ -1079,6 +1085,22 @@ impl_runtime_apis! {
 		fn get_reserves(asset1: Box<MultiLocation>, asset2: Box<MultiLocation>) -> Option<(Balance, Balance)> {
 			AssetConversion::get_reserves(&asset1, &asset2).ok()
 		}
+		fn get_pool_curve(asset1: Box<MultiLocation>, asset2: Box<MultiLocation>) -> Option<pallet_asset_conversion::PoolCurve> {
+			AssetConversion::get_pool_curve(asset1, asset2)
+		}
+	}
+
+	impl pallet_asset_conversion::AssetConversionLimitOrdersApi<
+		Block,
+		AccountId,
+		Balance,
+		u128,
+		Box<MultiLocation>,
+	> for Runtime
+	{
+		fn open_orders(who: AccountId, asset1: Box<MultiLocation>, asset2: Box<MultiLocation>) -> Vec<(pallet_asset_conversion::OrderId, pallet_asset_conversion::LimitOrder<AccountId, Box<MultiLocation>, u128, Balance>)> {
+			AssetConversion::open_orders(who, asset1, asset2)
+		}
 	}
 
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
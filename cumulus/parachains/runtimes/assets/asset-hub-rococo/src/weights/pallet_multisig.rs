@@ -61,6 +61,10 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			// Standard Error: 5
 			.saturating_add(Weight::from_parts(598, 0).saturating_mul(z.into()))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// The range of component `s` is `[2, 100]`.
@@ -78,7 +82,15 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(1_543, 0).saturating_mul(z.into()))
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// The range of component `s` is `[3, 100]`.
@@ -96,7 +108,15 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(1_504, 0).saturating_mul(z.into()))
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// Storage: `System::Account` (r:1 w:1)
@@ -116,7 +136,15 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(1_653, 0).saturating_mul(z.into()))
 			.saturating_add(T::DbWeight::get().reads(2))
 			.saturating_add(T::DbWeight::get().writes(2))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// The range of component `s` is `[2, 100]`.
@@ -131,7 +159,15 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(121_410, 0).saturating_mul(s.into()))
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// The range of component `s` is `[2, 100]`.
@@ -146,7 +182,15 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(114_584, 0).saturating_mul(s.into()))
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// The range of component `s` is `[2, 100]`.
@@ -161,5 +205,21 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(122_011, 0).saturating_mul(s.into()))
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
+	}
+	fn create_managed(s: u32, ) -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_multisig::weights::SubstrateWeight<T> as pallet_multisig::WeightInfo>::create_managed(s)
+	}
+	fn set_managed_signatories(s: u32, ) -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_multisig::weights::SubstrateWeight<T> as pallet_multisig::WeightInfo>::set_managed_signatories(s)
+	}
+	fn clear_stale_multisig() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_multisig::weights::SubstrateWeight<T> as pallet_multisig::WeightInfo>::clear_stale_multisig()
 	}
 }
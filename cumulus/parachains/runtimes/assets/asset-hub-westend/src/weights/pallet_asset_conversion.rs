@@ -153,4 +153,24 @@ impl<T: frame_system::Config> pallet_asset_conversion::WeightInfo for WeightInfo
 			.saturating_add(T::DbWeight::get().reads(8))
 			.saturating_add(T::DbWeight::get().writes(8))
 	}
+	fn place_limit_order() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_asset_conversion::weights::SubstrateWeight<T> as pallet_asset_conversion::WeightInfo>::place_limit_order()
+	}
+	fn cancel_limit_order() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_asset_conversion::weights::SubstrateWeight<T> as pallet_asset_conversion::WeightInfo>::cancel_limit_order()
+	}
+	fn match_limit_order() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_asset_conversion::weights::SubstrateWeight<T> as pallet_asset_conversion::WeightInfo>::match_limit_order()
+	}
+	fn stable_swap_hop() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_asset_conversion::weights::SubstrateWeight<T> as pallet_asset_conversion::WeightInfo>::stable_swap_hop()
+	}
+	fn weighted_swap_hop() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_asset_conversion::weights::SubstrateWeight<T> as pallet_asset_conversion::WeightInfo>::weighted_swap_hop()
+	}
 }
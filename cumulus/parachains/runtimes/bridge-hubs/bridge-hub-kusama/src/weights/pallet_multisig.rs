@@ -60,6 +60,10 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			// Standard Error: 8
 			.saturating_add(Weight::from_parts(518, 0).saturating_mul(z.into()))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// The range of component `s` is `[2, 100]`.
@@ -77,7 +81,15 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(1_448, 0).saturating_mul(z.into()))
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// The range of component `s` is `[3, 100]`.
@@ -95,7 +107,15 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(1_457, 0).saturating_mul(z.into()))
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// Storage: `System::Account` (r:1 w:1)
@@ -115,7 +135,15 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(1_545, 0).saturating_mul(z.into()))
 			.saturating_add(T::DbWeight::get().reads(2))
 			.saturating_add(T::DbWeight::get().writes(2))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// The range of component `s` is `[2, 100]`.
@@ -130,7 +158,15 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(145_390, 0).saturating_mul(s.into()))
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// The range of component `s` is `[2, 100]`.
@@ -145,7 +181,15 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(114_298, 0).saturating_mul(s.into()))
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
 	}
+	/// Storage: `Multisig::ManagedBy` (r:1 w:0)
+	/// Proof: `Multisig::ManagedBy` (`max_values`: None, `max_size`: Some(72), added: 2547, mode: `MaxEncodedLen`)
+	/// Storage: `Multisig::ManagedMultisigs` (r:1 w:0)
+	/// Proof: `Multisig::ManagedMultisigs` (`max_values`: None, `max_size`: Some(3269), added: 5744, mode: `MaxEncodedLen`)
 	/// Storage: `Multisig::Multisigs` (r:1 w:1)
 	/// Proof: `Multisig::Multisigs` (`max_values`: None, `max_size`: Some(3346), added: 5821, mode: `MaxEncodedLen`)
 	/// The range of component `s` is `[2, 100]`.
@@ -160,5 +204,21 @@ impl<T: frame_system::Config> pallet_multisig::WeightInfo for WeightInfo<T> {
 			.saturating_add(Weight::from_parts(126_583, 0).saturating_mul(s.into()))
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2))
+	}
+	fn create_managed(s: u32, ) -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_multisig::weights::SubstrateWeight<T> as pallet_multisig::WeightInfo>::create_managed(s)
+	}
+	fn set_managed_signatories(s: u32, ) -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_multisig::weights::SubstrateWeight<T> as pallet_multisig::WeightInfo>::set_managed_signatories(s)
+	}
+	fn clear_stale_multisig() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_multisig::weights::SubstrateWeight<T> as pallet_multisig::WeightInfo>::clear_stale_multisig()
 	}
 }
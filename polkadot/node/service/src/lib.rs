@@ -599,6 +599,15 @@ where
 				beefy: polkadot_rpc::BeefyDeps {
 					beefy_finality_proof_stream: beefy_rpc_links.from_voter_justif_stream.clone(),
 					beefy_best_block_stream: beefy_rpc_links.from_voter_best_beefy_stream.clone(),
+					beefy_all_finality_proof_stream: beefy_rpc_links
+						.from_voter_all_justifs_stream
+						.clone(),
+					beefy_justif_request_sender: beefy_rpc_links
+						.to_voter_justif_request_sender
+						.clone(),
+					beefy_justif_response_stream: beefy_rpc_links
+						.from_voter_justif_response_stream
+						.clone(),
 					subscription_executor,
 				},
 				backend: backend.clone(),
@@ -988,6 +997,21 @@ pub fn new_full<OverseerGenerator: OverseerGen>(
 
 	let (block_import, link_half, babe_link, beefy_links) = import_setup;
 
+	// Report equivocations seen by any node, including those of blocks not checked on import.
+	let equivocation_watcher = sc_consensus_slots::EquivocationWatcher::new(
+		client.clone(),
+		babe::BabeEquivocationReporter::new(
+			client.clone(),
+			&babe_link,
+			OffchainTransactionPoolFactory::new(transaction_pool.clone()),
+		),
+	);
+	task_manager.spawn_handle().spawn(
+		"babe-equivocation-watcher",
+		Some("block-authoring"),
+		equivocation_watcher.run(),
+	);
+
 	let overseer_client = client.clone();
 	let spawner = task_manager.spawn_handle();
 
@@ -24,7 +24,8 @@ use jsonrpsee::RpcModule;
 use polkadot_primitives::{AccountId, Balance, Block, BlockNumber, Hash, Nonce};
 use sc_client_api::AuxStore;
 use sc_consensus_beefy::communication::notification::{
-	BeefyBestBlockStream, BeefyVersionedFinalityProofStream,
+	BeefyBestBlockStream, BeefyJustificationRequestSender, BeefyJustificationResponseStream,
+	BeefyVersionedFinalityProofStream,
 };
 use sc_consensus_grandpa::FinalityProofProvider;
 pub use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
@@ -67,6 +68,12 @@ pub struct BeefyDeps {
 	pub beefy_finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
 	/// Receives notifications about best block events from BEEFY.
 	pub beefy_best_block_stream: BeefyBestBlockStream<Block>,
+	/// Receives notifications about all finality proofs known to BEEFY.
+	pub beefy_all_finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
+	/// Sends justification requests to BEEFY.
+	pub beefy_justif_request_sender: BeefyJustificationRequestSender<Block>,
+	/// Receives answers to justification requests from BEEFY.
+	pub beefy_justif_response_stream: BeefyJustificationResponseStream<Block>,
 	/// Executor to drive the subscription manager in the BEEFY RPC handler.
 	pub subscription_executor: sc_rpc::SubscriptionTaskExecutor,
 }
@@ -168,6 +175,9 @@ where
 		Beefy::<Block>::new(
 			beefy.beefy_finality_proof_stream,
 			beefy.beefy_best_block_stream,
+			beefy.beefy_all_finality_proof_stream,
+			beefy.beefy_justif_request_sender,
+			beefy.beefy_justif_response_stream,
 			beefy.subscription_executor,
 		)?
 		.into_rpc(),
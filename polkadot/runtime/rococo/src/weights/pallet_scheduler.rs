@@ -201,4 +201,24 @@ impl<T: frame_system::Config> pallet_scheduler::WeightInfo for WeightInfo<T> {
 			.saturating_add(T::DbWeight::get().reads(2))
 			.saturating_add(T::DbWeight::get().writes(2))
 	}
+	fn schedule_retry(s: u32, ) -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_scheduler::weights::SubstrateWeight<T> as pallet_scheduler::WeightInfo>::schedule_retry(s)
+	}
+	fn set_retry() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_scheduler::weights::SubstrateWeight<T> as pallet_scheduler::WeightInfo>::set_retry()
+	}
+	fn set_retry_named() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_scheduler::weights::SubstrateWeight<T> as pallet_scheduler::WeightInfo>::set_retry_named()
+	}
+	fn cancel_retry() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_scheduler::weights::SubstrateWeight<T> as pallet_scheduler::WeightInfo>::cancel_retry()
+	}
+	fn cancel_retry_named() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_scheduler::weights::SubstrateWeight<T> as pallet_scheduler::WeightInfo>::cancel_retry_named()
+	}
 }
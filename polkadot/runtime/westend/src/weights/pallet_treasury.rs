@@ -220,4 +220,12 @@ impl<T: frame_system::Config> pallet_treasury::WeightInfo for WeightInfo<T> {
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
 	}
+	fn spend_stream() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_treasury::weights::SubstrateWeight<T> as pallet_treasury::WeightInfo>::spend_stream()
+	}
+	fn cancel_stream() -> Weight {
+		// Placeholder until benchmarked for this runtime.
+		<pallet_treasury::weights::SubstrateWeight<T> as pallet_treasury::WeightInfo>::cancel_stream()
+	}
 }
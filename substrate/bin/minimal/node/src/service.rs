@@ -174,7 +174,7 @@ pub fn new_full(config: Configuration, consensus: Consensus) -> Result<TaskManag
 		task_manager: &mut task_manager,
 		transaction_pool: transaction_pool.clone(),
 		rpc_builder: rpc_extensions_builder,
-		backend,
+		backend: backend.clone(),
 		system_rpc_tx,
 		tx_handler_controller,
 		sync_service,
@@ -231,6 +231,7 @@ pub fn new_full(config: Configuration, consensus: Consensus) -> Result<TaskManag
 				block_import: client.clone(),
 				env: proposer,
 				client,
+				backend: Some(backend),
 				pool: transaction_pool,
 				select_chain,
 				commands_stream: Box::pin(commands_stream),
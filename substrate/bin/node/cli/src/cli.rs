@@ -91,9 +91,15 @@ pub enum Subcommand {
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
+	/// Export the state of a given block into a binary state snapshot.
+	ExportStateSnapshot(sc_cli::ExportStateSnapshotCmd),
+
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
+	/// Bootstrap an empty database from a binary state snapshot.
+	ImportStateSnapshot(sc_cli::ImportStateSnapshotCmd),
+
 	/// Remove the whole chain.
 	PurgeChain(sc_cli::PurgeChainCmd),
 
@@ -102,4 +108,11 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Check the integrity of the database.
+	CheckDb(sc_cli::CheckDbCmd),
+
+	/// Database maintenance utilities.
+	#[command(subcommand)]
+	Db(sc_cli::DbSubcommand),
 }
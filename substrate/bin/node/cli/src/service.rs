@@ -476,6 +476,21 @@ pub fn new_full_base(
 
 	(with_startup_data)(&block_import, &babe_link);
 
+	// Report equivocations seen by any node, including those of blocks not checked on import.
+	let equivocation_watcher = sc_consensus_slots::EquivocationWatcher::new(
+		client.clone(),
+		sc_consensus_babe::BabeEquivocationReporter::new(
+			client.clone(),
+			&babe_link,
+			OffchainTransactionPoolFactory::new(transaction_pool.clone()),
+		),
+	);
+	task_manager.spawn_handle().spawn(
+		"babe-equivocation-watcher",
+		Some("block-authoring"),
+		equivocation_watcher.run(),
+	);
+
 	if let sc_service::config::Role::Authority { .. } = &role {
 		let proposer = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
@@ -233,11 +233,20 @@ pub enum SyncMode {
 	FastUnsafe,
 	/// Prove finality and download the latest state.
 	Warp,
+	/// Download and verify the state of a trusted checkpoint block, then sync forward from it.
+	/// The checkpoint is given with `--checkpoint` or the `checkpoint` chain spec extension.
+	Checkpoint,
 }
 
-impl Into<sc_network::config::SyncMode> for SyncMode {
-	fn into(self) -> sc_network::config::SyncMode {
-		match self {
+impl SyncMode {
+	/// Convert to the network sync mode, using `checkpoint` for [`SyncMode::Checkpoint`].
+	///
+	/// Returns `None` if checkpoint sync is requested without a checkpoint.
+	pub fn into_network(
+		self,
+		checkpoint: Option<sc_network::config::Checkpoint>,
+	) -> Option<sc_network::config::SyncMode> {
+		Some(match self {
 			SyncMode::Full => sc_network::config::SyncMode::Full,
 			SyncMode::Fast => sc_network::config::SyncMode::LightState {
 				skip_proofs: false,
@@ -248,6 +257,17 @@ impl Into<sc_network::config::SyncMode> for SyncMode {
 				storage_chain_mode: false,
 			},
 			SyncMode::Warp => sc_network::config::SyncMode::Warp,
-		}
+			SyncMode::Checkpoint => sc_network::config::SyncMode::Checkpoint(checkpoint?),
+		})
 	}
 }
+
+/// Type of the transaction pool.
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+#[value(rename_all = "kebab-case")]
+pub enum TransactionPoolType {
+	/// Keep a single set of transactions, maintained against the best block.
+	SingleState,
+	/// Additionally keep a separate view of the transactions for every unfinalized fork.
+	ForkAware,
+}
@@ -75,6 +75,7 @@ impl ChainInfoCmd {
 		let db_config = sc_client_db::DatabaseSettings {
 			trie_cache_maximum_size: config.trie_cache_maximum_size,
 			state_pruning: config.state_pruning.clone(),
+			state_checkpoint_interval: config.state_checkpoint_interval,
 			source: config.database.clone(),
 			blocks_pruning: config.blocks_pruning,
 		};
@@ -0,0 +1,153 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{error, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use clap::Parser;
+use sc_client_api::Backend;
+use sc_client_db::check::{check_database, CheckSettings, IntegrityReport};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, One, Saturating};
+use std::fmt::Debug;
+
+/// The `check-db` command used to check the integrity of the node database.
+///
+/// Walks the canonical chain, validates the state-db journals and checks that the states retained
+/// by the pruning window are present.
+#[derive(Debug, Clone, Parser)]
+pub struct CheckDbCmd {
+	/// Walk the complete state of every retained block.
+	///
+	/// By default only the complete state of the last canonicalized block is walked, while just
+	/// the roots of the other retained states are checked.
+	#[arg(long)]
+	pub full_state: bool,
+
+	/// Report canonical blocks without a body.
+	///
+	/// Only use this with archive nodes that were not warp synced, other nodes may lack bodies
+	/// of pruned or never downloaded blocks.
+	#[arg(long)]
+	pub require_bodies: bool,
+
+	/// Revert the chain to the block before the first inconsistent block.
+	///
+	/// Blocks can only be reverted as long as the state of their parents is retained. Consensus
+	/// data, such as the BABE epochs or GRANDPA authority sets, is not reverted and should be
+	/// reverted with the `revert` command of the node.
+	#[arg(long)]
+	pub repair: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl CheckDbCmd {
+	/// Run the check-db command
+	pub fn run<B: BlockT>(&self, config: &sc_service::Configuration) -> error::Result<()> {
+		let settings =
+			CheckSettings { full_state: self.full_state, require_bodies: self.require_bodies };
+		let db_config = sc_client_db::DatabaseSettings {
+			trie_cache_maximum_size: config.trie_cache_maximum_size,
+			state_pruning: config.state_pruning.clone(),
+			state_checkpoint_interval: config.state_checkpoint_interval,
+			source: config.database.clone(),
+			blocks_pruning: config.blocks_pruning,
+		};
+		// The backend fails to open when the state-db journals are damaged, in which case the
+		// database is still checked, but can not be repaired.
+		let backend = match sc_service::new_db_backend::<B>(db_config) {
+			Ok(backend) => backend,
+			Err(e) => {
+				eprintln!("Failed to open the database backend: {}", e);
+				let report = check_database::<B>(&config.database, &settings)?;
+				print_report(&report);
+				return Err(e.into())
+			},
+		};
+		let report = backend.check_integrity(&settings)?;
+		print_report(&report);
+		if report.is_consistent() {
+			return Ok(())
+		}
+		let Some(inconsistency) = report.first_inconsistent else {
+			return Err(error::Error::Input("The state journals are inconsistent".into()))
+		};
+		if !self.repair {
+			return Err(error::Error::Input(
+				"The database is inconsistent, run with `--repair` to revert the chain".into(),
+			))
+		}
+
+		let best_number = backend.blockchain().info().best_number;
+		let blocks = best_number.saturating_sub(inconsistency.number) + One::one();
+		let (reverted, _) = backend.revert(blocks, true)?;
+		println!("Reverted {} of {} blocks", reverted, blocks);
+		if reverted < blocks {
+			return Err(error::Error::Input(
+				"Not all inconsistent blocks could be reverted, the database needs to be resynced"
+					.into(),
+			))
+		}
+		Ok(())
+	}
+}
+
+fn print_report<B: BlockT>(report: &IntegrityReport<B>) {
+	println!(
+		"Checked {} blocks and {} retained states",
+		report.checked_blocks, report.checked_states
+	);
+	if let Some(error) = &report.journal_error {
+		println!("State journals are invalid: {}", error);
+	}
+	if let Some(inconsistency) = &report.first_inconsistent {
+		match inconsistency.hash {
+			Some(hash) => println!(
+				"First inconsistent block #{} ({}): {}",
+				inconsistency.number, hash, inconsistency.reason
+			),
+			None => println!(
+				"First inconsistent block #{}: {}",
+				inconsistency.number, inconsistency.reason
+			),
+		}
+	}
+}
+
+impl CliConfiguration for CheckDbCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
@@ -0,0 +1,86 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{error, params::DatabaseParams, CliConfiguration, SharedParams};
+use clap::Parser;
+use sc_client_db::migrate::{migrate_to_parity_db, MigrationSettings, DEFAULT_BATCH_SIZE};
+use sp_runtime::traits::Block as BlockT;
+use std::{fmt::Debug, path::PathBuf};
+
+/// The `db migrate` command used to migrate the node database to ParityDb.
+///
+/// All columns are copied from the RocksDB database into a new ParityDb database. An interrupted
+/// migration is resumed when the command is run again with the same target.
+#[derive(Debug, Clone, Parser)]
+pub struct DbMigrateCmd {
+	/// Path of the ParityDb database to create.
+	///
+	/// Defaults to the path used for ParityDb when the database backend is selected
+	/// automatically, which is the case unless `--database` is given. The node opens the
+	/// migrated database once the RocksDB database is removed.
+	#[arg(long, value_name = "PATH")]
+	pub target: Option<PathBuf>,
+
+	/// Number of entries written to the target database per commit.
+	#[arg(long, value_name = "COUNT", default_value_t = DEFAULT_BATCH_SIZE)]
+	pub batch_size: usize,
+
+	/// Do not read back the migrated entries from the target database.
+	#[arg(long)]
+	pub no_verify: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl DbMigrateCmd {
+	/// Run the migrate command
+	pub fn run<B: BlockT>(&self, config: &sc_service::Configuration) -> error::Result<()> {
+		if self.batch_size == 0 {
+			return Err(error::Error::Input("Batch size must be greater than zero".into()))
+		}
+		let settings = MigrationSettings { batch_size: self.batch_size, verify: !self.no_verify };
+		let reports =
+			migrate_to_parity_db::<B>(&config.database, self.target.as_deref(), &settings)
+				.map_err(|e| error::Error::Application(Box::new(e)))?;
+		for report in reports.iter().filter(|report| report.keys > 0) {
+			println!(
+				"column {}: {} keys, digest 0x{}",
+				report.column,
+				report.keys,
+				array_bytes::bytes2hex("", report.digest)
+			);
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for DbMigrateCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
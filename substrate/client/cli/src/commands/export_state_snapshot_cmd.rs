@@ -0,0 +1,107 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{BlockNumberOrHash, DatabaseParams, PruningParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use sc_client_api::{BlockBackend, HeaderBackend, StorageProvider, UsageProvider};
+use sc_service::chain_ops::export_state_snapshot;
+use sp_api::CallApiAt;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::{
+	fmt::Debug,
+	fs,
+	io::{self, BufWriter, Write},
+	path::PathBuf,
+	str::FromStr,
+	sync::Arc,
+};
+
+/// The `export-state-snapshot` command used to export the state of a block into a binary
+/// snapshot, which can be imported with `import-state-snapshot`.
+///
+/// Only states whose entries all use the state version of the runtime can be imported again.
+#[derive(Debug, Clone, Parser)]
+pub struct ExportStateSnapshotCmd {
+	/// Output file name or stdout if unspecified.
+	#[arg()]
+	pub output: Option<PathBuf>,
+
+	/// Block hash or number to export the state of.
+	/// Default is the last finalized block.
+	#[arg(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl ExportStateSnapshotCmd {
+	/// Run the export-state-snapshot command
+	pub async fn run<B, BA, C>(&self, client: Arc<C>) -> error::Result<()>
+	where
+		B: BlockT,
+		BA: sc_client_api::backend::Backend<B>,
+		C: UsageProvider<B>
+			+ HeaderBackend<B>
+			+ BlockBackend<B>
+			+ StorageProvider<B, BA>
+			+ CallApiAt<B>,
+		<B::Hash as FromStr>::Err: Debug,
+		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
+	{
+		let block_id = self.at.as_ref().map(|b| b.parse()).transpose()?;
+		let hash = match block_id {
+			Some(id) => client.expect_block_hash_from_id(&id)?,
+			None => client.usage_info().chain.finalized_hash,
+		};
+
+		let file: Box<dyn Write> = match &self.output {
+			Some(filename) => Box::new(fs::File::create(filename)?),
+			None => Box::new(io::stdout()),
+		};
+
+		export_state_snapshot(client, hash, BufWriter::new(file)).map_err(Into::into)
+	}
+}
+
+impl CliConfiguration for ExportStateSnapshotCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
@@ -0,0 +1,82 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{ImportParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use sc_client_api::HeaderBackend;
+use sc_service::chain_ops::import_state_snapshot;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	fs,
+	io::{self, BufReader, Read},
+	path::PathBuf,
+	sync::Arc,
+};
+
+/// The `import-state-snapshot` command used to bootstrap an empty database from a state
+/// snapshot created with `export-state-snapshot`.
+///
+/// The state is verified against the state root of the snapshot block when it is imported. States
+/// that mix entries of both state versions, like partially migrated ones, are rejected.
+#[derive(Debug, Parser)]
+pub struct ImportStateSnapshotCmd {
+	/// Input file or stdin if unspecified.
+	#[arg()]
+	pub input: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl ImportStateSnapshotCmd {
+	/// Run the import-state-snapshot command
+	pub async fn run<B, C, IQ>(&self, client: Arc<C>, import_queue: IQ) -> error::Result<()>
+	where
+		B: BlockT,
+		C: HeaderBackend<B>,
+		IQ: sc_service::ImportQueue<B>,
+	{
+		let file: Box<dyn Read> = match &self.input {
+			Some(filename) => Box::new(fs::File::open(filename)?),
+			None => Box::new(io::stdin()),
+		};
+
+		import_state_snapshot(client, import_queue, BufReader::new(file))
+			.await
+			.map_err(Into::into)
+	}
+}
+
+impl CliConfiguration for ImportStateSnapshotCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
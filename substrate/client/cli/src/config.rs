@@ -169,6 +169,8 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		default_listen_port: u16,
 	) -> Result<NetworkConfiguration> {
 		Ok(if let Some(network_params) = self.network_params() {
+			// Fail early instead of falling back to full sync without a checkpoint.
+			network_params.sync_mode(chain_spec)?;
 			network_params.network_config(
 				chain_spec,
 				is_dev,
@@ -251,6 +253,15 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			.unwrap_or_else(|| Ok(Default::default()))
 	}
 
+	/// Get the state checkpoint interval.
+	///
+	/// By default this is retrieved from `PruningParams` if it is available. Otherwise its `None`.
+	fn state_checkpoint_interval(&self) -> Result<Option<u32>> {
+		self.pruning_params()
+			.map(|x| x.state_checkpoint_interval())
+			.unwrap_or_else(|| Ok(None))
+	}
+
 	/// Get the block pruning mode.
 	///
 	/// By default this is retrieved from `block_pruning` if it is available. Otherwise its
@@ -489,6 +500,7 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			data_path: config_dir,
 			trie_cache_maximum_size: self.trie_cache_maximum_size()?,
 			state_pruning: self.state_pruning()?,
+			state_checkpoint_interval: self.state_checkpoint_interval()?,
 			blocks_pruning: self.blocks_pruning()?,
 			wasm_method: self.wasm_method()?,
 			wasm_runtime_overrides: self.wasm_runtime_overrides(),
@@ -16,11 +16,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{arg_enums::SyncMode, params::node_key_params::NodeKeyParams};
+use crate::{arg_enums::SyncMode, error, params::node_key_params::NodeKeyParams};
 use clap::Args;
+use sc_chain_spec::get_extension;
 use sc_network::{
 	config::{
-		NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, SetConfig, TransportConfig,
+		Checkpoint, NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, SetConfig,
+		TransportConfig,
 	},
 	multiaddr::Protocol,
 };
@@ -28,7 +30,8 @@ use sc_service::{
 	config::{Multiaddr, MultiaddrWithPeerId},
 	ChainSpec, ChainType,
 };
-use std::{borrow::Cow, num::NonZeroUsize, path::PathBuf};
+use sp_core::Bytes;
+use std::{borrow::Cow, num::NonZeroUsize, path::PathBuf, str::FromStr};
 
 /// Parameters used to create the network configuration.
 #[derive(Debug, Clone, Args)]
@@ -160,6 +163,12 @@ pub struct NetworkParams {
 	)]
 	pub sync: SyncMode,
 
+	/// Trusted checkpoint to sync from with `--sync checkpoint`, as `<BLOCK_HASH>:<STATE_ROOT>`.
+	///
+	/// Defaults to the `checkpoint` extension of the chain spec.
+	#[arg(long, value_name = "HASH:STATE_ROOT", value_parser = parse_checkpoint)]
+	pub checkpoint: Option<Checkpoint>,
+
 	/// Maximum number of blocks per request.
 	///
 	/// Try reducing this number from the default value if you have a slow network connection
@@ -168,8 +177,36 @@ pub struct NetworkParams {
 	pub max_blocks_per_request: u32,
 }
 
+fn parse_checkpoint(s: &str) -> Result<Checkpoint, String> {
+	let (hash, state_root) = s
+		.split_once(':')
+		.ok_or_else(|| "expected `<BLOCK_HASH>:<STATE_ROOT>`".to_string())?;
+	let parse = |hash: &str| Bytes::from_str(hash).map_err(|e| format!("invalid hash {hash}: {e}"));
+	Ok(Checkpoint { hash: parse(hash)?, state_root: parse(state_root)? })
+}
+
 impl NetworkParams {
+	/// Get the sync mode, taking the checkpoint of `--sync checkpoint` from the command line or
+	/// from the chain spec.
+	pub fn sync_mode(
+		&self,
+		chain_spec: &Box<dyn ChainSpec>,
+	) -> error::Result<sc_network::config::SyncMode> {
+		let checkpoint = self.checkpoint.clone().or_else(|| {
+			get_extension::<Option<Checkpoint>>(chain_spec.extensions()).cloned().flatten()
+		});
+		self.sync.into_network(checkpoint).ok_or_else(|| {
+			error::Error::Input(
+				"Checkpoint sync requires `--checkpoint` or a `checkpoint` chain spec extension"
+					.into(),
+			)
+		})
+	}
+
 	/// Fill the given `NetworkConfiguration` by looking at the cli parameters.
+	///
+	/// Falls back to full sync if checkpoint sync is requested without a checkpoint, see
+	/// [`Self::sync_mode`].
 	pub fn network_config(
 		&self,
 		chain_spec: &Box<dyn ChainSpec>,
@@ -260,7 +297,7 @@ impl NetworkParams {
 			kademlia_replication_factor: self.kademlia_replication_factor,
 			yamux_window_size: None,
 			ipfs_server: self.ipfs_server,
-			sync_mode: self.sync.into(),
+			sync_mode: self.sync_mode(chain_spec).unwrap_or_default(),
 		}
 	}
 }
@@ -315,4 +352,25 @@ mod tests {
 
 		assert_eq!(SyncMode::Warp, params.network_params.sync);
 	}
+
+	#[test]
+	fn checkpoint_is_parsed() {
+		let hash = format!("0x{}", "01".repeat(32));
+		let state_root = format!("0x{}", "02".repeat(32));
+		let params = Cli::try_parse_from([
+			"",
+			"--sync",
+			"checkpoint",
+			"--checkpoint",
+			&format!("{hash}:{state_root}"),
+		])
+		.expect("Parses network params");
+
+		assert_eq!(SyncMode::Checkpoint, params.network_params.sync);
+		assert_eq!(
+			Some(Checkpoint { hash: vec![1; 32].into(), state_root: vec![2; 32].into() }),
+			params.network_params.checkpoint,
+		);
+		assert!(Cli::try_parse_from(["", "--checkpoint", &hash]).is_err());
+	}
 }
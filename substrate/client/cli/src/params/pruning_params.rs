@@ -19,6 +19,7 @@
 use crate::error;
 use clap::Args;
 use sc_service::{BlocksPruning, PruningMode};
+use std::time::Duration;
 
 /// Parameters to define the pruning mode
 #[derive(Debug, Clone, Args)]
@@ -34,10 +35,26 @@ pub struct PruningParams {
 	///  - archive: Keep the state of all blocks.
 	///  - 'archive-canonical' Keep only the state of finalized blocks.
 	///  - number Keep the state of the last number of finalized blocks.
+	///  - age Keep the state of blocks finalized within the given time, e.g. '7d' or '168h'.
+	///    Supported units are 's', 'm', 'h', 'd' and 'w'. At most one block per second of the
+	///    given time is kept, so blocks finalized during a major sync are pruned as well.
+	///  - size Keep the state of as many recent finalized blocks as fit into the given database
+	///    size, e.g. '500GiB'. Supported units are 'B', 'KiB', 'MiB', 'GiB', 'TiB', 'KB', 'MB',
+	///    'GB' and 'TB'. The size of the database is measured on disk about once a minute. The
+	///    state of the last 256 finalized blocks is always kept, even if it exceeds the size.
 	/// [default: 256]
 	#[arg(alias = "pruning", long, value_name = "PRUNING_MODE")]
 	pub state_pruning: Option<DatabasePruningMode>,
 
+	/// Retain the state of every block with a number divisible by this interval.
+	///
+	/// The state of these checkpoints is kept after they leave the `--state-pruning` window, so
+	/// the state of other historical blocks can be recomputed by re-executing blocks from the
+	/// nearest checkpoint. Applied to the given `--state-pruning` or, if it is omitted, to the
+	/// mode stored in the database. Only valid with a number, age or size based pruning mode.
+	#[arg(long, value_name = "BLOCKS")]
+	pub state_checkpoint_interval: Option<u32>,
+
 	/// Specify the blocks pruning mode.
 	///
 	/// This mode specifies when the block's body (including justifications)
@@ -47,6 +64,13 @@ pub struct PruningParams {
 	///  - 'archive-canonical' Keep only finalized blocks.
 	///  - number
 	///  Keep the last `number` of finalized blocks.
+	///  - age
+	///  Keep the blocks finalized within the given time, e.g. '7d', but at most one block per
+	///  second of it.
+	///  - size
+	///  Keep as many recent finalized blocks as fit into the given database size, e.g. '500GiB',
+	///  but never fewer than the last 256 finalized blocks.
+	/// Age and size accept the same units as `--state-pruning`.
 	#[arg(
 		alias = "keep-blocks",
 		long,
@@ -62,6 +86,21 @@ impl PruningParams {
 		Ok(self.state_pruning.map(|v| v.into()))
 	}
 
+	/// Get the state checkpoint interval from the parameters
+	pub fn state_checkpoint_interval(&self) -> error::Result<Option<u32>> {
+		match (self.state_checkpoint_interval, self.state_pruning) {
+			(Some(0), _) =>
+				Err(error::Error::Input("State checkpoint interval must not be zero".into())),
+			(
+				Some(_),
+				Some(DatabasePruningMode::Archive | DatabasePruningMode::ArchiveCanonical),
+			) => Err(error::Error::Input(
+				"State checkpoints require a constrained state pruning mode".into(),
+			)),
+			(interval, _) => Ok(interval),
+		}
+	}
+
 	/// Get the block pruning value from the parameters
 	pub fn blocks_pruning(&self) -> error::Result<BlocksPruning> {
 		Ok(self.blocks_pruning.into())
@@ -81,6 +120,12 @@ pub enum DatabasePruningMode {
 	ArchiveCanonical,
 	/// Keep the data of the last number of finalized blocks.
 	Custom(u32),
+	/// Keep the data of blocks finalized within the given time, but at most one block per second
+	/// of it.
+	Age(Duration),
+	/// Keep the data of as many recent finalized blocks as fit into the given database size in
+	/// bytes, but at least the last 256 finalized blocks.
+	Size(u64),
 }
 
 impl std::str::FromStr for DatabasePruningMode {
@@ -90,10 +135,42 @@ impl std::str::FromStr for DatabasePruningMode {
 		match input {
 			"archive" => Ok(Self::Archive),
 			"archive-canonical" => Ok(Self::ArchiveCanonical),
-			bc => bc
-				.parse()
-				.map_err(|_| "Invalid pruning mode specified".to_string())
-				.map(Self::Custom),
+			bc => {
+				let split = bc.find(|c: char| !c.is_ascii_digit()).unwrap_or(bc.len());
+				let (value, unit) = bc.split_at(split);
+				let value: u64 =
+					value.parse().map_err(|_| "Invalid pruning mode specified".to_string())?;
+				let seconds = |multiplier: u64| {
+					Self::Age(Duration::from_secs(value.saturating_mul(multiplier)))
+				};
+				let bytes = |multiplier: u64| {
+					value
+						.checked_mul(multiplier)
+						.map(Self::Size)
+						.ok_or_else(|| "Pruning size is too large".to_string())
+				};
+				match unit {
+					"" => value
+						.try_into()
+						.map(Self::Custom)
+						.map_err(|_| "Invalid pruning mode specified".to_string()),
+					"s" => Ok(seconds(1)),
+					"m" => Ok(seconds(60)),
+					"h" => Ok(seconds(60 * 60)),
+					"d" => Ok(seconds(24 * 60 * 60)),
+					"w" => Ok(seconds(7 * 24 * 60 * 60)),
+					"B" => bytes(1),
+					"KB" => bytes(1_000),
+					"MB" => bytes(1_000_000),
+					"GB" => bytes(1_000_000_000),
+					"TB" => bytes(1_000_000_000_000),
+					"KiB" => bytes(1 << 10),
+					"MiB" => bytes(1 << 20),
+					"GiB" => bytes(1 << 30),
+					"TiB" => bytes(1 << 40),
+					unit => Err(format!("Invalid pruning unit specified: {unit}")),
+				}
+			},
 		}
 	}
 }
@@ -104,6 +181,8 @@ impl Into<PruningMode> for DatabasePruningMode {
 			DatabasePruningMode::Archive => PruningMode::ArchiveAll,
 			DatabasePruningMode::ArchiveCanonical => PruningMode::ArchiveCanonical,
 			DatabasePruningMode::Custom(n) => PruningMode::blocks_pruning(n),
+			DatabasePruningMode::Age(age) => PruningMode::age_pruning(age),
+			DatabasePruningMode::Size(size) => PruningMode::size_pruning(size),
 		}
 	}
 }
@@ -114,6 +193,54 @@ impl Into<BlocksPruning> for DatabasePruningMode {
 			DatabasePruningMode::Archive => BlocksPruning::KeepAll,
 			DatabasePruningMode::ArchiveCanonical => BlocksPruning::KeepFinalized,
 			DatabasePruningMode::Custom(n) => BlocksPruning::Some(n),
+			DatabasePruningMode::Age(age) => BlocksPruning::KeepFor(age),
+			DatabasePruningMode::Size(size) => BlocksPruning::KeepSize(size),
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn pruning_mode_parses_blocks_age_and_size() {
+		let parse = |input: &str| input.parse::<DatabasePruningMode>();
+
+		assert_eq!(parse("archive"), Ok(DatabasePruningMode::Archive));
+		assert_eq!(parse("archive-canonical"), Ok(DatabasePruningMode::ArchiveCanonical));
+		assert_eq!(parse("256"), Ok(DatabasePruningMode::Custom(256)));
+		assert_eq!(parse("90s"), Ok(DatabasePruningMode::Age(Duration::from_secs(90))));
+		assert_eq!(parse("7d"), Ok(DatabasePruningMode::Age(Duration::from_secs(7 * 24 * 3600))));
+		assert_eq!(parse("168h"), parse("1w"));
+		assert_eq!(parse("500GiB"), Ok(DatabasePruningMode::Size(500 << 30)));
+		assert_eq!(parse("2TB"), Ok(DatabasePruningMode::Size(2_000_000_000_000)));
+
+		assert!(parse("").is_err());
+		assert!(parse("7 days").is_err());
+		assert!(parse("d").is_err());
+		assert!(parse("5000000000").is_err());
+		assert!(parse("99999999999999999999TiB").is_err());
+	}
+
+	#[test]
+	fn state_checkpoints_require_constrained_pruning() {
+		let params = |state_pruning, state_checkpoint_interval| PruningParams {
+			state_pruning,
+			state_checkpoint_interval,
+			blocks_pruning: DatabasePruningMode::ArchiveCanonical,
+		};
+
+		let custom = params(Some(DatabasePruningMode::Custom(64)), Some(1000));
+		assert_eq!(custom.state_checkpoint_interval().unwrap(), Some(1000));
+		assert_eq!(custom.state_pruning().unwrap(), Some(PruningMode::blocks_pruning(64)));
+		// without a pruning mode the interval is applied to the mode stored in the database
+		let stored = params(None, Some(1000));
+		assert_eq!(stored.state_checkpoint_interval().unwrap(), Some(1000));
+		assert_eq!(stored.state_pruning().unwrap(), None);
+		assert!(params(Some(DatabasePruningMode::Archive), Some(1000))
+			.state_checkpoint_interval()
+			.is_err());
+		assert!(params(None, Some(0)).state_checkpoint_interval().is_err());
+	}
+}
@@ -16,8 +16,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::arg_enums::TransactionPoolType;
 use clap::Args;
-use sc_service::config::TransactionPoolOptions;
+use sc_service::config::{TransactionPoolOptions, TransactionPoolSenderLimit};
+use std::path::PathBuf;
 
 /// Parameters used to create the pool configuration.
 #[derive(Debug, Clone, Args)]
@@ -30,6 +32,39 @@ pub struct TransactionPoolParams {
 	#[arg(long, value_name = "COUNT", default_value_t = 20480)]
 	pub pool_kbytes: usize,
 
+	/// Maximum number of transactions of a single sender in the transaction pool.
+	///
+	/// Senders are identified by the leading bytes of the first tag their transactions
+	/// provide, see `--pool-sender-tag-prefix`. When set, evictions caused by a full pool
+	/// also prefer the senders with the most transactions. No per-sender limit by default.
+	#[arg(long, value_name = "COUNT")]
+	pub pool_sender_limit: Option<usize>,
+
+	/// Number of leading bytes of the first tag provided by a transaction that identify its
+	/// sender.
+	///
+	/// Defaults to the encoded length of a 32-byte account id.
+	#[arg(long, value_name = "BYTES", default_value_t = 32)]
+	pub pool_sender_tag_prefix: usize,
+
+	/// The type of the transaction pool.
+	#[arg(
+		long,
+		value_enum,
+		value_name = "POOL_TYPE",
+		default_value_t = TransactionPoolType::SingleState,
+		ignore_case = true
+	)]
+	pub pool_type: TransactionPoolType,
+
+	/// Path of the file the transaction pool is persisted to.
+	///
+	/// Ready and future transactions and the list of banned transactions are written to this
+	/// file periodically and on shutdown, and restored when the node starts. Restored
+	/// transactions are revalidated against the best block.
+	#[arg(long, value_name = "PATH")]
+	pub pool_journal: Option<PathBuf>,
+
 	/// How long a transaction is banned for.
 	///
 	/// If it is considered invalid. Defaults to 1800s.
@@ -51,6 +86,16 @@ impl TransactionPoolParams {
 		opts.future.count = self.pool_limit / factor;
 		opts.future.total_bytes = self.pool_kbytes * 1024 / factor;
 
+		// per-sender limits
+		opts.sender = self.pool_sender_limit.map(|count| TransactionPoolSenderLimit {
+			tag_prefix_len: self.pool_sender_tag_prefix,
+			count,
+		});
+
+		opts.fork_aware = self.pool_type == TransactionPoolType::ForkAware;
+
+		opts.journal = self.pool_journal.clone();
+
 		opts.ban_time = if let Some(ban_seconds) = self.tx_ban_seconds {
 			std::time::Duration::from_secs(ban_seconds)
 		} else if is_dev {
@@ -250,6 +250,7 @@ mod tests {
 				database: sc_client_db::DatabaseSource::ParityDb { path: root.clone() },
 				trie_cache_maximum_size: None,
 				state_pruning: None,
+				state_checkpoint_interval: None,
 				blocks_pruning: sc_client_db::BlocksPruning::KeepAll,
 				chain_spec: Box::new(
 					GenericChainSpec::<()>::builder(Default::default(), NoExtension::None)
@@ -34,17 +34,19 @@ use std::{fmt::Debug, marker::PhantomData, pin::Pin, sync::Arc};
 
 use codec::Codec;
 use futures::prelude::*;
+use log::debug;
 
 use sc_client_api::{backend::AuxStore, BlockOf};
 use sc_consensus::{BlockImport, BlockImportParams, ForkChoiceStrategy, StateAction};
 use sc_consensus_slots::{
-	BackoffAuthoringBlocksStrategy, InherentDataProviderExt, SimpleSlotWorkerToSlotWorker,
-	SlotInfo, StorageChanges,
+	BackoffAuthoringBlocksStrategy, EquivocationReporter, InherentDataProviderExt,
+	SimpleSlotWorkerToSlotWorker, SlotInfo, StorageChanges,
 };
 use sc_telemetry::TelemetryHandle;
-use sp_api::{Core, ProvideRuntimeApi};
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sp_api::{ApiExt, Core, ProvideRuntimeApi};
 use sp_application_crypto::AppPublic;
-use sp_blockchain::HeaderBackend;
+use sp_blockchain::{Error as ClientError, HeaderBackend, Result as ClientResult};
 use sp_consensus::{BlockOrigin, Environment, Error as ConsensusError, Proposer, SelectChain};
 use sp_consensus_slots::Slot;
 use sp_core::crypto::Pair;
@@ -65,7 +67,7 @@ pub use sp_consensus::SyncOracle;
 pub use sp_consensus_aura::{
 	digests::CompatibleDigestItem,
 	inherents::{InherentDataProvider, InherentType as AuraInherent, INHERENT_IDENTIFIER},
-	AuraApi, ConsensusLog, SlotDuration, AURA_ENGINE_ID,
+	AuraApi, AuraEquivocationApi, ConsensusLog, EquivocationProof, SlotDuration, AURA_ENGINE_ID,
 };
 
 const LOG_TARGET: &str = "aura";
@@ -504,6 +506,101 @@ impl<B: BlockT> From<crate::standalone::PreDigestLookupError> for Error<B> {
 	}
 }
 
+/// Reports Aura equivocations detected by an [`sc_consensus_slots::EquivocationWatcher`].
+///
+/// Reports are only submitted if the runtime implements [`AuraEquivocationApi`].
+pub struct AuraEquivocationReporter<B: BlockT, C, P> {
+	client: Arc<C>,
+	compatibility_mode: CompatibilityMode<NumberFor<B>>,
+	offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+	_phantom: PhantomData<fn() -> P>,
+}
+
+impl<B: BlockT, C, P> AuraEquivocationReporter<B, C, P> {
+	/// Create a new reporter.
+	pub fn new(
+		client: Arc<C>,
+		compatibility_mode: CompatibilityMode<NumberFor<B>>,
+		offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+	) -> Self {
+		Self { client, compatibility_mode, offchain_tx_pool_factory, _phantom: PhantomData }
+	}
+}
+
+impl<B, C, P> EquivocationReporter<B> for AuraEquivocationReporter<B, C, P>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + Send + Sync,
+	C::Api: AuraApi<B, AuthorityId<P>> + AuraEquivocationApi<B, AuthorityId<P>>,
+	P: Pair,
+	P::Public: Codec + Debug,
+	P::Signature: Codec,
+{
+	type AuthorityId = AuthorityId<P>;
+
+	fn slot_author(&self, header: &B::Header) -> ClientResult<Option<(Slot, AuthorityId<P>)>> {
+		let slot = match find_pre_digest::<B, P::Signature>(header) {
+			Ok(slot) => slot,
+			Err(_) => return Ok(None),
+		};
+
+		let authorities = authorities(
+			&*self.client,
+			*header.parent_hash(),
+			*header.number(),
+			&self.compatibility_mode,
+		)
+		.map_err(ClientError::Consensus)?;
+
+		Ok(standalone::slot_author::<P>(slot, &authorities).map(|author| (slot, author.clone())))
+	}
+
+	fn report_equivocation(
+		&self,
+		at: B::Hash,
+		equivocation_proof: EquivocationProof<B::Header, AuthorityId<P>>,
+	) -> ClientResult<bool> {
+		let mut runtime_api = self.client.runtime_api();
+		if !runtime_api.has_api::<dyn AuraEquivocationApi<B, AuthorityId<P>>>(at)? {
+			debug!(target: LOG_TARGET, "Runtime doesn't support reporting equivocations.");
+			return Ok(false)
+		}
+
+		// try to generate the key ownership proof at the parent of the equivocating header first,
+		// where the session of the equivocation is live, and then at the best block. The runtime
+		// at the parent may predate the equivocation API, so check for it there as well.
+		let parent_hash = *equivocation_proof.second_header.parent_hash();
+		let mut key_owner_proof = None;
+		for at_hash in [parent_hash, at] {
+			if !runtime_api.has_api::<dyn AuraEquivocationApi<B, AuthorityId<P>>>(at_hash)? {
+				continue
+			}
+			key_owner_proof = runtime_api.generate_key_ownership_proof(
+				at_hash,
+				equivocation_proof.slot,
+				equivocation_proof.offender.clone(),
+			)?;
+			if key_owner_proof.is_some() {
+				break
+			}
+		}
+		let key_owner_proof = match key_owner_proof {
+			Some(proof) => proof,
+			None => {
+				debug!(target: LOG_TARGET, "Equivocation offender is not part of the authority set.");
+				return Ok(false)
+			},
+		};
+
+		// Register the offchain tx pool to be able to use it from the runtime.
+		runtime_api.register_extension(self.offchain_tx_pool_factory.offchain_transaction_pool(at));
+
+		Ok(runtime_api
+			.submit_report_equivocation_unsigned_extrinsic(at, equivocation_proof, key_owner_proof)?
+			.is_some())
+	}
+}
+
 fn authorities<A, B, C>(
 	client: &C,
 	parent_hash: B::Hash,
@@ -551,9 +648,12 @@ mod tests {
 	use sc_block_builder::BlockBuilderBuilder;
 	use sc_client_api::BlockchainEvents;
 	use sc_consensus::BoxJustificationImport;
-	use sc_consensus_slots::{BackoffAuthoringOnFinalizedHeadLagging, SimpleSlotWorker};
+	use sc_consensus_slots::{
+		BackoffAuthoringOnFinalizedHeadLagging, EquivocationWatcher, SimpleSlotWorker,
+	};
 	use sc_keystore::LocalKeystore;
 	use sc_network_test::{Block as TestBlock, *};
+	use sc_transaction_pool_api::RejectAllTxPool;
 	use sp_application_crypto::{key_types::AURA, AppCrypto};
 	use sp_consensus::{DisableProofRecording, NoNetwork as DummyOracle, Proposal};
 	use sp_consensus_aura::sr25519::AuthorityPair;
@@ -880,4 +980,82 @@ mod tests {
 		// The returned block should be imported and we should be able to get its header by now.
 		assert!(client.header(res.block.hash()).unwrap().is_some());
 	}
+
+	#[tokio::test]
+	async fn equivocation_reporter_finds_slot_authors() {
+		let mut net = AuraTestNet::new(1);
+
+		let keystore_path = tempfile::tempdir().expect("Creates keystore path");
+		let keystore = LocalKeystore::open(keystore_path.path(), None).expect("Creates keystore.");
+		keystore
+			.sr25519_generate_new(AuthorityPair::ID, Some(&Keyring::Alice.to_seed()))
+			.expect("Key should be created");
+
+		let peer = net.peer(0);
+		let client = peer.client().as_client();
+
+		let mut worker = AuraWorker {
+			client: client.clone(),
+			block_import: client.clone(),
+			env: DummyFactory(client.clone()),
+			keystore: keystore.into(),
+			sync_oracle: DummyOracle,
+			justification_sync_link: (),
+			force_authoring: false,
+			backoff_authoring_blocks: Option::<()>::None,
+			telemetry: None,
+			block_proposal_slot_portion: SlotProportion::new(0.5),
+			max_block_proposal_slot_portion: None,
+			compatibility_mode: Default::default(),
+			_phantom: PhantomData::<fn() -> AuthorityPair>,
+		};
+
+		// author two blocks on slot 0 with the same authority.
+		let genesis_header = client.expect_header(client.info().genesis_hash).unwrap();
+		let mut headers = Vec::new();
+		for _ in 0..2 {
+			let res = worker
+				.on_slot(SlotInfo {
+					slot: 0.into(),
+					ends_at: Instant::now() + Duration::from_secs(100),
+					create_inherent_data: Box::new(()),
+					duration: Duration::from_millis(1000),
+					chain_head: genesis_header.clone(),
+					block_size_limit: None,
+				})
+				.await
+				.unwrap();
+			headers.push(res.block.header().clone());
+		}
+
+		let reporter = AuraEquivocationReporter::<_, _, AuthorityPair>::new(
+			client.clone(),
+			CompatibilityMode::None,
+			OffchainTransactionPoolFactory::new(RejectAllTxPool::default()),
+		);
+
+		assert_eq!(reporter.slot_author(&genesis_header).unwrap(), None);
+		assert_eq!(
+			reporter.slot_author(&headers[0]).unwrap(),
+			Some((0.into(), Keyring::Alice.public().into())),
+		);
+
+		let mut watcher = EquivocationWatcher::new(client.clone(), reporter);
+		assert!(watcher.note_header(&headers[0], BlockOrigin::Own).unwrap().is_none());
+		let proof = watcher
+			.note_header(&headers[1], BlockOrigin::Own)
+			.unwrap()
+			.expect("second header is an equivocation");
+		assert_eq!(proof.offender, Keyring::Alice.public().into());
+		assert_eq!(proof.first_header, headers[0]);
+		assert_eq!(proof.second_header, headers[1]);
+
+		// the test runtime doesn't provide key ownership proofs, so nothing gets submitted.
+		let reporter = AuraEquivocationReporter::<_, _, AuthorityPair>::new(
+			client.clone(),
+			CompatibilityMode::None,
+			OffchainTransactionPoolFactory::new(RejectAllTxPool::default()),
+		);
+		assert!(!reporter.report_equivocation(client.info().best_hash, proof).unwrap());
+	}
 }
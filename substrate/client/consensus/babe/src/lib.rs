@@ -103,8 +103,8 @@ use sc_consensus_epochs::{
 	descendent_query, Epoch as EpochT, EpochChangesFor, SharedEpochChanges, ViableEpochDescriptor,
 };
 use sc_consensus_slots::{
-	check_equivocation, BackoffAuthoringBlocksStrategy, CheckedHeader, InherentDataProviderExt,
-	SlotInfo, StorageChanges,
+	check_equivocation, BackoffAuthoringBlocksStrategy, CheckedHeader, EquivocationReporter,
+	InherentDataProviderExt, SlotInfo, StorageChanges,
 };
 use sc_telemetry::{telemetry, TelemetryHandle, CONSENSUS_DEBUG, CONSENSUS_TRACE};
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
@@ -116,14 +116,14 @@ use sp_blockchain::{
 	Result as ClientResult,
 };
 use sp_consensus::{BlockOrigin, Environment, Error as ConsensusError, Proposer, SelectChain};
-use sp_consensus_babe::inherents::BabeInherentData;
+use sp_consensus_babe::{inherents::BabeInherentData, EquivocationProof};
 use sp_consensus_slots::Slot;
 use sp_core::traits::SpawnEssentialNamed;
 use sp_inherents::{CreateInherentDataProviders, InherentData, InherentDataProvider};
 use sp_keystore::KeystorePtr;
 use sp_runtime::{
 	generic::OpaqueDigestItemId,
-	traits::{Block as BlockT, Header, NumberFor, SaturatedConversion, Zero},
+	traits::{Block as BlockT, Header, NumberFor, One, SaturatedConversion, Zero},
 	DigestItem,
 };
 
@@ -976,6 +976,152 @@ impl<Block: BlockT> BabeLink<Block> {
 	}
 }
 
+/// Generates a key ownership proof for the offender of the given equivocation and submits a
+/// report of it, through the offchain transaction pool, on top of `best_hash`.
+///
+/// Returns `false` if the offender is not part of the authority set or if the runtime failed to
+/// create the report, e.g. because equivocation reporting is disabled.
+fn submit_equivocation_report<Block, Client>(
+	client: &Client,
+	offchain_tx_pool_factory: &OffchainTransactionPoolFactory<Block>,
+	best_hash: Block::Hash,
+	equivocation_proof: EquivocationProof<Block::Header>,
+) -> Result<bool, sp_api::ApiError>
+where
+	Block: BlockT,
+	Client: ProvideRuntimeApi<Block>,
+	Client::Api: BabeApi<Block>,
+{
+	// generate a key ownership proof. we start by trying to generate the
+	// key ownership proof at the parent of the equivocating header, this
+	// will make sure that proof generation is successful since it happens
+	// during the on-going session (i.e. session keys are available in the
+	// state to be able to generate the proof). this might fail if the
+	// equivocation happens on the first block of the session, in which case
+	// its parent would be on the previous session. if generation on the
+	// parent header fails we try with best block as well.
+	let generate_key_owner_proof = |at_hash: Block::Hash| {
+		client.runtime_api().generate_key_ownership_proof(
+			at_hash,
+			equivocation_proof.slot,
+			equivocation_proof.offender.clone(),
+		)
+	};
+
+	let parent_hash = *equivocation_proof.second_header.parent_hash();
+	let key_owner_proof = match generate_key_owner_proof(parent_hash)? {
+		Some(proof) => proof,
+		None => match generate_key_owner_proof(best_hash)? {
+			Some(proof) => proof,
+			None => return Ok(false),
+		},
+	};
+
+	// submit equivocation report at best block.
+	let mut runtime_api = client.runtime_api();
+
+	// Register the offchain tx pool to be able to use it from the runtime.
+	runtime_api.register_extension(offchain_tx_pool_factory.offchain_transaction_pool(best_hash));
+
+	runtime_api
+		.submit_report_equivocation_unsigned_extrinsic(
+			best_hash,
+			equivocation_proof,
+			key_owner_proof,
+		)
+		.map(|submitted| submitted.is_some())
+}
+
+/// Reports BABE equivocations detected by an [`sc_consensus_slots::EquivocationWatcher`].
+pub struct BabeEquivocationReporter<Block: BlockT, Client> {
+	client: Arc<Client>,
+	config: BabeConfiguration,
+	epoch_changes: SharedEpochChanges<Block, Epoch>,
+	offchain_tx_pool_factory: OffchainTransactionPoolFactory<Block>,
+}
+
+impl<Block: BlockT, Client> BabeEquivocationReporter<Block, Client> {
+	/// Create a new reporter using the epoch data of the given BABE link.
+	pub fn new(
+		client: Arc<Client>,
+		link: &BabeLink<Block>,
+		offchain_tx_pool_factory: OffchainTransactionPoolFactory<Block>,
+	) -> Self {
+		Self {
+			client,
+			config: link.config.clone(),
+			epoch_changes: link.epoch_changes.clone(),
+			offchain_tx_pool_factory,
+		}
+	}
+}
+
+impl<Block, Client> EquivocationReporter<Block> for BabeEquivocationReporter<Block, Client>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = ClientError>
+		+ ProvideRuntimeApi<Block>
+		+ Send
+		+ Sync,
+	Client::Api: BabeApi<Block>,
+{
+	type AuthorityId = AuthorityId;
+
+	fn slot_author(&self, header: &Block::Header) -> ClientResult<Option<(Slot, AuthorityId)>> {
+		// the genesis block has no author.
+		if header.number().is_zero() {
+			return Ok(None)
+		}
+		let pre_digest = match find_pre_digest::<Block>(header) {
+			Ok(pre_digest) => pre_digest,
+			Err(_) => return Ok(None),
+		};
+
+		let epoch = self
+			.epoch_changes
+			.shared_data()
+			.epoch_data_for_child_of(
+				descendent_query(&*self.client),
+				header.parent_hash(),
+				*header.number() - One::one(),
+				pre_digest.slot(),
+				|slot| Epoch::genesis(&self.config, slot),
+			)
+			.map_err(|e| ClientError::Backend(e.to_string()))?;
+
+		Ok(epoch.and_then(|epoch| {
+			epoch
+				.authorities
+				.get(pre_digest.authority_index() as usize)
+				.map(|(author, _)| (pre_digest.slot(), author.clone()))
+		}))
+	}
+
+	fn reported_on_import(&self, origin: BlockOrigin) -> bool {
+		// the verifier checks blocks coming from the network (except during initial sync) and
+		// reports equivocations itself, own blocks bypass it.
+		matches!(
+			origin,
+			BlockOrigin::NetworkBroadcast | BlockOrigin::ConsensusBroadcast | BlockOrigin::File
+		)
+	}
+
+	fn report_equivocation(
+		&self,
+		at: Block::Hash,
+		equivocation_proof: EquivocationProof<Block::Header>,
+	) -> ClientResult<bool> {
+		submit_equivocation_report(
+			&*self.client,
+			&self.offchain_tx_pool_factory,
+			at,
+			equivocation_proof,
+		)
+		.map_err(ClientError::RuntimeApiError)
+	}
+}
+
 /// A verifier for Babe blocks.
 pub struct BabeVerifier<Block: BlockT, Client, SelectChain, CIDP> {
 	client: Arc<Client>,
@@ -1059,50 +1205,20 @@ where
 			.map(|h| h.hash())
 			.map_err(|e| Error::Client(e.into()))?;
 
-		// generate a key ownership proof. we start by trying to generate the
-		// key ownership proof at the parent of the equivocating header, this
-		// will make sure that proof generation is successful since it happens
-		// during the on-going session (i.e. session keys are available in the
-		// state to be able to generate the proof). this might fail if the
-		// equivocation happens on the first block of the session, in which case
-		// its parent would be on the previous session. if generation on the
-		// parent header fails we try with best block as well.
-		let generate_key_owner_proof = |at_hash: Block::Hash| {
-			self.client
-				.runtime_api()
-				.generate_key_ownership_proof(at_hash, slot, equivocation_proof.offender.clone())
-				.map_err(Error::RuntimeApi)
-		};
-
-		let parent_hash = *header.parent_hash();
-		let key_owner_proof = match generate_key_owner_proof(parent_hash)? {
-			Some(proof) => proof,
-			None => match generate_key_owner_proof(best_hash)? {
-				Some(proof) => proof,
-				None => {
-					debug!(
-						target: LOG_TARGET,
-						"Equivocation offender is not part of the authority set."
-					);
-					return Ok(())
-				},
-			},
-		};
-
-		// submit equivocation report at best block.
-		let mut runtime_api = self.client.runtime_api();
-
-		// Register the offchain tx pool to be able to use it from the runtime.
-		runtime_api
-			.register_extension(self.offchain_tx_pool_factory.offchain_transaction_pool(best_hash));
-
-		runtime_api
-			.submit_report_equivocation_unsigned_extrinsic(
-				best_hash,
-				equivocation_proof,
-				key_owner_proof,
-			)
-			.map_err(Error::RuntimeApi)?;
+		if !submit_equivocation_report(
+			&*self.client,
+			&self.offchain_tx_pool_factory,
+			best_hash,
+			equivocation_proof,
+		)
+		.map_err(Error::RuntimeApi)?
+		{
+			debug!(
+				target: LOG_TARGET,
+				"Equivocation offender is not part of the authority set or reporting is disabled."
+			);
+			return Ok(())
+		}
 
 		info!(target: LOG_TARGET, "Submitted equivocation report for author {:?}", author);
 
@@ -24,7 +24,7 @@ use sc_block_builder::{BlockBuilder, BlockBuilderBuilder};
 use sc_client_api::{BlockchainEvents, Finalizer};
 use sc_consensus::{BoxBlockImport, BoxJustificationImport};
 use sc_consensus_epochs::{EpochIdentifier, EpochIdentifierPosition};
-use sc_consensus_slots::BackoffAuthoringOnFinalizedHeadLagging;
+use sc_consensus_slots::{BackoffAuthoringOnFinalizedHeadLagging, EquivocationWatcher};
 use sc_network_test::{Block as TestBlock, *};
 use sc_transaction_pool_api::RejectAllTxPool;
 use sp_application_crypto::key_types::BABE;
@@ -1008,6 +1008,69 @@ async fn verify_slots_are_strictly_increasing() {
 	propose_and_import_block(&b1, Some(999.into()), &mut proposer_factory, &mut block_import).await;
 }
 
+#[tokio::test]
+async fn equivocation_watcher_detects_equivocations() {
+	let mut net = BabeTestNet::new(1);
+
+	let peer = net.peer(0);
+	let data = peer.data.as_ref().expect("babe link set up during initialization");
+
+	let client = peer.client().as_client();
+	let mut block_import = data.block_import.lock().take().expect("import set up during init");
+
+	let mut proposer_factory = DummyFactory {
+		client: client.clone(),
+		epoch_changes: data.link.epoch_changes.clone(),
+		mutator: Arc::new(|_, _| ()),
+	};
+
+	let genesis_header = client.header(client.chain_info().genesis_hash).unwrap().unwrap();
+
+	// build `B1 (999) <- B2 (1000)` and a fork `B2' (1000)` on genesis, with all blocks
+	// authored by the same authority.
+	let b1 = propose_and_import_block(
+		&genesis_header,
+		Some(999.into()),
+		&mut proposer_factory,
+		&mut block_import,
+	)
+	.await;
+	let b1 = client.header(b1).unwrap().unwrap();
+	let b2 =
+		propose_and_import_block(&b1, Some(1000.into()), &mut proposer_factory, &mut block_import)
+			.await;
+	let b2 = client.header(b2).unwrap().unwrap();
+	let fork = propose_and_import_block(
+		&genesis_header,
+		Some(1000.into()),
+		&mut proposer_factory,
+		&mut block_import,
+	)
+	.await;
+	let fork = client.header(fork).unwrap().unwrap();
+
+	let reporter = BabeEquivocationReporter::new(
+		client.clone(),
+		&data.link,
+		OffchainTransactionPoolFactory::new(RejectAllTxPool::default()),
+	);
+	let mut watcher = EquivocationWatcher::new(client.clone(), reporter);
+
+	assert!(watcher.note_header(&genesis_header, BlockOrigin::Own).unwrap().is_none());
+	assert!(watcher.note_header(&b1, BlockOrigin::Own).unwrap().is_none());
+	assert!(watcher.note_header(&b2, BlockOrigin::Own).unwrap().is_none());
+
+	let proof = watcher
+		.note_header(&fork, BlockOrigin::Own)
+		.unwrap()
+		.expect("fork is an equivocation");
+	let genesis_epoch = Epoch::genesis(&data.link.config, 999.into());
+	assert_eq!(proof.offender, genesis_epoch.authorities[0].0);
+	assert_eq!(proof.slot, Slot::from(1000));
+	assert_eq!(proof.first_header, b2);
+	assert_eq!(proof.second_header, fork);
+}
+
 #[tokio::test]
 async fn obsolete_blocks_aux_data_cleanup() {
 	let mut net = BabeTestNet::new(1);
@@ -21,12 +21,17 @@
 #![warn(missing_docs)]
 
 use parking_lot::RwLock;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use sc_rpc::SubscriptionTaskExecutor;
-use sp_runtime::traits::Block as BlockT;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
 
-use futures::{task::SpawnError, FutureExt, StreamExt};
+use futures::{
+	future::{self, Either},
+	task::SpawnError,
+	FutureExt, StreamExt,
+};
+use futures_timer::Delay;
 use jsonrpsee::{
 	core::{async_trait, Error as JsonRpseeError, RpcResult},
 	proc_macros::rpc,
@@ -36,11 +41,16 @@ use jsonrpsee::{
 use log::warn;
 
 use sc_consensus_beefy::communication::notification::{
-	BeefyBestBlockStream, BeefyVersionedFinalityProofStream,
+	BeefyBestBlockStream, BeefyJustificationRequestSender, BeefyJustificationResponseStream,
+	BeefyVersionedFinalityProofStream,
 };
 
 mod notification;
 
+/// Maximum time to wait for the BEEFY gadget to answer a justification request, which might
+/// involve requesting it from several peers.
+const JUSTIFICATION_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Debug, thiserror::Error)]
 /// Top-level error type for the RPC handler
 pub enum Error {
@@ -50,6 +60,9 @@ pub enum Error {
 	/// The BEEFY RPC background task failed to spawn.
 	#[error("BEEFY RPC background task failed to spawn")]
 	RpcTaskFailure(#[from] SpawnError),
+	/// The BEEFY gadget did not answer a justification request in time.
+	#[error("BEEFY justification request timed out")]
+	JustificationRequestTimeout,
 }
 
 /// The error codes returned by jsonrpc.
@@ -58,6 +71,8 @@ pub enum ErrorCode {
 	NotReady = 1,
 	/// Returned on BEEFY RPC background task failure.
 	TaskFailure = 2,
+	/// Returned when a BEEFY justification request timed out.
+	RequestTimeout = 3,
 }
 
 impl From<Error> for ErrorCode {
@@ -65,6 +80,7 @@ impl From<Error> for ErrorCode {
 		match error {
 			Error::EndpointNotReady => ErrorCode::NotReady,
 			Error::RpcTaskFailure(_) => ErrorCode::TaskFailure,
+			Error::JustificationRequestTimeout => ErrorCode::RequestTimeout,
 		}
 	}
 }
@@ -83,7 +99,7 @@ impl From<Error> for JsonRpseeError {
 
 // Provides RPC methods for interacting with BEEFY.
 #[rpc(client, server)]
-pub trait BeefyApi<Notification, Hash> {
+pub trait BeefyApi<Notification, Hash, Number> {
 	/// Returns the block most recently finalized by BEEFY, alongside its justification.
 	#[subscription(
 		name = "beefy_subscribeJustifications" => "beefy_justifications",
@@ -92,6 +108,25 @@ pub trait BeefyApi<Notification, Hash> {
 	)]
 	fn subscribe_justifications(&self);
 
+	/// Returns all the BEEFY justifications known to this client as they are finalized, imported
+	/// or fetched from peers, including the ones for blocks older than the latest BEEFY finalized
+	/// block.
+	#[subscription(
+		name = "beefy_subscribeAllJustifications" => "beefy_allJustifications",
+		unsubscribe = "beefy_unsubscribeAllJustifications",
+		item = Notification,
+	)]
+	fn subscribe_all_justifications(&self);
+
+	/// Returns the BEEFY justification of the given block, either stored by this client or
+	/// requested from its peers.
+	///
+	/// Returns `null` if the block is not finalized by BEEFY or if no justification could be
+	/// found, which is the case for blocks BEEFY validators did not vote on. Also returns `null`
+	/// without asking the peers while too many requests to them are already pending.
+	#[method(name = "beefy_getJustification")]
+	async fn justification(&self, block: Number) -> RpcResult<Option<Notification>>;
+
 	/// Returns hash of the latest BEEFY finalized block as seen by this client.
 	///
 	/// The latest BEEFY block might not be available if the BEEFY gadget is not running
@@ -104,6 +139,9 @@ pub trait BeefyApi<Notification, Hash> {
 /// Implements the BeefyApi RPC trait for interacting with BEEFY.
 pub struct Beefy<Block: BlockT> {
 	finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
+	all_finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
+	justif_request_sender: BeefyJustificationRequestSender<Block>,
+	justif_response_stream: BeefyJustificationResponseStream<Block>,
 	beefy_best_block: Arc<RwLock<Option<Block::Hash>>>,
 	executor: SubscriptionTaskExecutor,
 }
@@ -116,6 +154,9 @@ where
 	pub fn new(
 		finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
 		best_block_stream: BeefyBestBlockStream<Block>,
+		all_finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
+		justif_request_sender: BeefyJustificationRequestSender<Block>,
+		justif_response_stream: BeefyJustificationResponseStream<Block>,
 		executor: SubscriptionTaskExecutor,
 	) -> Result<Self, Error> {
 		let beefy_best_block = Arc::new(RwLock::new(None));
@@ -128,12 +169,20 @@ where
 		});
 
 		executor.spawn("substrate-rpc-subscription", Some("rpc"), future.map(drop).boxed());
-		Ok(Self { finality_proof_stream, beefy_best_block, executor })
+		Ok(Self {
+			finality_proof_stream,
+			all_finality_proof_stream,
+			justif_request_sender,
+			justif_response_stream,
+			beefy_best_block,
+			executor,
+		})
 	}
 }
 
 #[async_trait]
-impl<Block> BeefyApiServer<notification::EncodedVersionedFinalityProof, Block::Hash>
+impl<Block>
+	BeefyApiServer<notification::EncodedVersionedFinalityProof, Block::Hash, NumberFor<Block>>
 	for Beefy<Block>
 where
 	Block: BlockT,
@@ -152,6 +201,39 @@ where
 		Ok(())
 	}
 
+	fn subscribe_all_justifications(&self, mut sink: SubscriptionSink) -> SubscriptionResult {
+		let stream = self
+			.all_finality_proof_stream
+			.subscribe(100_000)
+			.map(|vfp| notification::EncodedVersionedFinalityProof::new::<Block>(vfp));
+
+		let fut = async move {
+			sink.pipe_from_stream(stream).await;
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+
+	async fn justification(
+		&self,
+		block: NumberFor<Block>,
+	) -> RpcResult<Option<notification::EncodedVersionedFinalityProof>> {
+		// Subscribe before sending the request, so that the answer can't be missed.
+		let mut responses = self.justif_response_stream.subscribe(100_000).filter_map(
+			|(number, finality_proof)| future::ready((number == block).then_some(finality_proof)),
+		);
+		self.justif_request_sender
+			.notify(|| Ok::<_, ()>(block))
+			.expect("forwards closure result; the closure always returns Ok; qed.");
+
+		match future::select(responses.next(), Delay::new(JUSTIFICATION_REQUEST_TIMEOUT)).await {
+			Either::Left((Some(finality_proof), _)) => Ok(finality_proof
+				.map(|vfp| notification::EncodedVersionedFinalityProof::new::<Block>(vfp))),
+			_ => Err(Error::JustificationRequestTimeout.into()),
+		}
+	}
+
 	async fn latest_finalized(&self) -> RpcResult<Block::Hash> {
 		self.beefy_best_block
 			.read()
@@ -169,7 +251,10 @@ mod tests {
 	use codec::{Decode, Encode};
 	use jsonrpsee::{types::EmptyServerParams as EmptyParams, RpcModule};
 	use sc_consensus_beefy::{
-		communication::notification::BeefyVersionedFinalityProofSender,
+		communication::notification::{
+			BeefyJustificationRequestStream, BeefyJustificationResponseSender,
+			BeefyVersionedFinalityProofSender,
+		},
 		justification::BeefyVersionedFinalityProof,
 	};
 	use sp_consensus_beefy::{known_payloads, Payload, SignedCommitment};
@@ -186,14 +271,56 @@ mod tests {
 	) -> (RpcModule<Beefy<Block>>, BeefyVersionedFinalityProofSender<Block>) {
 		let (finality_proof_sender, finality_proof_stream) =
 			BeefyVersionedFinalityProofStream::<Block>::channel();
-
-		let handler =
-			Beefy::new(finality_proof_stream, best_block_stream, sc_rpc::testing::test_executor())
-				.expect("Setting up the BEEFY RPC handler works");
+		let (_, all_finality_proof_stream) = BeefyVersionedFinalityProofStream::<Block>::channel();
+		let (justif_request_sender, _) = BeefyJustificationRequestStream::<Block>::channel();
+		let (_, justif_response_stream) = BeefyJustificationResponseStream::<Block>::channel();
+
+		let handler = Beefy::new(
+			finality_proof_stream,
+			best_block_stream,
+			all_finality_proof_stream,
+			justif_request_sender,
+			justif_response_stream,
+			sc_rpc::testing::test_executor(),
+		)
+		.expect("Setting up the BEEFY RPC handler works");
 
 		(handler.into_rpc(), finality_proof_sender)
 	}
 
+	fn setup_io_handler_with_justif_requests() -> (
+		RpcModule<Beefy<Block>>,
+		BeefyVersionedFinalityProofSender<Block>,
+		BeefyJustificationRequestStream<Block>,
+		BeefyJustificationResponseSender<Block>,
+	) {
+		let (_, finality_proof_stream) = BeefyVersionedFinalityProofStream::<Block>::channel();
+		let (_, best_block_stream) = BeefyBestBlockStream::<Block>::channel();
+		let (all_finality_proof_sender, all_finality_proof_stream) =
+			BeefyVersionedFinalityProofStream::<Block>::channel();
+		let (justif_request_sender, justif_request_stream) =
+			BeefyJustificationRequestStream::<Block>::channel();
+		let (justif_response_sender, justif_response_stream) =
+			BeefyJustificationResponseStream::<Block>::channel();
+
+		let handler = Beefy::new(
+			finality_proof_stream,
+			best_block_stream,
+			all_finality_proof_stream,
+			justif_request_sender,
+			justif_response_stream,
+			sc_rpc::testing::test_executor(),
+		)
+		.expect("Setting up the BEEFY RPC handler works");
+
+		(
+			handler.into_rpc(),
+			all_finality_proof_sender,
+			justif_request_stream,
+			justif_response_sender,
+		)
+	}
+
 	#[tokio::test]
 	async fn uninitialized_rpc_handler() {
 		let (rpc, _) = setup_io_handler();
@@ -300,4 +427,55 @@ mod tests {
 		assert_eq!(&recv_sub_id, sub.subscription_id());
 		assert_eq!(recv_finality_proof, finality_proof);
 	}
+
+	#[tokio::test]
+	async fn subscribe_and_listen_to_all_justifications() {
+		let (rpc, all_finality_proof_sender, _, _) = setup_io_handler_with_justif_requests();
+
+		let mut sub = rpc
+			.subscribe("beefy_subscribeAllJustifications", EmptyParams::new())
+			.await
+			.unwrap();
+
+		let finality_proof = create_finality_proof();
+		let r: Result<(), ()> = all_finality_proof_sender.notify(|| Ok(finality_proof.clone()));
+		r.unwrap();
+
+		let (bytes, recv_sub_id) = sub.next::<sp_core::Bytes>().await.unwrap().unwrap();
+		let recv_finality_proof: BeefyVersionedFinalityProof<Block> =
+			Decode::decode(&mut &bytes[..]).unwrap();
+		assert_eq!(&recv_sub_id, sub.subscription_id());
+		assert_eq!(recv_finality_proof, finality_proof);
+	}
+
+	#[tokio::test]
+	async fn get_justification_rpc() {
+		let (rpc, _, justif_request_stream, justif_response_sender) =
+			setup_io_handler_with_justif_requests();
+
+		// Act as the BEEFY gadget: only block 5 has a justification.
+		let finality_proof = create_finality_proof();
+		let mut requests = justif_request_stream.subscribe(100_000);
+		let gadget = async {
+			for _ in 0..2 {
+				let block = requests.next().await.unwrap();
+				let answer = (block == 5).then(|| finality_proof.clone());
+				let r: Result<(), ()> = justif_response_sender.notify(|| Ok((block, answer)));
+				r.unwrap();
+			}
+		};
+		let requester = async {
+			let found: Option<sp_core::Bytes> =
+				rpc.call("beefy_getJustification", [5u64]).await.unwrap();
+			let missing: Option<sp_core::Bytes> =
+				rpc.call("beefy_getJustification", [6u64]).await.unwrap();
+			(found, missing)
+		};
+		let (_, (found, missing)) = futures::join!(gadget, requester);
+
+		let recv_finality_proof: BeefyVersionedFinalityProof<Block> =
+			Decode::decode(&mut &found.unwrap()[..]).unwrap();
+		assert_eq!(recv_finality_proof, finality_proof);
+		assert!(missing.is_none());
+	}
 }
@@ -17,7 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use sc_utils::notification::{NotificationSender, NotificationStream, TracingKeyStr};
-use sp_runtime::traits::Block as BlockT;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
 
 use crate::justification::BeefyVersionedFinalityProof;
 
@@ -40,6 +40,27 @@ pub type BeefyVersionedFinalityProofSender<Block> =
 pub type BeefyVersionedFinalityProofStream<Block> =
 	NotificationStream<BeefyVersionedFinalityProof<Block>, BeefyVersionedFinalityProofTracingKey>;
 
+/// The sending half of the notifications channel used to request the BEEFY justification of a
+/// block from the gadget, e.g. by the RPC layer.
+pub type BeefyJustificationRequestSender<Block> = NotificationSender<NumberFor<Block>>;
+
+/// The receiving half of the notifications channel used to receive requests for the BEEFY
+/// justification of a block on the gadget side.
+pub type BeefyJustificationRequestStream<Block> =
+	NotificationStream<NumberFor<Block>, BeefyJustificationRequestTracingKey>;
+
+/// The sending half of the notifications channel used to answer requests for the BEEFY
+/// justification of a block, with `None` if the justification could not be found.
+pub type BeefyJustificationResponseSender<Block> =
+	NotificationSender<(NumberFor<Block>, Option<BeefyVersionedFinalityProof<Block>>)>;
+
+/// The receiving half of the notifications channel used to receive answers to requests for the
+/// BEEFY justification of a block.
+pub type BeefyJustificationResponseStream<Block> = NotificationStream<
+	(NumberFor<Block>, Option<BeefyVersionedFinalityProof<Block>>),
+	BeefyJustificationResponseTracingKey,
+>;
+
 /// Provides tracing key for BEEFY best block stream.
 #[derive(Clone)]
 pub struct BeefyBestBlockTracingKey;
@@ -53,3 +74,17 @@ pub struct BeefyVersionedFinalityProofTracingKey;
 impl TracingKeyStr for BeefyVersionedFinalityProofTracingKey {
 	const TRACING_KEY: &'static str = "mpsc_beefy_versioned_finality_proof_notification_stream";
 }
+
+/// Provides tracing key for BEEFY justification request stream.
+#[derive(Clone)]
+pub struct BeefyJustificationRequestTracingKey;
+impl TracingKeyStr for BeefyJustificationRequestTracingKey {
+	const TRACING_KEY: &'static str = "mpsc_beefy_justification_request_notification_stream";
+}
+
+/// Provides tracing key for BEEFY justification response stream.
+#[derive(Clone)]
+pub struct BeefyJustificationResponseTracingKey;
+impl TracingKeyStr for BeefyJustificationResponseTracingKey {
+	const TRACING_KEY: &'static str = "mpsc_beefy_justification_response_notification_stream";
+}
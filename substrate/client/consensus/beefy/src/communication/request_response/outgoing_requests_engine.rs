@@ -155,6 +155,11 @@ impl<B: Block> OnDemandJustificationsEngine<B> {
 		}
 	}
 
+	/// Returns `true` if there is no request in progress.
+	pub fn is_idle(&self) -> bool {
+		matches!(self.state, State::Idle)
+	}
+
 	/// Cancel any pending request for block numbers smaller or equal to `block`.
 	pub fn cancel_requests_older_than(&mut self, block: NumberFor<B>) {
 		match &self.state {
@@ -19,7 +19,9 @@
 use crate::{
 	communication::{
 		notification::{
-			BeefyBestBlockSender, BeefyBestBlockStream, BeefyVersionedFinalityProofSender,
+			BeefyBestBlockSender, BeefyBestBlockStream, BeefyJustificationRequestSender,
+			BeefyJustificationRequestStream, BeefyJustificationResponseSender,
+			BeefyJustificationResponseStream, BeefyVersionedFinalityProofSender,
 			BeefyVersionedFinalityProofStream,
 		},
 		peers::KnownPeers,
@@ -113,11 +115,20 @@ pub struct BeefyVoterLinks<B: Block> {
 	/// Stream of BEEFY signed commitments from block import to voter.
 	pub from_block_import_justif_stream: BeefyVersionedFinalityProofStream<B>,
 
+	// RPC -> Voter links
+	/// Stream of BEEFY justification requests from RPC to voter.
+	pub from_rpc_justif_request_stream: BeefyJustificationRequestStream<B>,
+
 	// Voter -> RPC links
 	/// Sends BEEFY signed commitments from voter to RPC.
 	pub to_rpc_justif_sender: BeefyVersionedFinalityProofSender<B>,
 	/// Sends BEEFY best block hashes from voter to RPC.
 	pub to_rpc_best_block_sender: BeefyBestBlockSender<B>,
+	/// Sends all BEEFY signed commitments known to the voter, including the ones for blocks
+	/// older than the best BEEFY block, from voter to RPC.
+	pub to_rpc_all_justifs_sender: BeefyVersionedFinalityProofSender<B>,
+	/// Sends answers to BEEFY justification requests from voter to RPC.
+	pub to_rpc_justif_response_sender: BeefyJustificationResponseSender<B>,
 }
 
 /// Links used by the BEEFY RPC layer, from the BEEFY background voter.
@@ -127,6 +138,12 @@ pub struct BeefyRPCLinks<B: Block> {
 	pub from_voter_justif_stream: BeefyVersionedFinalityProofStream<B>,
 	/// Stream of BEEFY best block hashes coming from the voter.
 	pub from_voter_best_beefy_stream: BeefyBestBlockStream<B>,
+	/// Stream of all signed commitments known to the voter, not only the best ones.
+	pub from_voter_all_justifs_stream: BeefyVersionedFinalityProofStream<B>,
+	/// Sends BEEFY justification requests to the voter.
+	pub to_voter_justif_request_sender: BeefyJustificationRequestSender<B>,
+	/// Stream of answers to BEEFY justification requests coming from the voter.
+	pub from_voter_justif_response_stream: BeefyJustificationResponseStream<B>,
 }
 
 /// Make block importer and link half necessary to tie the background voter to it.
@@ -148,6 +165,14 @@ where
 		BeefyVersionedFinalityProofStream::<B>::channel();
 	let (to_rpc_best_block_sender, from_voter_best_beefy_stream) =
 		BeefyBestBlockStream::<B>::channel();
+	let (to_rpc_all_justifs_sender, from_voter_all_justifs_stream) =
+		BeefyVersionedFinalityProofStream::<B>::channel();
+	let (to_rpc_justif_response_sender, from_voter_justif_response_stream) =
+		BeefyJustificationResponseStream::<B>::channel();
+
+	// RPC -> Voter links
+	let (to_voter_justif_request_sender, from_rpc_justif_request_stream) =
+		BeefyJustificationRequestStream::<B>::channel();
 
 	// BlockImport -> Voter links
 	let (to_voter_justif_sender, from_block_import_justif_stream) =
@@ -164,10 +189,19 @@ where
 	);
 	let voter_links = BeefyVoterLinks {
 		from_block_import_justif_stream,
+		from_rpc_justif_request_stream,
 		to_rpc_justif_sender,
 		to_rpc_best_block_sender,
+		to_rpc_all_justifs_sender,
+		to_rpc_justif_response_sender,
+	};
+	let rpc_links = BeefyRPCLinks {
+		from_voter_best_beefy_stream,
+		from_voter_justif_stream,
+		from_voter_all_justifs_stream,
+		to_voter_justif_request_sender,
+		from_voter_justif_response_stream,
 	};
-	let rpc_links = BeefyRPCLinks { from_voter_best_beefy_stream, from_voter_justif_stream };
 
 	(import, voter_links, rpc_links)
 }
@@ -254,6 +288,7 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S>(
 	// reuse the streams, so we don't miss notifications while waiting for pallet to be available.
 	let mut finality_notifications = client.finality_notification_stream().fuse();
 	let mut block_import_justif = links.from_block_import_justif_stream.subscribe(100_000).fuse();
+	let mut rpc_justif_requests = links.from_rpc_justif_request_stream.subscribe(100_000).fuse();
 
 	let known_peers = Arc::new(Mutex::new(KnownPeers::new()));
 	// Default votes filter is to discard everything.
@@ -274,14 +309,23 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S>(
 	let on_demand_justifications = OnDemandJustificationsEngine::new(
 		network.clone(),
 		justifications_protocol_name.clone(),
-		known_peers,
+		known_peers.clone(),
 		prometheus_registry.clone(),
 	);
+	// Separate engine serving justification requests from the RPC layer, so that they don't
+	// compete with the voter's own requests for mandatory blocks.
+	let rpc_justifications = OnDemandJustificationsEngine::new(
+		network.clone(),
+		justifications_protocol_name,
+		known_peers,
+		None,
+	);
 	let mut beefy_comms = worker::BeefyComms {
 		gossip_engine,
 		gossip_validator,
 		gossip_report_stream,
 		on_demand_justifications,
+		rpc_justifications,
 	};
 
 	// We re-create and re-run the worker in this loop in order to quickly reinit and resume after
@@ -328,11 +372,17 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S>(
 			links: links.clone(),
 			metrics: metrics.clone(),
 			pending_justifications: BTreeMap::new(),
+			pending_justif_requests: VecDeque::new(),
+			justif_request_in_flight: None,
 			persisted_state,
 		};
 
 		match futures::future::select(
-			Box::pin(worker.run(&mut block_import_justif, &mut finality_notifications)),
+			Box::pin(worker.run(
+				&mut block_import_justif,
+				&mut rpc_justif_requests,
+				&mut finality_notifications,
+			)),
 			Box::pin(on_demand_justifications_handler.run()),
 		)
 		.await
@@ -60,6 +60,10 @@ use std::{
 /// of justifications possible in a single session.
 const MAX_BUFFERED_JUSTIFICATIONS: usize = 2400;
 
+/// Bound for the number of justification requests from the RPC layer waiting to be sent to our
+/// peers, since each of them may take several network round-trips to answer.
+const MAX_PENDING_JUSTIF_REQUESTS: usize = 32;
+
 pub(crate) enum RoundAction {
 	Drop,
 	Process,
@@ -321,6 +325,7 @@ pub(crate) struct BeefyComms<B: Block> {
 	pub gossip_validator: Arc<GossipValidator<B>>,
 	pub gossip_report_stream: TracingUnboundedReceiver<PeerReport>,
 	pub on_demand_justifications: OnDemandJustificationsEngine<B>,
+	pub rpc_justifications: OnDemandJustificationsEngine<B>,
 }
 
 /// A BEEFY worker plays the BEEFY protocol
@@ -344,6 +349,11 @@ pub(crate) struct BeefyWorker<B: Block, BE, P, RuntimeApi, S> {
 	pub metrics: Option<VoterMetrics>,
 	/// Buffer holding justifications for future processing.
 	pub pending_justifications: BTreeMap<NumberFor<B>, BeefyVersionedFinalityProof<B>>,
+	/// Justification requests from the RPC layer waiting to be sent to our peers, along with the
+	/// validator set expected to have signed them.
+	pub pending_justif_requests: VecDeque<(NumberFor<B>, ValidatorSet<AuthorityId>)>,
+	/// Justification request from the RPC layer currently in progress.
+	pub justif_request_in_flight: Option<NumberFor<B>>,
 	/// Persisted voter state.
 	pub persisted_state: PersistedState<B>,
 }
@@ -551,11 +561,158 @@ where
 					);
 				}
 			},
-			RoundAction::Drop => metric_inc!(self, beefy_stale_justifications),
+			RoundAction::Drop => {
+				metric_inc!(self, beefy_stale_justifications);
+				// Keep it anyway if we don't have a justification for this block yet.
+				self.store_justification(block_num, &justification)?;
+			},
+		};
+		Ok(())
+	}
+
+	/// Returns the BEEFY justification stored for the block with the given `hash`, if any.
+	fn stored_justification(
+		&self,
+		hash: B::Hash,
+	) -> Result<Option<BeefyVersionedFinalityProof<B>>, Error> {
+		let encoded = self
+			.backend
+			.blockchain()
+			.justifications(hash)
+			.map_err(|e| Error::Backend(e.to_string()))?
+			.and_then(|justifs| justifs.into_justification(BEEFY_ENGINE_ID));
+		encoded
+			.map(|encoded| BeefyVersionedFinalityProof::<B>::decode_all(&mut &encoded[..]))
+			.transpose()
+			.map_err(|e| Error::Backend(format!("Invalid stored justification: {}", e)))
+	}
+
+	/// Store a valid justification for an already BEEFY finalized block, if there isn't one
+	/// stored already, and send it to the RPC layer.
+	fn store_justification(
+		&self,
+		block_num: NumberFor<B>,
+		justification: &BeefyVersionedFinalityProof<B>,
+	) -> Result<(), Error> {
+		let hash = self
+			.backend
+			.blockchain()
+			.expect_block_hash_from_id(&BlockId::Number(block_num))
+			.map_err(|e| Error::Backend(e.to_string()))?;
+		if self.stored_justification(hash)?.is_some() {
+			return Ok(())
+		}
+
+		self.backend
+			.append_justification(hash, (BEEFY_ENGINE_ID, justification.encode()))
+			.map_err(|e| Error::Backend(e.to_string()))?;
+		self.links
+			.to_rpc_all_justifs_sender
+			.notify(|| Ok::<_, ()>(justification.clone()))
+			.expect("forwards closure result; the closure always returns Ok; qed.");
+		Ok(())
+	}
+
+	/// Handle a request for the justification of `block` coming from the RPC layer: answer with
+	/// the stored justification if there is one, otherwise queue an on-demand request to our
+	/// peers.
+	fn handle_justif_request(&mut self, block: NumberFor<B>) -> Result<(), Error> {
+		if self.justif_request_in_flight == Some(block) ||
+			self.pending_justif_requests.iter().any(|(pending, _)| *pending == block)
+		{
+			// Will be answered once the pending request completes.
+			return Ok(())
+		}
+
+		let hash = match self
+			.backend
+			.blockchain()
+			.hash(block)
+			.map_err(|e| Error::Backend(e.to_string()))?
+		{
+			Some(hash) => hash,
+			None => {
+				self.answer_justif_request(block, None);
+				return Ok(())
+			},
 		};
+
+		if let Some(justification) = self.stored_justification(hash)? {
+			self.answer_justif_request(block, Some(justification));
+			return Ok(())
+		}
+
+		// There can't be any justification for blocks not finalized by BEEFY yet.
+		if block > self.persisted_state.voting_oracle.best_beefy_block {
+			self.answer_justif_request(block, None);
+			return Ok(())
+		}
+
+		if self.pending_justif_requests.len() >= MAX_PENDING_JUSTIF_REQUESTS {
+			debug!(
+				target: LOG_TARGET,
+				"🥩 Too many pending justification requests, rejecting request for #{:?}", block
+			);
+			self.answer_justif_request(block, None);
+			return Ok(())
+		}
+
+		match self.runtime.runtime_api().validator_set(hash).map_err(Error::RuntimeApi)? {
+			Some(active_set) => {
+				self.pending_justif_requests.push_back((block, active_set));
+				self.process_justif_requests();
+			},
+			None => self.answer_justif_request(block, None),
+		}
 		Ok(())
 	}
 
+	/// Send the next queued justification request to our peers, if there isn't one in progress.
+	fn process_justif_requests(&mut self) {
+		if !self.comms.rpc_justifications.is_idle() {
+			return
+		}
+		if let Some(block) = self.justif_request_in_flight.take() {
+			// The request completed without a valid justification.
+			self.answer_justif_request(block, None);
+		}
+		while let Some((block, active_set)) = self.pending_justif_requests.pop_front() {
+			self.comms.rpc_justifications.request(block, active_set);
+			if self.comms.rpc_justifications.is_idle() {
+				// No peers to request it from.
+				self.answer_justif_request(block, None);
+			} else {
+				self.justif_request_in_flight = Some(block);
+				return
+			}
+		}
+	}
+
+	/// Handle a valid justification received for a request from the RPC layer.
+	fn handle_requested_justif(&mut self, justification: BeefyVersionedFinalityProof<B>) {
+		let block_num = match justification {
+			VersionedFinalityProof::V1(ref sc) => sc.commitment.block_number,
+		};
+		if self.justif_request_in_flight == Some(block_num) {
+			self.justif_request_in_flight = None;
+		}
+		if let Err(err) = self.store_justification(block_num, &justification) {
+			debug!(target: LOG_TARGET, "🥩 Error storing requested justification: {}", err);
+		}
+		self.answer_justif_request(block_num, Some(justification));
+	}
+
+	fn answer_justif_request(
+		&self,
+		block: NumberFor<B>,
+		justification: Option<BeefyVersionedFinalityProof<B>>,
+	) {
+		self.links
+			.to_rpc_justif_response_sender
+			.notify(|| Ok::<_, ()>((block, justification)))
+			.expect("forwards closure result; the closure always returns Ok; qed.");
+	}
+
 	fn handle_vote(
 		&mut self,
 		vote: VoteMessage<NumberFor<B>, AuthorityId, Signature>,
@@ -647,6 +804,10 @@ where
 			);
 		}
 
+		self.links
+			.to_rpc_all_justifs_sender
+			.notify(|| Ok::<_, ()>(finality_proof.clone()))
+			.expect("forwards closure result; the closure always returns Ok; qed.");
 		self.links
 			.to_rpc_justif_sender
 			.notify(|| Ok::<_, ()>(finality_proof))
@@ -820,6 +981,7 @@ where
 	pub(crate) async fn run(
 		mut self,
 		block_import_justif: &mut Fuse<NotificationReceiver<BeefyVersionedFinalityProof<B>>>,
+		justif_requests: &mut Fuse<NotificationReceiver<NumberFor<B>>>,
 		finality_notifications: &mut Fuse<FinalityNotifications<B>>,
 	) -> (Error, BeefyComms<B>) {
 		info!(
@@ -897,6 +1059,30 @@ where
 						},
 					}
 				},
+				// Process justifications requested by the RPC layer.
+				response_info = self.comms.rpc_justifications.next().fuse() => {
+					match response_info {
+						ResponseInfo::ValidProof(justif, peer_report) => {
+							self.handle_requested_justif(justif);
+							self.comms.gossip_engine.report(peer_report.who, peer_report.cost_benefit);
+						},
+						ResponseInfo::PeerReport(peer_report) => {
+							self.comms.gossip_engine.report(peer_report.who, peer_report.cost_benefit);
+						},
+						ResponseInfo::Pending => {},
+					}
+					self.process_justif_requests();
+					continue;
+				},
+				request = justif_requests.next() => {
+					if let Some(block) = request {
+						if let Err(err) = self.handle_justif_request(block) {
+							debug!(target: LOG_TARGET, "🥩 {}", err);
+							self.answer_justif_request(block, None);
+						}
+					}
+					continue;
+				},
 				justif = block_import_justif.next() => {
 					if let Some(justif) = justif {
 						// Block import justifications have already been verified to be valid
@@ -1062,7 +1248,10 @@ where
 pub(crate) mod tests {
 	use super::*;
 	use crate::{
-		communication::notification::{BeefyBestBlockStream, BeefyVersionedFinalityProofStream},
+		communication::notification::{
+			BeefyBestBlockStream, BeefyJustificationRequestStream,
+			BeefyJustificationResponseStream, BeefyVersionedFinalityProofStream,
+		},
 		tests::{
 			create_beefy_keystore, get_beefy_streams, make_beefy_ids, BeefyPeer, BeefyTestNet,
 			TestApi,
@@ -1083,7 +1272,7 @@ pub(crate) mod tests {
 	use sp_runtime::traits::One;
 	use substrate_test_runtime_client::{
 		runtime::{Block, Digest, DigestItem, Header},
-		Backend,
+		Backend, ClientExt,
 	};
 
 	impl<B: super::Block> PersistedState<B> {
@@ -1128,17 +1317,31 @@ pub(crate) mod tests {
 			BeefyVersionedFinalityProofStream::<Block>::channel();
 		let (to_rpc_best_block_sender, from_voter_best_beefy_stream) =
 			BeefyBestBlockStream::<Block>::channel();
+		let (to_rpc_all_justifs_sender, from_voter_all_justifs_stream) =
+			BeefyVersionedFinalityProofStream::<Block>::channel();
+		let (to_rpc_justif_response_sender, from_voter_justif_response_stream) =
+			BeefyJustificationResponseStream::<Block>::channel();
+		let (to_voter_justif_request_sender, from_rpc_justif_request_stream) =
+			BeefyJustificationRequestStream::<Block>::channel();
 		let (_, from_block_import_justif_stream) =
 			BeefyVersionedFinalityProofStream::<Block>::channel();
 
-		let beefy_rpc_links =
-			BeefyRPCLinks { from_voter_justif_stream, from_voter_best_beefy_stream };
+		let beefy_rpc_links = BeefyRPCLinks {
+			from_voter_justif_stream,
+			from_voter_best_beefy_stream,
+			from_voter_all_justifs_stream,
+			to_voter_justif_request_sender,
+			from_voter_justif_response_stream,
+		};
 		*peer.data.beefy_rpc_links.lock() = Some(beefy_rpc_links);
 
 		let links = BeefyVoterLinks {
 			from_block_import_justif_stream,
+			from_rpc_justif_request_stream,
 			to_rpc_justif_sender,
 			to_rpc_best_block_sender,
+			to_rpc_all_justifs_sender,
+			to_rpc_justif_response_sender,
 		};
 
 		let backend = peer.client().as_backend();
@@ -1158,6 +1361,12 @@ pub(crate) mod tests {
 		);
 		let metrics = None;
 		let on_demand_justifications = OnDemandJustificationsEngine::new(
+			network.clone(),
+			"/beefy/justifs/1".into(),
+			known_peers.clone(),
+			None,
+		);
+		let rpc_justifications = OnDemandJustificationsEngine::new(
 			network.clone(),
 			"/beefy/justifs/1".into(),
 			known_peers,
@@ -1184,6 +1393,7 @@ pub(crate) mod tests {
 			gossip_validator,
 			gossip_report_stream,
 			on_demand_justifications,
+			rpc_justifications,
 		};
 		BeefyWorker {
 			backend,
@@ -1195,6 +1405,8 @@ pub(crate) mod tests {
 			metrics,
 			sync: Arc::new(sync),
 			pending_justifications: BTreeMap::new(),
+			pending_justif_requests: VecDeque::new(),
+			justif_request_in_flight: None,
 			persisted_state,
 		}
 	}
@@ -1587,6 +1799,101 @@ pub(crate) mod tests {
 		assert!(justifs.get(BEEFY_ENGINE_ID).is_some())
 	}
 
+	#[tokio::test]
+	async fn should_answer_justification_requests() {
+		let keys = [Keyring::Alice];
+		let validator_set = ValidatorSet::new(make_beefy_ids(&keys), 0).unwrap();
+		let mut net = BeefyTestNet::new(1);
+		let mut worker = create_beefy_worker(net.peer(0), &keys[0], 1, validator_set.clone());
+		worker.persisted_state.voting_oracle.sessions.clear();
+		worker
+			.persisted_state
+			.voting_oracle
+			.add_session(Rounds::new(1, validator_set.clone()));
+
+		let rpc_links = net.peer(0).data.beefy_rpc_links.lock().clone().unwrap();
+		let mut responses = rpc_links.from_voter_justif_response_stream.subscribe(100_000);
+		let mut all_justifs = rpc_links.from_voter_all_justifs_stream.subscribe(100_000);
+
+		let hashes = net.peer(0).push_blocks(1, false);
+		net.peer(0).client().as_client().finalize_block(hashes[0], None).unwrap();
+		let commitment = Commitment {
+			payload: Payload::from_single_entry(known_payloads::MMR_ROOT_ID, vec![]),
+			block_number: 1,
+			validator_set_id: validator_set.id(),
+		};
+		let justif =
+			VersionedFinalityProof::V1(SignedCommitment { commitment, signatures: vec![None] });
+		worker.finalize(justif.clone()).unwrap();
+		assert_eq!(all_justifs.next().await, Some(justif.clone()));
+
+		// stored justification is returned.
+		worker.handle_justif_request(1).unwrap();
+		assert_eq!(responses.next().await, Some((1, Some(justif))));
+
+		// unknown block.
+		worker.handle_justif_request(5).unwrap();
+		assert_eq!(responses.next().await, Some((5, None)));
+
+		// block not yet finalized by BEEFY.
+		net.peer(0).push_blocks(1, false);
+		worker.handle_justif_request(2).unwrap();
+		assert_eq!(responses.next().await, Some((2, None)));
+		assert!(worker.pending_justif_requests.is_empty());
+	}
+
+	#[tokio::test]
+	async fn should_request_justifications_from_peers() {
+		let keys = [Keyring::Alice];
+		let validator_set = ValidatorSet::new(make_beefy_ids(&keys), 0).unwrap();
+		let mut net = BeefyTestNet::new(1);
+		let mut worker = create_beefy_worker(net.peer(0), &keys[0], 1, validator_set.clone());
+		worker.persisted_state.voting_oracle.sessions.clear();
+		worker
+			.persisted_state
+			.voting_oracle
+			.add_session(Rounds::new(1, validator_set.clone()));
+
+		let rpc_links = net.peer(0).data.beefy_rpc_links.lock().clone().unwrap();
+		let mut responses = rpc_links.from_voter_justif_response_stream.subscribe(100_000);
+		let mut all_justifs = rpc_links.from_voter_all_justifs_stream.subscribe(100_000);
+
+		// blocks finalized by BEEFY, without a stored justification.
+		let hashes = net.peer(0).push_blocks(2, false);
+		net.peer(0).client().as_client().finalize_block(hashes[1], None).unwrap();
+		worker.persisted_state.set_best_beefy(2);
+
+		// there are no peers to request it from.
+		worker.handle_justif_request(1).unwrap();
+		assert_eq!(responses.next().await, Some((1, None)));
+		assert!(worker.pending_justif_requests.is_empty());
+		assert_eq!(worker.justif_request_in_flight, None);
+
+		// a justification received from a peer answers the request in flight and gets stored.
+		let commitment = Commitment {
+			payload: Payload::from_single_entry(known_payloads::MMR_ROOT_ID, vec![]),
+			block_number: 1,
+			validator_set_id: validator_set.id(),
+		};
+		let justif =
+			VersionedFinalityProof::V1(SignedCommitment { commitment, signatures: vec![None] });
+		worker.justif_request_in_flight = Some(1);
+		worker.handle_requested_justif(justif.clone());
+		assert_eq!(worker.justif_request_in_flight, None);
+		assert_eq!(responses.next().await, Some((1, Some(justif.clone()))));
+		assert_eq!(all_justifs.next().await, Some(justif.clone()));
+		worker.handle_justif_request(1).unwrap();
+		assert_eq!(responses.next().await, Some((1, Some(justif))));
+
+		// requests are rejected while too many are pending.
+		for block in 0..MAX_PENDING_JUSTIF_REQUESTS as u64 {
+			worker.pending_justif_requests.push_back((100 + block, validator_set.clone()));
+		}
+		worker.handle_justif_request(2).unwrap();
+		assert_eq!(responses.next().await, Some((2, None)));
+		assert_eq!(worker.pending_justif_requests.len(), MAX_PENDING_JUSTIF_REQUESTS);
+	}
+
 	#[tokio::test]
 	async fn should_init_session() {
 		let keys = &[Keyring::Alice, Keyring::Bob];
@@ -64,6 +64,16 @@ pub trait GrandpaApi<Notification, Hash, Number> {
 	/// in the set and all the intermediary headers to link them together.
 	#[method(name = "grandpa_proveFinality")]
 	async fn prove_finality(&self, block: Number) -> RpcResult<Option<EncodedFinalityProof>>;
+
+	/// Prove finality for all the blocks after `from` up to and including `to`, by returning the
+	/// Justifications for all the authority set changes in the range, a Justification for a block
+	/// at or above `to` and all the intermediary headers to link them together.
+	#[method(name = "grandpa_proveFinalityRange")]
+	async fn prove_finality_range(
+		&self,
+		from: Number,
+		to: Number,
+	) -> RpcResult<Option<EncodedFinalityProof>>;
 }
 
 /// Provides RPC methods for interacting with GRANDPA.
@@ -130,6 +140,20 @@ where
 			})
 			.map_err(Into::into)
 	}
+
+	async fn prove_finality_range(
+		&self,
+		from: NumberFor<Block>,
+		to: NumberFor<Block>,
+	) -> RpcResult<Option<EncodedFinalityProof>> {
+		self.finality_proof_provider
+			.rpc_prove_finality_range(from, to)
+			.map_err(|e| {
+				warn!("Error proving finality of range: {}", e);
+				error::Error::ProveFinalityFailed(e)
+			})
+			.map_err(Into::into)
+	}
 }
 
 #[cfg(test)]
@@ -209,6 +233,14 @@ mod tests {
 					.into(),
 			)))
 		}
+
+		fn rpc_prove_finality_range(
+			&self,
+			_from: NumberFor<Block>,
+			_to: NumberFor<Block>,
+		) -> Result<Option<EncodedFinalityProof>, sc_consensus_grandpa::FinalityProofError> {
+			Err(sc_consensus_grandpa::FinalityProofError::BlockNotYetFinalized)
+		}
 	}
 
 	impl ReportVoterState for TestVoterState {
@@ -417,4 +449,15 @@ mod tests {
 		let finality_proof_rpc: FinalityProof<Header> = Decode::decode(&mut &bytes[..]).unwrap();
 		assert_eq!(finality_proof_rpc, finality_proof);
 	}
+
+	#[tokio::test]
+	async fn prove_finality_range_reports_errors() {
+		let (rpc, _) = setup_io_handler(TestVoterState);
+
+		let err = rpc
+			.call::<_, Option<sp_core::Bytes>>("grandpa_proveFinalityRange", [1, 42])
+			.await
+			.unwrap_err();
+		assert!(err.to_string().contains("Block not yet finalized"));
+	}
 }
@@ -35,14 +35,19 @@
 //! Finality proof provider can choose how to provide finality proof on its own. The incomplete
 //! finality proof (that finalizes some block C that is ancestor of the B and descendant
 //! of the U) could be returned.
+//!
+//! Finality of a range of blocks (U; T] is proved with a [`FinalityRangeProof`], which contains the
+//! justifications of every authority set change in the range, a justification for a block F >= T
+//! and the headers (U; F], so that the verifier can follow the authority set handoffs and check
+//! that all of them are part of the same chain.
 
 use log::{trace, warn};
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use parity_scale_codec::{Decode, Encode};
 use sc_client_api::backend::Backend;
 use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
-use sp_consensus_grandpa::GRANDPA_ENGINE_ID;
+use sp_consensus_grandpa::{AuthorityList, SetId, GRANDPA_ENGINE_ID};
 use sp_runtime::{
 	generic::BlockId,
 	traits::{Block as BlockT, Header as HeaderT, NumberFor, One},
@@ -52,7 +57,8 @@ use crate::{
 	authorities::{AuthoritySetChangeId, AuthoritySetChanges},
 	best_justification,
 	justification::GrandpaJustification,
-	SharedAuthoritySet, LOG_TARGET,
+	warp_proof::{self, set_change_fragment, verify_set_change},
+	BlockNumberOps, SharedAuthoritySet, LOG_TARGET,
 };
 
 const MAX_UNKNOWN_HEADERS: usize = 100_000;
@@ -130,6 +136,35 @@ where
 
 		prove_finality(&*self.backend, authority_set_changes, block, collect_unknown_headers)
 	}
+
+	/// Prove finality for all the blocks in the range `(from; to]` in bytes, see
+	/// [`FinalityRangeProof`].
+	pub fn prove_finality_range(
+		&self,
+		from: NumberFor<Block>,
+		to: NumberFor<Block>,
+	) -> Result<Option<Vec<u8>>, FinalityProofError> {
+		Ok(self.prove_finality_range_proof(from, to)?.map(|proof| proof.encode()))
+	}
+
+	/// Prove finality for all the blocks in the range `(from; to]`, see [`FinalityRangeProof`].
+	pub fn prove_finality_range_proof(
+		&self,
+		from: NumberFor<Block>,
+		to: NumberFor<Block>,
+	) -> Result<Option<FinalityRangeProof<Block>>, FinalityProofError> {
+		let authority_set_changes = if let Some(changes) = self
+			.shared_authority_set
+			.as_ref()
+			.map(SharedAuthoritySet::authority_set_changes)
+		{
+			changes
+		} else {
+			return Ok(None)
+		};
+
+		prove_finality_range(&*self.backend, authority_set_changes, from, to)
+	}
 }
 
 /// Finality for block B is proved by providing:
@@ -145,6 +180,96 @@ pub struct FinalityProof<Header: HeaderT> {
 	pub unknown_headers: Vec<Header>,
 }
 
+/// Finality for all blocks in the range (U; T] is proved by providing:
+/// 1) the justifications for the last block of every authority set that ended in (U; F);
+/// 2) the justification for block F, with F >= T;
+/// 3) headers sub-chain (U; F].
+///
+/// The verifier must know the hash of block U and the authority set finalizing its descendants.
+#[derive(Debug, PartialEq, Encode, Decode, Clone)]
+pub struct FinalityRangeProof<Block: BlockT> {
+	/// Justifications of the authority set changes in the range, followed by the justification of
+	/// block F. Ordered.
+	pub justifications: Vec<GrandpaJustification<Block>>,
+	/// The headers in the range (U; F]. Ordered.
+	pub headers: Vec<Block::Header>,
+}
+
+impl<Block: BlockT> FinalityRangeProof<Block> {
+	/// Verifies the proof for the range starting after the block `from`, which is finalized by the
+	/// authority set with the given set id and authorities, and ending at `to`.
+	///
+	/// Returns the set id and authorities of the authority set finalizing the descendants of the
+	/// last header of the proof.
+	pub fn verify(
+		&self,
+		from: Block::Hash,
+		to: NumberFor<Block>,
+		set_id: SetId,
+		authorities: AuthorityList,
+	) -> Result<(SetId, AuthorityList), FinalityProofError>
+	where
+		NumberFor<Block>: BlockNumberOps,
+	{
+		let invalid = |err: &str| FinalityProofError::InvalidProof(err.to_string());
+
+		let mut parent_hash = from;
+		let mut parent_number = None;
+		let mut positions = HashMap::with_capacity(self.headers.len());
+		for (position, header) in self.headers.iter().enumerate() {
+			if *header.parent_hash() != parent_hash ||
+				parent_number.map_or(false, |number| *header.number() != number + One::one())
+			{
+				return Err(invalid("Headers are not a chain starting at the first block"))
+			}
+			parent_hash = header.hash();
+			parent_number = Some(*header.number());
+			positions.insert(parent_hash, position);
+		}
+
+		match parent_number {
+			Some(number) if number >= to => {},
+			_ => return Err(invalid("Headers do not reach the last block")),
+		}
+
+		let mut current_set_id = set_id;
+		let mut current_authorities = authorities;
+		let mut last_position = None;
+		for (index, justification) in self.justifications.iter().enumerate() {
+			let position = *positions
+				.get(&justification.target().1)
+				.ok_or_else(|| invalid("Justification target is not part of the headers"))?;
+			if last_position.map_or(false, |last| position <= last) {
+				return Err(invalid("Justifications are not ordered"))
+			}
+			last_position = Some(position);
+
+			let is_last = index == self.justifications.len() - 1;
+			match verify_set_change::<Block>(
+				&self.headers[position],
+				justification,
+				current_set_id,
+				&current_authorities,
+			)? {
+				Some((set_id, authorities)) => {
+					current_set_id = set_id;
+					current_authorities = authorities;
+				},
+				// Only the last justification is allowed to be missing the authority set change.
+				None if !is_last =>
+					return Err(invalid("Header is missing authority set change digest")),
+				None => {},
+			}
+		}
+
+		if last_position != Some(self.headers.len() - 1) {
+			return Err(invalid("Last header is not justified"))
+		}
+
+		Ok((current_set_id, current_authorities))
+	}
+}
+
 /// Errors occurring when trying to prove finality
 #[derive(Debug, thiserror::Error)]
 pub enum FinalityProofError {
@@ -155,6 +280,15 @@ pub enum FinalityProofError {
 	/// in the latest authority set, and the subscription API is more appropriate.
 	#[error("Block not covered by authority set changes")]
 	BlockNotInAuthoritySetChanges,
+	/// The requested range of blocks can not be proven.
+	#[error("Invalid range: {0}")]
+	InvalidRange(String),
+	/// The finality proof is invalid.
+	#[error("Invalid finality proof: {0}")]
+	InvalidProof(String),
+	/// Errors when reading or verifying authority set changes.
+	#[error(transparent)]
+	SetChange(#[from] warp_proof::Error),
 	/// Errors originating from the client.
 	#[error(transparent)]
 	Client(#[from] sp_blockchain::Error),
@@ -256,6 +390,97 @@ where
 	}))
 }
 
+/// Prove finality for all the blocks in the range `(from; to]`, by returning the justifications of
+/// all the authority set changes in the range, followed by the justification for the last block of
+/// the authority set of which `to` is part of, or a justification for the latest finalized block if
+/// `to` is part of the current authority set.
+fn prove_finality_range<Block, B>(
+	backend: &B,
+	authority_set_changes: AuthoritySetChanges<NumberFor<Block>>,
+	from: NumberFor<Block>,
+	to: NumberFor<Block>,
+) -> Result<Option<FinalityRangeProof<Block>>, FinalityProofError>
+where
+	Block: BlockT,
+	B: Backend<Block>,
+{
+	if from >= to {
+		return Err(FinalityProofError::InvalidRange(format!(
+			"start #{} is not lower than end #{}",
+			from, to
+		)))
+	}
+
+	let blockchain = backend.blockchain();
+	let finalized_number = blockchain.info().finalized_number;
+	if finalized_number < to {
+		trace!(
+			target: LOG_TARGET,
+			"Requested finality proof for range ending at #{} while we only have finalized #{}.",
+			to,
+			finalized_number,
+		);
+		return Err(FinalityProofError::BlockNotYetFinalized)
+	}
+
+	let set_changes = authority_set_changes
+		.iter_from(from)
+		.ok_or(FinalityProofError::BlockNotInAuthoritySetChanges)?;
+
+	let mut justifications = Vec::new();
+	let mut last_block = None;
+	for (_, set_last_block) in set_changes {
+		let Some(fragment) = set_change_fragment(blockchain, *set_last_block)? else {
+			return Err(FinalityProofError::InvalidRange(format!(
+				"authority set was changed through a forced change at #{}",
+				set_last_block
+			)))
+		};
+		justifications.push(fragment.justification);
+		if *set_last_block >= to {
+			last_block = Some(*set_last_block);
+			break
+		}
+	}
+
+	let last_block = match last_block {
+		Some(last_block) => last_block,
+		None => match best_justification(backend)?
+			.filter(|justification: &GrandpaJustification<Block>| justification.target().0 >= to)
+		{
+			Some(justification) => {
+				let last_block = justification.target().0;
+				justifications.push(justification);
+				last_block
+			},
+			None => {
+				trace!(
+					target: LOG_TARGET,
+					"No justification found for the latest finalized block. \
+					Returning empty proof.",
+				);
+				return Ok(None)
+			},
+		},
+	};
+
+	let mut headers = Vec::new();
+	let mut current = from + One::one();
+	while current <= last_block {
+		if headers.len() >= MAX_UNKNOWN_HEADERS {
+			return Err(FinalityProofError::InvalidRange(format!(
+				"more than {} headers in the range",
+				MAX_UNKNOWN_HEADERS
+			)))
+		}
+		let hash = blockchain.expect_block_hash_from_id(&BlockId::Number(current))?;
+		headers.push(blockchain.expect_header(hash)?);
+		current += One::one();
+	}
+
+	Ok(Some(FinalityRangeProof { justifications, headers }))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -269,8 +494,8 @@ mod tests {
 	use sp_keyring::Ed25519Keyring;
 	use substrate_test_runtime_client::{
 		runtime::{Block, Header, H256},
-		Backend as TestBackend, ClientBlockImportExt, ClientExt, DefaultTestClientBuilderExt,
-		TestClient, TestClientBuilder, TestClientBuilderExt,
+		Backend as TestBackend, BlockBuilderExt, ClientBlockImportExt, ClientExt,
+		DefaultTestClientBuilderExt, TestClient, TestClientBuilder, TestClientBuilderExt,
 	};
 
 	/// Check GRANDPA proof-of-finality for the given block.
@@ -595,4 +820,118 @@ mod tests {
 			}
 		);
 	}
+
+	fn authorities(keyrings: &[Ed25519Keyring]) -> AuthorityList {
+		keyrings.iter().map(|keyring| (keyring.public().into(), 1)).collect()
+	}
+
+	/// Builds a chain where the authority set changes to each of the given `changes` at the
+	/// corresponding block, justified by the previous authorities.
+	fn test_blockchain_with_set_changes(
+		number_of_blocks: u64,
+		changes: &[(u64, Ed25519Keyring)],
+	) -> (Arc<TestClient>, Arc<TestBackend>, Vec<Block>) {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let mut client = Arc::new(builder.build());
+
+		let mut blocks = Vec::new();
+		let mut current_authorities = Ed25519Keyring::Alice;
+		for n in 1..=number_of_blocks {
+			let mut builder = BlockBuilderBuilder::new(&*client)
+				.on_parent_block(client.chain_info().best_hash)
+				.with_parent_block_number(client.chain_info().best_number)
+				.build()
+				.unwrap();
+			let change = changes.iter().position(|(number, _)| *number == n);
+			if let Some(change) = change {
+				let next_authorities = authorities(&[changes[change].1]);
+				let digest = sp_runtime::generic::DigestItem::Consensus(
+					ID,
+					sp_consensus_grandpa::ConsensusLog::ScheduledChange(
+						sp_consensus_grandpa::ScheduledChange { delay: 0u64, next_authorities },
+					)
+					.encode(),
+				);
+				builder.push_deposit_log_digest_item(digest).unwrap();
+			}
+			let block = builder.build().unwrap().block;
+			block_on(client.import(BlockOrigin::Own, block.clone())).unwrap();
+
+			if let Some(change) = change {
+				let commit = create_commit(block.clone(), n, change as u64, &[current_authorities]);
+				let justification = GrandpaJustification::from_commit(&client, n, commit).unwrap();
+				client.finalize_block(block.hash(), Some((ID, justification.encode()))).unwrap();
+				current_authorities = changes[change].1;
+			}
+			blocks.push(block);
+		}
+		(client, backend, blocks)
+	}
+
+	#[test]
+	fn finality_range_proof_works() {
+		let (client, backend, blocks) = test_blockchain_with_set_changes(
+			16,
+			&[(5, Ed25519Keyring::Bob), (10, Ed25519Keyring::Charlie)],
+		);
+		let mut authority_set_changes = AuthoritySetChanges::empty();
+		authority_set_changes.append(0, 5);
+		authority_set_changes.append(1, 10);
+
+		let commit = create_commit(blocks[14].clone(), 15, 2, &[Ed25519Keyring::Charlie]);
+		let grandpa_just15 = GrandpaJustification::from_commit(&client, 15, commit).unwrap();
+		client.finalize_block(blocks[14].hash(), None).unwrap();
+		store_best_justification(&client, &grandpa_just15);
+
+		let alice = authorities(&[Ed25519Keyring::Alice]);
+
+		// the range ends in the latest set, so the best justification is used.
+		let proof =
+			prove_finality_range::<Block, _>(&*backend, authority_set_changes.clone(), 2, 12)
+				.unwrap()
+				.unwrap();
+		assert_eq!(proof.justifications.len(), 3);
+		assert_eq!(proof.justifications[2], grandpa_just15);
+		assert_eq!(
+			proof.headers,
+			blocks[2..15].iter().map(|block| block.header().clone()).collect::<Vec<_>>(),
+		);
+		assert_eq!(
+			proof.verify(blocks[1].hash(), 12, 0, alice.clone()).unwrap(),
+			(2, authorities(&[Ed25519Keyring::Charlie])),
+		);
+
+		// the range ends in the first set, so the proof ends at the first set change.
+		let proof = prove_finality_range::<Block, _>(&*backend, authority_set_changes, 2, 4)
+			.unwrap()
+			.unwrap();
+		assert_eq!(proof.justifications.len(), 1);
+		assert_eq!(proof.headers.len(), 3);
+		assert_eq!(
+			proof.verify(blocks[1].hash(), 4, 0, alice.clone()).unwrap(),
+			(1, authorities(&[Ed25519Keyring::Bob])),
+		);
+
+		// wrong authorities, starting block or range end are rejected.
+		assert!(proof.verify(blocks[1].hash(), 4, 1, alice.clone()).is_err());
+		assert!(proof.verify(blocks[0].hash(), 4, 0, alice.clone()).is_err());
+		assert!(proof.verify(blocks[1].hash(), 6, 0, alice).is_err());
+	}
+
+	#[test]
+	fn finality_range_proof_fails_for_invalid_range() {
+		let (_, backend, _) = test_blockchain(6, &[4]);
+		let mut authority_set_changes = AuthoritySetChanges::empty();
+		authority_set_changes.append(0, 4);
+
+		assert!(matches!(
+			prove_finality_range::<Block, _>(&*backend, authority_set_changes.clone(), 3, 3),
+			Err(FinalityProofError::InvalidRange(_)),
+		));
+		assert!(matches!(
+			prove_finality_range::<Block, _>(&*backend, authority_set_changes, 3, 5),
+			Err(FinalityProofError::BlockNotYetFinalized),
+		));
+	}
 }
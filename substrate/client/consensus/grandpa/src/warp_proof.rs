@@ -116,30 +116,12 @@ impl<Block: BlockT> WarpSyncProof<Block> {
 		let set_changes = set_changes.iter_from(begin_number).ok_or(Error::MissingData)?;
 
 		for (_, last_block) in set_changes {
-			let hash = blockchain.block_hash_from_id(&BlockId::Number(*last_block))?
-				.expect("header number comes from previously applied set changes; corresponding hash must exist in db; qed.");
-
-			let header = blockchain
-				.header(hash)?
-				.expect("header hash obtained from header number exists in db; corresponding header must exist in db too; qed.");
-
-			// the last block in a set is the one that triggers a change to the next set,
-			// therefore the block must have a digest that signals the authority set change
-			if find_scheduled_change::<Block>(&header).is_none() {
+			let Some(proof) = set_change_fragment(blockchain, *last_block)? else {
 				// if it doesn't contain a signal for standard change then the set must have changed
 				// through a forced changed, in which case we stop collecting proofs as the chain of
 				// trust in authority handoffs was broken.
 				break
-			}
-
-			let justification = blockchain
-				.justifications(header.hash())?
-				.and_then(|just| just.into_justification(GRANDPA_ENGINE_ID))
-				.ok_or_else(|| Error::MissingData)?;
-
-			let justification = GrandpaJustification::<Block>::decode_all(&mut &justification[..])?;
-
-			let proof = WarpSyncFragment { header: header.clone(), justification };
+			};
 			let proof_size = proof.encoded_size();
 
 			// Check for the limit. We remove some bytes from the maximum size, because we're only
@@ -207,34 +189,83 @@ impl<Block: BlockT> WarpSyncProof<Block> {
 			if let Some((set_id, list)) = hard_forks.get(&(hash, number)) {
 				current_set_id = *set_id;
 				current_authorities = list.clone();
-			} else {
-				proof
-					.justification
-					.verify(current_set_id, &current_authorities)
-					.map_err(|err| Error::InvalidProof(err.to_string()))?;
-
-				if proof.justification.target().1 != hash {
-					return Err(Error::InvalidProof(
-						"Mismatch between header and justification".to_owned(),
-					))
-				}
-
-				if let Some(scheduled_change) = find_scheduled_change::<Block>(&proof.header) {
-					current_authorities = scheduled_change.next_authorities;
-					current_set_id += 1;
-				} else if fragment_num != self.proofs.len() - 1 || !self.is_finished {
-					// Only the last fragment of the last proof message is allowed to be missing the
-					// authority set change.
-					return Err(Error::InvalidProof(
-						"Header is missing authority set change digest".to_string(),
-					))
-				}
+			} else if let Some((set_id, authorities)) = verify_set_change::<Block>(
+				&proof.header,
+				&proof.justification,
+				current_set_id,
+				&current_authorities,
+			)? {
+				current_set_id = set_id;
+				current_authorities = authorities;
+			} else if fragment_num != self.proofs.len() - 1 || !self.is_finished {
+				// Only the last fragment of the last proof message is allowed to be missing the
+				// authority set change.
+				return Err(Error::InvalidProof(
+					"Header is missing authority set change digest".to_string(),
+				))
 			}
 		}
 		Ok((current_set_id, current_authorities))
 	}
 }
 
+/// Fetches the header and justification of the last block of an authority set. Returns `None` if
+/// the set was not ended by a standard authority set change.
+pub(crate) fn set_change_fragment<Block, Blockchain>(
+	blockchain: &Blockchain,
+	last_block: NumberFor<Block>,
+) -> Result<Option<WarpSyncFragment<Block>>, Error>
+where
+	Block: BlockT,
+	Blockchain: BlockchainBackend<Block>,
+{
+	let hash = blockchain.block_hash_from_id(&BlockId::Number(last_block))?
+		.expect("header number comes from previously applied set changes; corresponding hash must exist in db; qed.");
+
+	let header = blockchain
+		.header(hash)?
+		.expect("header hash obtained from header number exists in db; corresponding header must exist in db too; qed.");
+
+	// the last block in a set is the one that triggers a change to the next set,
+	// therefore the block must have a digest that signals the authority set change
+	if find_scheduled_change::<Block>(&header).is_none() {
+		return Ok(None)
+	}
+
+	let justification = blockchain
+		.justifications(header.hash())?
+		.and_then(|just| just.into_justification(GRANDPA_ENGINE_ID))
+		.ok_or_else(|| Error::MissingData)?;
+
+	let justification = GrandpaJustification::<Block>::decode_all(&mut &justification[..])?;
+
+	Ok(Some(WarpSyncFragment { header, justification }))
+}
+
+/// Verifies that `justification` finalizes `header` with the given authority set. Returns the next
+/// authority set if the header signals a standard authority set change.
+pub(crate) fn verify_set_change<Block>(
+	header: &Block::Header,
+	justification: &GrandpaJustification<Block>,
+	set_id: SetId,
+	authorities: &AuthorityList,
+) -> Result<Option<(SetId, AuthorityList)>, Error>
+where
+	Block: BlockT,
+	NumberFor<Block>: BlockNumberOps,
+{
+	justification
+		.verify(set_id, authorities)
+		.map_err(|err| Error::InvalidProof(err.to_string()))?;
+
+	if justification.target().1 != header.hash() {
+		return Err(Error::InvalidProof("Mismatch between header and justification".to_owned()))
+	}
+
+	Ok(find_scheduled_change::<Block>(header)
+		.map(|scheduled_change| (set_id + 1, scheduled_change.next_authorities)))
+}
+
 /// Implements network API for warp sync.
 pub struct NetworkProvider<Block: BlockT, Backend: ClientBackend<Block>>
 where
@@ -184,6 +184,17 @@ where
 
 		Ok(epoch.as_ref().clone())
 	}
+
+	/// The slot of the new block, derived from the timestamp inherent if present, so that it
+	/// follows the time advanced with [`EngineCommand::AdvanceTime`](crate::EngineCommand).
+	fn slot(&self, inherents: &InherentData) -> Result<Slot, Error> {
+		if let Some(timestamp) = inherents.timestamp_inherent_data()? {
+			return Ok(Slot::from_timestamp(timestamp, self.config.slot_duration()))
+		}
+		inherents
+			.babe_inherent_data()?
+			.ok_or_else(|| Error::StringError("No babe inherent data".into()))
+	}
 }
 
 impl<B, C, P> ConsensusDataProvider<B> for BabeConsensusDataProvider<B, C, P>
@@ -200,9 +211,7 @@ where
 	type Proof = P;
 
 	fn create_digest(&self, parent: &B::Header, inherents: &InherentData) -> Result<Digest, Error> {
-		let slot = inherents
-			.babe_inherent_data()?
-			.ok_or_else(|| Error::StringError("No babe inherent data".into()))?;
+		let slot = self.slot(inherents)?;
 		let epoch = self.epoch(parent, slot)?;
 
 		// this is a dev node environment, we should always be able to claim a slot.
@@ -267,9 +276,7 @@ where
 		inherents: &InherentData,
 		_proof: Self::Proof,
 	) -> Result<(), Error> {
-		let slot = inherents
-			.babe_inherent_data()?
-			.ok_or_else(|| Error::StringError("No babe inherent data".into()))?;
+		let slot = self.slot(inherents)?;
 		let epoch_changes = self.epoch_changes.shared_data();
 		let mut epoch_descriptor = epoch_changes
 			.epoch_descriptor_for_child_of(
@@ -39,7 +39,9 @@ use std::{marker::PhantomData, sync::Arc, time::Duration};
 
 mod error;
 mod finalize_block;
+mod revert_block;
 mod seal_block;
+mod storage_override;
 
 pub mod consensus;
 pub mod rpc;
@@ -48,11 +50,13 @@ pub use self::{
 	consensus::ConsensusDataProvider,
 	error::Error,
 	finalize_block::{finalize_block, FinalizeBlockParams},
-	rpc::{CreatedBlock, EngineCommand},
+	revert_block::{revert_block, RevertBlockParams},
+	rpc::{CreatedBlock, EngineCommand, MAX_SEAL_BLOCKS},
 	seal_block::{seal_block, SealBlockParams, MAX_PROPOSAL_DURATION},
+	storage_override::StorageOverride,
 };
 use sc_transaction_pool_api::TransactionPool;
-use sp_api::ProvideRuntimeApi;
+use sp_api::{CallApiAt, ProvideRuntimeApi};
 
 const LOG_TARGET: &str = "manual-seal";
 
@@ -87,7 +91,7 @@ where
 }
 
 /// Params required to start the instant sealing authorship task.
-pub struct ManualSealParams<B: BlockT, BI, E, C: ProvideRuntimeApi<B>, TP, SC, CS, CIDP, P> {
+pub struct ManualSealParams<B: BlockT, BI, E, C: ProvideRuntimeApi<B>, CB, TP, SC, CS, CIDP, P> {
 	/// Block import instance.
 	pub block_import: BI,
 
@@ -97,6 +101,10 @@ pub struct ManualSealParams<B: BlockT, BI, E, C: ProvideRuntimeApi<B>, TP, SC, C
 	/// Client instance
 	pub client: Arc<C>,
 
+	/// Client backend, required to revert the chain with [`EngineCommand::RevertTo`] and to
+	/// override storage with [`EngineCommand::OverrideStorage`].
+	pub backend: Option<Arc<CB>>,
+
 	/// Shared reference to the transaction pool.
 	pub pool: Arc<TP>,
 
@@ -156,16 +164,17 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 		mut block_import,
 		mut env,
 		client,
+		backend,
 		pool,
 		mut commands_stream,
 		select_chain,
 		consensus_data_provider,
 		create_inherent_data_providers,
-	}: ManualSealParams<B, BI, E, C, TP, SC, CS, CIDP, P>,
+	}: ManualSealParams<B, BI, E, C, CB, TP, SC, CS, CIDP, P>,
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
-	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + CallApiAt<B> + 'static,
 	CB: ClientBackend<B> + 'static,
 	E: Environment<B> + 'static,
 	E::Proposer: Proposer<B, Proof = P>,
@@ -175,6 +184,8 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 	CIDP: CreateInherentDataProviders<B, ()>,
 	P: codec::Encode + Send + Sync + 'static,
 {
+	let mut time_offset = 0;
+	let mut storage_overrides = Vec::new();
 	while let Some(command) = commands_stream.next().await {
 		match command {
 			EngineCommand::SealNewBlock { create_empty, finalize, parent_hash, sender } => {
@@ -190,9 +201,83 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 					pool: pool.clone(),
 					client: client.clone(),
 					create_inherent_data_providers: &create_inherent_data_providers,
+					time_offset,
+					storage_overrides: &mut storage_overrides,
+					backend: backend.clone(),
 				})
 				.await;
 			},
+			EngineCommand::SealBlocks { count, mut sender } => {
+				if count > MAX_SEAL_BLOCKS {
+					let err = Error::StringError(format!(
+						"Can't seal more than {} blocks at once",
+						MAX_SEAL_BLOCKS
+					));
+					rpc::send_result(&mut sender, Err(err));
+					continue
+				}
+				let result = async {
+					let mut blocks = Vec::new();
+					for _ in 0..count {
+						let (block_sender, block_receiver) = futures::channel::oneshot::channel();
+						seal_block(SealBlockParams {
+							sender: Some(block_sender),
+							parent_hash: None,
+							finalize: false,
+							create_empty: true,
+							env: &mut env,
+							select_chain: &select_chain,
+							block_import: &mut block_import,
+							consensus_data_provider: consensus_data_provider.as_deref(),
+							pool: pool.clone(),
+							client: client.clone(),
+							create_inherent_data_providers: &create_inherent_data_providers,
+							time_offset,
+							storage_overrides: &mut storage_overrides,
+							backend: backend.clone(),
+						})
+						.await;
+						blocks.push(block_receiver.await??);
+					}
+					Ok::<_, Error>(blocks)
+				}
+				.await;
+				rpc::send_result(&mut sender, result);
+			},
+			EngineCommand::AdvanceTime { duration, mut sender } => {
+				time_offset = time_offset.saturating_add(duration);
+				log::info!(
+					target: LOG_TARGET,
+					"⏩ Advanced time by {} ms, total {} ms",
+					duration,
+					time_offset
+				);
+				rpc::send_result(&mut sender, Ok(time_offset));
+			},
+			EngineCommand::OverrideStorage { overrides, mut sender } => {
+				if backend.is_none() {
+					// Accepting the overrides would make sealing fail until they are cleared.
+					let err =
+						Error::StringError("Overriding storage requires the client backend".into());
+					rpc::send_result(&mut sender, Err(err));
+					continue
+				}
+				log::info!(
+					target: LOG_TARGET,
+					"✍️ Applying {} storage overrides to the next block",
+					overrides.len()
+				);
+				storage_overrides.extend(overrides);
+				rpc::send_result(&mut sender, Ok(()));
+			},
+			EngineCommand::RevertTo { hash, sender } =>
+				revert_block(RevertBlockParams {
+					hash,
+					sender,
+					client: client.clone(),
+					backend: backend.clone(),
+				})
+				.await,
 			EngineCommand::FinalizeBlock { hash, sender, justification } => {
 				let justification = justification.map(|j| (MANUAL_SEAL_ENGINE_ID, j));
 				finalize_block(FinalizeBlockParams {
@@ -224,7 +309,7 @@ pub async fn run_instant_seal<B, BI, CB, E, C, TP, SC, CIDP, P>(
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
-	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + CallApiAt<B> + 'static,
 	CB: ClientBackend<B> + 'static,
 	E: Environment<B> + 'static,
 	E::Proposer: Proposer<B, Proof = P>,
@@ -246,6 +331,7 @@ pub async fn run_instant_seal<B, BI, CB, E, C, TP, SC, CIDP, P>(
 		block_import,
 		env,
 		client,
+		backend: None,
 		pool,
 		commands_stream,
 		select_chain,
@@ -274,7 +360,7 @@ pub async fn run_instant_seal_and_finalize<B, BI, CB, E, C, TP, SC, CIDP, P>(
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
-	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + CallApiAt<B> + 'static,
 	CB: ClientBackend<B> + 'static,
 	E: Environment<B> + 'static,
 	E::Proposer: Proposer<B, Proof = P>,
@@ -296,6 +382,7 @@ pub async fn run_instant_seal_and_finalize<B, BI, CB, E, C, TP, SC, CIDP, P>(
 		block_import,
 		env,
 		client,
+		backend: None,
 		pool,
 		commands_stream,
 		select_chain,
@@ -347,11 +434,14 @@ pub async fn run_delayed_finalize<B, CB, C, S>(
 mod tests {
 	use super::*;
 	use sc_basic_authorship::ProposerFactory;
+	use sc_client_api::StorageProvider;
 	use sc_consensus::ImportedAux;
 	use sc_transaction_pool::{BasicPool, FullChainApi, Options, RevalidationType};
 	use sc_transaction_pool_api::{MaintainedTransactionPool, TransactionPool, TransactionSource};
+	use sp_core::storage::{StorageData, StorageKey};
 	use sp_inherents::InherentData;
 	use sp_runtime::generic::{Digest, DigestItem};
+	use sp_timestamp::TimestampInherentData;
 	use substrate_test_runtime_client::{
 		AccountKeyring::*, DefaultTestClientBuilderExt, TestClientBuilder, TestClientBuilderExt,
 	};
@@ -393,6 +483,35 @@ mod tests {
 		}
 	}
 
+	/// Records the timestamp inherent of the sealed blocks.
+	#[derive(Clone, Default)]
+	struct TimestampRecorder(Arc<std::sync::Mutex<Vec<u64>>>);
+	impl<B: BlockT> ConsensusDataProvider<B> for TimestampRecorder {
+		type Proof = ();
+
+		fn create_digest(
+			&self,
+			_parent: &B::Header,
+			inherents: &InherentData,
+		) -> Result<Digest, Error> {
+			let timestamp = inherents
+				.timestamp_inherent_data()?
+				.ok_or_else(|| Error::StringError("Missing timestamp inherent".into()))?;
+			self.0.lock().unwrap().push(*timestamp);
+			Ok(Digest { logs: vec![] })
+		}
+
+		fn append_block_import(
+			&self,
+			_parent: &B::Header,
+			_params: &mut BlockImportParams<B>,
+			_inherents: &InherentData,
+			_proof: Self::Proof,
+		) -> Result<(), Error> {
+			Ok(())
+		}
+	}
+
 	#[tokio::test]
 	async fn instant_seal() {
 		let builder = TestClientBuilder::new();
@@ -433,6 +552,7 @@ mod tests {
 			block_import: client.clone(),
 			env,
 			client: client.clone(),
+			backend: None,
 			pool: pool.clone(),
 			commands_stream,
 			select_chain,
@@ -512,6 +632,7 @@ mod tests {
 			commands_stream,
 			env,
 			client: client.clone(),
+			backend: None,
 			pool: pool.clone(),
 			select_chain,
 			create_inherent_data_providers: |_, _| async { Ok(()) },
@@ -593,6 +714,7 @@ mod tests {
 			block_import: client.clone(),
 			env,
 			client: client.clone(),
+			backend: None,
 			pool: pool.clone(),
 			commands_stream,
 			select_chain,
@@ -679,6 +801,7 @@ mod tests {
 			block_import: client.clone(),
 			env,
 			client: client.clone(),
+			backend: None,
 			pool: pool.clone(),
 			commands_stream,
 			select_chain,
@@ -785,6 +908,7 @@ mod tests {
 			block_import: client.clone(),
 			env,
 			client: client.clone(),
+			backend: None,
 			pool: pool.clone(),
 			commands_stream,
 			select_chain,
@@ -811,4 +935,276 @@ mod tests {
 		let header = client.header(created_block.hash).unwrap().unwrap();
 		assert_eq!(header.number, 1);
 	}
+
+	#[tokio::test]
+	async fn manual_seal_blocks_advance_time_and_revert() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+		let timestamps = TimestampRecorder::default();
+
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			backend: Some(backend),
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: Some(Box::new(timestamps.clone())),
+			create_inherent_data_providers: |_, _| async {
+				Ok(sp_timestamp::InherentDataProvider::from_system_time())
+			},
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealBlocks { count: 3, sender: Some(tx) })
+			.await
+			.unwrap();
+		let created_blocks = rx.await.unwrap().unwrap();
+		assert_eq!(created_blocks.len(), 3);
+		assert_eq!(client.info().best_number, 3);
+		assert_eq!(client.info().best_hash, created_blocks[2].hash);
+		assert_eq!(timestamps.0.lock().unwrap().len(), 3);
+
+		for (duration, total) in [(1_000, 1_000), (500, 1_500)] {
+			let (tx, rx) = futures::channel::oneshot::channel();
+			sink.send(EngineCommand::AdvanceTime { duration, sender: Some(tx) })
+				.await
+				.unwrap();
+			assert_eq!(rx.await.unwrap().unwrap(), total);
+		}
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::RevertTo { hash: created_blocks[0].hash, sender: Some(tx) })
+			.await
+			.unwrap();
+		rx.await.unwrap().unwrap();
+		assert_eq!(client.info().best_number, 1);
+		assert_eq!(client.info().best_hash, created_blocks[0].hash);
+
+		// blocks are sealed on top of the reverted chain, with the time advanced.
+		let before = *sp_timestamp::Timestamp::current();
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealBlocks { count: 1, sender: Some(tx) })
+			.await
+			.unwrap();
+		let created_block = &rx.await.unwrap().unwrap()[0];
+		let after = *sp_timestamp::Timestamp::current();
+		let header = client.header(created_block.hash).unwrap().unwrap();
+		assert_eq!(header.number, 2);
+		assert_eq!(header.parent_hash, created_blocks[0].hash);
+		let timestamp = *timestamps.0.lock().unwrap().last().unwrap();
+		assert!((before + 1_500..=after + 1_500).contains(&timestamp));
+
+		// unknown blocks can not be reverted to.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::RevertTo { hash: created_blocks[2].hash, sender: Some(tx) })
+			.await
+			.unwrap();
+		assert!(rx.await.unwrap().is_err());
+	}
+
+	#[tokio::test]
+	async fn manual_seal_rejects_too_many_blocks_and_time_without_timestamp() {
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			backend: None,
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: None,
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealBlocks { count: MAX_SEAL_BLOCKS + 1, sender: Some(tx) })
+			.await
+			.unwrap();
+		assert!(rx.await.unwrap().is_err());
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::AdvanceTime { duration: 1_000, sender: Some(tx) })
+			.await
+			.unwrap();
+		assert_eq!(rx.await.unwrap().unwrap(), 1_000);
+
+		// there is no timestamp to advance.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealBlocks { count: 1, sender: Some(tx) })
+			.await
+			.unwrap();
+		assert!(rx.await.unwrap().is_err());
+		assert_eq!(client.info().best_number, 0);
+	}
+
+	#[tokio::test]
+	async fn manual_seal_applies_storage_overrides() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			backend: Some(backend),
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: None,
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+
+		let key = b"manual-seal:override".to_vec();
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::OverrideStorage {
+			overrides: vec![StorageOverride::Storage {
+				key: key.clone().into(),
+				value: Some(vec![42].into()),
+			}],
+			sender: Some(tx),
+		})
+		.await
+		.unwrap();
+		rx.await.unwrap().unwrap();
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealBlocks { count: 2, sender: Some(tx) })
+			.await
+			.unwrap();
+		let created_blocks = rx.await.unwrap().unwrap();
+
+		let key = StorageKey(key);
+		assert_eq!(client.storage(genesis_hash, &key).unwrap(), None);
+		for created_block in created_blocks {
+			let value = client.storage(created_block.hash, &key).unwrap();
+			assert_eq!(value, Some(StorageData(vec![42])));
+		}
+	}
+
+	#[tokio::test]
+	async fn manual_seal_rejects_storage_overrides_without_backend() {
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			backend: None,
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: None,
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+
+		let key = b"manual-seal:override".to_vec();
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::OverrideStorage {
+			overrides: vec![StorageOverride::Storage {
+				key: key.clone().into(),
+				value: Some(vec![42].into()),
+			}],
+			sender: Some(tx),
+		})
+		.await
+		.unwrap();
+		assert!(rx.await.unwrap().is_err());
+
+		// the rejected overrides do not prevent sealing.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealBlocks { count: 1, sender: Some(tx) })
+			.await
+			.unwrap();
+		let created_block = &rx.await.unwrap().unwrap()[0];
+		let value = client.storage(created_block.hash, &StorageKey(key)).unwrap();
+		assert_eq!(value, None);
+	}
 }
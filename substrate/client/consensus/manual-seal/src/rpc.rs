@@ -18,7 +18,7 @@
 
 //! RPC interface for the `ManualSeal` Engine.
 
-use crate::error::Error;
+use crate::{error::Error, StorageOverride};
 use futures::{
 	channel::{mpsc, oneshot},
 	SinkExt,
@@ -29,8 +29,12 @@ use jsonrpsee::{
 };
 use sc_consensus::ImportedAux;
 use serde::{Deserialize, Serialize};
+use sp_core::Bytes;
 use sp_runtime::EncodedJustification;
 
+/// max number of blocks sealed by a single [`EngineCommand::SealBlocks`]
+pub const MAX_SEAL_BLOCKS: u32 = 1_000;
+
 /// Sender passed to the authorship task to report errors or successes.
 pub type Sender<T> = Option<oneshot::Sender<std::result::Result<T, Error>>>;
 
@@ -62,6 +66,40 @@ pub enum EngineCommand<Hash> {
 		/// finalization justification
 		justification: Option<EncodedJustification>,
 	},
+	/// Tells the engine to seal `count` blocks, including empty ones, on top of the best block
+	SealBlocks {
+		/// number of blocks to seal, at most [`MAX_SEAL_BLOCKS`]
+		count: u32,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<Vec<CreatedBlock<Hash>>>,
+	},
+	/// Tells the engine to add `duration` milliseconds to the timestamp inherent of all
+	/// following blocks.
+	///
+	/// The offset is added to the timestamp provided by the inherent data providers, so it
+	/// persists with providers that follow the system time. Sealing fails while there is an
+	/// offset and no timestamp inherent data provider. The total offset is reported back.
+	AdvanceTime {
+		/// milliseconds to advance the time by
+		duration: u64,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<u64>,
+	},
+	/// Tells the engine to apply storage overrides to the state of the next sealed block
+	OverrideStorage {
+		/// the storage overrides
+		overrides: Vec<StorageOverride>,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
+	/// Tells the engine to revert the best chain to the block with the supplied hash, including
+	/// finalized blocks.
+	RevertTo {
+		/// hash of the block
+		hash: Hash,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
 }
 
 /// RPC trait that provides methods for interacting with the manual-seal authorship task over rpc.
@@ -83,6 +121,33 @@ pub trait ManualSealApi<Hash> {
 		hash: Hash,
 		justification: Option<EncodedJustification>,
 	) -> RpcResult<bool>;
+
+	/// Instructs the manual-seal authorship task to seal `count` blocks on top of the best block,
+	/// at most [`MAX_SEAL_BLOCKS`]
+	#[method(name = "engine_sealBlocks")]
+	async fn seal_blocks(&self, count: u32) -> RpcResult<Vec<CreatedBlock<Hash>>>;
+
+	/// Instructs the manual-seal authorship task to advance the timestamp of the following
+	/// blocks by `duration` milliseconds, returns the total time advanced
+	#[method(name = "engine_advanceTime")]
+	async fn advance_time(&self, duration: u64) -> RpcResult<u64>;
+
+	/// Instructs the manual-seal authorship task to revert the chain to a block
+	#[method(name = "engine_revertTo")]
+	async fn revert_to(&self, hash: Hash) -> RpcResult<bool>;
+
+	/// Sets storage items, or removes them if the value is `null`, in the state of the next
+	/// sealed block
+	#[method(name = "engine_setStorage")]
+	async fn set_storage(&self, changes: Vec<(Bytes, Option<Bytes>)>) -> RpcResult<bool>;
+
+	/// Sets the free balance of an account in the state of the next sealed block
+	#[method(name = "engine_setBalance")]
+	async fn set_balance(&self, account: Bytes, free: u128) -> RpcResult<bool>;
+
+	/// Sets the sudo key in the state of the next sealed block
+	#[method(name = "engine_setSudoKey")]
+	async fn set_sudo_key(&self, account: Bytes) -> RpcResult<bool>;
 }
 
 /// A struct that implements the [`ManualSealApiServer`].
@@ -106,6 +171,22 @@ impl<Hash> ManualSeal<Hash> {
 	pub fn new(import_block_channel: mpsc::Sender<EngineCommand<Hash>>) -> Self {
 		Self { import_block_channel }
 	}
+
+	/// Send a command to the authorship task and wait for its result.
+	async fn send_command<T>(
+		&self,
+		command: impl FnOnce(Sender<T>) -> EngineCommand<Hash>,
+	) -> RpcResult<T> {
+		let mut sink = self.import_block_channel.clone();
+		let (sender, receiver) = oneshot::channel();
+		sink.send(command(Some(sender))).await?;
+
+		match receiver.await {
+			Ok(Ok(rx)) => Ok(rx),
+			Ok(Err(e)) => Err(e.into()),
+			Err(e) => Err(JsonRpseeError::to_call_error(e)),
+		}
+	}
 }
 
 #[async_trait]
@@ -146,6 +227,45 @@ impl<Hash: Send + 'static> ManualSealApiServer<Hash> for ManualSeal<Hash> {
 		sink.send(command).await?;
 		receiver.await.map(|_| true).map_err(|e| JsonRpseeError::to_call_error(e))
 	}
+
+	async fn seal_blocks(&self, count: u32) -> RpcResult<Vec<CreatedBlock<Hash>>> {
+		self.send_command(|sender| EngineCommand::SealBlocks { count, sender }).await
+	}
+
+	async fn advance_time(&self, duration: u64) -> RpcResult<u64> {
+		self.send_command(|sender| EngineCommand::AdvanceTime { duration, sender })
+			.await
+	}
+
+	async fn revert_to(&self, hash: Hash) -> RpcResult<bool> {
+		self.send_command(|sender| EngineCommand::RevertTo { hash, sender })
+			.await
+			.map(|_| true)
+	}
+
+	async fn set_storage(&self, changes: Vec<(Bytes, Option<Bytes>)>) -> RpcResult<bool> {
+		let overrides = changes
+			.into_iter()
+			.map(|(key, value)| StorageOverride::Storage { key, value })
+			.collect();
+		self.send_command(|sender| EngineCommand::OverrideStorage { overrides, sender })
+			.await
+			.map(|_| true)
+	}
+
+	async fn set_balance(&self, account: Bytes, free: u128) -> RpcResult<bool> {
+		let overrides = vec![StorageOverride::Balance { account, free }];
+		self.send_command(|sender| EngineCommand::OverrideStorage { overrides, sender })
+			.await
+			.map(|_| true)
+	}
+
+	async fn set_sudo_key(&self, account: Bytes) -> RpcResult<bool> {
+		let overrides = vec![StorageOverride::SudoKey { account }];
+		self.send_command(|sender| EngineCommand::OverrideStorage { overrides, sender })
+			.await
+			.map(|_| true)
+	}
 }
 
 /// report any errors or successes encountered by the authorship task back
@@ -18,22 +18,27 @@
 
 //! Block sealing utilities
 
-use crate::{rpc, ConsensusDataProvider, CreatedBlock, Error};
+use crate::{
+	rpc, storage_override::apply_storage_overrides, ConsensusDataProvider, CreatedBlock, Error,
+	StorageOverride,
+};
 use futures::prelude::*;
+use sc_client_api::backend::Backend as ClientBackend;
 use sc_consensus::{BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult, StateAction};
 use sc_transaction_pool_api::TransactionPool;
-use sp_api::ProvideRuntimeApi;
+use sp_api::{CallApiAt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_consensus::{self, BlockOrigin, Environment, Proposer, SelectChain};
 use sp_inherents::{CreateInherentDataProviders, InherentDataProvider};
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use sp_timestamp::TimestampInherentData;
 use std::{sync::Arc, time::Duration};
 
 /// max duration for creating a proposal in secs
 pub const MAX_PROPOSAL_DURATION: u64 = 10;
 
 /// params for sealing a new block
-pub struct SealBlockParams<'a, B: BlockT, BI, SC, C: ProvideRuntimeApi<B>, E, TP, CIDP, P> {
+pub struct SealBlockParams<'a, B: BlockT, BI, SC, C: ProvideRuntimeApi<B>, CB, E, TP, CIDP, P> {
 	/// if true, empty blocks(without extrinsics) will be created.
 	/// otherwise, will return Error::EmptyTransactionPool.
 	pub create_empty: bool,
@@ -57,10 +62,18 @@ pub struct SealBlockParams<'a, B: BlockT, BI, SC, C: ProvideRuntimeApi<B>, E, TP
 	pub block_import: &'a mut BI,
 	/// Something that can create the inherent data providers.
 	pub create_inherent_data_providers: &'a CIDP,
+	/// Milliseconds added to the timestamp inherent, see [`rpc::EngineCommand::AdvanceTime`].
+	/// Sealing fails if it is set and the inherent data providers don't provide a timestamp.
+	pub time_offset: u64,
+	/// Storage overrides applied on top of the state of this block, see
+	/// [`rpc::EngineCommand::OverrideStorage`]. Cleared once the block has been imported.
+	pub storage_overrides: &'a mut Vec<StorageOverride>,
+	/// Client backend, required to apply `storage_overrides`.
+	pub backend: Option<Arc<CB>>,
 }
 
 /// seals a new block with the given params
-pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
+pub async fn seal_block<B, BI, SC, C, CB, E, TP, CIDP, P>(
 	SealBlockParams {
 		create_empty,
 		finalize,
@@ -73,11 +86,15 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 		create_inherent_data_providers,
 		consensus_data_provider: digest_provider,
 		mut sender,
-	}: SealBlockParams<'_, B, BI, SC, C, E, TP, CIDP, P>,
+		time_offset,
+		storage_overrides,
+		backend,
+	}: SealBlockParams<'_, B, BI, SC, C, CB, E, TP, CIDP, P>,
 ) where
 	B: BlockT,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
-	C: HeaderBackend<B> + ProvideRuntimeApi<B>,
+	C: HeaderBackend<B> + ProvideRuntimeApi<B> + CallApiAt<B>,
+	CB: ClientBackend<B>,
 	E: Environment<B>,
 	E::Proposer: Proposer<B, Proof = P>,
 	TP: TransactionPool<Block = B>,
@@ -104,7 +121,14 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 			.await
 			.map_err(|e| Error::Other(e))?;
 
-		let inherent_data = inherent_data_providers.create_inherent_data().await?;
+		let mut inherent_data = inherent_data_providers.create_inherent_data().await?;
+		if time_offset > 0 {
+			let timestamp = inherent_data.timestamp_inherent_data()?.ok_or_else(|| {
+				Error::StringError("Advancing the time requires a timestamp inherent".into())
+			})?;
+			inherent_data
+				.replace_data(sp_timestamp::INHERENT_IDENTIFIER, &(timestamp + time_offset));
+		}
 
 		let proposer = env.init(&parent).map_err(|err| Error::StringError(err.to_string())).await?;
 		let inherents_len = inherent_data.len();
@@ -129,16 +153,34 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 			return Err(Error::EmptyTransactionPool)
 		}
 
-		let (header, body) = proposal.block.deconstruct();
+		let (mut header, body) = proposal.block.deconstruct();
+		let mut storage_changes = proposal.storage_changes;
+		if !storage_overrides.is_empty() {
+			let backend = backend.as_ref().ok_or_else(|| {
+				Error::StringError("Client backend is required to override storage".into())
+			})?;
+			let state = backend.state_at(parent.hash())?;
+			let state_version = client
+				.runtime_version_at(parent.hash())
+				.map_err(|e| Error::StringError(e.to_string()))?
+				.state_version();
+			let state_root = apply_storage_overrides::<B, _>(
+				&state,
+				&mut storage_changes,
+				storage_overrides,
+				state_version,
+			)?;
+			header.set_state_root(state_root);
+		}
+
 		let proof = proposal.proof;
 		let proof_size = proof.encoded_size();
 		let mut params = BlockImportParams::new(BlockOrigin::Own, header.clone());
 		params.body = Some(body);
 		params.finalized = finalize;
 		params.fork_choice = Some(ForkChoiceStrategy::LongestChain);
-		params.state_action = StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(
-			proposal.storage_changes,
-		));
+		params.state_action =
+			StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(storage_changes));
 
 		if let Some(digest_provider) = digest_provider {
 			digest_provider.append_block_import(&parent, &mut params, &inherent_data, proof)?;
@@ -150,11 +192,14 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 		post_header.digest_mut().logs.extend(params.post_digests.iter().cloned());
 
 		match block_import.import_block(params).await? {
-			ImportResult::Imported(aux) => Ok(CreatedBlock {
-				hash: <B as BlockT>::Header::hash(&post_header),
-				aux,
-				proof_size,
-			}),
+			ImportResult::Imported(aux) => {
+				storage_overrides.clear();
+				Ok(CreatedBlock {
+					hash: <B as BlockT>::Header::hash(&post_header),
+					aux,
+					proof_size,
+				})
+			},
 			other => Err(other.into()),
 		}
 	};
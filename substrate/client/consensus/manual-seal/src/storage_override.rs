@@ -0,0 +1,235 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Storage overrides applied to the state of sealed blocks
+
+use crate::Error;
+use codec::{Decode, Encode};
+use sp_api::{StateBackend, StorageChanges};
+use sp_core::{
+	hashing::{blake2_128, twox_128},
+	storage::{ChildInfo, StateVersion},
+	Bytes,
+};
+use sp_runtime::traits::{Block as BlockT, HashingFor};
+use std::collections::BTreeMap;
+
+/// A storage override applied to the state of the next sealed block, without executing a
+/// dispatchable.
+///
+/// Blocks with overrides can not be re-executed by other nodes, so overrides are only meant for
+/// testing on a single node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageOverride {
+	/// Set the value of a top level storage key, or remove it if `value` is `None`.
+	Storage {
+		/// the storage key
+		key: Bytes,
+		/// the new value
+		value: Option<Bytes>,
+	},
+	/// Set the free balance of an account and adjust the total issuance accordingly.
+	///
+	/// Assumes the `frame_system` account info of the runtime holds `pallet_balances` account
+	/// data with `u32` nonces and `u128` balances.
+	Balance {
+		/// the SCALE encoded account id
+		account: Bytes,
+		/// the new free balance
+		free: u128,
+	},
+	/// Set the `pallet_sudo` key.
+	SudoKey {
+		/// the SCALE encoded account id
+		account: Bytes,
+	},
+}
+
+/// `frame_system::AccountInfo` with `pallet_balances::AccountData`.
+#[derive(Debug, Default, PartialEq, Encode, Decode)]
+struct AccountInfo {
+	nonce: u32,
+	consumers: u32,
+	providers: u32,
+	sufficients: u32,
+	data: AccountData,
+}
+
+#[derive(Debug, Default, PartialEq, Encode, Decode)]
+struct AccountData {
+	free: u128,
+	reserved: u128,
+	frozen: u128,
+	flags: u128,
+}
+
+fn storage_value_key(pallet: &[u8], item: &[u8]) -> Vec<u8> {
+	[twox_128(pallet), twox_128(item)].concat()
+}
+
+fn account_key(account: &[u8]) -> Vec<u8> {
+	[storage_value_key(b"System", b"Account"), blake2_128(account).to_vec(), account.to_vec()]
+		.concat()
+}
+
+/// Apply the storage overrides on top of the changes of a block built on `state`, returning the
+/// new state root of the block.
+pub(crate) fn apply_storage_overrides<B, S>(
+	state: &S,
+	changes: &mut StorageChanges<B>,
+	overrides: &[StorageOverride],
+	state_version: StateVersion,
+) -> Result<B::Hash, Error>
+where
+	B: BlockT,
+	S: StateBackend<HashingFor<B>>,
+{
+	let mut main_changes = std::mem::take(&mut changes.main_storage_changes)
+		.into_iter()
+		.collect::<BTreeMap<_, _>>();
+	let read = |main_changes: &BTreeMap<Vec<u8>, Option<Vec<u8>>>, key: &[u8]| match main_changes
+		.get(key)
+	{
+		Some(value) => Ok(value.clone()),
+		None => state.storage(key).map_err(|e| Error::StringError(e.to_string())),
+	};
+	let decode_error = |e: codec::Error| Error::StringError(format!("Invalid account info: {}", e));
+
+	for storage_override in overrides {
+		match storage_override {
+			StorageOverride::Storage { key, value } => {
+				main_changes.insert(key.to_vec(), value.as_ref().map(|value| value.to_vec()));
+			},
+			StorageOverride::Balance { account, free } => {
+				let key = account_key(account);
+				let mut info = match read(&main_changes, &key)? {
+					Some(info) => AccountInfo::decode(&mut &info[..]).map_err(decode_error)?,
+					None => AccountInfo { providers: 1, ..Default::default() },
+				};
+				let issuance_key = storage_value_key(b"Balances", b"TotalIssuance");
+				let issuance = match read(&main_changes, &issuance_key)? {
+					Some(issuance) => u128::decode(&mut &issuance[..]).map_err(decode_error)?,
+					None => 0,
+				};
+				let issuance = issuance.saturating_sub(info.data.free).saturating_add(*free);
+				info.data.free = *free;
+				main_changes.insert(key, Some(info.encode()));
+				main_changes.insert(issuance_key, Some(issuance.encode()));
+			},
+			StorageOverride::SudoKey { account } => {
+				main_changes.insert(storage_value_key(b"Sudo", b"Key"), Some(account.to_vec()));
+			},
+		}
+	}
+
+	let child_infos = changes
+		.child_storage_changes
+		.iter()
+		.map(|(storage_key, _)| ChildInfo::new_default(storage_key))
+		.collect::<Vec<_>>();
+	let (root, transaction) = state.full_storage_root(
+		main_changes.iter().map(|(k, v)| (&k[..], v.as_deref())),
+		child_infos
+			.iter()
+			.zip(&changes.child_storage_changes)
+			.map(|(child_info, (_, changes))| {
+				(child_info, changes.iter().map(|(k, v)| (&k[..], v.as_deref())))
+			}),
+		state_version,
+	);
+	changes.main_storage_changes = main_changes.into_iter().collect();
+	changes.transaction = transaction;
+	changes.transaction_storage_root = root;
+	Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_api::InMemoryBackend;
+	use sp_runtime::traits::BlakeTwo256;
+	use substrate_test_runtime_client::runtime::Block;
+
+	#[test]
+	fn storage_overrides_are_applied() {
+		let alice = Bytes(vec![1; 32]);
+		let bob = Bytes(vec![2; 32]);
+		let bob_info = AccountInfo {
+			nonce: 5,
+			providers: 1,
+			data: AccountData { free: 100, ..Default::default() },
+			..Default::default()
+		};
+		let issuance_key = storage_value_key(b"Balances", b"TotalIssuance");
+		let state = InMemoryBackend::<BlakeTwo256>::from((
+			vec![
+				(None, vec![(account_key(&bob), Some(bob_info.encode()))]),
+				(None, vec![(issuance_key.clone(), Some(1_000u128.encode()))]),
+				(None, vec![(b"removed".to_vec(), Some(vec![1]))]),
+			],
+			StateVersion::V1,
+		));
+		let mut changes = StorageChanges::<Block>::default();
+		changes.main_storage_changes.push((b"changed".to_vec(), Some(vec![1])));
+
+		let root = apply_storage_overrides::<Block, _>(
+			&state,
+			&mut changes,
+			&[
+				StorageOverride::Storage { key: Bytes(b"removed".to_vec()), value: None },
+				StorageOverride::Storage {
+					key: Bytes(b"changed".to_vec()),
+					value: Some(Bytes(vec![2])),
+				},
+				StorageOverride::Balance { account: alice.clone(), free: 50 },
+				StorageOverride::Balance { account: bob.clone(), free: 500 },
+				StorageOverride::SudoKey { account: alice.clone() },
+			],
+			StateVersion::V1,
+		)
+		.unwrap();
+
+		let changes_map = changes.main_storage_changes.iter().cloned().collect::<BTreeMap<_, _>>();
+		assert_eq!(changes_map[&b"removed".to_vec()], None);
+		assert_eq!(changes_map[&b"changed".to_vec()], Some(vec![2]));
+		assert_eq!(
+			changes_map[&account_key(&alice)],
+			Some(
+				AccountInfo {
+					providers: 1,
+					data: AccountData { free: 50, ..Default::default() },
+					..Default::default()
+				}
+				.encode()
+			)
+		);
+		assert_eq!(
+			changes_map[&account_key(&bob)],
+			Some(
+				AccountInfo { data: AccountData { free: 500, ..Default::default() }, ..bob_info }
+					.encode()
+			)
+		);
+		assert_eq!(changes_map[&issuance_key], Some(1_450u128.encode()));
+		assert_eq!(changes_map[&storage_value_key(b"Sudo", b"Key")], Some(alice.0));
+
+		let mut expected = state.clone();
+		expected.insert(vec![(None, changes.main_storage_changes.clone())], StateVersion::V1);
+		assert_eq!(root, *expected.root());
+		assert_eq!(changes.transaction_storage_root, root);
+	}
+}
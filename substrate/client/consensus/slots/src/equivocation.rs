@@ -0,0 +1,299 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Detection and reporting of slot equivocations, independent of block authoring.
+//!
+//! The [`EquivocationWatcher`] follows every block imported by the client, tracks the headers
+//! seen for each slot and author, and reports equivocations to the runtime through an
+//! engine-specific [`EquivocationReporter`].
+
+use crate::{LOG_TARGET, MAX_SLOT_CAPACITY};
+use codec::{Decode, Encode};
+use futures::StreamExt;
+use log::{debug, info, warn};
+use sc_client_api::BlockchainEvents;
+use sp_blockchain::{HeaderBackend, Result as ClientResult};
+use sp_consensus::BlockOrigin;
+use sp_consensus_slots::{EquivocationProof, Slot};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::{collections::BTreeMap, fmt::Debug, sync::Arc};
+
+/// Engine-specific logic used by the [`EquivocationWatcher`].
+pub trait EquivocationReporter<B: BlockT>: Send + Sync {
+	/// The identifier of slot authors.
+	type AuthorityId: Clone + Encode + Decode + PartialEq + Debug + Send + Sync;
+
+	/// Returns the slot of the given header along with the authority who authored it, or `None`
+	/// if the header doesn't carry a slot claim of this engine.
+	fn slot_author(&self, header: &B::Header) -> ClientResult<Option<(Slot, Self::AuthorityId)>>;
+
+	/// Whether the engine already checks blocks imported with the given `origin` for
+	/// equivocations and reports them during import.
+	///
+	/// Equivocations between two such blocks are not reported again by the watcher.
+	fn reported_on_import(&self, _origin: BlockOrigin) -> bool {
+		false
+	}
+
+	/// Submits a report of the given equivocation through the runtime state of block `at`.
+	///
+	/// Returns `false` if no report was submitted, e.g. because the runtime doesn't support
+	/// equivocation reporting or the offender is not part of the authority set.
+	fn report_equivocation(
+		&self,
+		at: B::Hash,
+		equivocation_proof: EquivocationProof<B::Header, Self::AuthorityId>,
+	) -> ClientResult<bool>;
+}
+
+/// A header seen for a given slot.
+struct SeenHeader<H, Id> {
+	author: Id,
+	header: H,
+	origin: BlockOrigin,
+	reported: bool,
+}
+
+/// Watches imported blocks for authors producing more than one block on the same slot.
+///
+/// Unlike the checks done during block import, equivocations are detected from all imported
+/// blocks regardless of their origin, so any node can run it, including non-authoring ones.
+/// Headers are tracked for the last [`MAX_SLOT_CAPACITY`] slots and every equivocation is
+/// reported at most once, skipping those already reported by the engine during import.
+pub struct EquivocationWatcher<B: BlockT, C, R: EquivocationReporter<B>> {
+	client: Arc<C>,
+	reporter: R,
+	seen_headers: BTreeMap<Slot, Vec<SeenHeader<B::Header, R::AuthorityId>>>,
+}
+
+impl<B, C, R> EquivocationWatcher<B, C, R>
+where
+	B: BlockT,
+	R: EquivocationReporter<B>,
+{
+	/// Create a new watcher reporting equivocations with the given `reporter`.
+	pub fn new(client: Arc<C>, reporter: R) -> Self {
+		Self { client, reporter, seen_headers: BTreeMap::new() }
+	}
+
+	/// Track the given header imported with `origin`, returning a proof of equivocation if its
+	/// author already authored a different header for the same slot that wasn't reported yet.
+	pub fn note_header(
+		&mut self,
+		header: &B::Header,
+		origin: BlockOrigin,
+	) -> ClientResult<Option<EquivocationProof<B::Header, R::AuthorityId>>> {
+		let (slot, author) = match self.reporter.slot_author(header)? {
+			Some(slot_author) => slot_author,
+			None => return Ok(None),
+		};
+
+		// Prune the slots out of our capacity and ignore headers for them.
+		if let Some(latest) = self.seen_headers.keys().next_back().copied() {
+			let oldest = latest.max(slot).saturating_sub(MAX_SLOT_CAPACITY);
+			if slot < oldest {
+				return Ok(None)
+			}
+			self.seen_headers = self.seen_headers.split_off(&oldest);
+		}
+
+		let seen = self.seen_headers.entry(slot).or_default();
+		match seen.iter_mut().find(|seen| seen.author == author) {
+			Some(seen) if seen.header.hash() != header.hash() && !seen.reported => {
+				seen.reported = true;
+				if self.reporter.reported_on_import(seen.origin) &&
+					self.reporter.reported_on_import(origin)
+				{
+					return Ok(None)
+				}
+				Ok(Some(EquivocationProof {
+					offender: author,
+					slot,
+					first_header: seen.header.clone(),
+					second_header: header.clone(),
+				}))
+			},
+			Some(_) => Ok(None),
+			None => {
+				seen.push(SeenHeader { author, header: header.clone(), origin, reported: false });
+				Ok(None)
+			},
+		}
+	}
+
+	/// Track the given header and report any equivocation it proves on top of the best block.
+	fn check_header(&mut self, header: &B::Header, origin: BlockOrigin) -> ClientResult<()>
+	where
+		C: HeaderBackend<B>,
+	{
+		let equivocation_proof = match self.note_header(header, origin)? {
+			Some(proof) => proof,
+			None => return Ok(()),
+		};
+
+		info!(
+			target: LOG_TARGET,
+			"Slot author {:?} is equivocating at slot {} with headers {:?} and {:?}",
+			equivocation_proof.offender,
+			equivocation_proof.slot,
+			equivocation_proof.first_header.hash(),
+			equivocation_proof.second_header.hash(),
+		);
+
+		let offender = equivocation_proof.offender.clone();
+		let best_hash = self.client.info().best_hash;
+		if self.reporter.report_equivocation(best_hash, equivocation_proof)? {
+			info!(target: LOG_TARGET, "Submitted equivocation report for author {:?}", offender);
+		} else {
+			debug!(target: LOG_TARGET, "Equivocation of author {:?} was not reported", offender);
+		}
+
+		Ok(())
+	}
+
+	/// Run the watcher until the client's import notification stream ends.
+	pub async fn run(mut self)
+	where
+		C: BlockchainEvents<B> + HeaderBackend<B>,
+	{
+		let mut imported_blocks = self.client.every_import_notification_stream();
+		while let Some(notification) = imported_blocks.next().await {
+			// don't report any equivocations during initial sync
+			// as they are most likely stale.
+			if notification.origin == BlockOrigin::NetworkInitialSync {
+				continue
+			}
+
+			if let Err(err) = self.check_header(&notification.header, notification.origin) {
+				warn!(
+					target: LOG_TARGET,
+					"Failed to check block {:?} for equivocations: {}", notification.hash, err,
+				);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::H256;
+	use substrate_test_runtime_client::runtime::{Block, Header as TestHeader};
+
+	/// Uses the header number as slot and the first byte of the state root as author, and
+	/// reports equivocations of all but our own blocks during import.
+	struct TestReporter;
+
+	impl EquivocationReporter<Block> for TestReporter {
+		type AuthorityId = u8;
+
+		fn slot_author(&self, header: &TestHeader) -> ClientResult<Option<(Slot, u8)>> {
+			Ok(Some((header.number.into(), header.state_root[0])))
+		}
+
+		fn reported_on_import(&self, origin: BlockOrigin) -> bool {
+			origin != BlockOrigin::Own
+		}
+
+		fn report_equivocation(
+			&self,
+			_at: H256,
+			_equivocation_proof: EquivocationProof<TestHeader, u8>,
+		) -> ClientResult<bool> {
+			Ok(true)
+		}
+	}
+
+	fn header(slot: u64, author: u8, extrinsics_root: u8) -> TestHeader {
+		TestHeader::new(
+			slot,
+			H256::repeat_byte(extrinsics_root),
+			H256::repeat_byte(author),
+			Default::default(),
+			Default::default(),
+		)
+	}
+
+	#[test]
+	fn detects_and_reports_equivocations_once() {
+		let mut watcher = EquivocationWatcher::<Block, (), _>::new(Arc::new(()), TestReporter);
+
+		let first = header(2, 1, 1);
+		assert!(watcher.note_header(&first, BlockOrigin::Own).unwrap().is_none());
+		// same header seen again.
+		assert!(watcher.note_header(&first, BlockOrigin::Own).unwrap().is_none());
+		// another author on the same slot.
+		assert!(watcher.note_header(&header(2, 2, 1), BlockOrigin::Own).unwrap().is_none());
+
+		let second = header(2, 1, 2);
+		assert_eq!(
+			watcher.note_header(&second, BlockOrigin::Own).unwrap(),
+			Some(EquivocationProof {
+				offender: 1,
+				slot: 2.into(),
+				first_header: first,
+				second_header: second,
+			}),
+		);
+
+		// the equivocation was already reported.
+		assert!(watcher.note_header(&header(2, 1, 3), BlockOrigin::Own).unwrap().is_none());
+	}
+
+	#[test]
+	fn skips_equivocations_reported_on_import() {
+		let mut watcher = EquivocationWatcher::<Block, (), _>::new(Arc::new(()), TestReporter);
+
+		// both headers were checked during import.
+		let origin = BlockOrigin::NetworkBroadcast;
+		assert!(watcher.note_header(&header(2, 1, 1), origin).unwrap().is_none());
+		assert!(watcher.note_header(&header(2, 1, 2), origin).unwrap().is_none());
+		// and the equivocation counts as reported.
+		assert!(watcher.note_header(&header(2, 1, 3), BlockOrigin::Own).unwrap().is_none());
+
+		// one of them is our own.
+		let first = header(3, 1, 1);
+		assert!(watcher.note_header(&first, BlockOrigin::Own).unwrap().is_none());
+		let second = header(3, 1, 2);
+		assert_eq!(
+			watcher.note_header(&second, origin).unwrap(),
+			Some(EquivocationProof {
+				offender: 1,
+				slot: 3.into(),
+				first_header: first,
+				second_header: second,
+			}),
+		);
+	}
+
+	#[test]
+	fn ignores_slots_out_of_capacity() {
+		let mut watcher = EquivocationWatcher::<Block, (), _>::new(Arc::new(()), TestReporter);
+
+		assert!(watcher.note_header(&header(1, 1, 1), BlockOrigin::Own).unwrap().is_none());
+		assert!(watcher
+			.note_header(&header(MAX_SLOT_CAPACITY + 2, 1, 1), BlockOrigin::Own)
+			.unwrap()
+			.is_none());
+
+		// slot 1 was pruned.
+		assert!(!watcher.seen_headers.contains_key(&Slot::from(1)));
+		assert!(watcher.note_header(&header(1, 1, 2), BlockOrigin::Own).unwrap().is_none());
+		assert!(!watcher.seen_headers.contains_key(&Slot::from(1)));
+	}
+}
@@ -26,9 +26,11 @@
 #![warn(missing_docs)]
 
 mod aux_schema;
+mod equivocation;
 mod slots;
 
 pub use aux_schema::{check_equivocation, MAX_SLOT_CAPACITY, PRUNING_BOUND};
+pub use equivocation::{EquivocationReporter, EquivocationWatcher};
 pub use slots::SlotInfo;
 use slots::Slots;
 
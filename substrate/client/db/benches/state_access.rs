@@ -120,6 +120,7 @@ fn create_backend(config: BenchmarkConfig, temp_dir: &TempDir) -> Backend<Block>
 	let settings = DatabaseSettings {
 		trie_cache_maximum_size,
 		state_pruning: Some(PruningMode::ArchiveAll),
+		state_checkpoint_interval: None,
 		source: DatabaseSource::ParityDb { path },
 		blocks_pruning: BlocksPruning::KeepAll,
 	};
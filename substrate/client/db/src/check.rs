@@ -0,0 +1,400 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integrity checks of a node database.
+//!
+//! The canonical chain is walked from genesis to the best block. Every header must be stored
+//! under its hash and link to its parent, and every stored body and justification must decode,
+//! with the body matching the extrinsics root of its header. The state-db journals are decoded
+//! and validated, and the state of every block retained by the pruning window is checked to be
+//! present in the state column. The checks work on the raw database, so they also run when the
+//! journals are too damaged for the backend to open.
+
+use crate::{columns, utils, BlockchainDb, DatabaseSource, DbHash, DbStateBuilder, StateMetaDb};
+use hash_db::{Prefix, EMPTY_PREFIX};
+use sc_state_db::PruningMode;
+use sp_blockchain::{Backend as _, HeaderBackend, Result as ClientResult};
+use sp_core::{
+	hexdisplay::HexDisplay,
+	storage::{well_known_keys, ChildInfo},
+};
+use sp_database::Database;
+use sp_runtime::{
+	traits::{
+		Block as BlockT, Hash, HashingFor, Header as HeaderT, NumberFor, One, SaturatedConversion,
+		Zero,
+	},
+	StateVersion,
+};
+use sp_state_machine::{Backend as _, DBValue, IterArgs};
+use sp_trie::prefixed_key;
+use std::sync::Arc;
+
+/// Settings of a database integrity check.
+#[derive(Debug, Clone, Default)]
+pub struct CheckSettings {
+	/// Walk the complete state of every retained block, rather than only of the last
+	/// canonicalized one. The roots of all retained states are checked either way.
+	pub full_state: bool,
+	/// Report canonical blocks without a body, which is only valid when bodies are pruned or
+	/// were never downloaded.
+	pub require_bodies: bool,
+}
+
+/// A block that failed an integrity check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inconsistency<Block: BlockT> {
+	/// Number of the block.
+	pub number: NumberFor<Block>,
+	/// Hash of the block, if it is known.
+	pub hash: Option<Block::Hash>,
+	/// Description of the failed check.
+	pub reason: String,
+}
+
+/// Result of a database integrity check.
+#[derive(Debug, Clone)]
+pub struct IntegrityReport<Block: BlockT> {
+	/// Number of canonical blocks that passed all checks.
+	pub checked_blocks: u64,
+	/// Number of retained states whose root was checked.
+	pub checked_states: u64,
+	/// Error found in the state-db journals, in which case no state was checked.
+	pub journal_error: Option<String>,
+	/// The first inconsistent canonical block.
+	pub first_inconsistent: Option<Inconsistency<Block>>,
+}
+
+impl<Block: BlockT> IntegrityReport<Block> {
+	/// Returns `true` if no inconsistency was found.
+	pub fn is_consistent(&self) -> bool {
+		self.journal_error.is_none() && self.first_inconsistent.is_none()
+	}
+
+	fn note(&mut self, inconsistency: Inconsistency<Block>) {
+		if self
+			.first_inconsistent
+			.as_ref()
+			.map_or(true, |first| inconsistency.number < first.number)
+		{
+			self.first_inconsistent = Some(inconsistency);
+		}
+	}
+}
+
+/// Check the integrity of the database of `source`.
+pub fn check_database<Block: BlockT>(
+	source: &DatabaseSource,
+	settings: &CheckSettings,
+) -> ClientResult<IntegrityReport<Block>> {
+	let db = utils::open_database::<Block>(source, utils::DatabaseType::Full, false)?;
+	check::<Block>(db, settings)
+}
+
+pub(crate) fn check<Block: BlockT>(
+	db: Arc<dyn Database<DbHash>>,
+	settings: &CheckSettings,
+) -> ClientResult<IntegrityReport<Block>> {
+	let blockchain = BlockchainDb::<Block>::new(db.clone())?;
+	let info = blockchain.info();
+	let mut report = IntegrityReport {
+		checked_blocks: 0,
+		checked_states: 0,
+		journal_error: None,
+		first_inconsistent: None,
+	};
+
+	let mut parent_hash = None;
+	let mut number: NumberFor<Block> = Zero::zero();
+	while number <= info.best_number {
+		if let Some((start, end)) = info.block_gap {
+			if start <= number && number <= end {
+				// headers of the gap are not downloaded yet
+				parent_hash = None;
+				number = end + One::one();
+				continue
+			}
+		}
+		match check_block(&blockchain, number, parent_hash, info.finalized_number, settings) {
+			Ok(hash) => parent_hash = Some(hash),
+			Err(inconsistency) => {
+				report.note(inconsistency);
+				break
+			},
+		}
+		report.checked_blocks += 1;
+		number += One::one();
+	}
+
+	let journals = sc_state_db::check_journals::<Block::Hash, Vec<u8>, _>(&StateMetaDb(db.clone()));
+	let summary = match journals {
+		Ok(summary) => summary,
+		Err(e) => {
+			report.journal_error = Some(format!("{:?}", e));
+			return Ok(report)
+		},
+	};
+	let Some((last_hash, last_number)) = summary.last_canonicalized else { return Ok(report) };
+	let retained: Vec<(u64, Block::Hash)> = match summary.mode {
+		PruningMode::Constrained(_) => summary.pruning_window,
+		PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => (0..=last_number)
+			.filter_map(|n| {
+				let hash = blockchain.hash(n.saturated_into()).ok().flatten()?;
+				Some((n, hash))
+			})
+			.collect(),
+	};
+
+	let storage = Arc::new(RawStorage { db: db.clone(), prefix_keys: !db.supports_ref_counting() });
+	for (n, hash) in retained {
+		let number: NumberFor<Block> = n.saturated_into();
+		if report.first_inconsistent.as_ref().map_or(false, |first| first.number <= number) {
+			break
+		}
+		let inconsistency = |reason: String| Inconsistency { number, hash: Some(hash), reason };
+		match blockchain.hash(number) {
+			Ok(Some(canonical)) if canonical == hash => (),
+			_ => {
+				report.note(inconsistency(
+					"State is retained for a block that is not canonical".into(),
+				));
+				break
+			},
+		}
+		let root = match blockchain.header(hash) {
+			Ok(Some(header)) => *header.state_root(),
+			_ => {
+				report.note(inconsistency("Header of a retained state is missing".into()));
+				break
+			},
+		};
+		let full = settings.full_state || (n == last_number && hash == last_hash);
+		if let Err(reason) = check_state::<Block>(&storage, root, full) {
+			report.note(inconsistency(reason));
+			break
+		}
+		report.checked_states += 1;
+	}
+	Ok(report)
+}
+
+/// Check the canonical block `number`, returning its hash.
+fn check_block<Block: BlockT>(
+	blockchain: &BlockchainDb<Block>,
+	number: NumberFor<Block>,
+	parent_hash: Option<Block::Hash>,
+	finalized_number: NumberFor<Block>,
+	settings: &CheckSettings,
+) -> Result<Block::Hash, Inconsistency<Block>> {
+	let inconsistency =
+		|hash: Option<Block::Hash>, reason: String| Inconsistency { number, hash, reason };
+	let hash = match blockchain.hash(number) {
+		Ok(Some(hash)) => hash,
+		Ok(None) => return Err(inconsistency(None, "Canonical hash is missing".into())),
+		Err(e) => return Err(inconsistency(None, format!("Canonical hash is invalid: {}", e))),
+	};
+	let header = match blockchain.header(hash) {
+		Ok(Some(header)) => header,
+		Ok(None) => return Err(inconsistency(Some(hash), "Header is missing".into())),
+		Err(e) => return Err(inconsistency(Some(hash), format!("Header is invalid: {}", e))),
+	};
+	if header.hash() != hash || *header.number() != number {
+		return Err(inconsistency(
+			Some(hash),
+			format!(
+				"Header of #{} ({}) is stored under a different block",
+				header.number(),
+				header.hash()
+			),
+		))
+	}
+	if parent_hash.map_or(false, |parent_hash| *header.parent_hash() != parent_hash) {
+		return Err(inconsistency(
+			Some(hash),
+			format!("Parent {} is not the previous canonical block", header.parent_hash()),
+		))
+	}
+
+	match blockchain.body(hash) {
+		Ok(Some(body)) => {
+			let extrinsics: Vec<_> = body.iter().map(codec::Encode::encode).collect();
+			// the state version of the extrinsics root is chosen by the runtime
+			let matches = [StateVersion::V0, StateVersion::V1].into_iter().any(|version| {
+				HashingFor::<Block>::ordered_trie_root(extrinsics.clone(), version) ==
+					*header.extrinsics_root()
+			});
+			if !matches {
+				return Err(inconsistency(
+					Some(hash),
+					"Body does not match the extrinsics root".into(),
+				))
+			}
+		},
+		Ok(None) if settings.require_bodies =>
+			return Err(inconsistency(Some(hash), "Body is missing".into())),
+		Ok(None) => (),
+		Err(e) => return Err(inconsistency(Some(hash), format!("Body is invalid: {}", e))),
+	}
+
+	match blockchain.justifications(hash) {
+		Ok(Some(_)) if number > finalized_number =>
+			Err(inconsistency(Some(hash), "Block is justified but not finalized".into())),
+		Ok(_) => Ok(hash),
+		Err(e) => Err(inconsistency(Some(hash), format!("Justifications are invalid: {}", e))),
+	}
+}
+
+/// Check that the root node of a state is present, and if `full` that all of its nodes are.
+fn check_state<Block: BlockT>(
+	storage: &Arc<RawStorage>,
+	root: Block::Hash,
+	full: bool,
+) -> Result<(), String> {
+	use sp_state_machine::Storage;
+
+	if Storage::<HashingFor<Block>>::get(&**storage, &root, EMPTY_PREFIX)?.is_none() {
+		return Err(format!("State root {} is missing", root))
+	}
+	if !full {
+		return Ok(())
+	}
+
+	let state = DbStateBuilder::<Block>::new(storage.clone(), root).build();
+	let mut child_tries = Vec::new();
+	for pair in state.pairs(IterArgs::default())? {
+		let (key, _) = pair.map_err(|e| format!("State of {} is incomplete: {}", root, e))?;
+		if let Some(child_key) = key.strip_prefix(well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX)
+		{
+			child_tries.push(ChildInfo::new_default(child_key));
+		}
+	}
+	for child_info in child_tries {
+		let args = IterArgs { child_info: Some(child_info.clone()), ..Default::default() };
+		for pair in state.pairs(args)? {
+			pair.map_err(|e| {
+				format!(
+					"Child trie 0x{} of state {} is incomplete: {}",
+					HexDisplay::from(&child_info.storage_key()),
+					root,
+					e
+				)
+			})?;
+		}
+	}
+	Ok(())
+}
+
+/// Reads trie nodes directly from the state column, bypassing the state-db overlay.
+struct RawStorage {
+	db: Arc<dyn Database<DbHash>>,
+	prefix_keys: bool,
+}
+
+impl<H: sp_core::Hasher> sp_state_machine::Storage<H> for RawStorage {
+	fn get(&self, key: &H::Out, prefix: Prefix) -> Result<Option<DBValue>, String> {
+		if self.prefix_keys {
+			Ok(self.db.get(columns::STATE, &prefixed_key::<H>(key, prefix)))
+		} else {
+			Ok(self.db.get(columns::STATE, key.as_ref()))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		tests::{insert_block, Block},
+		utils::number_and_hash_to_lookup_key,
+		Backend,
+	};
+	use codec::Encode;
+	use sp_core::H256;
+	use sp_database::Transaction;
+	use sp_runtime::{testing::ExtrinsicWrapper, traits::BlakeTwo256};
+
+	fn build_chain(backend: &Backend<Block>, length: u64) -> Vec<H256> {
+		let body = vec![ExtrinsicWrapper::from(7u64)];
+		let extrinsics_root = BlakeTwo256::ordered_trie_root(
+			body.iter().map(Encode::encode).collect(),
+			StateVersion::V0,
+		);
+		let mut hashes: Vec<H256> = Vec::new();
+		for number in 0..length {
+			let parent_hash = hashes.last().cloned().unwrap_or_default();
+			hashes.push(
+				insert_block(
+					backend,
+					number,
+					parent_hash,
+					None,
+					extrinsics_root,
+					body.clone(),
+					None,
+				)
+				.unwrap(),
+			);
+		}
+		hashes
+	}
+
+	fn settings() -> CheckSettings {
+		CheckSettings { full_state: true, require_bodies: true }
+	}
+
+	#[test]
+	fn consistent_database_passes() {
+		let backend = Backend::<Block>::new_test(10, 0);
+		build_chain(&backend, 5);
+		let report = check::<Block>(backend.storage.db.clone(), &settings()).unwrap();
+		assert!(report.is_consistent());
+		assert_eq!(report.checked_blocks, 5);
+		assert!(report.checked_states > 0);
+	}
+
+	#[test]
+	fn reports_missing_body() {
+		let backend = Backend::<Block>::new_test(10, 0);
+		let hashes = build_chain(&backend, 5);
+		let mut transaction = Transaction::new();
+		transaction.remove(columns::BODY, &number_and_hash_to_lookup_key(3, hashes[3]).unwrap());
+		backend.storage.db.commit(transaction).unwrap();
+
+		let report = check::<Block>(backend.storage.db.clone(), &settings()).unwrap();
+		let inconsistency = report.first_inconsistent.unwrap();
+		assert_eq!((inconsistency.number, inconsistency.hash), (3, Some(hashes[3])));
+		assert_eq!(inconsistency.reason, "Body is missing");
+		assert_eq!(report.checked_blocks, 3);
+	}
+
+	#[test]
+	fn reports_missing_state_root() {
+		let backend = Backend::<Block>::new_test(10, 0);
+		let hashes = build_chain(&backend, 5);
+		let root = backend.blockchain.header(hashes[2]).unwrap().unwrap().state_root;
+		let mut transaction = Transaction::new();
+		transaction.remove(columns::STATE, &prefixed_key::<BlakeTwo256>(&root, EMPTY_PREFIX));
+		backend.storage.db.commit(transaction).unwrap();
+
+		let report = check::<Block>(backend.storage.db.clone(), &settings()).unwrap();
+		assert_eq!(report.checked_blocks, 5);
+		let inconsistency = report.first_inconsistent.unwrap();
+		assert_eq!(inconsistency.number, 2);
+		assert_eq!(inconsistency.reason, format!("State root {} is missing", root));
+	}
+}
@@ -31,6 +31,8 @@
 pub mod offchain;
 
 pub mod bench;
+pub mod check;
+pub mod migrate;
 
 mod children;
 mod parity_db;
@@ -48,7 +50,8 @@ use std::{
 	collections::{HashMap, HashSet},
 	io,
 	path::{Path, PathBuf},
-	sync::Arc,
+	sync::{Arc, Weak},
+	time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use crate::{
@@ -65,7 +68,7 @@ use sc_client_api::{
 	utils::is_descendent_of,
 	IoInfo, MemoryInfo, MemorySize, UsageInfo,
 };
-use sc_state_db::{IsPruned, LastCanonicalized, StateDb};
+use sc_state_db::{Constraints, IsPruned, LastCanonicalized, StateDb, MIN_AGE_PRUNING_BLOCK_TIME};
 use sp_arithmetic::traits::Saturating;
 use sp_blockchain::{
 	Backend as _, CachedHeaderMetadata, Error as ClientError, HeaderBackend, HeaderMetadata,
@@ -301,6 +304,11 @@ pub struct DatabaseSettings {
 	pub trie_cache_maximum_size: Option<usize>,
 	/// Requested state pruning mode.
 	pub state_pruning: Option<PruningMode>,
+	/// Retain the state of every block with a number divisible by this interval.
+	///
+	/// Applied to the requested or, if none is requested, the stored state pruning mode, which
+	/// must be constrained.
+	pub state_checkpoint_interval: Option<u32>,
 	/// Where to find the database.
 	pub source: DatabaseSource,
 	/// Block pruning mode.
@@ -318,6 +326,14 @@ pub enum BlocksPruning {
 	KeepFinalized,
 	/// Keep N recent finalized blocks.
 	Some(u32),
+	/// Keep finalized blocks for the given time after they were finalized, but no more than are
+	/// produced within this time at [`sc_state_db::MIN_AGE_PRUNING_BLOCK_TIME`].
+	KeepFor(Duration),
+	/// Keep as many recent finalized blocks as fit into the given database size in bytes, but at
+	/// least [`MIN_SIZE_PRUNING_BLOCKS`].
+	///
+	/// Has no effect for databases that are not stored on disk.
+	KeepSize(u64),
 }
 
 /// Where to find the database..
@@ -1061,6 +1077,52 @@ impl<Block: BlockT> sp_state_machine::Storage<HashingFor<Block>> for EmptyStorag
 	}
 }
 
+/// How often the on-disk size of the database is measured for size based pruning.
+const DATABASE_SIZE_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Measures the on-disk size of the database every `DATABASE_SIZE_REFRESH_INTERVAL` on a separate
+/// thread, so that walking the database directories doesn't delay finalization. The thread stops
+/// once `size` is dropped together with the backend.
+fn spawn_database_size_monitor(source: DatabaseSource, size: Weak<Mutex<Option<u64>>>) {
+	if let DatabaseSource::Custom { .. } = source {
+		return
+	}
+
+	let monitor = move || {
+		while let Some(size) = size.upgrade() {
+			*size.lock() = utils::database_size(&source);
+			drop(size);
+			std::thread::sleep(DATABASE_SIZE_REFRESH_INTERVAL);
+		}
+	};
+	if let Err(e) = std::thread::Builder::new().name("db-size-monitor".into()).spawn(monitor) {
+		warn!(target: "db", "Failed to spawn the database size monitor: {}", e);
+	}
+}
+
+/// Number of finalized blocks pruned per finalization while the database exceeds the size of
+/// `BlocksPruning::KeepSize`, shrinking the kept history by one block each time.
+const BLOCKS_SIZE_PRUNING_STEP: u32 = 2;
+
+/// Number of recent finalized blocks `BlocksPruning::KeepSize` keeps, whatever the size of the
+/// database.
+pub const MIN_SIZE_PRUNING_BLOCKS: u32 = sc_state_db::MIN_SIZE_PRUNING_WINDOW;
+
+/// Current unix time in milliseconds.
+fn unix_time_millis() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_millis() as u64)
+		.unwrap_or_default()
+}
+
+/// Meta key of the time at which the block with the given number was finalized.
+fn finalized_at_key<N: TryInto<u32>>(number: N) -> ClientResult<Vec<u8>> {
+	let mut key = meta_keys::FINALIZED_AT_PREFIX.to_vec();
+	key.extend(utils::number_index_key(number)?);
+	Ok(key)
+}
+
 /// Frozen `value` at time `at`.
 ///
 /// Used as inner structure under lock in `FrozenForDuration`.
@@ -1117,6 +1179,10 @@ pub struct Backend<Block: BlockT> {
 	state_usage: Arc<StateUsageStats>,
 	genesis_state: RwLock<Option<Arc<DbGenesisStorage<Block>>>>,
 	shared_trie_cache: Option<sp_trie::cache::SharedTrieCache<HashingFor<Block>>>,
+	/// On-disk size of the database, see `spawn_database_size_monitor`.
+	database_size: Arc<Mutex<Option<u64>>>,
+	/// Next finalized block to be pruned by `BlocksPruning::KeepFor` or `BlocksPruning::KeepSize`.
+	blocks_pruning_cursor: Mutex<Option<NumberFor<Block>>>,
 }
 
 impl<Block: BlockT> Backend<Block> {
@@ -1167,10 +1233,13 @@ impl<Block: BlockT> Backend<Block> {
 			BlocksPruning::KeepAll => PruningMode::ArchiveAll,
 			BlocksPruning::KeepFinalized => PruningMode::ArchiveCanonical,
 			BlocksPruning::Some(n) => PruningMode::blocks_pruning(n),
+			// Age and size based state pruning are covered by `sc-state-db`.
+			BlocksPruning::KeepFor(_) | BlocksPruning::KeepSize(_) => PruningMode::ArchiveCanonical,
 		};
 		let db_setting = DatabaseSettings {
 			trie_cache_maximum_size: Some(16 * 1024 * 1024),
 			state_pruning: Some(state_pruning),
+			state_checkpoint_interval: None,
 			source: DatabaseSource::Custom { db, require_create_flag: true },
 			blocks_pruning,
 		};
@@ -1207,9 +1276,10 @@ impl<Block: BlockT> Backend<Block> {
 		let state_meta_db = StateMetaDb(db.clone());
 		let map_e = sp_blockchain::Error::from_state_db;
 
-		let (state_db_init_commit_set, state_db) = StateDb::open(
+		let (state_db_init_commit_set, state_db) = StateDb::open_with_checkpoint_interval(
 			state_meta_db,
 			requested_state_pruning,
+			config.state_checkpoint_interval,
 			!db.supports_ref_counting(),
 			should_init,
 		)
@@ -1225,6 +1295,18 @@ impl<Block: BlockT> Backend<Block> {
 			StorageDb { db: db.clone(), state_db, prefix_keys: !db.supports_ref_counting() };
 
 		let offchain_storage = offchain::LocalStorage::new(db.clone());
+		let blocks_pruning_cursor = db
+			.get(columns::META, meta_keys::BLOCKS_PRUNING_CURSOR)
+			.and_then(|cursor| Decode::decode(&mut &cursor[..]).ok());
+
+		let database_size = Arc::new(Mutex::new(None));
+		if matches!(config.blocks_pruning, BlocksPruning::KeepSize(_)) ||
+			matches!(
+				state_pruning_used,
+				PruningMode::Constrained(Constraints { max_size: Some(_), .. })
+			) {
+			spawn_database_size_monitor(config.source.clone(), Arc::downgrade(&database_size));
+		}
 
 		let backend = Backend {
 			storage: Arc::new(storage_db),
@@ -1240,6 +1322,8 @@ impl<Block: BlockT> Backend<Block> {
 			shared_trie_cache: config.trie_cache_maximum_size.map(|maximum_size| {
 				SharedTrieCache::new(sp_trie::cache::CacheSize::new(maximum_size))
 			}),
+			database_size,
+			blocks_pruning_cursor: Mutex::new(blocks_pruning_cursor),
 		};
 
 		// Older DB versions have no last state key. Check if the state is available and set it.
@@ -1792,6 +1876,13 @@ impl<Block: BlockT> Backend<Block> {
 		};
 
 		if requires_canonicalization && sc_client_api::Backend::have_state_at(self, f_hash, f_num) {
+			if let PruningMode::Constrained(Constraints { max_size: Some(_), .. }) =
+				self.storage.state_db.pruning_mode()
+			{
+				if let Some(size) = self.database_size() {
+					self.storage.state_db.note_database_size(size);
+				}
+			}
 			let commit = self.storage.state_db.canonicalize_block(&f_hash).map_err(
 				sp_blockchain::Error::from_state_db::<
 					sc_state_db::Error<sp_database::error::DatabaseError>,
@@ -1827,26 +1918,22 @@ impl<Block: BlockT> Backend<Block> {
 				let keep = std::cmp::max(blocks_pruning, 1);
 				if finalized_number >= keep.into() {
 					let number = finalized_number.saturating_sub(keep.into());
-
-					// Before we prune a block, check if it is pinned
-					if let Some(hash) = self.blockchain.hash(number)? {
-						self.blockchain.insert_persisted_body_if_pinned(hash)?;
-
-						// If the block was finalized in this transaction, it will not be in the db
-						// yet.
-						if let Some(justification) =
-							current_transaction_justifications.remove(&hash)
-						{
-							self.blockchain.insert_justifications_if_pinned(hash, justification);
-						} else {
-							self.blockchain.insert_persisted_justifications_if_pinned(hash)?;
-						}
-					};
-
-					self.prune_block(transaction, BlockId::<Block>::number(number))?;
+					self.prune_finalized_block(
+						transaction,
+						number,
+						current_transaction_justifications,
+					)?;
 				}
 				self.prune_displaced_branches(transaction, finalized_hash, displaced)?;
 			},
+			BlocksPruning::KeepFor(_) | BlocksPruning::KeepSize(_) => {
+				self.prune_finalized_blocks_by_constraint(
+					transaction,
+					finalized_number,
+					current_transaction_justifications,
+				)?;
+				self.prune_displaced_branches(transaction, finalized_hash, displaced)?;
+			},
 			BlocksPruning::KeepFinalized => {
 				self.prune_displaced_branches(transaction, finalized_hash, displaced)?;
 			},
@@ -1854,6 +1941,155 @@ impl<Block: BlockT> Backend<Block> {
 		Ok(())
 	}
 
+	fn prune_finalized_block(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		number: NumberFor<Block>,
+		current_transaction_justifications: &mut HashMap<Block::Hash, Justification>,
+	) -> ClientResult<()> {
+		// Before we prune a block, check if it is pinned
+		if let Some(hash) = self.blockchain.hash(number)? {
+			self.blockchain.insert_persisted_body_if_pinned(hash)?;
+
+			// If the block was finalized in this transaction, it will not be in the db
+			// yet.
+			if let Some(justification) = current_transaction_justifications.remove(&hash) {
+				self.blockchain.insert_justifications_if_pinned(hash, justification);
+			} else {
+				self.blockchain.insert_persisted_justifications_if_pinned(hash)?;
+			}
+		};
+
+		self.prune_block(transaction, BlockId::<Block>::number(number))
+	}
+
+	// Records the finalization time of `finalized_number` and prunes the oldest finalized blocks
+	// that are too old or no longer fit into the target database size. Blocks finalized before
+	// such a pruning mode was enabled are kept, like with `BlocksPruning::Some`.
+	fn prune_finalized_blocks_by_constraint(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		finalized_number: NumberFor<Block>,
+		current_transaction_justifications: &mut HashMap<Block::Hash, Justification>,
+	) -> ClientResult<()> {
+		let now = unix_time_millis();
+		if let BlocksPruning::KeepFor(_) = self.blocks_pruning {
+			transaction.set_from_vec(
+				columns::META,
+				&finalized_at_key(finalized_number)?,
+				now.encode(),
+			);
+		}
+
+		let mut size_budget = match self.blocks_pruning {
+			BlocksPruning::KeepSize(max_size)
+				if self.database_size().map_or(false, |size| size > max_size) =>
+				BLOCKS_SIZE_PRUNING_STEP,
+			_ => 0,
+		};
+
+		let mut cursor = self.blocks_pruning_cursor.lock();
+		let mut next = *cursor.get_or_insert(finalized_number);
+		// Always keep the last finalized block
+		while next < finalized_number {
+			let prune = match self.blocks_pruning {
+				// Blocks finalized in quick succession, e.g. during a major sync, are pruned by
+				// their number.
+				BlocksPruning::KeepFor(age)
+					if (finalized_number - next).saturated_into::<u128>() >=
+						age.as_millis() / MIN_AGE_PRUNING_BLOCK_TIME.as_millis() =>
+					true,
+				BlocksPruning::KeepFor(age) => {
+					let key = finalized_at_key(next)?;
+					match self.storage.db.get(columns::META, &key) {
+						Some(at) => {
+							let at = u64::decode(&mut &at[..]).map_err(|e| {
+								sp_blockchain::Error::Backend(format!(
+									"Error decoding finalization time: {e}"
+								))
+							})?;
+							now.saturating_sub(at) >= age.as_millis() as u64
+						},
+						// Finalized within this transaction.
+						None => false,
+					}
+				},
+				_ if size_budget > 0 &&
+					finalized_number - next >= MIN_SIZE_PRUNING_BLOCKS.into() =>
+				{
+					size_budget -= 1;
+					true
+				},
+				_ => false,
+			};
+			if !prune {
+				break
+			}
+
+			self.prune_finalized_block(transaction, next, current_transaction_justifications)?;
+			transaction.remove(columns::META, &finalized_at_key(next)?);
+			next += One::one();
+		}
+		*cursor = Some(next);
+		transaction.set_from_vec(columns::META, meta_keys::BLOCKS_PRUNING_CURSOR, next.encode());
+		Ok(())
+	}
+
+	/// Returns the blocks before the state pruning window whose state is retained as a
+	/// checkpoint, ordered by block number.
+	///
+	/// Empty unless state pruning is constrained with a checkpoint interval.
+	pub fn state_checkpoints(&self) -> Vec<(NumberFor<Block>, Block::Hash)> {
+		self.storage
+			.state_db
+			.checkpoints()
+			.into_iter()
+			.map(|(number, hash)| (number.saturated_into(), hash))
+			.collect()
+	}
+
+	/// Returns the closest block at or before `number` whose state is retained as a checkpoint.
+	///
+	/// The state of a pruned block can be recomputed by re-executing the blocks following this
+	/// checkpoint.
+	pub fn nearest_state_checkpoint(
+		&self,
+		number: NumberFor<Block>,
+	) -> Option<(NumberFor<Block>, Block::Hash)> {
+		self.state_checkpoints().into_iter().take_while(|(n, _)| *n <= number).last()
+	}
+
+	/// Check the integrity of the database, see [`check`] for the performed checks.
+	pub fn check_integrity(
+		&self,
+		settings: &check::CheckSettings,
+	) -> ClientResult<check::IntegrityReport<Block>> {
+		check::check(self.storage.db.clone(), settings)
+	}
+
+	fn state_discarded_error(
+		&self,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+	) -> sp_blockchain::Error {
+		match self.nearest_state_checkpoint(number) {
+			Some((checkpoint_number, checkpoint_hash)) =>
+				sp_blockchain::Error::UnknownBlock(format!(
+					"State already discarded for {:?}, nearest state checkpoint is #{} ({:?})",
+					hash, checkpoint_number, checkpoint_hash,
+				)),
+			None => sp_blockchain::Error::UnknownBlock(format!(
+				"State already discarded for {:?}",
+				hash
+			)),
+		}
+	}
+
+	/// Size in bytes of the on-disk database, as last measured by the size monitor.
+	fn database_size(&self) -> Option<u64> {
+		*self.database_size.lock()
+	}
+
 	fn prune_displaced_branches(
 		&self,
 		transaction: &mut Transaction<DbHash>,
@@ -2447,10 +2683,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 					let state = RefTrackingState::new(db_state, self.storage.clone(), Some(hash));
 					Ok(RecordStatsState::new(state, Some(hash), self.state_usage.clone()))
 				} else {
-					Err(sp_blockchain::Error::UnknownBlock(format!(
-						"State already discarded for {:?}",
-						hash
-					)))
+					Err(self.state_discarded_error(hash, hdr.number))
 				}
 			},
 			Err(e) => Err(e),
@@ -2689,6 +2922,7 @@ pub(crate) mod tests {
 			DatabaseSettings {
 				trie_cache_maximum_size: Some(16 * 1024 * 1024),
 				state_pruning: Some(PruningMode::blocks_pruning(1)),
+				state_checkpoint_interval: None,
 				source: DatabaseSource::Custom { db: backing, require_create_flag: false },
 				blocks_pruning: BlocksPruning::KeepFinalized,
 			},
@@ -3429,6 +3663,49 @@ pub(crate) mod tests {
 		}
 	}
 
+	#[test]
+	fn prune_blocks_on_finalize_by_age() {
+		for (max_age, pruned) in [
+			(Duration::ZERO, 3),
+			(MIN_AGE_PRUNING_BLOCK_TIME * 2, 2),
+			(Duration::from_secs(3600), 0),
+		] {
+			let backend =
+				Backend::<Block>::new_test_with_tx_storage(BlocksPruning::KeepFor(max_age), 0);
+			let mut blocks = Vec::new();
+			let mut prev_hash = Default::default();
+			for i in 0..5 {
+				let hash = insert_block(
+					&backend,
+					i,
+					prev_hash,
+					None,
+					Default::default(),
+					vec![i.into()],
+					None,
+				)
+				.unwrap();
+				blocks.push(hash);
+				prev_hash = hash;
+			}
+
+			for i in 1..5 {
+				let mut op = backend.begin_operation().unwrap();
+				backend.begin_state_operation(&mut op, blocks[4]).unwrap();
+				op.mark_finalized(blocks[i], None).unwrap();
+				backend.commit_operation(op).unwrap();
+			}
+			let bc = backend.blockchain();
+
+			for i in 1..4 {
+				let expected = if i <= pruned { None } else { Some(vec![(i as u64).into()]) };
+				assert_eq!(expected, bc.body(blocks[i]).unwrap());
+			}
+			// The last finalized block is always kept.
+			assert_eq!(Some(vec![4.into()]), bc.body(blocks[4]).unwrap());
+		}
+	}
+
 	#[test]
 	fn prune_blocks_on_finalize_with_fork() {
 		sp_tracing::try_init_simple();
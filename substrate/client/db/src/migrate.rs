@@ -0,0 +1,503 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Migration of a node database between database backends.
+//!
+//! A RocksDB database is copied column by column into a new ParityDb database, so that a node
+//! can switch backends without resyncing. ParityDb stores most columns under hashed keys, which
+//! can not be enumerated, so the opposite direction is not supported.
+//!
+//! RocksDB does not count references, so trie nodes are stored under keys prefixed with their
+//! position in the trie and the pruning journal is interpreted in memory. When copied, the prefix
+//! is stripped and each prefixed copy of a node adds a reference to it. The state journals are
+//! rewritten accordingly, and the preimages of indexed transactions are stored as many times as
+//! they are referenced.
+//!
+//! The progress is committed to the target database together with every batch, so an
+//! interrupted migration resumes from the last committed batch. Once all columns are copied,
+//! every entry of the source is optionally read back from the target and checked against the
+//! number of keys and the digest recorded while copying.
+
+use crate::{
+	columns,
+	utils::{meta_keys, DatabaseType, NUM_COLUMNS},
+	DatabaseSource, DbHash, DB_HASH_LEN,
+};
+use codec::{Decode, Encode};
+use kvdb::KeyValueDB;
+use log::{debug, info};
+use sp_core::hashing::blake2_256;
+use sp_database::{Database, Transaction};
+use sp_runtime::traits::Block as BlockT;
+use std::{collections::HashMap, fmt, io, path::Path};
+
+/// Default number of entries written to the target database per commit.
+pub const DEFAULT_BATCH_SIZE: usize = 10_000;
+
+/// Settings of a database migration.
+#[derive(Debug, Clone)]
+pub struct MigrationSettings {
+	/// Number of entries written to the target database per commit.
+	pub batch_size: usize,
+	/// Read back every migrated entry from the target database.
+	pub verify: bool,
+}
+
+impl Default for MigrationSettings {
+	fn default() -> Self {
+		MigrationSettings { batch_size: DEFAULT_BATCH_SIZE, verify: true }
+	}
+}
+
+/// Number of keys and digest of the entries of a migrated column.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode)]
+pub struct ColumnReport {
+	/// Column index.
+	pub column: u32,
+	/// Number of keys copied from the source database.
+	pub keys: u64,
+	/// Chained blake2 hash of the copied keys and the values written for them.
+	pub digest: [u8; 32],
+}
+
+impl ColumnReport {
+	fn new(column: u32) -> Self {
+		ColumnReport { column, ..Default::default() }
+	}
+
+	fn note(&mut self, key: &[u8], value: &[u8]) {
+		self.keys += 1;
+		self.digest = blake2_256(&(&self.digest, key, value).encode());
+	}
+}
+
+/// Progress of a migration, stored in the meta column of the target database.
+#[derive(Default, Encode, Decode)]
+struct Progress {
+	/// Columns that were copied completely.
+	copied: Vec<ColumnReport>,
+	/// The column being copied and the last key copied from it.
+	current: Option<(ColumnReport, Vec<u8>)>,
+}
+
+/// Database migration error.
+#[derive(Debug)]
+pub enum MigrationError {
+	/// Migrating between the given databases is not supported.
+	Unsupported(String),
+	/// A database could not be opened.
+	Open(String),
+	/// Reading the source database failed.
+	Io(io::Error),
+	/// Writing the target database failed.
+	Database(sp_database::error::DatabaseError),
+	/// The source database contains unexpected data.
+	Corrupted(String),
+	/// The target database contains data that was not written by a migration.
+	TargetNotEmpty,
+	/// A migrated column does not match the source.
+	Verification {
+		/// Column index.
+		column: u32,
+		/// Description of the mismatch.
+		reason: String,
+	},
+}
+
+impl fmt::Display for MigrationError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			MigrationError::Unsupported(e) => write!(f, "Unsupported migration: {}", e),
+			MigrationError::Open(e) => write!(f, "Failed to open database: {}", e),
+			MigrationError::Io(e) => write!(f, "Failed to read source database: {}", e),
+			MigrationError::Database(e) => write!(f, "Failed to write target database: {}", e),
+			MigrationError::Corrupted(e) => write!(f, "Source database is corrupted: {}", e),
+			MigrationError::TargetNotEmpty =>
+				write!(f, "Target database already exists and was not created by a migration"),
+			MigrationError::Verification { column, reason } =>
+				write!(f, "Verification of column {} failed: {}", column, reason),
+		}
+	}
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<io::Error> for MigrationError {
+	fn from(err: io::Error) -> Self {
+		MigrationError::Io(err)
+	}
+}
+
+impl From<sp_database::error::DatabaseError> for MigrationError {
+	fn from(err: sp_database::error::DatabaseError) -> Self {
+		MigrationError::Database(err)
+	}
+}
+
+/// Migrate the RocksDB database of `source` into a ParityDb database at `target`.
+///
+/// For an `Auto` source, the database is migrated from its RocksDB path to its ParityDb path.
+/// Returns the reports of all columns.
+pub fn migrate_to_parity_db<Block: BlockT>(
+	source: &DatabaseSource,
+	target: Option<&Path>,
+	settings: &MigrationSettings,
+) -> Result<Vec<ColumnReport>, MigrationError> {
+	let (source_path, cache_size, default_target) = match source {
+		DatabaseSource::Auto { rocksdb_path, paritydb_path, cache_size } =>
+			(rocksdb_path.as_path(), *cache_size, Some(paritydb_path.as_path())),
+		#[cfg(feature = "rocksdb")]
+		DatabaseSource::RocksDb { path, cache_size } => (path.as_path(), *cache_size, None),
+		DatabaseSource::ParityDb { .. } =>
+			return Err(MigrationError::Unsupported(
+				"ParityDb stores hashed keys that can not be migrated to another database".into(),
+			)),
+		DatabaseSource::Custom { .. } =>
+			return Err(MigrationError::Unsupported("custom databases can not be migrated".into())),
+	};
+	let target = target.or(default_target).ok_or_else(|| {
+		MigrationError::Unsupported("a path for the ParityDb database is required".into())
+	})?;
+	if target == source_path {
+		return Err(MigrationError::Unsupported(
+			"source and target databases must be in different directories".into(),
+		))
+	}
+
+	let source = open_source::<Block>(source_path, cache_size)?;
+	let target = crate::parity_db::open::<DbHash>(target, DatabaseType::Full, true, false)
+		.map_err(|e| MigrationError::Open(e.to_string()))?;
+	migrate::<Block>(&source, &*target, settings)
+}
+
+#[cfg(any(feature = "rocksdb", test))]
+fn open_source<Block: BlockT>(
+	path: &Path,
+	cache_size: usize,
+) -> Result<kvdb_rocksdb::Database, MigrationError> {
+	crate::utils::open_raw_kvdb_rocksdb::<Block>(path, DatabaseType::Full, false, cache_size)
+		.map_err(|e| MigrationError::Open(e.to_string()))
+}
+
+#[cfg(not(any(feature = "rocksdb", test)))]
+fn open_source<Block: BlockT>(
+	_path: &Path,
+	_cache_size: usize,
+) -> Result<kvdb_memorydb::InMemory, MigrationError> {
+	Err(MigrationError::Open("`with-kvdb-rocksdb` feature not enabled".into()))
+}
+
+/// Copy all columns of `source`, which does not count references, into `target`, which does.
+fn migrate<Block: BlockT>(
+	source: &dyn KeyValueDB,
+	target: &dyn Database<DbHash>,
+	settings: &MigrationSettings,
+) -> Result<Vec<ColumnReport>, MigrationError> {
+	if !target.supports_ref_counting() {
+		return Err(MigrationError::Unsupported(
+			"the target database must support reference counting".into(),
+		))
+	}
+	let mut progress = match target.get(columns::META, meta_keys::MIGRATION_PROGRESS) {
+		Some(progress) => Progress::decode(&mut progress.as_slice())
+			.map_err(|e| MigrationError::Corrupted(format!("invalid migration progress: {}", e)))?,
+		None if target.get(columns::META, meta_keys::TYPE).is_some() =>
+			return Err(MigrationError::TargetNotEmpty),
+		None => Progress::default(),
+	};
+	if !progress.copied.is_empty() || progress.current.is_some() {
+		info!(target: "db", "Resuming database migration at column {}", progress.copied.len());
+	}
+	let journals = ref_counted_journals::<Block>(source)?;
+
+	while progress.copied.len() < NUM_COLUMNS as usize {
+		let column = progress.copied.len() as u32;
+		let (mut report, last_key) = match progress.current.take() {
+			Some((report, last_key)) => (report, Some(last_key)),
+			None => (ColumnReport::new(column), None),
+		};
+		let mut transaction = Transaction::new();
+		let mut pending = 0;
+		let mut last = None;
+		for entry in source.iter(column) {
+			let (key, value) = entry?;
+			if last_key.as_ref().map_or(false, |last_key| key.as_ref() <= last_key.as_slice()) {
+				continue
+			}
+			let Some((value, references)) = migrated_value(source, &journals, column, &key, value)?
+			else {
+				continue
+			};
+			write_entry(&mut transaction, column, &key, &value, references);
+			report.note(&key, &value);
+			last = Some(key.to_vec());
+			pending += 1;
+			if pending >= settings.batch_size {
+				progress.current = last.take().map(|last| (report.clone(), last));
+				commit_progress(target, transaction, &progress)?;
+				progress.current = None;
+				debug!(target: "db", "Migrated {} keys of column {}", report.keys, column);
+				transaction = Transaction::new();
+				pending = 0;
+			}
+		}
+		info!(target: "db", "Migrated column {}: {} keys", column, report.keys);
+		progress.copied.push(report);
+		commit_progress(target, transaction, &progress)?;
+	}
+
+	if settings.verify {
+		for report in progress.copied.iter() {
+			verify_column(source, target, &journals, report)?;
+		}
+		info!(target: "db", "Verified all migrated columns");
+	}
+	let mut transaction = Transaction::new();
+	transaction.remove(columns::META, meta_keys::MIGRATION_PROGRESS);
+	target.commit(transaction)?;
+	Ok(progress.copied)
+}
+
+fn commit_progress(
+	target: &dyn Database<DbHash>,
+	mut transaction: Transaction<DbHash>,
+	progress: &Progress,
+) -> Result<(), MigrationError> {
+	transaction.set_from_vec(columns::META, meta_keys::MIGRATION_PROGRESS, progress.encode());
+	target.commit(transaction)?;
+	Ok(())
+}
+
+/// Value to write for a source entry and the number of references to it. `None` for entries
+/// that are not copied.
+fn migrated_value(
+	source: &dyn KeyValueDB,
+	journals: &HashMap<Vec<u8>, Vec<u8>>,
+	column: u32,
+	key: &[u8],
+	value: Vec<u8>,
+) -> Result<Option<(Vec<u8>, u32)>, MigrationError> {
+	Ok(match column {
+		columns::STATE => {
+			if key.len() < DB_HASH_LEN {
+				return Err(MigrationError::Corrupted(format!("invalid state key {:02x?}", key)))
+			}
+			Some((value, 1))
+		},
+		columns::STATE_META => Some((journals.get(key).cloned().unwrap_or(value), 1)),
+		columns::TRANSACTION => {
+			// reference counters are stored next to the preimage, under the key suffixed by `0`
+			if key.len() != DB_HASH_LEN {
+				return Ok(None)
+			}
+			let mut counter_key = key.to_vec();
+			counter_key.push(0);
+			let references = match source.get(column, &counter_key)? {
+				Some(counter) => <[u8; 4]>::try_from(counter.as_slice())
+					.map(u32::from_le_bytes)
+					.map_err(|_| {
+						MigrationError::Corrupted(format!(
+							"invalid reference counter of {:02x?}",
+							key
+						))
+					})?,
+				None => 1,
+			};
+			Some((value, references))
+		},
+		_ => Some((value, 1)),
+	})
+}
+
+fn write_entry(
+	transaction: &mut Transaction<DbHash>,
+	column: u32,
+	key: &[u8],
+	value: &[u8],
+	references: u32,
+) {
+	match column {
+		// every prefixed copy of a trie node adds a reference to it
+		columns::STATE => transaction.set(column, &key[key.len() - DB_HASH_LEN..], value),
+		columns::TRANSACTION =>
+			for _ in 0..references {
+				transaction.store(column, DbHash::from_slice(key), value.to_vec());
+			},
+		_ => transaction.set(column, key, value),
+	}
+}
+
+fn target_key(column: u32, key: &[u8]) -> &[u8] {
+	match column {
+		columns::STATE => &key[key.len() - DB_HASH_LEN..],
+		_ => key,
+	}
+}
+
+fn verify_column(
+	source: &dyn KeyValueDB,
+	target: &dyn Database<DbHash>,
+	journals: &HashMap<Vec<u8>, Vec<u8>>,
+	report: &ColumnReport,
+) -> Result<(), MigrationError> {
+	let column = report.column;
+	let mut verified = ColumnReport::new(column);
+	for entry in source.iter(column) {
+		let (key, value) = entry?;
+		let Some((expected, _)) = migrated_value(source, journals, column, &key, value)? else {
+			continue
+		};
+		let value = target.get(column, target_key(column, &key)).ok_or_else(|| {
+			MigrationError::Verification { column, reason: format!("missing key {:02x?}", key) }
+		})?;
+		if value != expected {
+			return Err(MigrationError::Verification {
+				column,
+				reason: format!("value mismatch for key {:02x?}", key),
+			})
+		}
+		verified.note(&key, &value);
+	}
+	if verified != *report {
+		return Err(MigrationError::Verification {
+			column,
+			reason: format!(
+				"copied {} keys, but {} keys were verified or the digest differs",
+				report.keys, verified.keys
+			),
+		})
+	}
+	Ok(())
+}
+
+struct SourceMetaDb<'a>(&'a dyn KeyValueDB);
+
+impl sc_state_db::MetaDb for SourceMetaDb<'_> {
+	type Error = io::Error;
+
+	fn get_meta(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.0.get(columns::STATE_META, key)
+	}
+}
+
+/// State journals of `source`, rewritten for a database that counts references.
+fn ref_counted_journals<Block: BlockT>(
+	source: &dyn KeyValueDB,
+) -> Result<HashMap<Vec<u8>, Vec<u8>>, MigrationError> {
+	let sanitize = |mut key: Vec<u8>| {
+		let _prefix = key.drain(0..key.len().saturating_sub(DB_HASH_LEN));
+		key
+	};
+	let journals = sc_state_db::ref_counted_journals::<Block::Hash, Vec<u8>, _>(
+		&SourceMetaDb(source),
+		sanitize,
+	)
+	.map_err(|e| MigrationError::Corrupted(format!("invalid state journal: {:?}", e)))?;
+	Ok(journals.inserted.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::Block;
+	use sp_core::H256;
+
+	#[test]
+	fn migrates_references_and_rejects_existing_target() {
+		let source = kvdb_memorydb::create(NUM_COLUMNS);
+		let node = H256::repeat_byte(1);
+		let preimage = H256::repeat_byte(2);
+		let mut transaction = source.transaction();
+		transaction.put(columns::META, meta_keys::TYPE, b"full");
+		transaction.put(columns::HEADER, b"header", b"value");
+		for prefix in [&[1u8][..], &[2, 3]] {
+			transaction.put(columns::STATE, &[prefix, node.as_bytes()].concat(), b"node");
+		}
+		transaction.put(columns::TRANSACTION, preimage.as_bytes(), b"extrinsic");
+		transaction.put(
+			columns::TRANSACTION,
+			&[preimage.as_bytes(), &[0]].concat(),
+			&2u32.to_le_bytes(),
+		);
+		source.write(transaction).unwrap();
+
+		let dir = tempfile::tempdir().unwrap();
+		let target =
+			crate::parity_db::open::<DbHash>(dir.path(), DatabaseType::Full, true, false).unwrap();
+		let settings = MigrationSettings { batch_size: 1, verify: true };
+		let reports = migrate::<Block>(&source, &*target, &settings).unwrap();
+		assert_eq!(reports.len(), NUM_COLUMNS as usize);
+		assert_eq!(reports[columns::STATE as usize].keys, 2);
+		assert_eq!(reports[columns::TRANSACTION as usize].keys, 1);
+		assert_eq!(target.get(columns::HEADER, b"header"), Some(b"value".to_vec()));
+		assert_eq!(target.get(columns::META, meta_keys::MIGRATION_PROGRESS), None);
+
+		// every prefixed copy of the node and every counted reference to the preimage is kept
+		for _ in 0..2 {
+			assert_eq!(target.get(columns::STATE, node.as_bytes()), Some(b"node".to_vec()));
+			assert_eq!(
+				target.get(columns::TRANSACTION, preimage.as_bytes()),
+				Some(b"extrinsic".to_vec())
+			);
+			let mut transaction = Transaction::new();
+			transaction.remove(columns::STATE, node.as_bytes());
+			transaction.release(columns::TRANSACTION, preimage);
+			target.commit(transaction).unwrap();
+		}
+		assert_eq!(target.get(columns::STATE, node.as_bytes()), None);
+		assert_eq!(target.get(columns::TRANSACTION, preimage.as_bytes()), None);
+
+		assert!(matches!(
+			migrate::<Block>(&source, &*target, &settings),
+			Err(MigrationError::TargetNotEmpty)
+		));
+	}
+
+	#[test]
+	fn resumes_interrupted_migration() {
+		let source = kvdb_memorydb::create(NUM_COLUMNS);
+		let mut transaction = source.transaction();
+		for i in 0..4u8 {
+			transaction.put(columns::HEADER, &[i], &[i]);
+		}
+		source.write(transaction).unwrap();
+
+		// pretend the first two headers were copied before the migration was interrupted
+		let dir = tempfile::tempdir().unwrap();
+		let target =
+			crate::parity_db::open::<DbHash>(dir.path(), DatabaseType::Full, true, false).unwrap();
+		let mut progress = Progress {
+			copied: (0..columns::HEADER).map(ColumnReport::new).collect(),
+			current: None,
+		};
+		let mut report = ColumnReport::new(columns::HEADER);
+		let mut transaction = Transaction::new();
+		for i in 0..2u8 {
+			transaction.set(columns::HEADER, &[i], &[i]);
+			report.note(&[i], &[i]);
+		}
+		progress.current = Some((report, vec![1]));
+		commit_progress(&*target, transaction, &progress).unwrap();
+
+		let reports = migrate::<Block>(&source, &*target, &MigrationSettings::default()).unwrap();
+		assert_eq!(reports[columns::HEADER as usize].keys, 4);
+		for i in 0..4u8 {
+			assert_eq!(target.get(columns::HEADER, &[i]), Some(vec![i]));
+		}
+	}
+}
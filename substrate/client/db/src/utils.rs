@@ -56,6 +56,12 @@ pub mod meta_keys {
 	pub const LEAF_PREFIX: &[u8; 4] = b"leaf";
 	/// Children prefix list key.
 	pub const CHILDREN_PREFIX: &[u8; 8] = b"children";
+	/// Next finalized block to be pruned by age or size based blocks pruning.
+	pub const BLOCKS_PRUNING_CURSOR: &[u8; 6] = b"bprune";
+	/// Finalization time prefix key, used by age based blocks pruning.
+	pub const FINALIZED_AT_PREFIX: &[u8; 5] = b"finat";
+	/// Progress of an interrupted database migration.
+	pub const MIGRATION_PROGRESS: &[u8; 7] = b"migrate";
 }
 
 /// Database metadata.
@@ -300,6 +306,18 @@ fn open_kvdb_rocksdb<Block: BlockT>(
 	create: bool,
 	cache_size: usize,
 ) -> OpenDbResult {
+	let db = open_raw_kvdb_rocksdb::<Block>(path, db_type, create, cache_size)?;
+	Ok(sp_database::as_database(db))
+}
+
+/// Opens a RocksDB database without wrapping it, so that its columns may be iterated.
+#[cfg(any(feature = "rocksdb", test))]
+pub(crate) fn open_raw_kvdb_rocksdb<Block: BlockT>(
+	path: &Path,
+	db_type: DatabaseType,
+	create: bool,
+	cache_size: usize,
+) -> Result<kvdb_rocksdb::Database, OpenDbError> {
 	// first upgrade database to required version
 	match crate::upgrade::upgrade_db::<Block>(path, db_type) {
 		// in case of missing version file, assume that database simply does not exist at given
@@ -340,7 +358,7 @@ fn open_kvdb_rocksdb<Block: BlockT>(
 	let db = kvdb_rocksdb::Database::open(&db_config, path)?;
 	// write database version only after the database is succesfully opened
 	crate::upgrade::update_version(path)?;
-	Ok(sp_database::as_database(db))
+	Ok(db)
 }
 
 #[cfg(not(any(feature = "rocksdb", test)))]
@@ -554,6 +572,39 @@ impl DatabaseType {
 	}
 }
 
+/// Size in bytes of the files of an on-disk database. `None` for custom databases.
+pub(crate) fn database_size(source: &DatabaseSource) -> Option<u64> {
+	let paths = match source {
+		DatabaseSource::Auto { paritydb_path, rocksdb_path, .. } =>
+			vec![paritydb_path.as_path(), rocksdb_path.as_path()],
+		#[cfg(feature = "rocksdb")]
+		DatabaseSource::RocksDb { path, .. } => vec![path.as_path()],
+		DatabaseSource::ParityDb { path } => vec![path.as_path()],
+		DatabaseSource::Custom { .. } => return None,
+	};
+	let mut size = 0;
+	for path in paths.into_iter().filter(|path| path.exists()) {
+		match directory_size(path) {
+			Ok(s) => size += s,
+			Err(e) => {
+				debug!(target: "db", "Failed to read the size of {}: {}", path.display(), e);
+				return None
+			},
+		}
+	}
+	Some(size)
+}
+
+fn directory_size(path: &Path) -> io::Result<u64> {
+	let mut size = 0;
+	for entry in fs::read_dir(path)? {
+		let entry = entry?;
+		let metadata = entry.metadata()?;
+		size += if metadata.is_dir() { directory_size(&entry.path())? } else { metadata.len() };
+	}
+	Ok(size)
+}
+
 pub(crate) struct JoinInput<'a, 'b>(&'a [u8], &'b [u8]);
 
 pub(crate) fn join_input<'a, 'b>(i1: &'a [u8], i2: &'b [u8]) -> JoinInput<'a, 'b> {
@@ -20,8 +20,35 @@
 
 pub mod message;
 
+use codec::DecodeAll;
+use serde::{Deserialize, Serialize};
+use sp_core::Bytes;
+
+/// Block trusted by the node operator to sync the state from, without verifying the chain up
+/// to it.
+///
+/// Can be provided on the command line or as the `checkpoint` chain spec extension. The network
+/// configuration isn't generic over the block, so it holds the encoded hashes, see
+/// [`Checkpoint::decode`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint<Hash = Bytes> {
+	/// Hash of the checkpoint block.
+	pub hash: Hash,
+	/// State root of the checkpoint block.
+	pub state_root: Hash,
+}
+
+impl Checkpoint {
+	/// Decode the hashes as the block hash type `Hash` of the chain.
+	pub fn decode<Hash: DecodeAll>(&self) -> Result<Checkpoint<Hash>, codec::Error> {
+		let decode = |hash: &Bytes| Hash::decode_all(&mut &hash[..]);
+		Ok(Checkpoint { hash: decode(&self.hash)?, state_root: decode(&self.state_root)? })
+	}
+}
+
 /// Sync operation mode.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum SyncMode {
 	/// Full block download and verification.
 	Full,
@@ -34,6 +61,8 @@ pub enum SyncMode {
 	},
 	/// Warp sync - verify authority set transitions and the latest state.
 	Warp,
+	/// Download and verify the state of a trusted checkpoint block, then sync forward from it.
+	Checkpoint(Checkpoint),
 }
 
 impl SyncMode {
@@ -46,6 +75,11 @@ impl SyncMode {
 	pub fn light_state(&self) -> bool {
 		matches!(self, Self::LightState { .. })
 	}
+
+	/// Returns `true` if `self` is [`Self::Checkpoint`].
+	pub fn is_checkpoint(&self) -> bool {
+		matches!(self, Self::Checkpoint(_))
+	}
 }
 
 impl Default for SyncMode {
@@ -152,8 +152,9 @@ pub enum BehaviourOut {
 		info: IdentifyInfo,
 	},
 
-	/// We have learned about the existence of a node on the default set.
-	Discovered(PeerId),
+	/// We have learned about the existence of a node on the default set, along with the
+	/// addresses it was discovered at, if any.
+	Discovered(PeerId, Vec<Multiaddr>),
 
 	/// Events generated by a DHT as a response to get_value or put_value requests as well as the
 	/// request duration.
@@ -344,7 +345,8 @@ impl From<DiscoveryOut> for BehaviourOut {
 				// implementation.
 				BehaviourOut::None
 			},
-			DiscoveryOut::Discovered(peer_id) => BehaviourOut::Discovered(peer_id),
+			DiscoveryOut::Discovered(peer_id, addresses) =>
+				BehaviourOut::Discovered(peer_id, addresses),
 			DiscoveryOut::ValueFound(results, duration) =>
 				BehaviourOut::Dht(DhtEvent::ValueFound(results), duration),
 			DiscoveryOut::ValueNotFound(key, duration) =>
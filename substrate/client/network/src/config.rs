@@ -43,7 +43,7 @@ use zeroize::Zeroize;
 
 pub use sc_network_common::{
 	role::{Role, Roles},
-	sync::SyncMode,
+	sync::{Checkpoint, SyncMode},
 	ExHashT,
 };
 
@@ -341,7 +341,8 @@ impl DiscoveryBehaviour {
 			k.add_address(&peer_id, addr.clone());
 		}
 
-		self.pending_events.push_back(DiscoveryOut::Discovered(peer_id));
+		self.pending_events
+			.push_back(DiscoveryOut::Discovered(peer_id, vec![addr.clone()]));
 		addrs_list.push(addr);
 	}
 
@@ -461,7 +462,9 @@ pub enum DiscoveryOut {
 	/// configured. If the peer is to be included in the routing table, it must
 	/// be explicitly added via
 	/// [`DiscoveryBehaviour::add_self_reported_address`].
-	Discovered(PeerId),
+	///
+	/// Also carries the addresses the peer was discovered at, if any.
+	Discovered(PeerId, Vec<Multiaddr>),
 
 	/// A peer connected to this node for whom no listen address is known.
 	///
@@ -725,16 +728,16 @@ impl NetworkBehaviour for DiscoveryBehaviour {
 		while let Poll::Ready(ev) = self.kademlia.poll(cx, params) {
 			match ev {
 				ToSwarm::GenerateEvent(ev) => match ev {
-					KademliaEvent::RoutingUpdated { peer, .. } => {
-						let ev = DiscoveryOut::Discovered(peer);
+					KademliaEvent::RoutingUpdated { peer, addresses, .. } => {
+						let ev = DiscoveryOut::Discovered(peer, addresses.into_vec());
 						return Poll::Ready(ToSwarm::GenerateEvent(ev))
 					},
 					KademliaEvent::UnroutablePeer { peer, .. } => {
 						let ev = DiscoveryOut::UnroutablePeer(peer);
 						return Poll::Ready(ToSwarm::GenerateEvent(ev))
 					},
-					KademliaEvent::RoutablePeer { peer, .. } => {
-						let ev = DiscoveryOut::Discovered(peer);
+					KademliaEvent::RoutablePeer { peer, address } => {
+						let ev = DiscoveryOut::Discovered(peer, vec![address]);
 						return Poll::Ready(ToSwarm::GenerateEvent(ev))
 					},
 					KademliaEvent::PendingRoutablePeer { .. } |
@@ -906,8 +909,9 @@ impl NetworkBehaviour for DiscoveryBehaviour {
 							continue
 						}
 
-						self.pending_events
-							.extend(list.map(|(peer_id, _)| DiscoveryOut::Discovered(peer_id)));
+						let discovered =
+							list.map(|(peer, addr)| DiscoveryOut::Discovered(peer, vec![addr]));
+						self.pending_events.extend(discovered);
 						if let Some(ev) = self.pending_events.pop_front() {
 							return Poll::Ready(ToSwarm::GenerateEvent(ev))
 						}
@@ -1051,7 +1055,7 @@ mod tests {
 								SwarmEvent::Behaviour(behavior) => {
 									match behavior {
 										DiscoveryOut::UnroutablePeer(other) |
-										DiscoveryOut::Discovered(other) => {
+										DiscoveryOut::Discovered(other, _) => {
 											// Call `add_self_reported_address` to simulate identify
 											// happening.
 											let addr = swarms
@@ -18,18 +18,26 @@
 
 //! [`PeerStore`] manages peer reputations and provides connection candidates to
 //! [`crate::protocol_controller::ProtocolController`].
+//!
+//! The store can optionally be persisted to disk (see [`PeerStore::new_persistent`]), in which
+//! case the peers, their reputations and the addresses they were reached at are periodically
+//! saved and restored on the next start, with the reputations decayed for the time the node was
+//! offline.
 
-use libp2p::PeerId;
+use libp2p::{Multiaddr, PeerId};
 use log::trace;
 use parking_lot::Mutex;
 use partial_sort::PartialSort;
 use sc_network_common::types::ReputationChange;
+use serde::{Deserialize, Serialize};
 use std::{
 	cmp::{Ord, Ordering, PartialOrd},
 	collections::{hash_map::Entry, HashMap, HashSet},
 	fmt::Debug,
+	fs, io,
+	path::{Path, PathBuf},
 	sync::Arc,
-	time::{Duration, Instant},
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use wasm_timer::Delay;
 
@@ -51,6 +59,10 @@ const INVERSE_DECREMENT: i32 = 50;
 /// Amount of time between the moment we last updated the [`PeerStore`] entry and the moment we
 /// remove it, once the reputation value reaches 0.
 const FORGET_AFTER: Duration = Duration::from_secs(3600);
+/// Interval between two snapshots of a persistent [`PeerStore`] written to disk.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
+/// Maximum number of addresses remembered for a single peer.
+const MAX_ADDRESSES_PER_PEER: usize = 4;
 
 /// Trait providing peer reputation management and connection candidates.
 pub trait PeerStoreProvider: Debug + Send {
@@ -118,6 +130,23 @@ impl PeerStoreHandle {
 	pub fn add_known_peer(&mut self, peer_id: PeerId) {
 		self.inner.lock().add_known_peer(peer_id);
 	}
+
+	/// Remember an address the peer was successfully reached at.
+	pub fn add_known_address(&mut self, peer_id: PeerId, address: Multiaddr) {
+		self.inner.lock().add_known_address(peer_id, address);
+	}
+
+	/// Get the remembered addresses of all known peers.
+	pub fn known_addresses(&self) -> Vec<(PeerId, Multiaddr)> {
+		self.inner
+			.lock()
+			.addresses
+			.iter()
+			.flat_map(|(peer_id, addresses)| {
+				addresses.iter().map(move |address| (*peer_id, address.clone()))
+			})
+			.collect()
+	}
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -190,6 +219,8 @@ impl PeerInfo {
 #[derive(Debug)]
 struct PeerStoreInner {
 	peers: HashMap<PeerId, PeerInfo>,
+	/// Addresses the peers were reached at, most recent first.
+	addresses: HashMap<PeerId, Vec<Multiaddr>>,
 	protocols: Vec<ProtocolHandle>,
 }
 
@@ -275,6 +306,8 @@ impl PeerStoreInner {
 		let now = Instant::now();
 		self.peers
 			.retain(|_, info| info.reputation != 0 || info.last_updated + FORGET_AFTER > now);
+		let peers = &self.peers;
+		self.addresses.retain(|peer_id, _| peers.contains_key(peer_id));
 	}
 
 	fn add_known_peer(&mut self, peer_id: PeerId) {
@@ -292,12 +325,128 @@ impl PeerStoreInner {
 			},
 		}
 	}
+
+	fn add_known_address(&mut self, peer_id: PeerId, address: Multiaddr) {
+		self.add_known_peer(peer_id);
+
+		let addresses = self.addresses.entry(peer_id).or_default();
+		addresses.retain(|known| *known != address);
+		addresses.insert(0, address);
+		addresses.truncate(MAX_ADDRESSES_PER_PEER);
+	}
+
+	fn snapshot(&self) -> Snapshot {
+		let now = Instant::now();
+		let unix_now = unix_time();
+
+		let peers = self
+			.peers
+			.iter()
+			.map(|(peer_id, info)| PeerSnapshot {
+				peer_id: peer_id.to_base58(),
+				reputation: info.reputation,
+				last_seen: unix_now
+					.saturating_sub(now.saturating_duration_since(info.last_updated).as_secs()),
+				addresses: self
+					.addresses
+					.get(peer_id)
+					.map(|addresses| addresses.iter().map(ToString::to_string).collect())
+					.unwrap_or_default(),
+			})
+			.collect();
+
+		Snapshot { saved_at: unix_now, peers }
+	}
+
+	fn restore(&mut self, snapshot: Snapshot) {
+		let now = Instant::now();
+		let unix_now = unix_time();
+		let offline = unix_now.saturating_sub(snapshot.saved_at);
+
+		for peer in snapshot.peers {
+			let Ok(peer_id) = peer.peer_id.parse::<PeerId>() else {
+				log::debug!(target: LOG_TARGET, "Skipping invalid peer id {} in snapshot.", peer.peer_id);
+				continue
+			};
+
+			let since_seen = Duration::from_secs(unix_now.saturating_sub(peer.last_seen));
+			let mut info = PeerInfo {
+				reputation: peer.reputation,
+				last_updated: now.checked_sub(since_seen).unwrap_or(now),
+			};
+			info.decay_reputation(offline);
+
+			// Apply the same rule as `progress_time` to peers forgotten while we were offline.
+			if info.reputation == 0 && since_seen >= FORGET_AFTER {
+				continue
+			}
+
+			self.peers.insert(peer_id, info);
+
+			let addresses = peer
+				.addresses
+				.iter()
+				.filter_map(|address| address.parse::<Multiaddr>().ok())
+				.take(MAX_ADDRESSES_PER_PEER)
+				.collect::<Vec<_>>();
+			if !addresses.is_empty() {
+				self.addresses.insert(peer_id, addresses);
+			}
+		}
+	}
+}
+
+/// On-disk representation of a single [`PeerStore`] entry.
+#[derive(Debug, Serialize, Deserialize)]
+struct PeerSnapshot {
+	/// Base58-encoded peer id.
+	peer_id: String,
+	reputation: i32,
+	/// Unix time (in seconds) of the last update of the entry.
+	last_seen: u64,
+	/// Addresses the peer was reached at, most recent first.
+	addresses: Vec<String>,
+}
+
+/// On-disk representation of the [`PeerStore`].
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+	/// Unix time (in seconds) the snapshot was taken at.
+	saved_at: u64,
+	peers: Vec<PeerSnapshot>,
+}
+
+fn unix_time() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn load_snapshot(path: &Path) -> io::Result<Option<Snapshot>> {
+	match fs::read(path) {
+		Ok(data) => serde_json::from_slice(&data)
+			.map(Some)
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+		Err(e) => Err(e),
+	}
+}
+
+fn save_snapshot(path: &Path, snapshot: &Snapshot) -> io::Result<()> {
+	let data = serde_json::to_vec(snapshot)?;
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)?;
+	}
+
+	// Write to a temporary file first so that a crash never leaves a truncated snapshot behind.
+	let tmp_path = path.with_extension("tmp");
+	fs::write(&tmp_path, data)?;
+	fs::rename(tmp_path, path)
 }
 
 /// Worker part of [`PeerStoreHandle`]
 #[derive(Debug)]
 pub struct PeerStore {
 	inner: Arc<Mutex<PeerStoreInner>>,
+	snapshot_path: Option<PathBuf>,
 }
 
 impl PeerStore {
@@ -309,11 +458,59 @@ impl PeerStore {
 					.into_iter()
 					.map(|peer_id| (peer_id, PeerInfo::default()))
 					.collect(),
+				addresses: HashMap::new(),
 				protocols: Vec::new(),
 			})),
+			snapshot_path: None,
 		}
 	}
 
+	/// Create a new peer store from the list of bootnodes, restoring the peers saved to
+	/// `snapshot_path` by a previous run.
+	///
+	/// While running, the store is periodically saved back to `snapshot_path`.
+	pub fn new_persistent(bootnodes: Vec<PeerId>, snapshot_path: PathBuf) -> Self {
+		let mut peer_store = Self::new(bootnodes);
+
+		match load_snapshot(&snapshot_path) {
+			Ok(Some(snapshot)) => {
+				let mut inner = peer_store.inner.lock();
+				inner.restore(snapshot);
+				log::debug!(
+					target: LOG_TARGET,
+					"Restored {} known peers from {}.",
+					inner.peers.len(),
+					snapshot_path.display(),
+				);
+			},
+			Ok(None) => {},
+			Err(e) => log::warn!(
+				target: LOG_TARGET,
+				"Failed to load peer store snapshot from {}: {e}",
+				snapshot_path.display(),
+			),
+		}
+
+		peer_store.snapshot_path = Some(snapshot_path);
+		peer_store
+	}
+
+	/// Save the store to its snapshot path, if it is persistent.
+	pub fn save_snapshot(&self) -> io::Result<()> {
+		let Some(path) = &self.snapshot_path else { return Ok(()) };
+		let snapshot = self.inner.lock().snapshot();
+		save_snapshot(path, &snapshot)
+	}
+
+	/// Save the store to its snapshot path on a blocking task, if it is persistent.
+	async fn save_snapshot_in_background(&self) -> io::Result<()> {
+		let Some(path) = self.snapshot_path.clone() else { return Ok(()) };
+		let snapshot = self.inner.lock().snapshot();
+		tokio::task::spawn_blocking(move || save_snapshot(&path, &snapshot))
+			.await
+			.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+	}
+
 	/// Get `PeerStoreHandle`.
 	pub fn handle(&self) -> PeerStoreHandle {
 		PeerStoreHandle { inner: self.inner.clone() }
@@ -323,6 +520,7 @@ impl PeerStore {
 	pub async fn run(self) {
 		let started = Instant::now();
 		let mut latest_time_update = started;
+		let mut latest_snapshot = started;
 
 		loop {
 			let now = Instant::now();
@@ -336,14 +534,38 @@ impl PeerStore {
 			};
 
 			self.inner.lock().progress_time(seconds_passed);
+
+			if now - latest_snapshot >= SNAPSHOT_INTERVAL {
+				latest_snapshot = now;
+				if let Err(e) = self.save_snapshot_in_background().await {
+					log::warn!(target: LOG_TARGET, "Failed to save peer store snapshot: {e}");
+				}
+			}
+
 			let _ = Delay::new(Duration::from_secs(1)).await;
 		}
 	}
 }
 
+impl Drop for PeerStore {
+	fn drop(&mut self) {
+		// The worker is only dropped on shutdown, so write the final snapshot right away rather
+		// than on a blocking task that may never get to run.
+		if let Err(e) = self.save_snapshot() {
+			log::warn!(target: LOG_TARGET, "Failed to save peer store snapshot: {e}");
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use super::PeerInfo;
+	use super::{PeerInfo, PeerStore, PeerStoreInner, FORGET_AFTER};
+	use libp2p::{Multiaddr, PeerId};
+	use std::{collections::HashMap, time::Duration};
+
+	fn empty_inner() -> PeerStoreInner {
+		PeerStoreInner { peers: HashMap::new(), addresses: HashMap::new(), protocols: Vec::new() }
+	}
 
 	#[test]
 	fn decaying_zero_reputation_yields_zero() {
@@ -410,4 +632,87 @@ mod tests {
 		peer_info.decay_reputation(SECONDS / 2);
 		assert_eq!(peer_info.reputation, 0);
 	}
+
+	#[test]
+	fn restoring_snapshot_decays_offline_reputation() {
+		let peer = PeerId::random();
+		let address: Multiaddr = "/ip4/127.0.0.1/tcp/30333".parse().unwrap();
+
+		let mut inner = empty_inner();
+		inner.add_known_address(peer, address.clone());
+		inner.peers.get_mut(&peer).unwrap().reputation = 1000;
+
+		let mut snapshot = inner.snapshot();
+		snapshot.saved_at -= 10;
+
+		let mut restored = empty_inner();
+		restored.restore(snapshot);
+		let reputation = restored.peers[&peer].reputation;
+		assert!(reputation > 0 && reputation < 1000);
+		assert_eq!(restored.addresses[&peer], vec![address]);
+	}
+
+	#[test]
+	fn restoring_snapshot_forgets_expired_peers() {
+		let banned = PeerId::random();
+		let recent = PeerId::random();
+
+		let mut inner = empty_inner();
+		inner.add_known_peer(banned);
+		inner.add_known_peer(recent);
+		inner.peers.get_mut(&banned).unwrap().reputation = i32::MIN;
+
+		let mut snapshot = inner.snapshot();
+		let offline = FORGET_AFTER.as_secs() + 1;
+		snapshot.saved_at -= offline;
+		snapshot
+			.peers
+			.iter_mut()
+			.filter(|peer| peer.peer_id == banned.to_base58())
+			.for_each(|peer| peer.last_seen -= offline);
+
+		let mut restored = empty_inner();
+		restored.restore(snapshot);
+		assert!(!restored.peers.contains_key(&banned));
+		assert_eq!(restored.peers[&recent].reputation, 0);
+	}
+
+	#[test]
+	fn persistent_peer_store_survives_restart() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("peers.json");
+		let peer = PeerId::random();
+		let address: Multiaddr = "/ip4/127.0.0.1/tcp/30333".parse().unwrap();
+
+		let peer_store = PeerStore::new_persistent(Vec::new(), path.clone());
+		assert_eq!(peer_store.handle().num_known_peers(), 0);
+		peer_store.handle().add_known_address(peer, address.clone());
+		peer_store.save_snapshot().unwrap();
+
+		let peer_store = PeerStore::new_persistent(Vec::new(), path);
+		assert_eq!(peer_store.handle().known_addresses(), vec![(peer, address)]);
+		assert!(
+			peer_store.inner.lock().peers[&peer].last_updated.elapsed() < Duration::from_secs(60)
+		);
+	}
+
+	#[tokio::test]
+	async fn persistent_peer_store_is_saved_in_background_and_on_drop() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("peers.json");
+		let first = PeerId::random();
+		let second = PeerId::random();
+
+		let peer_store = PeerStore::new_persistent(Vec::new(), path.clone());
+		peer_store.handle().add_known_peer(first);
+		peer_store.save_snapshot_in_background().await.unwrap();
+		assert_eq!(
+			PeerStore::new_persistent(Vec::new(), path.clone()).handle().num_known_peers(),
+			1
+		);
+
+		peer_store.handle().add_known_peer(second);
+		drop(peer_store);
+		assert_eq!(PeerStore::new_persistent(Vec::new(), path).handle().num_known_peers(), 2);
+	}
 }
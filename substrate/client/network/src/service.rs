@@ -507,6 +507,11 @@ where
 			);
 		}
 
+		// Add addresses of peers remembered from previous runs.
+		for (peer_id, addr) in params.peer_store.known_addresses() {
+			swarm.behaviour_mut().add_known_address(peer_id, addr);
+		}
+
 		let listen_addresses = Arc::new(Mutex::new(HashSet::new()));
 		let peers_notifications_sinks = Arc::new(Mutex::new(HashMap::new()));
 
@@ -1462,8 +1467,11 @@ where
 				}
 				self.peer_store_handle.add_known_peer(peer_id);
 			},
-			SwarmEvent::Behaviour(BehaviourOut::Discovered(peer_id)) => {
+			SwarmEvent::Behaviour(BehaviourOut::Discovered(peer_id, addresses)) => {
 				self.peer_store_handle.add_known_peer(peer_id);
+				for address in addresses {
+					self.peer_store_handle.add_known_address(peer_id, address);
+				}
 			},
 			SwarmEvent::Behaviour(BehaviourOut::RandomKademliaStarted) => {
 				if let Some(metrics) = self.metrics.as_ref() {
@@ -1593,6 +1601,10 @@ where
 					debug!(target: "sub-libp2p", "Libp2p => Connected({:?})", peer_id);
 				}
 
+				if let ConnectedPoint::Dialer { address, .. } = &endpoint {
+					self.peer_store_handle.add_known_address(peer_id, address.clone());
+				}
+
 				if let Some(metrics) = self.metrics.as_ref() {
 					let direction = match endpoint {
 						ConnectedPoint::Dialer { .. } => "out",
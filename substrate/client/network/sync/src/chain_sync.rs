@@ -32,7 +32,7 @@ use crate::{
 	blocks::BlockCollection,
 	extra_requests::ExtraRequests,
 	schema::v1::StateResponse,
-	state::{ImportResult, StateSync},
+	state::{self, ImportResult, StateSync},
 	types::{
 		BadPeer, Metrics, OpaqueStateRequest, OpaqueStateResponse, PeerInfo, SyncMode, SyncState,
 		SyncStatus,
@@ -47,10 +47,14 @@ use codec::Encode;
 use libp2p::PeerId;
 use log::{debug, error, info, trace, warn};
 
-use sc_client_api::{BlockBackend, ProofProvider};
+use sc_client_api::{AuxStore, BlockBackend, ProofProvider};
 use sc_consensus::{BlockImportError, BlockImportStatus, IncomingBlock};
-use sc_network_common::sync::message::{
-	BlockAnnounce, BlockAttributes, BlockData, BlockRequest, BlockResponse, Direction, FromBlock,
+use sc_network_common::sync::{
+	message::{
+		BlockAnnounce, BlockAttributes, BlockData, BlockRequest, BlockResponse, Direction,
+		FromBlock,
+	},
+	Checkpoint,
 };
 use sp_arithmetic::traits::Saturating;
 use sp_blockchain::{Error as ClientError, HeaderBackend, HeaderMetadata};
@@ -248,6 +252,8 @@ pub struct ChainSync<B: BlockT, Client> {
 	warp_sync_config: Option<WarpSyncConfig<B>>,
 	/// A temporary storage for warp sync target block until warp sync is initialized.
 	warp_sync_target_block_header: Option<B::Header>,
+	/// The trusted checkpoint block, until its header is downloaded.
+	checkpoint: Option<Checkpoint<B::Hash>>,
 	/// Enable importing existing blocks. This is used used after the state download to
 	/// catch up to the latest state while re-importing blocks.
 	import_existing: bool,
@@ -320,6 +326,8 @@ pub(crate) enum PeerSyncState<B: BlockT> {
 	DownloadingWarpProof,
 	/// Downloading warp sync target block.
 	DownloadingWarpTargetBlock,
+	/// Downloading the trusted checkpoint block.
+	DownloadingCheckpointBlock,
 	/// Actively downloading block history after warp sync.
 	DownloadingGap(NumberFor<B>),
 }
@@ -337,6 +345,7 @@ where
 		+ BlockBackend<B>
 		+ HeaderMetadata<B, Error = sp_blockchain::Error>
 		+ ProofProvider<B>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -349,6 +358,14 @@ where
 		max_blocks_per_request: u32,
 		warp_sync_config: Option<WarpSyncConfig<B>>,
 	) -> Result<Self, ClientError> {
+		let checkpoint = match &mode {
+			SyncMode::Checkpoint(checkpoint) => Some(
+				checkpoint
+					.decode::<B::Hash>()
+					.map_err(|e| ClientError::Application(Box::new(e)))?,
+			),
+			_ => None,
+		};
 		let mut sync = Self {
 			client,
 			peers: HashMap::new(),
@@ -369,10 +386,18 @@ where
 			gap_sync: None,
 			warp_sync_config,
 			warp_sync_target_block_header: None,
+			checkpoint: None,
 			actions: Vec::new(),
 		};
 
 		sync.reset_sync_start_point()?;
+		if let (SyncMode::Checkpoint(_), Some(Checkpoint { hash, .. })) = (&sync.mode, checkpoint) {
+			match StateSync::resume(sync.client.clone(), false) {
+				Some(state_sync) if state_sync.target() == hash =>
+					sync.state_sync = Some(state_sync),
+				_ => sync.checkpoint = checkpoint,
+			}
+		}
 		Ok(sync)
 	}
 
@@ -919,6 +944,56 @@ where
 							return Ok(())
 						}
 					},
+					PeerSyncState::DownloadingCheckpointBlock => {
+						peer.state = PeerSyncState::Available;
+						let Some(Checkpoint { hash, state_root }) = self.checkpoint else {
+							debug!(
+								target: LOG_TARGET,
+								"Logic error: we think we are downloading checkpoint block from {}, but no checkpoint sync is happening.",
+								peer_id,
+							);
+							return Ok(())
+						};
+						if blocks.len() != 1 {
+							debug!(
+								target: LOG_TARGET,
+								"Unexpected number of blocks ({}) in checkpoint block response from {}",
+								blocks.len(),
+								peer_id,
+							);
+							return Err(BadPeer(*peer_id, rep::NO_BLOCK))
+						}
+						validate_blocks::<B>(&blocks, peer_id, Some(request))?;
+						let block = blocks.pop().expect("`blocks` len checked above.");
+						match block.header {
+							Some(header)
+								if header.hash() == hash && *header.state_root() == state_root =>
+							{
+								info!(
+									target: LOG_TARGET,
+									"Downloading state of checkpoint block #{} ({hash})",
+									header.number(),
+								);
+								self.checkpoint = None;
+								self.state_sync = Some(StateSync::new(
+									self.client.clone(),
+									header,
+									block.body,
+									block.justifications,
+									false,
+								));
+								self.allowed_requests.set_all();
+								return Ok(())
+							},
+							_ => {
+								debug!(
+									target: LOG_TARGET,
+									"Checkpoint block from {peer_id} doesn't match the checkpoint",
+								);
+								return Err(BadPeer(*peer_id, rep::VERIFICATION_FAIL))
+							},
+						}
+					},
 					PeerSyncState::Available |
 					PeerSyncState::DownloadingJustification(..) |
 					PeerSyncState::DownloadingState |
@@ -1044,7 +1119,10 @@ where
 				heads.sort();
 				let median = heads[heads.len() / 2];
 				if number + STATE_SYNC_FINALITY_THRESHOLD.saturated_into() >= median {
-					if let Ok(Some(header)) = self.client.header(*hash) {
+					if let Some(state_sync) = self.resume_state_sync(*skip_proofs, median) {
+						self.state_sync = Some(state_sync);
+						self.allowed_requests.set_all();
+					} else if let Ok(Some(header)) = self.client.header(*hash) {
 						log::debug!(
 							target: LOG_TARGET,
 							"Starting state sync for #{number} ({hash})",
@@ -1163,6 +1241,13 @@ where
 			gap_sync.blocks.clear_peer_download(peer_id)
 		}
 		self.peers.remove(peer_id);
+		if let Some(state_sync) = &mut self.state_sync {
+			state_sync.peer_disconnected(peer_id);
+		}
+		if let Some(warp_sync) = &mut self.warp_sync {
+			warp_sync.peer_disconnected(peer_id);
+		}
+		self.drop_stale_state_sync();
 		self.extra_justifications.peer_disconnected(peer_id);
 		self.allowed_requests.set_all();
 		self.fork_targets.retain(|_, target| {
@@ -1204,7 +1289,9 @@ where
 		match self.mode {
 			SyncMode::Full =>
 				BlockAttributes::HEADER | BlockAttributes::JUSTIFICATION | BlockAttributes::BODY,
-			SyncMode::LightState { storage_chain_mode: false, .. } | SyncMode::Warp =>
+			SyncMode::LightState { storage_chain_mode: false, .. } |
+			SyncMode::Warp |
+			SyncMode::Checkpoint(_) =>
 				BlockAttributes::HEADER | BlockAttributes::JUSTIFICATION | BlockAttributes::BODY,
 			SyncMode::LightState { storage_chain_mode: true, .. } =>
 				BlockAttributes::HEADER |
@@ -1218,6 +1305,7 @@ where
 			SyncMode::Full => false,
 			SyncMode::LightState { .. } => true,
 			SyncMode::Warp => true,
+			SyncMode::Checkpoint(_) => true,
 		}
 	}
 
@@ -1367,6 +1455,19 @@ where
 			);
 			self.mode = SyncMode::Full;
 		}
+		if matches!(self.mode, SyncMode::Checkpoint(_)) &&
+			(info.finalized_state.is_some() ||
+				(!info.best_number.is_zero() &&
+					self.client.block_status(info.best_hash)? ==
+						BlockStatus::InChainWithState))
+		{
+			warn!(
+				target: LOG_TARGET,
+				"Can't use checkpoint sync mode with a partially synced database. Reverting to full sync mode."
+			);
+			self.mode = SyncMode::Full;
+			self.checkpoint = None;
+		}
 		self.import_existing = false;
 		self.best_queued_hash = info.best_hash;
 		self.best_queued_number = info.best_number;
@@ -1488,6 +1589,35 @@ where
 		None
 	}
 
+	/// Generate block request for downloading of the trusted checkpoint block.
+	fn checkpoint_block_request(&mut self) -> Option<(PeerId, BlockRequest<B>)> {
+		let hash = self.checkpoint?.hash;
+
+		if self.allowed_requests.is_empty() ||
+			self.peers
+				.iter()
+				.any(|(_, peer)| peer.state == PeerSyncState::DownloadingCheckpointBlock)
+		{
+			// Only one pending checkpoint block request is allowed.
+			return None
+		}
+
+		let (id, peer) = self.peers.iter_mut().find(|(_, peer)| peer.state.is_available())?;
+		trace!(target: LOG_TARGET, "New checkpoint block request for {id}");
+		peer.state = PeerSyncState::DownloadingCheckpointBlock;
+		self.allowed_requests.clear();
+		let request = BlockRequest::<B> {
+			id: 0,
+			fields: BlockAttributes::HEADER |
+				BlockAttributes::BODY |
+				BlockAttributes::JUSTIFICATION,
+			from: FromBlock::Hash(hash),
+			direction: Direction::Ascending,
+			max: Some(1),
+		};
+		Some((*id, request))
+	}
+
 	/// Submit blocks received in a response.
 	pub fn on_block_response(
 		&mut self,
@@ -1570,6 +1700,12 @@ where
 				.map_or_else(|| Vec::new(), |req| Vec::from([req]))
 		}
 
+		if self.checkpoint.is_some() {
+			return self
+				.checkpoint_block_request()
+				.map_or_else(|| Vec::new(), |req| Vec::from([req]))
+		}
+
 		if self.allowed_requests.is_empty() || self.state_sync.is_some() {
 			return Vec::new()
 		}
@@ -1689,50 +1825,52 @@ where
 			.collect()
 	}
 
-	/// Get a state request scheduled by sync to be sent out (if any).
-	fn state_request(&mut self) -> Option<(PeerId, OpaqueStateRequest)> {
-		if self.allowed_requests.is_empty() {
-			return None
-		}
-		if (self.state_sync.is_some() || self.warp_sync.is_some()) &&
-			self.peers.iter().any(|(_, peer)| peer.state == PeerSyncState::DownloadingState)
-		{
-			// Only one pending state request is allowed.
-			return None
-		}
-		if let Some(sync) = &self.state_sync {
+	/// Get the state requests scheduled by sync to be sent out.
+	///
+	/// State is downloaded in parallel from all available peers serving the target block. Unlike
+	/// other requests, these are not gated by `allowed_requests`, so that slow state ranges can be
+	/// reassigned without waiting for a response.
+	fn state_requests(&mut self) -> Vec<(PeerId, OpaqueStateRequest)> {
+		let mut requests = Vec::new();
+		if let Some(sync) = &mut self.state_sync {
 			if sync.is_complete() {
-				return None
+				return requests
 			}
 
+			let target = sync.target_block_num();
+			// The checkpoint block is not imported yet, so there is no common block with peers.
+			let checkpoint = self.mode.is_checkpoint();
 			for (id, peer) in self.peers.iter_mut() {
-				if peer.state.is_available() && peer.common_number >= sync.target_block_num() {
-					peer.state = PeerSyncState::DownloadingState;
-					let request = sync.next_request();
-					trace!(target: LOG_TARGET, "New StateRequest for {}: {:?}", id, request);
-					self.allowed_requests.clear();
-					return Some((*id, OpaqueStateRequest(Box::new(request))))
+				let number = if checkpoint { peer.best_number } else { peer.common_number };
+				if peer.state.is_available() && number >= target {
+					if let Some(request) = sync.next_request(*id) {
+						trace!(target: LOG_TARGET, "New StateRequest for {}: {:?}", id, request);
+						peer.state = PeerSyncState::DownloadingState;
+						requests.push((*id, OpaqueStateRequest(Box::new(request))));
+					}
 				}
 			}
 		}
-		if let Some(sync) = &self.warp_sync {
+		if let Some(sync) = &mut self.warp_sync {
 			if sync.is_complete() {
-				return None
+				return requests
 			}
-			if let (Some(request), Some(target)) =
-				(sync.next_state_request(), sync.target_block_number())
-			{
+			if let Some(target) = sync.target_block_number() {
 				for (id, peer) in self.peers.iter_mut() {
 					if peer.state.is_available() && peer.best_number >= target {
-						trace!(target: LOG_TARGET, "New StateRequest for {id}: {request:?}");
-						peer.state = PeerSyncState::DownloadingState;
-						self.allowed_requests.clear();
-						return Some((*id, OpaqueStateRequest(Box::new(request))))
+						if let Some(request) = sync.next_state_request(*id) {
+							trace!(target: LOG_TARGET, "New StateRequest for {id}: {request:?}");
+							peer.state = PeerSyncState::DownloadingState;
+							requests.push((*id, OpaqueStateRequest(Box::new(request))));
+						}
 					}
 				}
 			}
 		}
-		None
+		if !requests.is_empty() {
+			self.allowed_requests.clear();
+		}
+		requests
 	}
 
 	/// Get a warp proof request scheduled by sync to be sent out (if any).
@@ -1796,7 +1934,7 @@ where
 				response.entries.len(),
 				response.proof.len(),
 			);
-			sync.import(*response)
+			sync.import(*peer_id, *response)
 		} else if let Some(sync) = &mut self.warp_sync {
 			debug!(
 				target: LOG_TARGET,
@@ -1805,7 +1943,7 @@ where
 				response.entries.len(),
 				response.proof.len(),
 			);
-			sync.import_state(*response)
+			sync.import_state(*peer_id, *response)
 		} else {
 			debug!(target: LOG_TARGET, "Ignored obsolete state response from {peer_id}");
 			return Err(BadPeer(*peer_id, rep::NOT_REQUESTED))
@@ -1833,11 +1971,48 @@ where
 			ImportResult::Continue => Ok(()),
 			ImportResult::BadResponse => {
 				debug!(target: LOG_TARGET, "Bad state data received from {peer_id}");
+				self.drop_stale_state_sync();
 				Err(BadPeer(*peer_id, rep::BAD_BLOCK))
 			},
 		}
 	}
 
+	/// Resume the state sync persisted by a previous run, unless its target is too old for peers
+	/// to still serve its state.
+	fn resume_state_sync(
+		&self,
+		skip_proofs: bool,
+		median: NumberFor<B>,
+	) -> Option<StateSync<B, Client>> {
+		let state_sync = StateSync::resume(self.client.clone(), skip_proofs)?;
+		let number = state_sync.target_block_num();
+		if number + STATE_SYNC_FINALITY_THRESHOLD.saturated_into() < median {
+			debug!(target: LOG_TARGET, "Dropping stale state sync progress of #{number}");
+			state::clear_progress(&*self.client);
+			return None
+		}
+		Some(state_sync)
+	}
+
+	/// Drop a state sync resumed from a previous run if its requests keep failing, so that
+	/// it is started over with a fresh target.
+	///
+	/// The target of a checkpoint sync is fixed, so it is kept regardless.
+	fn drop_stale_state_sync(&mut self) {
+		if self.mode.is_checkpoint() || !self.state_sync.as_ref().map_or(false, |s| s.is_stale()) {
+			return
+		}
+
+		warn!(
+			target: LOG_TARGET,
+			"Giving up on the resumed state sync of #{}, peers keep failing to serve it.",
+			self.state_sync.as_ref().map_or(Zero::zero(), |s| s.target_block_num()),
+		);
+		state::clear_progress(&*self.client);
+		self.state_sync = None;
+		self.allowed_requests.set_all();
+	}
+
 	/// Submit a warp proof response received.
 	pub fn on_warp_sync_response(&mut self, peer_id: &PeerId, response: EncodedProof) {
 		if let Some(peer) = self.peers.get_mut(peer_id) {
@@ -1941,6 +2116,7 @@ where
 							"State sync is complete ({} MiB), restarting block sync.",
 							self.state_sync.as_ref().map_or(0, |s| s.progress().size / (1024 * 1024)),
 						);
+						state::clear_progress(&*self.client);
 						self.state_sync = None;
 						self.mode = SyncMode::Full;
 						self.restart();
@@ -1955,6 +2131,7 @@ where
 							"Warp sync is complete ({} MiB), restarting block sync.",
 							self.warp_sync.as_ref().map_or(0, |s| s.progress().total_bytes / (1024 * 1024)),
 						);
+						state::clear_progress(&*self.client);
 						self.warp_sync = None;
 						self.mode = SyncMode::Full;
 						self.restart();
@@ -2011,6 +2188,14 @@ where
 				},
 				e @ Err(BlockImportError::UnknownParent) | e @ Err(BlockImportError::Other(_)) => {
 					warn!(target: LOG_TARGET, "💔 Error importing block {hash:?}: {}", e.unwrap_err());
+					// Don't resume the download of a state that failed to import.
+					if self.state_sync.as_ref().map_or(false, |s| s.target() == hash) ||
+						self.warp_sync
+							.as_ref()
+							.map_or(false, |s| s.target_block_hash() == Some(hash))
+					{
+						state::clear_progress(&*self.client);
+					}
 					self.state_sync = None;
 					self.warp_sync = None;
 					self.restart();
@@ -2038,7 +2223,7 @@ where
 		self.actions.extend(justification_requests);
 
 		let state_request = self
-			.state_request()
+			.state_requests()
 			.into_iter()
 			.map(|(peer_id, request)| ChainSyncAction::SendStateRequest { peer_id, request });
 		self.actions.extend(state_request);
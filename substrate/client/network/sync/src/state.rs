@@ -17,37 +17,200 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! State sync support.
+//!
+//! The key values of every verified state response are persisted to the auxiliary storage of the
+//! client along with the cursor of their range, so that an interrupted state sync can be resumed
+//! with [`StateSync::resume`] after a restart.
 
 use crate::{
 	schema::v1::{StateEntry, StateRequest, StateResponse},
 	types::StateDownloadProgress,
 };
 use codec::{Decode, Encode};
-use log::debug;
-use sc_client_api::{CompactProof, ProofProvider};
+use libp2p::PeerId;
+use log::{debug, info, warn};
+use sc_client_api::{AuxStore, CompactProof, ProofProvider};
 use sc_consensus::ImportedState;
-use smallvec::SmallVec;
+use smallvec::{smallvec, SmallVec};
 use sp_core::storage::well_known_keys;
 use sp_runtime::{
 	traits::{Block as BlockT, Header, NumberFor},
 	Justifications,
 };
-use std::{collections::HashMap, sync::Arc};
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// Auxiliary storage key of the persisted [`Progress`].
+const PROGRESS_KEY: &[u8] = b"state_sync_progress";
+/// Auxiliary storage key of the number of persisted [`Chunk`]s.
+const CHUNKS_COUNT_KEY: &[u8] = b"state_sync_chunks";
+/// Auxiliary storage key prefix of the persisted [`Chunk`]s.
+const CHUNK_KEY_PREFIX: &[u8] = b"state_sync_chunk:";
+/// Number of consecutive failed state requests after which a resumed state sync is considered
+/// stale, as peers most likely pruned the state of its target in the meantime.
+const MAX_RESUMED_FAILURES: u32 = 5;
+
+fn chunk_key(index: u32) -> Vec<u8> {
+	let mut key = CHUNK_KEY_PREFIX.to_vec();
+	key.extend(index.to_le_bytes());
+	key
+}
+
+/// Target of an interrupted state sync, as persisted in the auxiliary storage.
+#[derive(Encode, Decode)]
+struct Progress<B: BlockT> {
+	target_header: B::Header,
+	target_body: Option<Vec<B::Extrinsic>>,
+	target_justifications: Option<Justifications>,
+	skip_proof: bool,
+}
+
+/// Verified state downloaded by a single response, as persisted in the auxiliary storage.
+#[derive(Encode, Decode)]
+struct Chunk {
+	/// Index of the range the response was for.
+	range: u32,
+	/// Cursor of the range after the response.
+	last_key: Vec<Vec<u8>>,
+	/// Whether the response completed the range.
+	complete: bool,
+	/// Key values of the top trie (with an empty root) and of the child tries, by trie root.
+	key_values: Vec<(Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>)>,
+	/// Roots of the child tries found in the top trie, along with their storage keys.
+	child_roots: Vec<(Vec<u8>, Vec<u8>)>,
+	/// Roots of the child tries whose download the response completed.
+	complete_child_roots: Vec<Vec<u8>>,
+	/// Size of the proof the response was verified with.
+	proof_size: u64,
+}
+
+/// Remove the persisted progress of an interrupted state sync, if any.
+pub fn clear_progress<Client: AuxStore>(client: &Client) {
+	let count = client
+		.get_aux(CHUNKS_COUNT_KEY)
+		.ok()
+		.flatten()
+		.and_then(|count| u32::decode(&mut count.as_slice()).ok())
+		.unwrap_or(0);
+	let keys = (0..count)
+		.map(chunk_key)
+		.chain([PROGRESS_KEY.to_vec(), CHUNKS_COUNT_KEY.to_vec()])
+		.collect::<Vec<_>>();
+	let delete = keys.iter().map(|key| key.as_slice()).collect::<Vec<_>>();
+
+	if let Err(e) = client.insert_aux(&[], &delete) {
+		warn!(target: "sync", "Failed to clear state sync progress: {}", e);
+	}
+}
+
+/// Number of key ranges the top trie key space is split in. Ranges are downloaded in parallel
+/// from different peers.
+const STATE_RANGES: usize = 16;
+/// A state request that has not been answered for this long may be handed over to a peer with a
+/// better throughput.
+const SLOW_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Part of the top trie key space downloaded by a single request stream.
+///
+/// Child tries are downloaded as part of the range containing their parent key.
+struct StateRange {
+	/// First byte of the keys in this range, used to estimate the progress.
+	first_byte: u8,
+	/// Last top trie key (inclusive) in this range, `None` for the last range.
+	end: Option<Vec<u8>>,
+	/// Cursor of the next request.
+	last_key: SmallVec<[Vec<u8>; 2]>,
+	/// Roots of the child tries whose parent keys are in this range.
+	child_roots: HashSet<Vec<u8>>,
+	complete: bool,
+}
+
+impl StateRange {
+	/// Split the top trie key space in `count` ranges on the first byte of the keys.
+	///
+	/// Requests start after (excluding) their cursor, so the first key of a range is kept by
+	/// the previous range, whose end is inclusive.
+	fn split(count: usize) -> Vec<Self> {
+		let first_bytes = (0..count).map(|i| (i * 256 / count) as u8).collect::<Vec<_>>();
+		first_bytes
+			.iter()
+			.enumerate()
+			.map(|(i, first_byte)| StateRange {
+				first_byte: *first_byte,
+				end: first_bytes.get(i + 1).map(|next| vec![*next]),
+				last_key: if i == 0 { SmallVec::new() } else { smallvec![vec![*first_byte]] },
+				child_roots: HashSet::new(),
+				complete: false,
+			})
+			.collect()
+	}
+
+	/// Number of first byte values of the keys in this range that are already downloaded.
+	fn downloaded_bytes(&self) -> u32 {
+		let end = self.end.as_ref().map_or(256, |end| end[0] as u32);
+		if self.complete {
+			return end - self.first_byte as u32
+		}
+		match self.last_key.get(0).and_then(|last| last.get(0)) {
+			Some(cursor) =>
+				(*cursor as u32).clamp(self.first_byte as u32, end) - self.first_byte as u32,
+			None => 0,
+		}
+	}
+}
+
+/// A state request in flight.
+struct PendingRequest {
+	range: usize,
+	sent_at: Instant,
+}
+
+/// Download throughput of a peer.
+#[derive(Default)]
+struct Throughput {
+	bytes: u64,
+	elapsed: Duration,
+}
+
+impl Throughput {
+	fn bytes_per_sec(&self) -> u64 {
+		match self.elapsed.as_millis() {
+			0 => u64::MAX,
+			millis => u64::try_from(self.bytes as u128 * 1000 / millis).unwrap_or(u64::MAX),
+		}
+	}
+}
 
 /// State sync state machine. Accumulates partial state data until it
 /// is ready to be imported.
+///
+/// The top trie key space is split in ranges, downloaded in parallel from the peers serving the
+/// target block.
 pub struct StateSync<B: BlockT, Client> {
 	target_block: B::Hash,
 	target_header: B::Header,
 	target_root: B::Hash,
 	target_body: Option<Vec<B::Extrinsic>>,
 	target_justifications: Option<Justifications>,
-	last_key: SmallVec<[Vec<u8>; 2]>,
+	ranges: Vec<StateRange>,
+	pending: HashMap<PeerId, PendingRequest>,
+	throughput: HashMap<PeerId, Throughput>,
 	state: HashMap<Vec<u8>, (Vec<(Vec<u8>, Vec<u8>)>, Vec<Vec<u8>>)>,
+	/// Roots of the child tries downloaded entirely. Other child tries with the same root are
+	/// not downloaded again.
+	complete_child_roots: HashSet<Vec<u8>>,
 	complete: bool,
 	client: Arc<Client>,
 	imported_bytes: u64,
 	skip_proof: bool,
+	persisted_chunks: u32,
+	/// Whether this sync was resumed from the progress of a previous run.
+	resumed: bool,
+	/// Number of consecutive failed requests.
+	failures: u32,
 }
 
 /// Import state chunk result.
@@ -63,15 +226,92 @@ pub enum ImportResult<B: BlockT> {
 impl<B, Client> StateSync<B, Client>
 where
 	B: BlockT,
-	Client: ProofProvider<B> + Send + Sync + 'static,
+	Client: ProofProvider<B> + AuxStore + Send + Sync + 'static,
 {
 	///  Create a new instance.
+	///
+	/// Any persisted progress of a previous state sync is discarded.
 	pub fn new(
 		client: Arc<Client>,
 		target_header: B::Header,
 		target_body: Option<Vec<B::Extrinsic>>,
 		target_justifications: Option<Justifications>,
 		skip_proof: bool,
+	) -> Self {
+		clear_progress(&*client);
+		Self::new_inner(client, target_header, target_body, target_justifications, skip_proof)
+	}
+
+	/// Resume the state sync persisted by a previous run.
+	///
+	/// The persisted state was verified against the target state root before being persisted.
+	/// Returns `None` if there is nothing to resume, or if the persisted progress was made with a
+	/// different `skip_proof` setting or can't be decoded.
+	pub fn resume(client: Arc<Client>, skip_proof: bool) -> Option<Self> {
+		let progress = client.get_aux(PROGRESS_KEY).ok().flatten()?;
+		let Ok(progress) = Progress::<B>::decode(&mut progress.as_slice()) else {
+			warn!(target: "sync", "Discarding undecodable state sync progress");
+			clear_progress(&*client);
+			return None
+		};
+		if progress.skip_proof != skip_proof {
+			return None
+		}
+
+		let count = client
+			.get_aux(CHUNKS_COUNT_KEY)
+			.ok()
+			.flatten()
+			.and_then(|count| u32::decode(&mut count.as_slice()).ok())
+			.unwrap_or(0);
+		let mut sync = Self::new_inner(
+			client.clone(),
+			progress.target_header,
+			progress.target_body,
+			progress.target_justifications,
+			skip_proof,
+		);
+		for index in 0..count {
+			let chunk = client
+				.get_aux(&chunk_key(index))
+				.ok()
+				.flatten()
+				.and_then(|chunk| Chunk::decode(&mut chunk.as_slice()).ok());
+			match chunk {
+				Some(chunk) if (chunk.range as usize) < sync.ranges.len() =>
+					sync.apply_chunk(chunk),
+				_ => {
+					warn!(
+						target: "sync",
+						"Discarding state sync progress of #{}: chunk {} can't be decoded",
+						sync.target_block_num(),
+						index,
+					);
+					clear_progress(&*client);
+					return None
+				},
+			}
+		}
+		sync.persisted_chunks = count;
+		sync.resumed = true;
+
+		info!(
+			target: "sync",
+			"Resuming state sync of #{} ({}) from {} chunks ({} bytes)",
+			sync.target_block_num(),
+			sync.target_block,
+			count,
+			sync.imported_bytes,
+		);
+		Some(sync)
+	}
+
+	fn new_inner(
+		client: Arc<Client>,
+		target_header: B::Header,
+		target_body: Option<Vec<B::Extrinsic>>,
+		target_justifications: Option<Justifications>,
+		skip_proof: bool,
 	) -> Self {
 		Self {
 			client,
@@ -80,38 +320,127 @@ where
 			target_header,
 			target_body,
 			target_justifications,
-			last_key: SmallVec::default(),
+			ranges: StateRange::split(STATE_RANGES),
+			pending: HashMap::default(),
+			throughput: HashMap::default(),
 			state: HashMap::default(),
+			complete_child_roots: HashSet::default(),
 			complete: false,
 			imported_bytes: 0,
 			skip_proof,
+			persisted_chunks: 0,
+			resumed: false,
+			failures: 0,
+		}
+	}
+
+	///  Validate and import a state response from `peer_id`.
+	///
+	/// Responses to requests that were reassigned to another peer in the meantime are ignored.
+	/// Responses that don't complete the state are persisted, to be able to resume the sync.
+	pub fn import(&mut self, peer_id: PeerId, response: StateResponse) -> ImportResult<B> {
+		let Some(request) = self.pending.remove(&peer_id) else {
+			debug!(target: "sync", "Ignoring state response from {}, range was reassigned", peer_id);
+			return ImportResult::Continue
+		};
+
+		let size = prost::Message::encoded_len(&response) as u64;
+		let Some(chunk) = self.verify_range(request.range, response) else {
+			self.failures += 1;
+			return ImportResult::BadResponse
+		};
+		self.failures = 0;
+		let throughput = self.throughput.entry(peer_id).or_default();
+		throughput.bytes += size;
+		throughput.elapsed += request.sent_at.elapsed();
+
+		let encoded = chunk.encode();
+		self.apply_chunk(chunk);
+		if self.ranges.iter().all(|range| range.complete) {
+			self.complete = true;
+			ImportResult::Import(
+				self.target_block,
+				self.target_header.clone(),
+				ImportedState {
+					block: self.target_block,
+					state: std::mem::take(&mut self.state).into(),
+				},
+				self.target_body.clone(),
+				self.target_justifications.clone(),
+			)
+		} else {
+			self.persist_chunk(encoded);
+			ImportResult::Continue
 		}
 	}
 
-	///  Validate and import a state response.
-	pub fn import(&mut self, response: StateResponse) -> ImportResult<B> {
+	fn persist_chunk(&mut self, chunk: Vec<u8>) {
+		let index = self.persisted_chunks;
+		let key = chunk_key(index);
+		let count = (index + 1).encode();
+		let progress = (index == 0).then(|| {
+			Progress::<B> {
+				target_header: self.target_header.clone(),
+				target_body: self.target_body.clone(),
+				target_justifications: self.target_justifications.clone(),
+				skip_proof: self.skip_proof,
+			}
+			.encode()
+		});
+
+		let mut insert =
+			vec![(key.as_slice(), chunk.as_slice()), (CHUNKS_COUNT_KEY, count.as_slice())];
+		if let Some(progress) = &progress {
+			insert.push((PROGRESS_KEY, progress.as_slice()));
+		}
+
+		match self.client.insert_aux(insert.iter(), &[]) {
+			Ok(()) => self.persisted_chunks += 1,
+			Err(e) => warn!(target: "sync", "Failed to persist state sync progress: {}", e),
+		}
+	}
+
+	/// Verify a response to a request for the range `index`, returning the state it adds to the
+	/// range along with the new cursor of the range.
+	fn verify_range(&self, index: usize, response: StateResponse) -> Option<Chunk> {
 		if response.entries.is_empty() && response.proof.is_empty() {
 			debug!(target: "sync", "Bad state response");
-			return ImportResult::BadResponse
+			return None
 		}
 		if !self.skip_proof && response.proof.is_empty() {
 			debug!(target: "sync", "Missing proof");
-			return ImportResult::BadResponse
+			return None
 		}
-		let complete = if !self.skip_proof {
+		let range = &self.ranges[index];
+		let mut chunk = Chunk {
+			range: index as u32,
+			last_key: range.last_key.to_vec(),
+			complete: false,
+			key_values: Vec::new(),
+			child_roots: Vec::new(),
+			complete_child_roots: Vec::new(),
+			proof_size: 0,
+		};
+		// Child tries are part of the range if their parent key is, including the parent keys
+		// of this response.
+		let is_child_of_range = |chunk: &Chunk, root: &Vec<u8>| {
+			range.child_roots.contains(root) ||
+				chunk.child_roots.iter().any(|(child_root, _)| child_root == root)
+		};
+		chunk.complete = if !self.skip_proof {
 			debug!(target: "sync", "Importing state from {} trie nodes", response.proof.len());
-			let proof_size = response.proof.len() as u64;
+			chunk.proof_size = response.proof.len() as u64;
 			let proof = match CompactProof::decode(&mut response.proof.as_ref()) {
 				Ok(proof) => proof,
 				Err(e) => {
 					debug!(target: "sync", "Error decoding proof: {:?}", e);
-					return ImportResult::BadResponse
+					return None
 				},
 			};
-			let (values, completed) = match self.client.verify_range_proof(
+			let (mut values, completed) = match self.client.verify_range_proof(
 				self.target_root,
 				proof,
-				self.last_key.as_slice(),
+				range.last_key.as_slice(),
 			) {
 				Err(e) => {
 					debug!(
@@ -119,57 +448,45 @@ where
 						"StateResponse failed proof verification: {}",
 						e,
 					);
-					return ImportResult::BadResponse
+					return None
 				},
 				Ok(values) => values,
 			};
 			debug!(target: "sync", "Imported with {} keys", values.len());
 
-			let complete = completed == 0;
-			if !complete && !values.update_last_key(completed, &mut self.last_key) {
-				debug!(target: "sync", "Error updating key cursor, depth: {}", completed);
-			};
-
-			for values in values.0 {
-				let key_values = if values.state_root.is_empty() {
-					// Read child trie roots.
-					values
-						.key_values
-						.into_iter()
-						.filter(|key_value| {
-							if well_known_keys::is_child_storage_key(key_value.0.as_slice()) {
-								self.state
-									.entry(key_value.1.clone())
-									.or_default()
-									.1
-									.push(key_value.0.clone());
-								false
-							} else {
-								true
-							}
-						})
-						.collect()
-				} else {
-					values.key_values
+			let mut complete = completed == 0;
+			if let Some(top) = values.0.iter_mut().find(|values| values.state_root.is_empty()) {
+				complete |= truncate_range(&mut top.key_values, range.end.as_ref(), |kv| &kv.0);
+			}
+			if !complete {
+				let mut last_key = range.last_key.clone();
+				if !values.update_last_key(completed, &mut last_key) {
+					debug!(target: "sync", "Error updating key cursor, depth: {}", completed);
 				};
-				let entry = self.state.entry(values.state_root).or_default();
-				if entry.0.len() > 0 && entry.1.len() > 1 {
-					// Already imported child_trie with same root.
-					// Warning this will not work with parallel download.
-				} else if entry.0.is_empty() {
-					for (key, _value) in key_values.iter() {
-						self.imported_bytes += key.len() as u64;
-					}
+				chunk.last_key = last_key.into_vec();
+			}
 
-					entry.0 = key_values;
-				} else {
-					for (key, value) in key_values {
-						self.imported_bytes += key.len() as u64;
-						entry.0.push((key, value))
+			let levels = values.0.len();
+			for (index, values) in values.0.into_iter().enumerate() {
+				if values.state_root.is_empty() {
+					// Read child trie roots.
+					let (child_roots, key_values): (Vec<_>, Vec<_>) =
+						values.key_values.into_iter().partition(|key_value| {
+							well_known_keys::is_child_storage_key(key_value.0.as_slice())
+						});
+					chunk
+						.child_roots
+						.extend(child_roots.into_iter().map(|(key, root)| (root, key)));
+					chunk.key_values.push((values.state_root, key_values));
+				} else if is_child_of_range(&chunk, &values.state_root) {
+					// Only the last child trie of the response may be partially read.
+					if completed != 2 || index + 1 < levels {
+						chunk.complete_child_roots.push(values.state_root.clone());
 					}
+					chunk.key_values.push((values.state_root, values.key_values));
 				}
+				// Otherwise it's the child trie of a parent key past the end of the range.
 			}
-			self.imported_bytes += proof_size;
 			complete
 		} else {
 			let mut complete = true;
@@ -177,74 +494,149 @@ where
 			// the parent cursor stays valid.
 			// Empty parent trie content only happens when all the response content
 			// is part of a single child trie.
-			if self.last_key.len() == 2 && response.entries[0].entries.is_empty() {
+			if chunk.last_key.len() == 2 && response.entries[0].entries.is_empty() {
 				// Do not remove the parent trie position.
-				self.last_key.pop();
+				chunk.last_key.pop();
 			} else {
-				self.last_key.clear();
+				chunk.last_key.clear();
 			}
-			for state in response.entries {
+			let mut reached_end = false;
+			for mut state_entry in response.entries {
 				debug!(
 					target: "sync",
 					"Importing state from {:?} to {:?}",
-					state.entries.last().map(|e| sp_core::hexdisplay::HexDisplay::from(&e.key)),
-					state.entries.first().map(|e| sp_core::hexdisplay::HexDisplay::from(&e.key)),
+					state_entry.entries.last().map(|e| sp_core::hexdisplay::HexDisplay::from(&e.key)),
+					state_entry.entries.first().map(|e| sp_core::hexdisplay::HexDisplay::from(&e.key)),
 				);
 
-				if !state.complete {
-					if let Some(e) = state.entries.last() {
-						self.last_key.push(e.key.clone());
+				let is_top = state_entry.state_root.is_empty();
+				if is_top {
+					reached_end |=
+						truncate_range(&mut state_entry.entries, range.end.as_ref(), |e| &e.key);
+				} else if !is_child_of_range(&chunk, &state_entry.state_root) {
+					// Child trie of a parent key past the end of the range.
+					continue
+				}
+				if !state_entry.complete {
+					if let Some(e) = state_entry.entries.last() {
+						chunk.last_key.push(e.key.clone());
 					}
 					complete = false;
+				} else if !is_top {
+					chunk.complete_child_roots.push(state_entry.state_root.clone());
 				}
-				let is_top = state.state_root.is_empty();
-				let entry = self.state.entry(state.state_root).or_default();
-				if entry.0.len() > 0 && entry.1.len() > 1 {
-					// Already imported child trie with same root.
-				} else {
-					let mut child_roots = Vec::new();
-					for StateEntry { key, value } in state.entries {
-						// Skip all child key root (will be recalculated on import).
-						if is_top && well_known_keys::is_child_storage_key(key.as_slice()) {
-							child_roots.push((value, key));
-						} else {
-							self.imported_bytes += key.len() as u64;
-							entry.0.push((key, value))
-						}
-					}
-					for (root, storage_key) in child_roots {
-						self.state.entry(root).or_default().1.push(storage_key);
+				let mut key_values = Vec::new();
+				for StateEntry { key, value } in state_entry.entries {
+					// Skip all child key root (will be recalculated on import).
+					if is_top && well_known_keys::is_child_storage_key(key.as_slice()) {
+						chunk.child_roots.push((value, key));
+					} else {
+						key_values.push((key, value));
 					}
 				}
+				chunk.key_values.push((state_entry.state_root, key_values));
 			}
-			complete
+			complete || reached_end
 		};
-		if complete {
-			self.complete = true;
-			ImportResult::Import(
-				self.target_block,
-				self.target_header.clone(),
-				ImportedState {
-					block: self.target_block,
-					state: std::mem::take(&mut self.state).into(),
-				},
-				self.target_body.clone(),
-				self.target_justifications.clone(),
-			)
-		} else {
-			ImportResult::Continue
+		Some(chunk)
+	}
+
+	/// Add the verified state of `chunk` to its range.
+	fn apply_chunk(&mut self, chunk: Chunk) {
+		let range = &mut self.ranges[chunk.range as usize];
+		range.last_key = chunk.last_key.into();
+		if chunk.complete {
+			debug!(target: "sync", "State range {} is complete", chunk.range);
+			range.complete = true;
+		}
+		for (root, storage_key) in chunk.child_roots {
+			range.child_roots.insert(root.clone());
+			self.state.entry(root).or_default().1.push(storage_key);
 		}
+		for (root, key_values) in chunk.key_values {
+			if self.complete_child_roots.contains(&root) {
+				// Child trie with the same root as one already downloaded entirely.
+				continue
+			}
+			if chunk.complete_child_roots.contains(&root) {
+				self.complete_child_roots.insert(root.clone());
+			}
+			let entry = self.state.entry(root).or_default();
+			for (key, _value) in key_values.iter() {
+				self.imported_bytes += key.len() as u64;
+			}
+			entry.0.extend(key_values);
+		}
+		self.imported_bytes += chunk.proof_size;
 	}
 
-	/// Produce next state request.
-	pub fn next_request(&self) -> StateRequest {
-		StateRequest {
+	/// Produce next state request to send to `peer_id`, if there is a range to download.
+	///
+	/// Ranges not being downloaded are handed out first. Once all of them are in flight, a range
+	/// requested more than [`SLOW_REQUEST_TIMEOUT`] ago from a slower peer is reassigned.
+	pub fn next_request(&mut self, peer_id: PeerId) -> Option<StateRequest> {
+		if self.complete || self.pending.contains_key(&peer_id) {
+			return None
+		}
+
+		let in_flight = self.pending.values().map(|request| request.range).collect::<HashSet<_>>();
+		let free_range = self
+			.ranges
+			.iter()
+			.enumerate()
+			.position(|(index, range)| !range.complete && !in_flight.contains(&index));
+		let range = match free_range {
+			Some(range) => range,
+			None => {
+				let throughput = |peer: &PeerId| {
+					self.throughput.get(peer).map_or(0, |throughput| throughput.bytes_per_sec())
+				};
+				// Peers we know nothing about yet are assumed to be fast.
+				let peer_throughput =
+					self.throughput.get(&peer_id).map_or(u64::MAX, |t| t.bytes_per_sec());
+				let slow_peer = self
+					.pending
+					.iter()
+					.filter(|(peer, request)| {
+						request.sent_at.elapsed() >= SLOW_REQUEST_TIMEOUT &&
+							throughput(peer) < peer_throughput
+					})
+					.min_by_key(|(peer, _)| throughput(peer))
+					.map(|(peer, _)| *peer)?;
+				let request = self.pending.remove(&slow_peer)?;
+				debug!(
+					target: "sync",
+					"Reassigning state range {} from slow peer {} to {}",
+					request.range,
+					slow_peer,
+					peer_id,
+				);
+				request.range
+			},
+		};
+
+		self.pending.insert(peer_id, PendingRequest { range, sent_at: Instant::now() });
+		Some(StateRequest {
 			block: self.target_block.encode(),
-			start: self.last_key.clone().into_vec(),
+			start: self.ranges[range].last_key.clone().into_vec(),
 			no_proof: self.skip_proof,
+		})
+	}
+
+	/// Release the range being downloaded from a disconnected peer.
+	///
+	/// Failed requests end with the peer being disconnected, so this counts as a failure.
+	pub fn peer_disconnected(&mut self, peer_id: &PeerId) {
+		if self.pending.remove(peer_id).is_some() {
+			self.failures += 1;
 		}
 	}
 
+	/// Whether this sync was resumed from a previous run and its requests keep failing since.
+	pub fn is_stale(&self) -> bool {
+		self.resumed && self.failures >= MAX_RESUMED_FAILURES
+	}
+
 	/// Check if the state is complete.
 	pub fn is_complete(&self) -> bool {
 		self.complete
@@ -262,8 +654,252 @@ where
 
 	/// Returns state sync estimated progress.
 	pub fn progress(&self) -> StateDownloadProgress {
-		let cursor = *self.last_key.get(0).and_then(|last| last.get(0)).unwrap_or(&0u8);
-		let percent_done = cursor as u32 * 100 / 256;
+		let downloaded = self.ranges.iter().map(StateRange::downloaded_bytes).sum::<u32>();
+		let percent_done = downloaded * 100 / 256;
 		StateDownloadProgress { percentage: percent_done, size: self.imported_bytes }
 	}
 }
+
+/// Drop the top trie entries past the (inclusive) `end` of a range.
+///
+/// Returns `true` if the end of the range is reached.
+fn truncate_range<T>(
+	entries: &mut Vec<T>,
+	end: Option<&Vec<u8>>,
+	key: impl Fn(&T) -> &Vec<u8>,
+) -> bool {
+	let Some(end) = end else { return false };
+	match entries.iter().position(|entry| key(entry) >= end) {
+		Some(position) => {
+			let keep = if key(&entries[position]) == end { position + 1 } else { position };
+			entries.truncate(keep);
+			true
+		},
+		None => false,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_client_api::StorageProvider;
+	use sp_blockchain::HeaderBackend;
+	use sp_core::storage::ChildInfo;
+	use std::collections::BTreeMap;
+	use substrate_test_runtime_client::{
+		runtime::Block, DefaultTestClientBuilderExt, TestClient, TestClientBuilder,
+		TestClientBuilderExt,
+	};
+
+	fn respond(client: &TestClient, request: &StateRequest, size_limit: usize) -> StateResponse {
+		let hash = Decode::decode(&mut request.block.as_slice()).unwrap();
+		let (proof, _) = client.read_proof_collection(hash, &request.start, size_limit).unwrap();
+		StateResponse { entries: Vec::new(), proof: proof.encode() }
+	}
+
+	#[test]
+	fn ranges_cover_key_space() {
+		let ranges = StateRange::split(STATE_RANGES);
+		assert_eq!(ranges.len(), STATE_RANGES);
+		assert!(ranges[0].last_key.is_empty());
+		for pair in ranges.windows(2) {
+			assert_eq!(pair[0].end.as_ref(), pair[1].last_key.get(0));
+		}
+		assert!(ranges[STATE_RANGES - 1].end.is_none());
+
+		let mut entries = vec![vec![0x0f], vec![0x10], vec![0x10, 0], vec![0x11]];
+		assert!(truncate_range(&mut entries, Some(&vec![0x10]), |key| key));
+		assert_eq!(entries, vec![vec![0x0f], vec![0x10]]);
+	}
+
+	#[test]
+	fn state_is_downloaded_in_parallel() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let genesis_hash = client.info().genesis_hash;
+		let header = client.header(genesis_hash).unwrap().unwrap();
+		let peers = (0..4).map(|_| PeerId::random()).collect::<Vec<_>>();
+
+		let mut sync = StateSync::<Block, _>::new(client.clone(), header, None, None, false);
+		let imported = loop {
+			let requests = peers
+				.iter()
+				.filter_map(|peer| sync.next_request(*peer).map(|request| (*peer, request)))
+				.collect::<Vec<_>>();
+			assert!(!requests.is_empty());
+			// Every peer downloads a different range.
+			assert!(requests.windows(2).all(|pair| pair[0].1.start != pair[1].1.start));
+
+			let mut imported = None;
+			for (peer, request) in requests {
+				match sync.import(peer, respond(&client, &request, 1024)) {
+					ImportResult::Import(_, _, state, _, _) => imported = Some(state),
+					ImportResult::Continue => {},
+					ImportResult::BadResponse => panic!("Valid response rejected"),
+				}
+			}
+			if let Some(imported) = imported {
+				break imported
+			}
+		};
+
+		let top = imported.state.0.iter().find(|level| level.state_root.is_empty()).unwrap();
+		let mut keys = top.key_values.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>();
+		keys.sort();
+		let expected = client
+			.storage_keys(genesis_hash, None, None)
+			.unwrap()
+			.map(|key| key.0)
+			.filter(|key| !well_known_keys::is_child_storage_key(key))
+			.collect::<Vec<_>>();
+		assert_eq!(keys, expected);
+	}
+
+	#[test]
+	fn identical_child_tries_are_downloaded_entirely() {
+		let child_data =
+			(0u32..64).map(|i| (i.encode(), vec![i as u8; 32])).collect::<BTreeMap<_, _>>();
+		let mut builder = TestClientBuilder::new();
+		for name in [&b"first"[..], b"second"] {
+			let child_info = ChildInfo::new_default(name);
+			for (key, value) in child_data.iter() {
+				builder = builder.add_child_storage(&child_info, key, value);
+			}
+		}
+		let client = Arc::new(builder.build());
+		let genesis_hash = client.info().genesis_hash;
+		let header = client.header(genesis_hash).unwrap().unwrap();
+		let peers = (0..4).map(|_| PeerId::random()).collect::<Vec<_>>();
+
+		let mut sync = StateSync::<Block, _>::new(client.clone(), header, None, None, false);
+		// The size limit splits the first child trie across responses.
+		let imported = loop {
+			let mut imported = None;
+			for peer in peers.iter() {
+				let Some(request) = sync.next_request(*peer) else { continue };
+				match sync.import(*peer, respond(&client, &request, 1024)) {
+					ImportResult::Import(_, _, state, _, _) => imported = Some(state),
+					ImportResult::Continue => {},
+					ImportResult::BadResponse => panic!("Valid response rejected"),
+				}
+			}
+			if let Some(imported) = imported {
+				break imported
+			}
+		};
+
+		let children = imported
+			.state
+			.0
+			.iter()
+			.filter(|level| !level.state_root.is_empty())
+			.collect::<Vec<_>>();
+		assert_eq!(children.len(), 1);
+		let mut storage_keys = children[0].parent_storage_keys.clone();
+		storage_keys.sort();
+		assert_eq!(
+			storage_keys,
+			vec![
+				ChildInfo::new_default(b"first").prefixed_storage_key().into_inner(),
+				ChildInfo::new_default(b"second").prefixed_storage_key().into_inner(),
+			]
+		);
+		let key_values = children[0].key_values.iter().cloned().collect::<BTreeMap<_, _>>();
+		assert_eq!(key_values, child_data);
+	}
+
+	#[test]
+	fn interrupted_state_sync_is_resumed() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let genesis_hash = client.info().genesis_hash;
+		let header = client.header(genesis_hash).unwrap().unwrap();
+		let peer = PeerId::random();
+
+		let mut sync = StateSync::<Block, _>::new(client.clone(), header, None, None, false);
+		// A tiny size limit splits the genesis state in many responses.
+		let request = sync.next_request(peer).unwrap();
+		assert!(matches!(sync.import(peer, respond(&client, &request, 1)), ImportResult::Continue));
+
+		assert!(StateSync::<Block, _>::resume(client.clone(), true).is_none());
+		let mut resumed = StateSync::<Block, _>::resume(client.clone(), false).unwrap();
+		assert_eq!(resumed.target(), genesis_hash);
+		assert_eq!(
+			resumed.next_request(peer).unwrap().start,
+			sync.next_request(peer).unwrap().start
+		);
+		assert_eq!(resumed.progress().size, sync.progress().size);
+
+		clear_progress(&*client);
+		assert!(StateSync::<Block, _>::resume(client, false).is_none());
+	}
+
+	#[test]
+	fn corrupted_state_sync_progress_is_discarded() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let genesis_hash = client.info().genesis_hash;
+		let header = client.header(genesis_hash).unwrap().unwrap();
+		let peer = PeerId::random();
+
+		let mut sync = StateSync::<Block, _>::new(client.clone(), header, None, None, false);
+		let request = sync.next_request(peer).unwrap();
+		assert!(matches!(sync.import(peer, respond(&client, &request, 1)), ImportResult::Continue));
+
+		// Replace the persisted chunk with one that can't be decoded.
+		client.insert_aux(&[(chunk_key(0).as_slice(), &[1, 2, 3][..])], &[]).unwrap();
+
+		assert!(StateSync::<Block, _>::resume(client.clone(), false).is_none());
+		assert!(client.get_aux(PROGRESS_KEY).unwrap().is_none());
+	}
+
+	#[test]
+	fn resumed_state_sync_becomes_stale_after_failures() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let genesis_hash = client.info().genesis_hash;
+		let header = client.header(genesis_hash).unwrap().unwrap();
+		let peer = PeerId::random();
+
+		let mut sync = StateSync::<Block, _>::new(client.clone(), header, None, None, false);
+		let request = sync.next_request(peer).unwrap();
+		assert!(matches!(sync.import(peer, respond(&client, &request, 1)), ImportResult::Continue));
+
+		let mut resumed = StateSync::<Block, _>::resume(client.clone(), false).unwrap();
+		let bad_response = || StateResponse { entries: Vec::new(), proof: vec![1, 2, 3] };
+		for _ in 1..MAX_RESUMED_FAILURES {
+			resumed.next_request(peer).unwrap();
+			assert!(matches!(resumed.import(peer, bad_response()), ImportResult::BadResponse));
+		}
+		assert!(!resumed.is_stale());
+
+		// A successful response resets the failures.
+		let request = resumed.next_request(peer).unwrap();
+		assert!(matches!(
+			resumed.import(peer, respond(&client, &request, 1)),
+			ImportResult::Continue
+		));
+		for _ in 0..MAX_RESUMED_FAILURES {
+			assert!(!resumed.is_stale());
+			resumed.next_request(peer).unwrap();
+			resumed.peer_disconnected(&peer);
+		}
+		assert!(resumed.is_stale());
+
+		// Fresh state syncs never become stale.
+		for _ in 0..MAX_RESUMED_FAILURES {
+			sync.next_request(peer).unwrap();
+			assert!(matches!(sync.import(peer, bad_response()), ImportResult::BadResponse));
+		}
+		assert!(!sync.is_stale());
+	}
+
+	#[test]
+	fn unrequested_responses_are_ignored() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let genesis_hash = client.info().genesis_hash;
+		let header = client.header(genesis_hash).unwrap().unwrap();
+
+		let mut sync = StateSync::<Block, _>::new(client.clone(), header, None, None, false);
+		let request = sync.next_request(PeerId::random()).unwrap();
+		let response = respond(&client, &request, 1);
+		assert!(matches!(sync.import(PeerId::random(), response), ImportResult::Continue));
+		assert_eq!(sync.progress().size, 0);
+	}
+}
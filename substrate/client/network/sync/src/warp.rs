@@ -22,12 +22,13 @@ pub use sp_consensus_grandpa::{AuthorityList, SetId};
 
 use crate::{
 	schema::v1::{StateRequest, StateResponse},
-	state::{ImportResult, StateSync},
+	state::{self, ImportResult, StateSync},
 };
 use codec::{Decode, Encode};
 use futures::channel::oneshot;
-use log::error;
-use sc_client_api::ProofProvider;
+use libp2p::PeerId;
+use log::{error, info, warn};
+use sc_client_api::{AuxStore, ProofProvider};
 use sc_network_common::sync::message::{
 	BlockAttributes, BlockData, BlockRequest, Direction, FromBlock,
 };
@@ -191,17 +192,42 @@ pub struct WarpSync<B: BlockT, Client> {
 	phase: Phase<B, Client>,
 	client: Arc<Client>,
 	total_proof_bytes: u64,
+	/// Configuration to start over with if the resumed state download of a previous warp sync
+	/// turns out to be stale, along with the target block set in the meantime.
+	fallback: Option<(WarpSyncConfig<B>, Option<B::Header>)>,
 }
 
 impl<B, Client> WarpSync<B, Client>
 where
 	B: BlockT,
-	Client: HeaderBackend<B> + ProofProvider<B> + 'static,
+	Client: HeaderBackend<B> + ProofProvider<B> + AuxStore + 'static,
 {
 	/// Create a new instance. When passing a warp sync provider we will be checking for proof and
 	/// authorities. Alternatively we can pass a target block when we want to skip downloading
 	/// proofs, in this case we will continue polling until the target block is known.
+	///
+	/// If the state download of a previous warp sync was interrupted, it is resumed instead and
+	/// the warp proofs (or the external target block) are only needed if it turns out to be
+	/// stale.
 	pub fn new(client: Arc<Client>, warp_sync_config: WarpSyncConfig<B>) -> Self {
+		if let Some(state_sync) = StateSync::resume(client.clone(), false) {
+			info!(
+				target: LOG_TARGET,
+				"Resuming interrupted warp sync at #{}.",
+				state_sync.target_block_num(),
+			);
+			return Self {
+				client,
+				phase: Phase::State(state_sync),
+				total_proof_bytes: 0,
+				fallback: Some((warp_sync_config, None)),
+			}
+		}
+
+		Self::new_inner(client, warp_sync_config)
+	}
+
+	fn new_inner(client: Arc<Client>, warp_sync_config: WarpSyncConfig<B>) -> Self {
 		let last_hash = client.hash(Zero::zero()).unwrap().expect("Genesis header always exists");
 		match warp_sync_config {
 			WarpSyncConfig::WithProvider(warp_sync_provider) => {
@@ -211,15 +237,52 @@ where
 					last_hash,
 					warp_sync_provider: warp_sync_provider.clone(),
 				};
-				Self { client, phase, total_proof_bytes: 0 }
+				Self { client, phase, total_proof_bytes: 0, fallback: None }
+			},
+			WarpSyncConfig::WaitForTarget => Self {
+				client,
+				phase: Phase::PendingTargetBlock,
+				total_proof_bytes: 0,
+				fallback: None,
 			},
-			WarpSyncConfig::WaitForTarget =>
-				Self { client, phase: Phase::PendingTargetBlock, total_proof_bytes: 0 },
+		}
+	}
+
+	/// Start over if the resumed state download of a previous warp sync is stale, i.e. peers
+	/// keep failing to serve the state of its target.
+	fn restart_if_stale(&mut self) {
+		let Phase::State(sync) = &self.phase else { return };
+		if !sync.is_stale() {
+			return
+		}
+		let Some((warp_sync_config, target_block)) = self.fallback.take() else { return };
+
+		warn!(
+			target: LOG_TARGET,
+			"Giving up on the resumed warp sync of #{}, peers keep failing to serve its state.",
+			sync.target_block_num(),
+		);
+		state::clear_progress(&*self.client);
+		*self = Self::new_inner(self.client.clone(), warp_sync_config);
+		if let Some(header) = target_block {
+			self.set_target_block(header);
 		}
 	}
 
 	/// Set target block externally in case we skip warp proof downloading.
 	pub fn set_target_block(&mut self, header: B::Header) {
+		if let Phase::State(_) = self.phase {
+			log::debug!(
+				target: LOG_TARGET,
+				"Deferring warp sync target block #{}, state sync is already in progress.",
+				header.number(),
+			);
+			if let Some((_, target_block)) = &mut self.fallback {
+				*target_block = Some(header);
+			}
+			return
+		}
+
 		let Phase::PendingTargetBlock = self.phase else {
 			error!(
 				target: LOG_TARGET,
@@ -232,14 +295,20 @@ where
 		self.phase = Phase::TargetBlock(header);
 	}
 
-	///  Validate and import a state response.
-	pub fn import_state(&mut self, response: StateResponse) -> ImportResult<B> {
+	///  Validate and import a state response from `peer_id`.
+	pub fn import_state(&mut self, peer_id: PeerId, response: StateResponse) -> ImportResult<B> {
 		match &mut self.phase {
 			Phase::WarpProof { .. } | Phase::TargetBlock(_) | Phase::PendingTargetBlock { .. } => {
 				log::debug!(target: "sync", "Unexpected state response");
 				ImportResult::BadResponse
 			},
-			Phase::State(sync) => sync.import(response),
+			Phase::State(sync) => {
+				let result = sync.import(peer_id, response);
+				if let ImportResult::BadResponse = result {
+					self.restart_if_stale();
+				}
+				result
+			},
 		}
 	}
 
@@ -315,13 +384,21 @@ where
 		}
 	}
 
-	/// Produce next state request.
-	pub fn next_state_request(&self) -> Option<StateRequest> {
-		match &self.phase {
+	/// Produce next state request to send to `peer_id`.
+	pub fn next_state_request(&mut self, peer_id: PeerId) -> Option<StateRequest> {
+		match &mut self.phase {
 			Phase::WarpProof { .. } | Phase::TargetBlock(_) | Phase::PendingTargetBlock { .. } =>
 				None,
-			Phase::State(sync) => Some(sync.next_request()),
+			Phase::State(sync) => sync.next_request(peer_id),
+		}
+	}
+
+	/// Release the state range being downloaded from a disconnected peer.
+	pub fn peer_disconnected(&mut self, peer_id: &PeerId) {
+		if let Phase::State(sync) = &mut self.phase {
+			sync.peer_disconnected(peer_id);
 		}
+		self.restart_if_stale();
 	}
 
 	/// Produce next warp proof request.
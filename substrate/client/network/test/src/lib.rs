@@ -748,7 +748,10 @@ pub trait TestNetFactory: Default + Sized + Send {
 			*genesis_extra_storage = storage;
 		}
 
-		if matches!(config.sync_mode, SyncMode::LightState { .. } | SyncMode::Warp) {
+		if matches!(
+			config.sync_mode,
+			SyncMode::LightState { .. } | SyncMode::Warp | SyncMode::Checkpoint(_)
+		) {
 			test_client_builder = test_client_builder.set_no_genesis();
 		}
 		let backend = test_client_builder.backend();
@@ -774,7 +777,7 @@ pub trait TestNetFactory: Default + Sized + Send {
 
 		let mut network_config =
 			NetworkConfiguration::new("test-node", "test-client", Default::default(), None);
-		network_config.sync_mode = config.sync_mode;
+		network_config.sync_mode = config.sync_mode.clone();
 		network_config.transport = TransportConfig::MemoryOnly;
 		network_config.listen_addresses = vec![listen_addr.clone()];
 		network_config.allow_non_globals_in_dht = true;
@@ -1293,6 +1293,37 @@ async fn warp_sync_to_target_block() {
 	.await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn checkpoint_sync() {
+	use sc_network::config::Checkpoint;
+
+	sp_tracing::try_init_simple();
+	let mut net = TestNet::new(0);
+	// Create 3 synced peers and 1 peer syncing from a checkpoint.
+	net.add_full_peer_with_config(Default::default());
+	net.add_full_peer_with_config(Default::default());
+	net.add_full_peer_with_config(Default::default());
+
+	let blocks = net.peer(0).push_blocks(64, false);
+	net.peer(1).push_blocks(64, false);
+	net.peer(2).push_blocks(64, false);
+
+	let checkpoint = net.peer(0).client.header(blocks[31]).unwrap().unwrap();
+	net.add_full_peer_with_config(FullPeerConfig {
+		sync_mode: SyncMode::Checkpoint(Checkpoint {
+			hash: checkpoint.hash().as_ref().to_vec().into(),
+			state_root: checkpoint.state_root.as_ref().to_vec().into(),
+		}),
+		..Default::default()
+	});
+
+	net.run_until_sync().await;
+	assert!(!net.peer(3).client().has_state_at(&BlockId::Number(1)));
+	assert!(net.peer(3).client().has_state_at(&BlockId::Number(32)));
+	assert!(net.peer(3).client().has_state_at(&BlockId::Number(64)));
+	assert_eq!(net.peer(3).client().info().best_hash, blocks[63]);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn syncs_huge_blocks() {
 	use sp_core::storage::well_known_keys::HEAP_PAGES;
@@ -18,7 +18,13 @@
 
 //! API trait of the archive methods.
 
-use crate::MethodResult;
+use crate::{
+	common::events::{
+		ArchiveStorageDiffItem, ArchiveStorageDiffResult, ArchiveStorageResult,
+		PaginatedStorageQuery,
+	},
+	MethodResult,
+};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 
 #[rpc(client, server)]
@@ -88,4 +94,40 @@ pub trait ArchiveApi<Hash> {
 		function: String,
 		call_parameters: String,
 	) -> RpcResult<MethodResult>;
+
+	/// Returns storage entries at a specific block's state.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "archive_unstable_storage", blocking)]
+	fn archive_unstable_storage(
+		&self,
+		hash: Hash,
+		items: Vec<PaginatedStorageQuery<String>>,
+		child_trie: Option<String>,
+	) -> RpcResult<ArchiveStorageResult>;
+
+	/// Returns the storage differences between two blocks.
+	///
+	/// The entries are compared between the block `hash` and `previous_hash`, which defaults
+	/// to the parent of the block. Each item selects a key prefix of the main trie or of a
+	/// child trie. If no items are provided, the whole main trie is compared. A changed child
+	/// trie is reported as a difference of its root key in the main trie, its entries are
+	/// compared by the items that set the `childTrieKey`.
+	///
+	/// At most `max_descendant_responses` differences are reported for an item; the remaining
+	/// ones are fetched by repeating the item with the `paginationStartKey` set to the last
+	/// reported key. Items beyond `max_queried_items` are discarded.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "archive_unstable_storageDiff", blocking)]
+	fn archive_unstable_storage_diff(
+		&self,
+		hash: Hash,
+		items: Vec<ArchiveStorageDiffItem<String>>,
+		previous_hash: Option<Hash>,
+	) -> RpcResult<ArchiveStorageDiffResult>;
 }
@@ -19,15 +19,23 @@
 //! API implementation for `archive`.
 
 use crate::{
-	archive::{error::Error as ArchiveError, ArchiveApiServer},
+	archive::{
+		archive_storage::ArchiveStorage, archive_storage_diff::ArchiveStorageDiff,
+		error::Error as ArchiveError, ArchiveApiServer,
+	},
 	chain_head::hex_string,
+	common::events::{
+		ArchiveStorageDiffItem, ArchiveStorageDiffResult, ArchiveStorageResult,
+		PaginatedStorageQuery,
+	},
 	MethodResult,
 };
 
 use codec::Encode;
 use jsonrpsee::core::{async_trait, RpcResult};
 use sc_client_api::{
-	Backend, BlockBackend, BlockchainEvents, CallExecutor, ExecutorProvider, StorageProvider,
+	Backend, BlockBackend, BlockchainEvents, CallExecutor, ChildInfo, ExecutorProvider, StorageKey,
+	StorageProvider,
 };
 use sp_api::{CallApiAt, CallContext, NumberFor};
 use sp_blockchain::{
@@ -35,11 +43,44 @@ use sp_blockchain::{
 };
 use sp_core::Bytes;
 use sp_runtime::{
-	traits::{Block as BlockT, Header as HeaderT},
+	traits::{Block as BlockT, HashingFor, Header as HeaderT},
 	SaturatedConversion,
 };
 use std::{collections::HashSet, marker::PhantomData, sync::Arc};
 
+/// The configuration of [`Archive`].
+pub struct ArchiveConfig {
+	/// The maximum number of items the `archive_storage` can return for a descendant query before
+	/// pagination is required.
+	///
+	/// Also bounds the number of differences the `archive_storageDiff` reports for an item.
+	pub max_descendant_responses: usize,
+	/// The maximum number of queried items allowed for the `archive_storage` and the
+	/// `archive_storageDiff` at a time.
+	pub max_queried_items: usize,
+}
+
+/// The maximum number of items the `archive_storage` can return for a descendant query before
+/// pagination is required.
+///
+/// Note: this is equivalent with the `chainHead_storage` method.
+const MAX_DESCENDANT_RESPONSES: usize = 5;
+
+/// The maximum number of queried items allowed for the `archive_storage` at a time.
+///
+/// Note: A queried item can also be a descendant query which can return up to
+/// `MAX_DESCENDANT_RESPONSES`.
+const MAX_QUERIED_ITEMS: usize = 8;
+
+impl Default for ArchiveConfig {
+	fn default() -> Self {
+		Self {
+			max_descendant_responses: MAX_DESCENDANT_RESPONSES,
+			max_queried_items: MAX_QUERIED_ITEMS,
+		}
+	}
+}
+
 /// An API for archive RPC calls.
 pub struct Archive<BE: Backend<Block>, Block: BlockT, Client> {
 	/// Substrate client.
@@ -48,6 +89,11 @@ pub struct Archive<BE: Backend<Block>, Block: BlockT, Client> {
 	backend: Arc<BE>,
 	/// The hexadecimal encoded hash of the genesis block.
 	genesis_hash: String,
+	/// The maximum number of items the `archive_storage` can return for a descendant query before
+	/// pagination is required.
+	storage_max_descendant_responses: usize,
+	/// The maximum number of queried items allowed for the `archive_storage` at a time.
+	storage_max_queried_items: usize,
 	/// Phantom member to pin the block type.
 	_phantom: PhantomData<(Block, BE)>,
 }
@@ -58,9 +104,17 @@ impl<BE: Backend<Block>, Block: BlockT, Client> Archive<BE, Block, Client> {
 		client: Arc<Client>,
 		backend: Arc<BE>,
 		genesis_hash: GenesisHash,
+		config: ArchiveConfig,
 	) -> Self {
 		let genesis_hash = hex_string(&genesis_hash.as_ref());
-		Self { client, backend, genesis_hash, _phantom: PhantomData }
+		Self {
+			client,
+			backend,
+			genesis_hash,
+			storage_max_descendant_responses: config.max_descendant_responses,
+			storage_max_queried_items: config.max_queried_items,
+			_phantom: PhantomData,
+		}
 	}
 }
 
@@ -185,4 +239,100 @@ where
 			Err(error) => MethodResult::err(error.to_string()),
 		})
 	}
+
+	fn archive_unstable_storage(
+		&self,
+		hash: Block::Hash,
+		items: Vec<PaginatedStorageQuery<String>>,
+		child_trie: Option<String>,
+	) -> RpcResult<ArchiveStorageResult> {
+		let items = items
+			.into_iter()
+			.map(|query| {
+				let key = StorageKey(parse_hex_param(query.key)?);
+				let pagination_start_key = query
+					.pagination_start_key
+					.map(|key| parse_hex_param(key).map(StorageKey))
+					.transpose()?;
+
+				// Paginated start key is only supported for descendant queries.
+				if pagination_start_key.is_some() && !query.query_type.is_descendant_query() {
+					return Err(ArchiveError::InvalidParam(
+						"Pagination start key is only supported for descendants queries"
+							.to_string(),
+					))
+				}
+
+				Ok(PaginatedStorageQuery {
+					key,
+					query_type: query.query_type,
+					pagination_start_key,
+				})
+			})
+			.collect::<Result<Vec<_>, ArchiveError>>()?;
+
+		let child_trie = child_trie
+			.map(|child_trie| parse_hex_param(child_trie))
+			.transpose()?
+			.map(ChildInfo::new_default_from_vec);
+
+		let storage_client = ArchiveStorage::<Client, Block, BE>::new(
+			self.client.clone(),
+			self.storage_max_descendant_responses,
+			self.storage_max_queried_items,
+		);
+		Ok(storage_client.handle_query(hash, items, child_trie))
+	}
+
+	fn archive_unstable_storage_diff(
+		&self,
+		hash: Block::Hash,
+		items: Vec<ArchiveStorageDiffItem<String>>,
+		previous_hash: Option<Block::Hash>,
+	) -> RpcResult<ArchiveStorageDiffResult> {
+		let items = items
+			.into_iter()
+			.map(|item| {
+				Ok(ArchiveStorageDiffItem {
+					key: parse_hex_param(item.key)?,
+					return_type: item.return_type,
+					child_trie_key: item.child_trie_key.map(parse_hex_param).transpose()?,
+					pagination_start_key: item
+						.pagination_start_key
+						.map(parse_hex_param)
+						.transpose()?,
+				})
+			})
+			.collect::<Result<Vec<_>, ArchiveError>>()?;
+
+		let previous_hash = match previous_hash {
+			Some(previous_hash) => previous_hash,
+			None => match self.client.header(hash) {
+				Ok(Some(header)) => *header.parent_hash(),
+				Ok(None) =>
+					return Ok(ArchiveStorageDiffResult::err(format!("Unknown block {hash:?}"))),
+				Err(error) => return Ok(ArchiveStorageDiffResult::err(error.to_string())),
+			},
+		};
+
+		let state = match self.backend.state_at(hash) {
+			Ok(state) => state,
+			Err(error) => return Ok(ArchiveStorageDiffResult::err(error.to_string())),
+		};
+		let previous_state = match self.backend.state_at(previous_hash) {
+			Ok(state) => state,
+			Err(error) => return Ok(ArchiveStorageDiffResult::err(error.to_string())),
+		};
+
+		let storage_diff = ArchiveStorageDiff::<_, HashingFor<Block>>::new(
+			&state,
+			&previous_state,
+			self.storage_max_descendant_responses,
+			self.storage_max_queried_items,
+		);
+		Ok(match storage_diff.diff(items) {
+			Ok((result, discarded_items)) => ArchiveStorageDiffResult::ok(result, discarded_items),
+			Err(error) => ArchiveStorageDiffResult::err(error),
+		})
+	}
 }
@@ -0,0 +1,368 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Implementation of the `archive_storageDiff` method.
+
+use std::marker::PhantomData;
+
+use sc_client_api::{ChildInfo, MerkleValue, StateBackend};
+use sp_core::Hasher;
+use sp_state_machine::{IterArgs, StorageIterator};
+
+use crate::{
+	chain_head::hex_string,
+	common::events::{
+		ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageDiffOperationType,
+		ArchiveStorageDiffType, StorageResultType,
+	},
+};
+
+/// Computes the storage differences between two states.
+///
+/// The keys present in either state are visited in lexicographic order, one key byte at a
+/// time. Subtrees that have the same closest descendant merkle value in both tries are
+/// skipped, which makes the cost of the comparison proportional to the number of changed
+/// entries rather than to the size of the state.
+pub struct ArchiveStorageDiff<'a, State, H> {
+	/// The state of the block.
+	state: &'a State,
+	/// The state against which the block is compared.
+	previous_state: &'a State,
+	/// The maximum number of differences reported for an item before pagination is required.
+	max_responses: usize,
+	/// The maximum number of items compared at a time.
+	max_queried_items: usize,
+	_phantom: PhantomData<H>,
+}
+
+/// Result of a comparison.
+type DiffResult = Result<(), String>;
+
+/// The comparison of a single item.
+struct DiffQuery<'a> {
+	/// The child trie in which the entries are compared.
+	child_info: Option<&'a ChildInfo>,
+	/// The type of the result reported for every difference.
+	return_type: ArchiveStorageDiffType,
+	/// Only differences at keys after this one are reported.
+	pagination_start_key: Option<&'a [u8]>,
+}
+
+impl DiffQuery<'_> {
+	/// Whether all the keys that start with the given prefix precede the pagination start key.
+	fn skips_prefix(&self, prefix: &[u8]) -> bool {
+		self.pagination_start_key
+			.map_or(false, |start_key| !start_key.starts_with(prefix) && prefix < start_key)
+	}
+
+	/// Whether the given key precedes or is the pagination start key.
+	fn skips_key(&self, key: &[u8]) -> bool {
+		self.pagination_start_key.map_or(false, |start_key| key <= start_key)
+	}
+}
+
+/// A step of the walk over the keys of both states.
+enum Step {
+	/// Compare the entries that start with the given prefix.
+	Prefix(Vec<u8>),
+	/// Compare the entries that start with `prefix` followed by a key byte that is at least
+	/// the last byte of `start_at`.
+	NextByte { prefix: Vec<u8>, start_at: Vec<u8> },
+}
+
+impl<'a, State, H> ArchiveStorageDiff<'a, State, H>
+where
+	H: Hasher,
+	State: StateBackend<H>,
+{
+	/// Constructs a new [`ArchiveStorageDiff`].
+	pub fn new(
+		state: &'a State,
+		previous_state: &'a State,
+		max_responses: usize,
+		max_queried_items: usize,
+	) -> Self {
+		Self { state, previous_state, max_responses, max_queried_items, _phantom: PhantomData }
+	}
+
+	/// Compare the entries located under the key prefixes of the provided items.
+	///
+	/// Returns the differences, at most `max_responses` for each item, together with the
+	/// number of discarded items. When no items are provided, the whole main trie is compared.
+	pub fn diff(
+		&self,
+		mut items: Vec<ArchiveStorageDiffItem<Vec<u8>>>,
+	) -> Result<(Vec<ArchiveStorageDiffEvent>, usize), String> {
+		if items.is_empty() {
+			items.push(ArchiveStorageDiffItem {
+				key: Vec::new(),
+				return_type: ArchiveStorageDiffType::Value,
+				child_trie_key: None,
+				pagination_start_key: None,
+			});
+		}
+		let discarded_items = items.len().saturating_sub(self.max_queried_items);
+		items.truncate(self.max_queried_items);
+
+		let mut results = Vec::new();
+		for item in items {
+			let child_info = item.child_trie_key.map(ChildInfo::new_default_from_vec);
+			let query = DiffQuery {
+				child_info: child_info.as_ref(),
+				return_type: item.return_type,
+				pagination_start_key: item.pagination_start_key.as_deref(),
+			};
+			let mut item_results = Vec::new();
+			self.diff_prefix(&item.key, &query, &mut item_results)?;
+			results.extend(item_results);
+		}
+
+		Ok((results, discarded_items))
+	}
+
+	/// Compare the entries that start with the given prefix, until `max_responses`
+	/// differences are reported.
+	fn diff_prefix(
+		&self,
+		prefix: &[u8],
+		query: &DiffQuery,
+		results: &mut Vec<ArchiveStorageDiffEvent>,
+	) -> DiffResult {
+		let mut steps = vec![Step::Prefix(prefix.to_vec())];
+		while let Some(step) = steps.pop() {
+			if results.len() >= self.max_responses {
+				break
+			}
+
+			match step {
+				Step::Prefix(prefix) => {
+					if query.skips_prefix(&prefix) {
+						continue
+					}
+					if !self.prefix_differs(&prefix, query, results)? {
+						continue
+					}
+					if !query.skips_key(&prefix) {
+						self.diff_key(&prefix, query, results)?;
+					}
+
+					// Continue at the first key byte that may follow the pagination start key.
+					let start_at = match query.pagination_start_key {
+						Some(start_key)
+							if start_key.len() > prefix.len() && start_key.starts_with(&prefix) =>
+							start_key[..=prefix.len()].to_vec(),
+						_ => [&prefix[..], &[0]].concat(),
+					};
+					steps.push(Step::NextByte { prefix, start_at });
+				},
+				Step::NextByte { prefix, start_at } => {
+					let next_key = match (
+						Self::first_key(self.state, &prefix, &start_at, query.child_info)?,
+						Self::first_key(self.previous_state, &prefix, &start_at, query.child_info)?,
+					) {
+						(Some(key), Some(previous_key)) => key.min(previous_key),
+						(Some(key), None) | (None, Some(key)) => key,
+						(None, None) => continue,
+					};
+
+					// The entries after the next key byte are compared once those that start
+					// with it are done.
+					let next_prefix = next_key[..=prefix.len()].to_vec();
+					if let Some(next_byte) = next_prefix[prefix.len()].checked_add(1) {
+						let start_at = [&prefix[..], &[next_byte]].concat();
+						steps.push(Step::NextByte { prefix, start_at });
+					}
+					steps.push(Step::Prefix(next_prefix));
+				},
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Check whether the entries that start with the given prefix may differ.
+	///
+	/// If the prefix exists in only one of the states, all its entries are reported instead.
+	fn prefix_differs(
+		&self,
+		prefix: &[u8],
+		query: &DiffQuery,
+		results: &mut Vec<ArchiveStorageDiffEvent>,
+	) -> Result<bool, String> {
+		let merkle_value = Self::closest_merkle_value(self.state, prefix, query.child_info)?;
+		let previous_merkle_value =
+			Self::closest_merkle_value(self.previous_state, prefix, query.child_info)?;
+
+		match (&merkle_value, &previous_merkle_value) {
+			(None, None) => Ok(false),
+			(Some(_), None) => {
+				self.report_all(
+					self.state,
+					prefix,
+					query,
+					ArchiveStorageDiffOperationType::Added,
+					results,
+				)?;
+				Ok(false)
+			},
+			(None, Some(_)) => {
+				self.report_all(
+					self.previous_state,
+					prefix,
+					query,
+					ArchiveStorageDiffOperationType::Deleted,
+					results,
+				)?;
+				Ok(false)
+			},
+			(Some(value), Some(previous_value)) if value == previous_value => {
+				// The same merkle value guarantees identical subtrees only if the node is
+				// placed at the same position in both tries.
+				let first_key = Self::first_key(self.state, prefix, prefix, query.child_info)?;
+				let previous_first_key =
+					Self::first_key(self.previous_state, prefix, prefix, query.child_info)?;
+				Ok(first_key != previous_first_key)
+			},
+			_ => Ok(true),
+		}
+	}
+
+	/// Compare the entry stored exactly at the given key.
+	fn diff_key(
+		&self,
+		key: &[u8],
+		query: &DiffQuery,
+		results: &mut Vec<ArchiveStorageDiffEvent>,
+	) -> DiffResult {
+		let result = Self::read(self.state, key, query)?;
+		let previous_result = Self::read(self.previous_state, key, query)?;
+
+		let (result, operation_type) = match (result, previous_result) {
+			(Some(result), None) => (result, ArchiveStorageDiffOperationType::Added),
+			(None, Some(previous_result)) =>
+				(previous_result, ArchiveStorageDiffOperationType::Deleted),
+			(Some(result), Some(previous_result)) if result != previous_result =>
+				(result, ArchiveStorageDiffOperationType::Modified),
+			_ => return Ok(()),
+		};
+
+		results.push(Self::event(key, result, query, operation_type));
+		Ok(())
+	}
+
+	/// Report the entries that start with the given prefix from the provided state, until
+	/// `max_responses` differences are reported.
+	fn report_all(
+		&self,
+		state: &State,
+		prefix: &[u8],
+		query: &DiffQuery,
+		operation_type: ArchiveStorageDiffOperationType,
+		results: &mut Vec<ArchiveStorageDiffEvent>,
+	) -> DiffResult {
+		let mut args = IterArgs::default();
+		args.prefix = Some(prefix);
+		args.child_info = query.child_info.cloned();
+		if let Some(start_key) = query.pagination_start_key.filter(|key| key.starts_with(prefix)) {
+			args.start_at = Some(start_key);
+			args.start_at_exclusive = true;
+		}
+		let mut iter = state.raw_iter(args).map_err(|error| error.to_string())?;
+
+		while results.len() < self.max_responses {
+			let Some(key) = iter.next_key(state) else { break };
+			let key = key.map_err(|error| error.to_string())?;
+			let Some(result) = Self::read(state, &key, query)? else { continue };
+			results.push(Self::event(&key, result, query, operation_type));
+		}
+
+		Ok(())
+	}
+
+	/// Construct the event reporting a difference.
+	fn event(
+		key: &[u8],
+		result: StorageResultType,
+		query: &DiffQuery,
+		operation_type: ArchiveStorageDiffOperationType,
+	) -> ArchiveStorageDiffEvent {
+		ArchiveStorageDiffEvent {
+			key: hex_string(&key),
+			result,
+			operation_type,
+			child_trie_key: query
+				.child_info
+				.map(|child_info| hex_string(&child_info.storage_key())),
+		}
+	}
+
+	/// Fetch the closest descendant merkle value of the given key.
+	fn closest_merkle_value(
+		state: &State,
+		key: &[u8],
+		child_info: Option<&ChildInfo>,
+	) -> Result<Option<MerkleValue<H::Out>>, String> {
+		let result = if let Some(child_info) = child_info {
+			state.child_closest_merkle_value(child_info, key)
+		} else {
+			state.closest_merkle_value(key)
+		};
+
+		result.map_err(|error| error.to_string())
+	}
+
+	/// Fetch the first key that starts with the given prefix, starting at the given key inclusive.
+	fn first_key(
+		state: &State,
+		prefix: &[u8],
+		start_at: &[u8],
+		child_info: Option<&ChildInfo>,
+	) -> Result<Option<Vec<u8>>, String> {
+		let mut args = IterArgs::default();
+		args.prefix = Some(prefix);
+		args.start_at = Some(start_at);
+		args.child_info = child_info.cloned();
+
+		let mut iter = state.raw_iter(args).map_err(|error| error.to_string())?;
+		iter.next_key(state).transpose().map_err(|error| error.to_string())
+	}
+
+	/// Read the value or the hash of the value stored at the given key.
+	fn read(
+		state: &State,
+		key: &[u8],
+		query: &DiffQuery,
+	) -> Result<Option<StorageResultType>, String> {
+		let result = match (query.return_type, query.child_info) {
+			(ArchiveStorageDiffType::Value, Some(child_info)) => state
+				.child_storage(child_info, key)
+				.map(|value| value.map(|value| StorageResultType::Value(hex_string(&value)))),
+			(ArchiveStorageDiffType::Value, None) => state
+				.storage(key)
+				.map(|value| value.map(|value| StorageResultType::Value(hex_string(&value)))),
+			(ArchiveStorageDiffType::Hash, Some(child_info)) => state
+				.child_storage_hash(child_info, key)
+				.map(|hash| hash.map(|hash| StorageResultType::Hash(hex_string(&hash.as_ref())))),
+			(ArchiveStorageDiffType::Hash, None) => state
+				.storage_hash(key)
+				.map(|hash| hash.map(|hash| StorageResultType::Hash(hex_string(&hash.as_ref())))),
+		};
+
+		result.map_err(|error| error.to_string())
+	}
+}
@@ -29,4 +29,8 @@ pub mod api;
 pub mod archive;
 pub mod error;
 
+mod archive_storage;
+mod archive_storage_diff;
+
 pub use api::ArchiveApiServer;
+pub use archive::{Archive, ArchiveConfig};
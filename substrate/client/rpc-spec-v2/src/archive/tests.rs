@@ -16,24 +16,37 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{chain_head::hex_string, MethodResult};
+use crate::{
+	chain_head::hex_string,
+	common::events::{
+		ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageDiffOperationType,
+		ArchiveStorageDiffResult, ArchiveStorageDiffType, ArchiveStorageMethodOk,
+		ArchiveStorageResult, PaginatedStorageQuery, StorageQueryType, StorageResult,
+		StorageResultType,
+	},
+	MethodResult,
+};
 
-use super::{archive::Archive, *};
+use super::{archive::Archive, archive_storage_diff::ArchiveStorageDiff, *};
 
 use assert_matches::assert_matches;
 use codec::{Decode, Encode};
 use jsonrpsee::{
 	core::error::Error,
+	rpc_params,
 	types::{error::CallError, EmptyServerParams as EmptyParams},
 	RpcModule,
 };
 use sc_block_builder::BlockBuilderBuilder;
+use sc_client_api::ChildInfo;
 use sp_blockchain::HeaderBackend;
 use sp_consensus::BlockOrigin;
+use sp_core::{storage::StateVersion, Blake2Hasher, Hasher};
 use sp_runtime::{
 	traits::{Block as BlockT, Header as HeaderT},
 	SaturatedConversion,
 };
+use sp_state_machine::{Backend as _, InMemoryBackend};
 use std::sync::Arc;
 use substrate_test_runtime::Transfer;
 use substrate_test_runtime_client::{
@@ -42,23 +55,43 @@ use substrate_test_runtime_client::{
 
 const CHAIN_GENESIS: [u8; 32] = [0; 32];
 const INVALID_HASH: [u8; 32] = [1; 32];
+const MAX_PAGINATION_LIMIT: usize = 5;
+const MAX_QUERIED_LIMIT: usize = 5;
+const KEY: &[u8] = b":mock";
+const VALUE: &[u8] = b"hello world";
+const CHILD_STORAGE_KEY: &[u8] = b"child";
+const CHILD_VALUE: &[u8] = b"child value";
 
 type Header = substrate_test_runtime_client::runtime::Header;
 type Block = substrate_test_runtime_client::runtime::Block;
 
-fn setup_api() -> (Arc<Client<Backend>>, RpcModule<Archive<Backend, Block, Client<Backend>>>) {
-	let builder = TestClientBuilder::new();
+fn setup_api(
+	max_descendant_responses: usize,
+	max_queried_items: usize,
+) -> (Arc<Client<Backend>>, RpcModule<Archive<Backend, Block, Client<Backend>>>) {
+	let child_info = ChildInfo::new_default(CHILD_STORAGE_KEY);
+	let builder = TestClientBuilder::new().add_extra_child_storage(
+		&child_info,
+		KEY.to_vec(),
+		CHILD_VALUE.to_vec(),
+	);
 	let backend = builder.backend();
 	let client = Arc::new(builder.build());
 
-	let api = Archive::new(client.clone(), backend, CHAIN_GENESIS).into_rpc();
+	let api = Archive::new(
+		client.clone(),
+		backend,
+		CHAIN_GENESIS,
+		ArchiveConfig { max_descendant_responses, max_queried_items },
+	)
+	.into_rpc();
 
 	(client, api)
 }
 
 #[tokio::test]
 async fn archive_genesis() {
-	let (_client, api) = setup_api();
+	let (_client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 
 	let genesis: String =
 		api.call("archive_unstable_genesisHash", EmptyParams::new()).await.unwrap();
@@ -67,7 +100,7 @@ async fn archive_genesis() {
 
 #[tokio::test]
 async fn archive_body() {
-	let (mut client, api) = setup_api();
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 
 	// Invalid block hash.
 	let invalid_hash = hex_string(&INVALID_HASH);
@@ -101,7 +134,7 @@ async fn archive_body() {
 
 #[tokio::test]
 async fn archive_header() {
-	let (mut client, api) = setup_api();
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 
 	// Invalid block hash.
 	let invalid_hash = hex_string(&INVALID_HASH);
@@ -135,7 +168,7 @@ async fn archive_header() {
 
 #[tokio::test]
 async fn archive_finalized_height() {
-	let (client, api) = setup_api();
+	let (client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 
 	let client_height: u32 = client.info().finalized_number.saturated_into();
 
@@ -147,7 +180,7 @@ async fn archive_finalized_height() {
 
 #[tokio::test]
 async fn archive_hash_by_height() {
-	let (mut client, api) = setup_api();
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 
 	// Genesis height.
 	let hashes: Vec<String> = api.call("archive_unstable_hashByHeight", [0]).await.unwrap();
@@ -253,7 +286,7 @@ async fn archive_hash_by_height() {
 
 #[tokio::test]
 async fn archive_call() {
-	let (mut client, api) = setup_api();
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 	let invalid_hash = hex_string(&INVALID_HASH);
 
 	// Invalid parameter (non-hex).
@@ -309,3 +342,575 @@ async fn archive_call() {
 	let expected = MethodResult::ok("0x0000000000000000");
 	assert_eq!(result, expected);
 }
+
+#[tokio::test]
+async fn archive_storage_hashes_values() {
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
+
+	let block = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap()
+		.build()
+		.unwrap()
+		.block;
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(block.hash())
+		.with_parent_block_number(1)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":m".to_vec(), Some(b"a".to_vec())).unwrap();
+	builder.push_storage_change(b":mo".to_vec(), Some(b"ab".to_vec())).unwrap();
+	builder.push_storage_change(b":moc".to_vec(), Some(b"abc".to_vec())).unwrap();
+	builder.push_storage_change(b":mock".to_vec(), Some(b"abcd".to_vec())).unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let items: Vec<PaginatedStorageQuery<String>> = vec![
+		PaginatedStorageQuery {
+			key: hex_string(b":m"),
+			query_type: StorageQueryType::DescendantsHashes,
+			pagination_start_key: None,
+		},
+		PaginatedStorageQuery {
+			key: hex_string(b":m"),
+			query_type: StorageQueryType::DescendantsValues,
+			pagination_start_key: None,
+		},
+		PaginatedStorageQuery {
+			key: hex_string(b":m"),
+			query_type: StorageQueryType::Hash,
+			pagination_start_key: None,
+		},
+		PaginatedStorageQuery {
+			key: hex_string(b":m"),
+			query_type: StorageQueryType::Value,
+			pagination_start_key: None,
+		},
+	];
+
+	let result: ArchiveStorageResult = api
+		.call("archive_unstable_storage", rpc_params![&block_hash, items.clone()])
+		.await
+		.unwrap();
+
+	match result {
+		ArchiveStorageResult::Ok(ArchiveStorageMethodOk { result, discarded_items }) => {
+			assert_eq!(result.len(), 10);
+			assert_eq!(discarded_items, 0);
+
+			// Descendant hashes.
+			assert_eq!(result[0].key, hex_string(b":m"));
+			assert_eq!(
+				result[0].result,
+				StorageResultType::Hash(format!("{:?}", Blake2Hasher::hash(b"a")))
+			);
+			assert_eq!(result[3].key, hex_string(b":mock"));
+			assert_eq!(
+				result[3].result,
+				StorageResultType::Hash(format!("{:?}", Blake2Hasher::hash(b"abcd")))
+			);
+
+			// Descendant values.
+			assert_eq!(result[4].key, hex_string(b":m"));
+			assert_eq!(result[4].result, StorageResultType::Value(hex_string(b"a")));
+			assert_eq!(result[7].key, hex_string(b":mock"));
+			assert_eq!(result[7].result, StorageResultType::Value(hex_string(b"abcd")));
+
+			// Direct queries.
+			assert_eq!(result[8].key, hex_string(b":m"));
+			assert_eq!(
+				result[8].result,
+				StorageResultType::Hash(format!("{:?}", Blake2Hasher::hash(b"a")))
+			);
+			assert_eq!(result[9].key, hex_string(b":m"));
+			assert_eq!(result[9].result, StorageResultType::Value(hex_string(b"a")));
+		},
+		_ => panic!("Unexpected result"),
+	};
+
+	// The genesis block does not have the keys.
+	let genesis_hash = format!("{:?}", client.genesis_hash());
+	let result: ArchiveStorageResult = api
+		.call("archive_unstable_storage", rpc_params![&genesis_hash, items])
+		.await
+		.unwrap();
+	assert_eq!(result, ArchiveStorageResult::ok(vec![], 0));
+}
+
+#[tokio::test]
+async fn archive_storage_child_trie() {
+	let (client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
+
+	let genesis_hash = format!("{:?}", client.genesis_hash());
+	let child_info = hex_string(&CHILD_STORAGE_KEY);
+	let result: ArchiveStorageResult = api
+		.call(
+			"archive_unstable_storage",
+			rpc_params![
+				&genesis_hash,
+				vec![
+					PaginatedStorageQuery {
+						key: hex_string(&KEY),
+						query_type: StorageQueryType::Value,
+						pagination_start_key: None,
+					},
+					PaginatedStorageQuery {
+						key: hex_string(&KEY),
+						query_type: StorageQueryType::Hash,
+						pagination_start_key: None,
+					}
+				],
+				&child_info
+			],
+		)
+		.await
+		.unwrap();
+
+	let expected = ArchiveStorageResult::ok(
+		vec![
+			StorageResult {
+				key: hex_string(&KEY),
+				result: StorageResultType::Value(hex_string(&CHILD_VALUE)),
+			},
+			StorageResult {
+				key: hex_string(&KEY),
+				result: StorageResultType::Hash(format!("{:?}", Blake2Hasher::hash(CHILD_VALUE))),
+			},
+		],
+		0,
+	);
+	assert_eq!(result, expected);
+
+	// The main trie does not contain the child value.
+	let result: ArchiveStorageResult = api
+		.call(
+			"archive_unstable_storage",
+			rpc_params![
+				&genesis_hash,
+				vec![PaginatedStorageQuery {
+					key: hex_string(&KEY),
+					query_type: StorageQueryType::Value,
+					pagination_start_key: None,
+				}]
+			],
+		)
+		.await
+		.unwrap();
+	assert_eq!(result, ArchiveStorageResult::ok(vec![], 0));
+}
+
+#[tokio::test]
+async fn archive_storage_paginate_iterations() {
+	// 1 iteration allowed before pagination kicks in.
+	let (mut client, api) = setup_api(1, MAX_QUERIED_LIMIT);
+
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":m".to_vec(), Some(b"a".to_vec())).unwrap();
+	builder.push_storage_change(b":mo".to_vec(), Some(b"ab".to_vec())).unwrap();
+	builder.push_storage_change(b":moc".to_vec(), Some(b"abc".to_vec())).unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let mut pagination_start_key = None;
+	for (key, value) in
+		[(&b":m"[..], &b"a"[..]), (&b":mo"[..], &b"ab"[..]), (&b":moc"[..], &b"abc"[..])]
+	{
+		let result: ArchiveStorageResult = api
+			.call(
+				"archive_unstable_storage",
+				rpc_params![
+					&block_hash,
+					vec![PaginatedStorageQuery {
+						key: hex_string(b":m"),
+						query_type: StorageQueryType::DescendantsValues,
+						pagination_start_key: pagination_start_key.clone(),
+					}]
+				],
+			)
+			.await
+			.unwrap();
+
+		match result {
+			ArchiveStorageResult::Ok(ArchiveStorageMethodOk { result, discarded_items }) => {
+				assert_eq!(result.len(), 1);
+				assert_eq!(discarded_items, 0);
+				assert_eq!(result[0].key, hex_string(&key));
+				assert_eq!(result[0].result, StorageResultType::Value(hex_string(&value)));
+				pagination_start_key = Some(result[0].key.clone());
+			},
+			_ => panic!("Unexpected result"),
+		};
+	}
+
+	// No more items after the last key.
+	let result: ArchiveStorageResult = api
+		.call(
+			"archive_unstable_storage",
+			rpc_params![
+				&block_hash,
+				vec![PaginatedStorageQuery {
+					key: hex_string(b":m"),
+					query_type: StorageQueryType::DescendantsValues,
+					pagination_start_key,
+				}]
+			],
+		)
+		.await
+		.unwrap();
+	assert_eq!(result, ArchiveStorageResult::ok(vec![], 0));
+}
+
+#[tokio::test]
+async fn archive_storage_discarded_items() {
+	// One query at a time.
+	let (client, api) = setup_api(MAX_PAGINATION_LIMIT, 1);
+
+	let genesis_hash = format!("{:?}", client.genesis_hash());
+	let result: ArchiveStorageResult = api
+		.call(
+			"archive_unstable_storage",
+			rpc_params![
+				&genesis_hash,
+				vec![
+					PaginatedStorageQuery {
+						key: hex_string(b":code"),
+						query_type: StorageQueryType::Hash,
+						pagination_start_key: None,
+					},
+					PaginatedStorageQuery {
+						key: hex_string(b":code"),
+						query_type: StorageQueryType::Value,
+						pagination_start_key: None,
+					},
+					PaginatedStorageQuery {
+						key: hex_string(b":code"),
+						query_type: StorageQueryType::ClosestDescendantMerkleValue,
+						pagination_start_key: None,
+					}
+				]
+			],
+		)
+		.await
+		.unwrap();
+
+	match result {
+		ArchiveStorageResult::Ok(ArchiveStorageMethodOk { result, discarded_items }) => {
+			assert_eq!(result.len(), 1);
+			assert_eq!(discarded_items, 2);
+			assert_eq!(result[0].key, hex_string(b":code"));
+			assert_matches!(result[0].result, StorageResultType::Hash(_));
+		},
+		_ => panic!("Unexpected result"),
+	};
+}
+
+#[tokio::test]
+async fn archive_storage_invalid_pagination_key() {
+	let (client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
+
+	// Pagination start keys are only accepted for descendant queries.
+	let genesis_hash = format!("{:?}", client.genesis_hash());
+	let err = api
+		.call::<_, serde_json::Value>(
+			"archive_unstable_storage",
+			rpc_params![
+				&genesis_hash,
+				vec![PaginatedStorageQuery {
+					key: hex_string(&KEY),
+					query_type: StorageQueryType::Value,
+					pagination_start_key: Some(hex_string(&KEY)),
+				}]
+			],
+		)
+		.await
+		.unwrap_err();
+	assert_matches!(err, Error::Call(CallError::Custom(ref err)) if err.code() == 3001 && err.message().contains("Invalid parameter"));
+}
+
+#[tokio::test]
+async fn archive_storage_diff() {
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
+
+	// Block 1 introduces the keys.
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":A".to_vec(), Some(b"a".to_vec())).unwrap();
+	builder.push_storage_change(b":AB".to_vec(), Some(b"ab".to_vec())).unwrap();
+	builder.push_storage_change(b":AC".to_vec(), Some(b"ac".to_vec())).unwrap();
+	let block_1 = builder.build().unwrap().block;
+	let block_1_hash = block_1.header.hash();
+	client.import(BlockOrigin::Own, block_1.clone()).await.unwrap();
+
+	// Block 2 modifies, deletes and adds keys.
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(block_1_hash)
+		.with_parent_block_number(1)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":AB".to_vec(), Some(b"11".to_vec())).unwrap();
+	builder.push_storage_change(b":AC".to_vec(), None).unwrap();
+	builder.push_storage_change(b":AD".to_vec(), Some(b"ad".to_vec())).unwrap();
+	let block_2 = builder.build().unwrap().block;
+	let block_2_hash = block_2.header.hash();
+	client.import(BlockOrigin::Own, block_2.clone()).await.unwrap();
+
+	let items = vec![ArchiveStorageDiffItem {
+		key: hex_string(b":A"),
+		return_type: ArchiveStorageDiffType::Value,
+		child_trie_key: None,
+		pagination_start_key: None,
+	}];
+
+	// Compare against the parent block by default.
+	let result: ArchiveStorageDiffResult = api
+		.call(
+			"archive_unstable_storageDiff",
+			rpc_params![&format!("{:?}", block_2_hash), items.clone()],
+		)
+		.await
+		.unwrap();
+	let expected = ArchiveStorageDiffResult::ok(
+		vec![
+			ArchiveStorageDiffEvent {
+				key: hex_string(b":AB"),
+				result: StorageResultType::Value(hex_string(b"11")),
+				operation_type: ArchiveStorageDiffOperationType::Modified,
+				child_trie_key: None,
+			},
+			ArchiveStorageDiffEvent {
+				key: hex_string(b":AC"),
+				result: StorageResultType::Value(hex_string(b"ac")),
+				operation_type: ArchiveStorageDiffOperationType::Deleted,
+				child_trie_key: None,
+			},
+			ArchiveStorageDiffEvent {
+				key: hex_string(b":AD"),
+				result: StorageResultType::Value(hex_string(b"ad")),
+				operation_type: ArchiveStorageDiffOperationType::Added,
+				child_trie_key: None,
+			},
+		],
+		0,
+	);
+	assert_eq!(result, expected);
+
+	// Compare against the genesis block reporting hashes.
+	let genesis_hash = client.genesis_hash();
+	let items = vec![ArchiveStorageDiffItem {
+		key: hex_string(b":A"),
+		return_type: ArchiveStorageDiffType::Hash,
+		child_trie_key: None,
+		pagination_start_key: None,
+	}];
+	let result: ArchiveStorageDiffResult = api
+		.call(
+			"archive_unstable_storageDiff",
+			rpc_params![&format!("{:?}", block_2_hash), items, &format!("{:?}", genesis_hash)],
+		)
+		.await
+		.unwrap();
+	let expected = ArchiveStorageDiffResult::ok(
+		[(&b":A"[..], &b"a"[..]), (&b":AB"[..], &b"11"[..]), (&b":AD"[..], &b"ad"[..])]
+			.into_iter()
+			.map(|(key, value)| ArchiveStorageDiffEvent {
+				key: hex_string(&key),
+				result: StorageResultType::Hash(format!("{:?}", Blake2Hasher::hash(value))),
+				operation_type: ArchiveStorageDiffOperationType::Added,
+				child_trie_key: None,
+			})
+			.collect(),
+		0,
+	);
+	assert_eq!(result, expected);
+
+	// Comparing a block with itself yields no differences.
+	let result: ArchiveStorageDiffResult = api
+		.call(
+			"archive_unstable_storageDiff",
+			rpc_params![
+				&format!("{:?}", block_2_hash),
+				Vec::<ArchiveStorageDiffItem<String>>::new(),
+				&format!("{:?}", block_2_hash)
+			],
+		)
+		.await
+		.unwrap();
+	assert_eq!(result, ArchiveStorageDiffResult::ok(vec![], 0));
+
+	// The whole main trie is compared when no items are provided.
+	let result: ArchiveStorageDiffResult = api
+		.call(
+			"archive_unstable_storageDiff",
+			rpc_params![
+				&format!("{:?}", block_2_hash),
+				Vec::<ArchiveStorageDiffItem<String>>::new()
+			],
+		)
+		.await
+		.unwrap();
+	let first_page = match result {
+		ArchiveStorageDiffResult::Ok(ok) => ok.result,
+		_ => panic!("Unexpected result"),
+	};
+	assert!(first_page.len() <= MAX_PAGINATION_LIMIT);
+
+	// The remaining differences are fetched page by page.
+	let mut keys = Vec::new();
+	let mut pagination_start_key = None;
+	loop {
+		let items = vec![ArchiveStorageDiffItem {
+			key: String::new(),
+			return_type: ArchiveStorageDiffType::Value,
+			child_trie_key: None,
+			pagination_start_key: pagination_start_key.clone(),
+		}];
+		let result: ArchiveStorageDiffResult = api
+			.call(
+				"archive_unstable_storageDiff",
+				rpc_params![&format!("{:?}", block_2_hash), items],
+			)
+			.await
+			.unwrap();
+		let page = match result {
+			ArchiveStorageDiffResult::Ok(ok) => ok.result,
+			_ => panic!("Unexpected result"),
+		};
+		assert!(page.len() <= MAX_PAGINATION_LIMIT);
+		if pagination_start_key.is_none() {
+			assert_eq!(page, first_page);
+		}
+
+		let Some(last) = page.last() else { break };
+		pagination_start_key = Some(last.key.clone());
+		keys.extend(page.into_iter().map(|event| event.key));
+	}
+
+	assert!(keys.windows(2).all(|keys| keys[0] < keys[1]));
+	assert!(keys.contains(&hex_string(b":AB")));
+	assert!(keys.contains(&hex_string(b":AC")));
+	assert!(keys.contains(&hex_string(b":AD")));
+	assert!(!keys.contains(&hex_string(b":A")));
+
+	// Items beyond the limit are discarded.
+	let items = vec![
+		ArchiveStorageDiffItem {
+			key: hex_string(b":A"),
+			return_type: ArchiveStorageDiffType::Value,
+			child_trie_key: None,
+			pagination_start_key: None,
+		};
+		MAX_QUERIED_LIMIT + 2
+	];
+	let result: ArchiveStorageDiffResult = api
+		.call("archive_unstable_storageDiff", rpc_params![&format!("{:?}", block_2_hash), items])
+		.await
+		.unwrap();
+	match result {
+		ArchiveStorageDiffResult::Ok(ok) => {
+			assert_eq!(ok.result.len(), MAX_QUERIED_LIMIT * 3);
+			assert_eq!(ok.discarded_items, 2);
+		},
+		_ => panic!("Unexpected result"),
+	};
+
+	// Unknown block.
+	let result: ArchiveStorageDiffResult = api
+		.call(
+			"archive_unstable_storageDiff",
+			rpc_params![&hex_string(&INVALID_HASH), Vec::<ArchiveStorageDiffItem<String>>::new()],
+		)
+		.await
+		.unwrap();
+	assert_matches!(result, ArchiveStorageDiffResult::Err(_));
+}
+
+#[test]
+fn archive_storage_diff_child_trie() {
+	let child_info = ChildInfo::new_default(CHILD_STORAGE_KEY);
+	let child_root_key = child_info.prefixed_storage_key().into_inner();
+
+	let previous_state = InMemoryBackend::<Blake2Hasher>::from((
+		vec![
+			(None, vec![(KEY.to_vec(), Some(VALUE.to_vec()))]),
+			(
+				Some(child_info.clone()),
+				vec![(b"a".to_vec(), Some(b"1".to_vec())), (b"b".to_vec(), Some(b"2".to_vec()))],
+			),
+		],
+		StateVersion::V1,
+	));
+	let state = InMemoryBackend::<Blake2Hasher>::from((
+		vec![
+			(None, vec![(KEY.to_vec(), Some(VALUE.to_vec()))]),
+			(
+				Some(child_info.clone()),
+				vec![(b"a".to_vec(), Some(b"11".to_vec())), (b"c".to_vec(), Some(b"3".to_vec()))],
+			),
+		],
+		StateVersion::V1,
+	));
+	let storage_diff = ArchiveStorageDiff::<_, Blake2Hasher>::new(
+		&state,
+		&previous_state,
+		MAX_PAGINATION_LIMIT,
+		MAX_QUERIED_LIMIT,
+	);
+
+	// The changed child trie is reported by its root key in the main trie.
+	let (result, discarded_items) = storage_diff.diff(vec![]).unwrap();
+	assert_eq!(discarded_items, 0);
+	let child_root = state.storage(&child_root_key).unwrap().unwrap();
+	assert_eq!(
+		result,
+		vec![ArchiveStorageDiffEvent {
+			key: hex_string(&child_root_key),
+			result: StorageResultType::Value(hex_string(&child_root)),
+			operation_type: ArchiveStorageDiffOperationType::Modified,
+			child_trie_key: None,
+		}]
+	);
+
+	// The entries of the child trie are compared when requested.
+	let item = ArchiveStorageDiffItem {
+		key: Vec::new(),
+		return_type: ArchiveStorageDiffType::Value,
+		child_trie_key: Some(CHILD_STORAGE_KEY.to_vec()),
+		pagination_start_key: None,
+	};
+	let child_event = |key: &[u8], value: &[u8], operation_type| ArchiveStorageDiffEvent {
+		key: hex_string(&key),
+		result: StorageResultType::Value(hex_string(&value)),
+		operation_type,
+		child_trie_key: Some(hex_string(&CHILD_STORAGE_KEY)),
+	};
+	let (result, _) = storage_diff.diff(vec![item.clone()]).unwrap();
+	assert_eq!(
+		result,
+		vec![
+			child_event(b"a", b"11", ArchiveStorageDiffOperationType::Modified),
+			child_event(b"b", b"2", ArchiveStorageDiffOperationType::Deleted),
+			child_event(b"c", b"3", ArchiveStorageDiffOperationType::Added),
+		]
+	);
+
+	// The comparison resumes after the pagination start key.
+	let storage_diff =
+		ArchiveStorageDiff::<_, Blake2Hasher>::new(&state, &previous_state, 1, MAX_QUERIED_LIMIT);
+	let (result, _) = storage_diff
+		.diff(vec![ArchiveStorageDiffItem { pagination_start_key: Some(b"a".to_vec()), ..item }])
+		.unwrap();
+	assert_eq!(result, vec![child_event(b"b", b"2", ArchiveStorageDiffOperationType::Deleted)]);
+}
@@ -23,32 +23,25 @@ use std::{collections::VecDeque, marker::PhantomData, sync::Arc};
 use sc_client_api::{Backend, ChildInfo, StorageKey, StorageProvider};
 use sc_utils::mpsc::TracingUnboundedSender;
 use sp_api::BlockT;
-use sp_core::storage::well_known_keys;
 
-use crate::chain_head::event::OperationStorageItems;
+use crate::{
+	chain_head::event::OperationStorageItems,
+	common::{
+		events::{StorageQuery, StorageQueryType},
+		storage::{is_key_queryable, IterQueryType, QueryIter, QueryIterResult, Storage},
+	},
+};
 
 use super::{
-	event::{
-		OperationError, OperationId, StorageQuery, StorageQueryType, StorageResult,
-		StorageResultType,
-	},
-	hex_string,
+	event::{OperationError, OperationId},
 	subscription::BlockGuard,
 	FollowEvent,
 };
 
-/// The query type of an interation.
-enum IterQueryType {
-	/// Iterating over (key, value) pairs.
-	Value,
-	/// Iterating over (key, hash) pairs.
-	Hash,
-}
-
 /// Generates the events of the `chainHead_storage` method.
 pub struct ChainHeadStorage<Client, Block, BE> {
-	/// Substrate client.
-	client: Arc<Client>,
+	/// Storage client.
+	client: Storage<Client, Block, BE>,
 	/// Queue of operations that may require pagination.
 	iter_operations: VecDeque<QueryIter>,
 	/// The maximum number of items reported by the `chainHead_storage` before
@@ -61,7 +54,7 @@ impl<Client, Block, BE> ChainHeadStorage<Client, Block, BE> {
 	/// Constructs a new [`ChainHeadStorage`].
 	pub fn new(client: Arc<Client>, operation_max_storage_items: usize) -> Self {
 		Self {
-			client,
+			client: Storage::new(client),
 			iter_operations: VecDeque::new(),
 			operation_max_storage_items,
 			_phandom: PhantomData,
@@ -69,163 +62,12 @@ impl<Client, Block, BE> ChainHeadStorage<Client, Block, BE> {
 	}
 }
 
-/// Query to iterate over storage.
-struct QueryIter {
-	/// The key from which the iteration was started.
-	query_key: StorageKey,
-	/// The key after which pagination should resume.
-	pagination_start_key: Option<StorageKey>,
-	/// The type of the query (either value or hash).
-	ty: IterQueryType,
-}
-
-/// Checks if the provided key (main or child key) is valid
-/// for queries.
-///
-/// Keys that are identical to `:child_storage:` or `:child_storage:default:`
-/// are not queryable.
-fn is_key_queryable(key: &[u8]) -> bool {
-	!well_known_keys::is_default_child_storage_key(key) &&
-		!well_known_keys::is_child_storage_key(key)
-}
-
-/// The result of making a query call.
-type QueryResult = Result<Option<StorageResult>, String>;
-
-/// The result of iterating over keys.
-type QueryIterResult = Result<(Vec<StorageResult>, Option<QueryIter>), String>;
-
 impl<Client, Block, BE> ChainHeadStorage<Client, Block, BE>
 where
 	Block: BlockT + 'static,
 	BE: Backend<Block> + 'static,
 	Client: StorageProvider<Block, BE> + 'static,
 {
-	/// Fetch the value from storage.
-	fn query_storage_value(
-		&self,
-		hash: Block::Hash,
-		key: &StorageKey,
-		child_key: Option<&ChildInfo>,
-	) -> QueryResult {
-		let result = if let Some(child_key) = child_key {
-			self.client.child_storage(hash, child_key, key)
-		} else {
-			self.client.storage(hash, key)
-		};
-
-		result
-			.map(|opt| {
-				QueryResult::Ok(opt.map(|storage_data| StorageResult {
-					key: hex_string(&key.0),
-					result: StorageResultType::Value(hex_string(&storage_data.0)),
-				}))
-			})
-			.unwrap_or_else(|error| QueryResult::Err(error.to_string()))
-	}
-
-	/// Fetch the hash of a value from storage.
-	fn query_storage_hash(
-		&self,
-		hash: Block::Hash,
-		key: &StorageKey,
-		child_key: Option<&ChildInfo>,
-	) -> QueryResult {
-		let result = if let Some(child_key) = child_key {
-			self.client.child_storage_hash(hash, child_key, key)
-		} else {
-			self.client.storage_hash(hash, key)
-		};
-
-		result
-			.map(|opt| {
-				QueryResult::Ok(opt.map(|storage_data| StorageResult {
-					key: hex_string(&key.0),
-					result: StorageResultType::Hash(hex_string(&storage_data.as_ref())),
-				}))
-			})
-			.unwrap_or_else(|error| QueryResult::Err(error.to_string()))
-	}
-
-	/// Fetch the closest merkle value.
-	fn query_storage_merkle_value(
-		&self,
-		hash: Block::Hash,
-		key: &StorageKey,
-		child_key: Option<&ChildInfo>,
-	) -> QueryResult {
-		let result = if let Some(child_key) = child_key {
-			self.client.child_closest_merkle_value(hash, child_key, key)
-		} else {
-			self.client.closest_merkle_value(hash, key)
-		};
-
-		result
-			.map(|opt| {
-				QueryResult::Ok(opt.map(|storage_data| {
-					let result = match &storage_data {
-						sc_client_api::MerkleValue::Node(data) => hex_string(&data.as_slice()),
-						sc_client_api::MerkleValue::Hash(hash) => hex_string(&hash.as_ref()),
-					};
-
-					StorageResult {
-						key: hex_string(&key.0),
-						result: StorageResultType::ClosestDescendantMerkleValue(result),
-					}
-				}))
-			})
-			.unwrap_or_else(|error| QueryResult::Err(error.to_string()))
-	}
-
-	/// Iterate over at most `operation_max_storage_items` keys.
-	///
-	/// Returns the storage result with a potential next key to resume iteration.
-	fn query_storage_iter_pagination(
-		&self,
-		query: QueryIter,
-		hash: Block::Hash,
-		child_key: Option<&ChildInfo>,
-	) -> QueryIterResult {
-		let QueryIter { ty, query_key, pagination_start_key } = query;
-
-		let mut keys_iter = if let Some(child_key) = child_key {
-			self.client.child_storage_keys(
-				hash,
-				child_key.to_owned(),
-				Some(&query_key),
-				pagination_start_key.as_ref(),
-			)
-		} else {
-			self.client.storage_keys(hash, Some(&query_key), pagination_start_key.as_ref())
-		}
-		.map_err(|err| err.to_string())?;
-
-		let mut ret = Vec::with_capacity(self.operation_max_storage_items);
-		let mut next_pagination_key = None;
-		for _ in 0..self.operation_max_storage_items {
-			let Some(key) = keys_iter.next() else { break };
-
-			next_pagination_key = Some(key.clone());
-
-			let result = match ty {
-				IterQueryType::Value => self.query_storage_value(hash, &key, child_key),
-				IterQueryType::Hash => self.query_storage_hash(hash, &key, child_key),
-			}?;
-
-			if let Some(value) = result {
-				ret.push(value);
-			}
-		}
-
-		// Save the next key if any to continue the iteration.
-		let maybe_next_query = keys_iter.next().map(|_| QueryIter {
-			ty,
-			query_key,
-			pagination_start_key: next_pagination_key,
-		});
-		Ok((ret, maybe_next_query))
-	}
-
 	/// Iterate over (key, hash) and (key, value) generating the `WaitingForContinue` event if
 	/// necessary.
 	async fn generate_storage_iter_events(
@@ -242,7 +84,12 @@ where
 				return
 			}
 
-			let result = self.query_storage_iter_pagination(query, hash, child_key.as_ref());
+			let result = self.client.query_iter_pagination(
+				query,
+				hash,
+				child_key.as_ref(),
+				self.operation_max_storage_items,
+			);
 			let (events, maybe_next_query) = match result {
 				QueryIterResult::Ok(result) => result,
 				QueryIterResult::Err(error) => {
@@ -311,7 +158,7 @@ where
 
 			match item.query_type {
 				StorageQueryType::Value => {
-					match self.query_storage_value(hash, &item.key, child_key.as_ref()) {
+					match self.client.query_value(hash, &item.key, child_key.as_ref()) {
 						Ok(Some(value)) => storage_results.push(value),
 						Ok(None) => continue,
 						Err(error) => {
@@ -321,7 +168,7 @@ where
 					}
 				},
 				StorageQueryType::Hash =>
-					match self.query_storage_hash(hash, &item.key, child_key.as_ref()) {
+					match self.client.query_hash(hash, &item.key, child_key.as_ref()) {
 						Ok(Some(value)) => storage_results.push(value),
 						Ok(None) => continue,
 						Err(error) => {
@@ -330,7 +177,7 @@ where
 						},
 					},
 				StorageQueryType::ClosestDescendantMerkleValue =>
-					match self.query_storage_merkle_value(hash, &item.key, child_key.as_ref()) {
+					match self.client.query_merkle_value(hash, &item.key, child_key.as_ref()) {
 						Ok(Some(value)) => storage_results.push(value),
 						Ok(None) => continue,
 						Err(error) => {
@@ -0,0 +1,349 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Common events for RPC-V2 spec.
+
+use serde::{Deserialize, Serialize};
+
+/// The storage item received as paramter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageQuery<Key> {
+	/// The provided key.
+	pub key: Key,
+	/// The type of the storage query.
+	#[serde(rename = "type")]
+	pub query_type: StorageQueryType,
+}
+
+/// The type of the storage query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageQueryType {
+	/// Fetch the value of the provided key.
+	Value,
+	/// Fetch the hash of the value of the provided key.
+	Hash,
+	/// Fetch the closest descendant merkle value.
+	ClosestDescendantMerkleValue,
+	/// Fetch the values of all descendants of they provided key.
+	DescendantsValues,
+	/// Fetch the hashes of the values of all descendants of they provided key.
+	DescendantsHashes,
+}
+
+impl StorageQueryType {
+	/// Returns `true` if the query is a descendant query.
+	pub fn is_descendant_query(&self) -> bool {
+		matches!(self, Self::DescendantsValues | Self::DescendantsHashes)
+	}
+}
+
+/// The storage result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageResult {
+	/// The hex-encoded key of the result.
+	pub key: String,
+	/// The result of the query.
+	#[serde(flatten)]
+	pub result: StorageResultType,
+}
+
+/// The type of the storage query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageResultType {
+	/// Fetch the value of the provided key.
+	Value(String),
+	/// Fetch the hash of the value of the provided key.
+	Hash(String),
+	/// Fetch the closest descendant merkle value.
+	ClosestDescendantMerkleValue(String),
+}
+
+/// The paginated storage item received as parameter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginatedStorageQuery<Key> {
+	/// The provided key.
+	pub key: Key,
+	/// The type of the storage query.
+	#[serde(rename = "type")]
+	pub query_type: StorageQueryType,
+	/// The pagination key from which the iteration should resume.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[serde(default)]
+	pub pagination_start_key: Option<Key>,
+}
+
+/// The result of an archive storage call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ArchiveStorageResult {
+	/// Query generated a result.
+	Ok(ArchiveStorageMethodOk),
+	/// Query encountered an error.
+	Err(ArchiveStorageMethodErr),
+}
+
+impl ArchiveStorageResult {
+	/// Create a new `ArchiveStorageResult::Ok` result.
+	pub fn ok(result: Vec<StorageResult>, discarded_items: usize) -> Self {
+		Self::Ok(ArchiveStorageMethodOk { result, discarded_items })
+	}
+
+	/// Create a new `ArchiveStorageResult::Err` result.
+	pub fn err(error: String) -> Self {
+		Self::Err(ArchiveStorageMethodErr { error })
+	}
+}
+
+/// The result of an archive storage call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageMethodOk {
+	/// Reported results.
+	pub result: Vec<StorageResult>,
+	/// Number of discarded items.
+	pub discarded_items: usize,
+}
+
+/// The error of a storage call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageMethodErr {
+	/// Reported error.
+	pub error: String,
+}
+
+/// The storage item of the `archive_storageDiff` method received as parameter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageDiffItem<Key> {
+	/// The key prefix of the storage entries to compare.
+	pub key: Key,
+	/// The type of the result reported for every difference.
+	pub return_type: ArchiveStorageDiffType,
+	/// The child trie key in which to compare the entries.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[serde(default)]
+	pub child_trie_key: Option<Key>,
+	/// The pagination key after which the comparison should resume.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[serde(default)]
+	pub pagination_start_key: Option<Key>,
+}
+
+/// The type of the result reported by the `archive_storageDiff` method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ArchiveStorageDiffType {
+	/// Report the value of the storage entry.
+	Value,
+	/// Report the hash of the value of the storage entry.
+	Hash,
+}
+
+/// The type of change between two blocks for a storage entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ArchiveStorageDiffOperationType {
+	/// The storage entry was added.
+	Added,
+	/// The storage entry was modified.
+	Modified,
+	/// The storage entry was deleted.
+	Deleted,
+}
+
+/// A difference reported by the `archive_storageDiff` method.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageDiffEvent {
+	/// The hex-encoded key of the result.
+	pub key: String,
+	/// The value or the hash of the storage entry.
+	///
+	/// For deleted entries this is the value (or hash) from the previous block.
+	#[serde(flatten)]
+	pub result: StorageResultType,
+	/// The type of the change.
+	#[serde(rename = "type")]
+	pub operation_type: ArchiveStorageDiffOperationType,
+	/// The hex-encoded child trie key of the entry, if the entry belongs to a child trie.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[serde(default)]
+	pub child_trie_key: Option<String>,
+}
+
+/// The result of an archive storage diff call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ArchiveStorageDiffResult {
+	/// Query generated a result.
+	Ok(ArchiveStorageDiffMethodOk),
+	/// Query encountered an error.
+	Err(ArchiveStorageMethodErr),
+}
+
+impl ArchiveStorageDiffResult {
+	/// Create a new `ArchiveStorageDiffResult::Ok` result.
+	pub fn ok(result: Vec<ArchiveStorageDiffEvent>, discarded_items: usize) -> Self {
+		Self::Ok(ArchiveStorageDiffMethodOk { result, discarded_items })
+	}
+
+	/// Create a new `ArchiveStorageDiffResult::Err` result.
+	pub fn err(error: String) -> Self {
+		Self::Err(ArchiveStorageMethodErr { error })
+	}
+}
+
+/// The successful result of an archive storage diff call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageDiffMethodOk {
+	/// Reported differences.
+	pub result: Vec<ArchiveStorageDiffEvent>,
+	/// Number of discarded items.
+	pub discarded_items: usize,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn paginated_storage_query_serialize() {
+		let item = PaginatedStorageQuery {
+			key: "0x1",
+			query_type: StorageQueryType::Value,
+			pagination_start_key: None,
+		};
+		// Encode
+		let ser = serde_json::to_string(&item).unwrap();
+		let exp = r#"{"key":"0x1","type":"value"}"#;
+		assert_eq!(ser, exp);
+		// Decode
+		let dec: PaginatedStorageQuery<&str> = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, item);
+
+		let item = PaginatedStorageQuery {
+			key: "0x1",
+			query_type: StorageQueryType::DescendantsValues,
+			pagination_start_key: Some("0x2"),
+		};
+		// Encode
+		let ser = serde_json::to_string(&item).unwrap();
+		let exp = r#"{"key":"0x1","type":"descendantsValues","paginationStartKey":"0x2"}"#;
+		assert_eq!(ser, exp);
+		// Decode
+		let dec: PaginatedStorageQuery<&str> = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, item);
+	}
+
+	#[test]
+	fn archive_storage_result_serialize() {
+		let ok = ArchiveStorageResult::ok(
+			vec![StorageResult {
+				key: "0x1".into(),
+				result: StorageResultType::Value("0x2".into()),
+			}],
+			1,
+		);
+		// Encode
+		let ser = serde_json::to_string(&ok).unwrap();
+		let exp = r#"{"result":[{"key":"0x1","value":"0x2"}],"discardedItems":1}"#;
+		assert_eq!(ser, exp);
+		// Decode
+		let dec: ArchiveStorageResult = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, ok);
+
+		let err = ArchiveStorageResult::err("error".into());
+		// Encode
+		let ser = serde_json::to_string(&err).unwrap();
+		let exp = r#"{"error":"error"}"#;
+		assert_eq!(ser, exp);
+		// Decode
+		let dec: ArchiveStorageResult = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, err);
+	}
+
+	#[test]
+	fn archive_storage_diff_item_serialize() {
+		let item = ArchiveStorageDiffItem {
+			key: "0x1",
+			return_type: ArchiveStorageDiffType::Value,
+			child_trie_key: None,
+			pagination_start_key: None,
+		};
+		// Encode
+		let ser = serde_json::to_string(&item).unwrap();
+		let exp = r#"{"key":"0x1","returnType":"value"}"#;
+		assert_eq!(ser, exp);
+		// Decode
+		let dec: ArchiveStorageDiffItem<&str> = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, item);
+
+		let item = ArchiveStorageDiffItem {
+			key: "0x1",
+			return_type: ArchiveStorageDiffType::Hash,
+			child_trie_key: Some("0x2"),
+			pagination_start_key: Some("0x13"),
+		};
+		// Encode
+		let ser = serde_json::to_string(&item).unwrap();
+		let exp =
+			r#"{"key":"0x1","returnType":"hash","childTrieKey":"0x2","paginationStartKey":"0x13"}"#;
+		assert_eq!(ser, exp);
+		// Decode
+		let dec: ArchiveStorageDiffItem<&str> = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, item);
+	}
+
+	#[test]
+	fn archive_storage_diff_event_serialize() {
+		let event = ArchiveStorageDiffEvent {
+			key: "0x1".into(),
+			result: StorageResultType::Value("0x2".into()),
+			operation_type: ArchiveStorageDiffOperationType::Added,
+			child_trie_key: None,
+		};
+		// Encode
+		let ser = serde_json::to_string(&event).unwrap();
+		let exp = r#"{"key":"0x1","value":"0x2","type":"added"}"#;
+		assert_eq!(ser, exp);
+		// Decode
+		let dec: ArchiveStorageDiffEvent = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, event);
+
+		let event = ArchiveStorageDiffEvent {
+			key: "0x1".into(),
+			result: StorageResultType::Hash("0x2".into()),
+			operation_type: ArchiveStorageDiffOperationType::Deleted,
+			child_trie_key: Some("0x3".into()),
+		};
+		// Encode
+		let ser = serde_json::to_string(&event).unwrap();
+		let exp = r#"{"key":"0x1","hash":"0x2","type":"deleted","childTrieKey":"0x3"}"#;
+		assert_eq!(ser, exp);
+		// Decode
+		let dec: ArchiveStorageDiffEvent = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, event);
+	}
+}
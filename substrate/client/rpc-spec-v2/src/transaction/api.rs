@@ -19,7 +19,7 @@
 //! API trait for transactions.
 
 use crate::transaction::event::TransactionEvent;
-use jsonrpsee::proc_macros::rpc;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use sp_core::Bytes;
 
 #[rpc(client, server)]
@@ -35,3 +35,29 @@ pub trait TransactionApi<Hash: Clone> {
 	)]
 	fn submit_and_watch(&self, bytes: Bytes);
 }
+
+#[rpc(client, server)]
+pub trait TransactionBroadcastApi {
+	/// Broadcast an extrinsic to the chain.
+	///
+	/// The extrinsic is submitted to the transaction pool and broadcasted to the peers
+	/// until it is finalized or the operation is stopped.
+	///
+	/// Returns the operation ID of the broadcast, or `null` if the server cannot accept
+	/// more broadcast operations at the moment.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "transaction_unstable_broadcast")]
+	fn broadcast(&self, bytes: Bytes) -> RpcResult<Option<String>>;
+
+	/// Stop broadcasting an extrinsic previously provided to
+	/// the `transaction_unstable_broadcast` method.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "transaction_unstable_stop")]
+	fn stop_broadcast(&self, operation_id: String) -> RpcResult<()>;
+}
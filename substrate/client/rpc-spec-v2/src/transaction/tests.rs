@@ -0,0 +1,238 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::chain_head::hex_string;
+use assert_matches::assert_matches;
+use codec::Encode;
+use futures::future::{BoxFuture, FutureExt};
+use jsonrpsee::{core::error::Error, rpc_params, types::error::CallError, RpcModule};
+use sc_block_builder::BlockBuilderBuilder;
+use sc_transaction_pool::{BasicPool, FullChainApi};
+use sc_transaction_pool_api::{ChainEvent, MaintainedTransactionPool, TransactionPool};
+use sp_consensus::BlockOrigin;
+use sp_core::{testing::TaskExecutor, traits::SpawnNamed};
+use sp_runtime::traits::Header as HeaderT;
+use std::{
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
+use substrate_test_runtime_client::{
+	prelude::*,
+	runtime::{Block, Extrinsic, ExtrinsicBuilder, Transfer},
+	Backend, Client, ClientBlockImportExt,
+};
+
+type FullTransactionPool = BasicPool<FullChainApi<Client<Backend>, Block>, Block>;
+
+/// Executor that keeps track of the number of spawned tasks still running.
+#[derive(Clone)]
+struct TrackingTaskExecutor {
+	executor: TaskExecutor,
+	running: Arc<AtomicUsize>,
+}
+
+impl TrackingTaskExecutor {
+	fn new() -> Self {
+		Self { executor: TaskExecutor::new(), running: Default::default() }
+	}
+
+	fn running(&self) -> usize {
+		self.running.load(Ordering::SeqCst)
+	}
+
+	fn track(&self, future: BoxFuture<'static, ()>) -> BoxFuture<'static, ()> {
+		let running = self.running.clone();
+		running.fetch_add(1, Ordering::SeqCst);
+		async move {
+			future.await;
+			running.fetch_sub(1, Ordering::SeqCst);
+		}
+		.boxed()
+	}
+}
+
+impl SpawnNamed for TrackingTaskExecutor {
+	fn spawn_blocking(
+		&self,
+		name: &'static str,
+		group: Option<&'static str>,
+		future: BoxFuture<'static, ()>,
+	) {
+		self.executor.spawn_blocking(name, group, self.track(future));
+	}
+
+	fn spawn(
+		&self,
+		name: &'static str,
+		group: Option<&'static str>,
+		future: BoxFuture<'static, ()>,
+	) {
+		self.executor.spawn(name, group, self.track(future));
+	}
+}
+
+fn uxt(sender: AccountKeyring, nonce: u64) -> Extrinsic {
+	let tx = Transfer {
+		amount: Default::default(),
+		nonce,
+		from: sender.into(),
+		to: AccountKeyring::Bob.into(),
+	};
+	ExtrinsicBuilder::new_transfer(tx).build()
+}
+
+fn setup_api() -> (
+	Arc<Client<Backend>>,
+	Arc<FullTransactionPool>,
+	TrackingTaskExecutor,
+	RpcModule<TransactionBroadcast<FullTransactionPool, Client<Backend>>>,
+) {
+	let client = Arc::new(TestClientBuilder::new().build());
+	let pool = BasicPool::new_full(
+		Default::default(),
+		true.into(),
+		None,
+		TaskExecutor::new(),
+		client.clone(),
+	);
+
+	let executor = TrackingTaskExecutor::new();
+	let api = TransactionBroadcast::new(client.clone(), pool.clone(), Arc::new(executor.clone()))
+		.into_rpc();
+
+	(client, pool, executor, api)
+}
+
+/// Wait until the provided condition holds.
+async fn wait_until(condition: impl Fn() -> bool) {
+	tokio::time::timeout(Duration::from_secs(10), async {
+		while !condition() {
+			tokio::time::sleep(Duration::from_millis(10)).await;
+		}
+	})
+	.await
+	.expect("Condition not met in time");
+}
+
+#[tokio::test]
+async fn tx_broadcast_enters_pool() {
+	let (_client, pool, executor, api) = setup_api();
+
+	let xt = hex_string(&uxt(AccountKeyring::Alice, 0).encode());
+	let operation_id: Option<String> =
+		api.call("transaction_unstable_broadcast", rpc_params![&xt]).await.unwrap();
+	assert!(operation_id.is_some());
+
+	// The transaction is submitted to the pool and the broadcast keeps running.
+	wait_until(|| pool.status().ready == 1).await;
+	assert_eq!(executor.running(), 1);
+}
+
+#[tokio::test]
+async fn tx_broadcast_stop() {
+	let (_client, pool, executor, api) = setup_api();
+
+	let xt = hex_string(&uxt(AccountKeyring::Alice, 0).encode());
+	let operation_id: String = api
+		.call::<_, Option<String>>("transaction_unstable_broadcast", rpc_params![&xt])
+		.await
+		.unwrap()
+		.unwrap();
+	wait_until(|| pool.status().ready == 1).await;
+
+	// Stop the broadcast.
+	let _: () = api.call("transaction_unstable_stop", rpc_params![&operation_id]).await.unwrap();
+	wait_until(|| executor.running() == 0).await;
+
+	// The operation is no longer valid.
+	let err = api
+		.call::<_, serde_json::Value>("transaction_unstable_stop", rpc_params![&operation_id])
+		.await
+		.unwrap_err();
+	assert_matches!(
+		err,
+		Error::Call(CallError::Custom(ref err)) if err.code() == super::error::json_rpc_spec::INVALID_PARAM_ERROR && err.message().contains("Invalid operation id")
+	);
+}
+
+#[tokio::test]
+async fn tx_broadcast_invalid_operation_id() {
+	let (_client, _pool, _executor, api) = setup_api();
+
+	let err = api
+		.call::<_, serde_json::Value>("transaction_unstable_stop", rpc_params!["invalid"])
+		.await
+		.unwrap_err();
+	assert_matches!(
+		err,
+		Error::Call(CallError::Custom(ref err)) if err.code() == super::error::json_rpc_spec::INVALID_PARAM_ERROR && err.message().contains("Invalid operation id")
+	);
+}
+
+#[tokio::test]
+async fn tx_broadcast_stops_after_finalization() {
+	let (mut client, pool, executor, api) = setup_api();
+
+	let uxt = uxt(AccountKeyring::Alice, 0);
+	let xt = hex_string(&uxt.encode());
+	let operation_id: Option<String> =
+		api.call("transaction_unstable_broadcast", rpc_params![&xt]).await.unwrap();
+	assert!(operation_id.is_some());
+	wait_until(|| pool.status().ready == 1).await;
+
+	// Include the transaction in a block.
+	let mut block_builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	block_builder.push(uxt).unwrap();
+	let block = block_builder.build().unwrap().block;
+	let block_hash = block.header.hash();
+	client.import(BlockOrigin::Own, block).await.unwrap();
+	pool.maintain(ChainEvent::NewBestBlock { hash: block_hash, tree_route: None })
+		.await;
+	assert_eq!(pool.status().ready, 0);
+
+	// The broadcast is still running until the block is finalized.
+	assert_eq!(executor.running(), 1);
+
+	client.finalize_block(block_hash, None).unwrap();
+	pool.maintain(ChainEvent::Finalized { hash: block_hash, tree_route: Arc::from(vec![]) })
+		.await;
+
+	// The broadcast terminates after finalization.
+	wait_until(|| executor.running() == 0).await;
+}
+
+#[tokio::test]
+async fn tx_broadcast_invalid_format() {
+	let (_client, pool, executor, api) = setup_api();
+
+	// The operation ID is provided even if the transaction cannot be decoded.
+	let operation_id: Option<String> =
+		api.call("transaction_unstable_broadcast", rpc_params!["0x00"]).await.unwrap();
+	assert!(operation_id.is_some());
+
+	assert_eq!(pool.status().ready, 0);
+	assert_eq!(executor.running(), 0);
+}
@@ -73,7 +73,7 @@ impl<Pool, Client> Transaction<Pool, Client> {
 /// Possibly in the future we could allow opt-in for special treatment
 /// of such transactions, so that the block authors can inject
 /// some unique transactions via RPC and have them included in the pool.
-const TX_SOURCE: TransactionSource = TransactionSource::External;
+pub(crate) const TX_SOURCE: TransactionSource = TransactionSource::External;
 
 /// Extrinsic has an invalid format.
 ///
@@ -0,0 +1,216 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! API implementation for broadcasting transactions.
+
+use crate::{
+	transaction::{
+		api::TransactionBroadcastApiServer, error::ErrorBroadcast, transaction::TX_SOURCE,
+	},
+	SubscriptionTaskExecutor,
+};
+use codec::Decode;
+use futures::{future::AbortHandle, FutureExt, Stream, StreamExt};
+use jsonrpsee::{
+	core::{async_trait, RpcResult},
+	server::{IdProvider, RandomStringIdProvider},
+	types::SubscriptionId,
+};
+use log::debug;
+use parking_lot::RwLock;
+use sc_client_api::BlockchainEvents;
+use sc_transaction_pool_api::{TransactionFor, TransactionPool};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::traits::Block as BlockT;
+use std::{collections::HashMap, sync::Arc};
+
+/// Log target of the transaction broadcast.
+const LOG_TARGET: &str = "rpc-spec-v2";
+
+/// The maximum number of ongoing broadcast operations.
+const MAX_ONGOING_BROADCASTS: usize = 512;
+
+/// The length of the generated operation IDs.
+const OPERATION_ID_LENGTH: usize = 16;
+
+/// An API for transaction RPC calls.
+pub struct TransactionBroadcast<Pool, Client> {
+	/// Substrate client.
+	client: Arc<Client>,
+	/// Transactions pool.
+	pool: Arc<Pool>,
+	/// Executor to spawn subscriptions.
+	executor: SubscriptionTaskExecutor,
+	/// The broadcast operation IDs.
+	broadcast_ids: Arc<RwLock<HashMap<String, BroadcastState>>>,
+}
+
+/// The state of a broadcast operation.
+struct BroadcastState {
+	/// Handle to abort the running future that broadcasts the transaction.
+	handle: AbortHandle,
+}
+
+impl<Pool, Client> TransactionBroadcast<Pool, Client> {
+	/// Creates a new [`TransactionBroadcast`].
+	pub fn new(client: Arc<Client>, pool: Arc<Pool>, executor: SubscriptionTaskExecutor) -> Self {
+		TransactionBroadcast { client, pool, executor, broadcast_ids: Default::default() }
+	}
+
+	/// Generate a unique operation ID for the `transaction_broadcast` RPC method.
+	fn generate_unique_id(&self) -> String {
+		let provider = RandomStringIdProvider::new(OPERATION_ID_LENGTH);
+
+		loop {
+			let id = match provider.next_id() {
+				SubscriptionId::Num(id) => id.to_string(),
+				SubscriptionId::Str(id) => id.into_owned(),
+			};
+
+			if !self.broadcast_ids.read().contains_key(&id) {
+				break id
+			}
+		}
+	}
+}
+
+#[async_trait]
+impl<Pool, Client> TransactionBroadcastApiServer for TransactionBroadcast<Pool, Client>
+where
+	Pool: TransactionPool + Sync + Send + 'static,
+	Client: HeaderBackend<Pool::Block> + BlockchainEvents<Pool::Block> + Send + Sync + 'static,
+{
+	fn broadcast(&self, bytes: Bytes) -> RpcResult<Option<String>> {
+		if self.broadcast_ids.read().len() >= MAX_ONGOING_BROADCASTS {
+			return Ok(None)
+		}
+
+		let id = self.generate_unique_id();
+
+		// The operation ID is returned even if the transaction cannot be decoded,
+		// in which case there is nothing to broadcast.
+		let decoded_extrinsic = match TransactionFor::<Pool>::decode(&mut &bytes[..]) {
+			Ok(decoded_extrinsic) => decoded_extrinsic,
+			Err(e) => {
+				debug!(target: LOG_TARGET, "Extrinsic of broadcast {id} has invalid format: {e}");
+				return Ok(Some(id))
+			},
+		};
+
+		let mut best_block_import_stream =
+			Box::pin(self.client.import_notification_stream().filter_map(
+				|notification| async move { notification.is_new_best.then_some(notification.hash) },
+			));
+
+		let pool = self.pool.clone();
+		let mut best_block_hash = self.client.info().best_hash;
+		let broadcast_id = id.clone();
+
+		let fut = async move {
+			loop {
+				match pool
+					.submit_and_watch(best_block_hash, TX_SOURCE, decoded_extrinsic.clone())
+					.await
+				{
+					Ok(mut stream) => {
+						// Flag to determine if we should broadcast the transaction again.
+						let mut is_retriable = false;
+
+						while let Some(event) = stream.next().await {
+							// Check if the transaction could be submitted again
+							// at a later time.
+							if event.is_retriable() {
+								is_retriable = true;
+								break
+							}
+
+							// Stop if this is the final event of the transaction stream
+							// and the event is not retriable.
+							if event.is_final() {
+								break
+							}
+						}
+
+						if !is_retriable {
+							return
+						}
+					},
+					Err(error) => {
+						debug!(
+							target: LOG_TARGET,
+							"Broadcast {broadcast_id} failed to submit the extrinsic: {error:?}"
+						);
+					},
+				}
+
+				// Try again once a new best block is imported.
+				match last_stream_element(&mut best_block_import_stream).await {
+					Some(hash) => best_block_hash = hash,
+					None => return,
+				}
+			}
+		};
+
+		// Convert the future into an abortable future, for easily terminating it from the
+		// `transaction_stop` method.
+		let (fut, handle) = futures::future::abortable(fut);
+		let broadcast_ids = self.broadcast_ids.clone();
+		let drop_id = id.clone();
+		// The future expected by the executor must be `Future<Output = ()>` instead of
+		// `Future<Output = Result<(), Aborted>>`.
+		let fut = fut.map(move |_| {
+			// Remove the entry from the broadcast IDs map.
+			broadcast_ids.write().remove(&drop_id);
+		});
+
+		// Keep track of this entry and the abortable handle.
+		self.broadcast_ids.write().insert(id.clone(), BroadcastState { handle });
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+
+		Ok(Some(id))
+	}
+
+	fn stop_broadcast(&self, operation_id: String) -> RpcResult<()> {
+		let Some(broadcast_state) = self.broadcast_ids.write().remove(&operation_id) else {
+			return Err(ErrorBroadcast::InvalidOperationID.into())
+		};
+
+		broadcast_state.handle.abort();
+
+		Ok(())
+	}
+}
+
+/// Returns the last element of the provided stream, or `None` if the stream is closed.
+///
+/// Waits for the stream to produce an element if none is available yet.
+async fn last_stream_element<S>(stream: &mut S) -> Option<S::Item>
+where
+	S: Stream + Unpin,
+{
+	let mut element = stream.next().await?;
+
+	// Drain the elements that are immediately available.
+	while let Some(Some(next)) = stream.next().now_or_never() {
+		element = next;
+	}
+
+	Some(element)
+}
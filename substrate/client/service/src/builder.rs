@@ -31,7 +31,7 @@ use log::info;
 use prometheus_endpoint::Registry;
 use sc_chain_spec::get_extension;
 use sc_client_api::{
-	execution_extensions::ExecutionExtensions, proof_provider::ProofProvider, BadBlocks,
+	execution_extensions::ExecutionExtensions, proof_provider::ProofProvider, AuxStore, BadBlocks,
 	BlockBackend, BlockchainEvents, ExecutorProvider, ForkBlocks, StorageProvider, UsageProvider,
 };
 use sc_client_db::{Backend, DatabaseSettings};
@@ -63,7 +63,10 @@ use sc_rpc::{
 	system::SystemApiServer,
 	DenyUnsafe, SubscriptionTaskExecutor,
 };
-use sc_rpc_spec_v2::{chain_head::ChainHeadApiServer, transaction::TransactionApiServer};
+use sc_rpc_spec_v2::{
+	chain_head::ChainHeadApiServer,
+	transaction::{TransactionApiServer, TransactionBroadcastApiServer},
+};
 use sc_telemetry::{telemetry, ConnectionMessage, Telemetry, TelemetryHandle, SUBSTRATE_INFO};
 use sc_transaction_pool_api::{MaintainedTransactionPool, TransactionPool};
 use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
@@ -222,7 +225,7 @@ where
 				wasm_runtime_overrides: config.wasm_runtime_overrides.clone(),
 				no_genesis: matches!(
 					config.network.sync_mode,
-					SyncMode::LightState { .. } | SyncMode::Warp { .. }
+					SyncMode::LightState { .. } | SyncMode::Warp { .. } | SyncMode::Checkpoint(_)
 				),
 				wasm_runtime_substitutes,
 			},
@@ -633,6 +636,13 @@ where
 	)
 	.into_rpc();
 
+	let transaction_broadcast_v2 = sc_rpc_spec_v2::transaction::TransactionBroadcast::new(
+		client.clone(),
+		transaction_pool.clone(),
+		task_executor.clone(),
+	)
+	.into_rpc();
+
 	let chain_head_v2 = sc_rpc_spec_v2::chain_head::ChainHead::new(
 		client.clone(),
 		backend.clone(),
@@ -661,6 +671,9 @@ where
 
 	// Part of the RPC v2 spec.
 	rpc_api.merge(transaction_v2).map_err(|e| Error::Application(e.into()))?;
+	rpc_api
+		.merge(transaction_broadcast_v2)
+		.map_err(|e| Error::Application(e.into()))?;
 	rpc_api.merge(chain_head_v2).map_err(|e| Error::Application(e.into()))?;
 
 	// Part of the old RPC spec.
@@ -721,6 +734,7 @@ where
 		+ BlockBackend<TBl>
 		+ BlockIdTo<TBl, Error = sp_blockchain::Error>
 		+ ProofProvider<TBl>
+		+ AuxStore
 		+ HeaderBackend<TBl>
 		+ BlockchainEvents<TBl>
 		+ 'static,
@@ -748,6 +762,8 @@ where
 			SyncMode::LightState { .. } =>
 				return Err("Fast sync doesn't work for archive nodes".into()),
 			SyncMode::Warp => return Err("Warp sync doesn't work for archive nodes".into()),
+			SyncMode::Checkpoint(_) =>
+				return Err("Checkpoint sync doesn't work for archive nodes".into()),
 			SyncMode::Full => {},
 		}
 	}
@@ -857,15 +873,18 @@ where
 	);
 	net_config.add_notification_protocol(transactions_handler_proto.set_config());
 
-	// Create `PeerStore` and initialize it with bootnode peer ids.
-	let peer_store = PeerStore::new(
-		net_config
-			.network_config
-			.boot_nodes
-			.iter()
-			.map(|bootnode| bootnode.peer_id)
-			.collect(),
-	);
+	// Create `PeerStore` and initialize it with bootnode peer ids, restoring the peers known
+	// from the previous run if the network configuration is stored on disk.
+	let bootnodes = net_config
+		.network_config
+		.boot_nodes
+		.iter()
+		.map(|bootnode| bootnode.peer_id)
+		.collect();
+	let peer_store = match &net_config.network_config.net_config_path {
+		Some(path) => PeerStore::new_persistent(bootnodes, path.join("peer_store.json")),
+		None => PeerStore::new(bootnodes),
+	};
 	let peer_store_handle = peer_store.handle();
 	spawn_handle.spawn("peer-store", Some("networking"), peer_store.run());
 
@@ -0,0 +1,403 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Binary state snapshots.
+//!
+//! A snapshot contains the header and justifications of a block together with all key value
+//! pairs of its state, including child tries. It is a SCALE encoded stream of:
+//!
+//! - the [`MAGIC`] bytes and the format version,
+//! - a [`SnapshotHeader`],
+//! - a sequence of `Some(Chunk)` terminated by `None`, where every [`Chunk`] holds sorted key value
+//!   pairs of either the top trie or a single child trie.
+//!
+//! The chunks are decoded directly into the state handed to the import queue, which verifies it
+//! against the state root of the header, the same way as the target state of a state sync. The
+//! whole state is held in memory during the import.
+//!
+//! The state root is recomputed with the single state version of the runtime. A state that mixes
+//! entries of both state versions, like a state whose trie migration has not completed, can not be
+//! verified from its key value pairs and is rejected by the import.
+
+use crate::error::Error;
+use codec::{Decode, Encode, IoReader as CodecIoReader};
+use futures::future;
+use log::info;
+use sc_client_api::{BlockBackend, HeaderBackend, StorageProvider};
+use sc_consensus::{
+	import_queue::{BlockImportError, BlockImportStatus, ImportQueue, IncomingBlock, Link},
+	ImportedState,
+};
+use sp_api::CallApiAt;
+use sp_consensus::BlockOrigin;
+use sp_core::storage::{well_known_keys, ChildInfo, StateVersion};
+use sp_runtime::{
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, Zero},
+	Justifications,
+};
+use sp_state_machine::{KeyValueStates, KeyValueStorageLevel};
+use std::{
+	collections::BTreeMap,
+	io::{Read, Write},
+	sync::Arc,
+	task::Poll,
+};
+
+/// Magic bytes at the start of every state snapshot.
+const MAGIC: [u8; 8] = *b"substate";
+
+/// Version of the snapshot format.
+const FORMAT_VERSION: u32 = 1;
+
+/// Maximum number of key value pairs per chunk.
+const CHUNK_SIZE: usize = 4096;
+
+type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// The block a snapshot was taken at.
+#[derive(Encode, Decode)]
+struct SnapshotHeader<B: BlockT> {
+	header: B::Header,
+	justifications: Option<Justifications>,
+	state_version: StateVersion,
+}
+
+/// Key value pairs of the top trie, or of the child trie with the given unprefixed storage key.
+#[derive(Encode, Decode)]
+struct Chunk {
+	child: Option<Vec<u8>>,
+	key_values: KeyValues,
+}
+
+/// A decoded state snapshot.
+struct Snapshot<B: BlockT> {
+	header: B::Header,
+	justifications: Option<Justifications>,
+	state_version: StateVersion,
+	state: KeyValueStates,
+}
+
+/// Buffers key value pairs and writes them as chunks.
+struct ChunkWriter<W> {
+	output: W,
+	child: Option<Vec<u8>>,
+	key_values: KeyValues,
+	pairs: u64,
+}
+
+impl<W: Write> ChunkWriter<W> {
+	fn push(&mut self, key: Vec<u8>, value: Vec<u8>) -> Result<(), Error> {
+		self.key_values.push((key, value));
+		self.pairs += 1;
+		if self.key_values.len() >= CHUNK_SIZE {
+			self.flush()?;
+		}
+		Ok(())
+	}
+
+	/// Write the buffered pairs and continue with the child trie `child`.
+	fn set_child(&mut self, child: Option<Vec<u8>>) -> Result<(), Error> {
+		self.flush()?;
+		self.child = child;
+		Ok(())
+	}
+
+	fn flush(&mut self) -> Result<(), Error> {
+		if self.key_values.is_empty() {
+			return Ok(())
+		}
+		let chunk =
+			Chunk { child: self.child.clone(), key_values: std::mem::take(&mut self.key_values) };
+		self.output.write_all(&Some(chunk).encode())?;
+		Ok(())
+	}
+
+	fn finish(mut self) -> Result<u64, Error> {
+		self.flush()?;
+		self.output.write_all(&None::<Chunk>.encode())?;
+		self.output.flush()?;
+		Ok(self.pairs)
+	}
+}
+
+/// Export the header, justifications and state of the block `hash` as a state snapshot.
+pub fn export_state_snapshot<B, BA, C>(
+	client: Arc<C>,
+	hash: B::Hash,
+	mut output: impl Write,
+) -> Result<(), Error>
+where
+	B: BlockT,
+	BA: sc_client_api::backend::Backend<B>,
+	C: HeaderBackend<B> + BlockBackend<B> + StorageProvider<B, BA> + CallApiAt<B>,
+{
+	let header = client
+		.header(hash)?
+		.ok_or_else(|| Error::Other(format!("Unknown block {:?}", hash)))?;
+	let state_version = client
+		.runtime_version_at(hash)
+		.map_err(|e| Error::Other(format!("Failed to get the runtime version: {}", e)))?
+		.state_version();
+	info!("Exporting state snapshot of block #{} ({:?})", header.number(), hash);
+
+	output.write_all(&MAGIC)?;
+	output.write_all(&FORMAT_VERSION.encode())?;
+	let justifications = client.justifications(hash)?;
+	output.write_all(&SnapshotHeader::<B> { header, justifications, state_version }.encode())?;
+
+	let mut writer = ChunkWriter { output, child: None, key_values: Vec::new(), pairs: 0 };
+	let mut children = Vec::new();
+	for (key, value) in client.storage_pairs(hash, None, None)? {
+		// Child roots are not exported, they are recomputed from the child tries.
+		match key.0.strip_prefix(well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX) {
+			Some(child) => children.push(child.to_vec()),
+			None => writer.push(key.0, value.0)?,
+		}
+	}
+	for child in children {
+		let child_info = ChildInfo::new_default(&child);
+		writer.set_child(Some(child))?;
+		for key in client.child_storage_keys(hash, child_info.clone(), None, None)? {
+			if let Some(value) = client.child_storage(hash, &child_info, &key)? {
+				writer.push(key.0, value.0)?;
+			}
+		}
+	}
+	let pairs = writer.finish()?;
+	info!("🎉 Exported {} key value pairs", pairs);
+	Ok(())
+}
+
+/// Read a state snapshot.
+///
+/// The state is not verified, the import checks it against the state root of the header.
+fn read_snapshot<B: BlockT>(input: impl Read) -> Result<Snapshot<B>, Error> {
+	let mut input = CodecIoReader(input);
+	let decode_error = |e: codec::Error| Error::Other(format!("Invalid state snapshot: {}", e));
+
+	if <[u8; 8]>::decode(&mut input).map_err(decode_error)? != MAGIC {
+		return Err(Error::Other("Not a state snapshot".into()))
+	}
+	let version = u32::decode(&mut input).map_err(decode_error)?;
+	if version != FORMAT_VERSION {
+		return Err(Error::Other(format!("Unsupported state snapshot version {}", version)))
+	}
+	let SnapshotHeader::<B> { header, justifications, state_version } =
+		Decode::decode(&mut input).map_err(decode_error)?;
+
+	// The first level holds the top trie, every child trie gets its own level.
+	let mut levels = vec![KeyValueStorageLevel {
+		state_root: Vec::new(),
+		parent_storage_keys: Vec::new(),
+		key_values: KeyValues::new(),
+	}];
+	let mut child_levels = BTreeMap::<Vec<u8>, usize>::new();
+	while let Some(Chunk { child, key_values }) =
+		Option::<Chunk>::decode(&mut input).map_err(decode_error)?
+	{
+		let level = match child {
+			Some(child) => *child_levels.entry(child).or_insert_with_key(|child| {
+				levels.push(KeyValueStorageLevel {
+					// The child roots are recomputed by the import.
+					state_root: Vec::new(),
+					parent_storage_keys: vec![ChildInfo::new_default(child)
+						.prefixed_storage_key()
+						.into_inner()],
+					key_values: KeyValues::new(),
+				});
+				levels.len() - 1
+			}),
+			None => {
+				if key_values.iter().any(|(key, _)| well_known_keys::is_child_storage_key(key)) {
+					return Err(Error::Other(
+						"Invalid state snapshot: child root in top trie".into(),
+					))
+				}
+				0
+			},
+		};
+		levels[level].key_values.extend(key_values);
+	}
+
+	Ok(Snapshot { header, justifications, state_version, state: KeyValueStates(levels) })
+}
+
+/// Import a state snapshot into an empty database.
+///
+/// The block of the snapshot is imported with its state through the import queue, the same way
+/// as the target block of a state or warp sync.
+pub async fn import_state_snapshot<B, IQ, C>(
+	client: Arc<C>,
+	mut import_queue: IQ,
+	input: impl Read,
+) -> Result<(), Error>
+where
+	B: BlockT,
+	C: HeaderBackend<B>,
+	IQ: ImportQueue<B>,
+{
+	#[derive(Default)]
+	struct WaitLink {
+		result: Option<Result<(), String>>,
+	}
+
+	impl<B: BlockT> Link<B> for WaitLink {
+		fn blocks_processed(
+			&mut self,
+			_imported: usize,
+			_num_expected_blocks: usize,
+			results: Vec<(Result<BlockImportStatus<NumberFor<B>>, BlockImportError>, B::Hash)>,
+		) {
+			for (result, hash) in results {
+				self.result = Some(result.map(|_| ()).map_err(|e| {
+					format!(
+						"Failed to import block {:?}: {}. States mixing entries of both state \
+						 versions can not be imported from a snapshot.",
+						hash, e,
+					)
+				}));
+			}
+		}
+	}
+
+	if !client.info().best_number.is_zero() {
+		return Err(Error::Other(
+			"State snapshots can only be imported into an empty database".into(),
+		))
+	}
+
+	let Snapshot { header, justifications, state_version, state } = read_snapshot::<B>(input)?;
+	let hash = header.hash();
+	info!(
+		"Importing state snapshot of block #{} ({:?}), state version {:?}",
+		header.number(),
+		hash,
+		state_version,
+	);
+	import_queue.service_ref().import_blocks(
+		BlockOrigin::File,
+		vec![IncomingBlock {
+			hash,
+			header: Some(header),
+			body: None,
+			indexed_body: None,
+			justifications,
+			origin: None,
+			allow_missing_state: true,
+			import_existing: true,
+			skip_execution: true,
+			state: Some(ImportedState { block: hash, state }),
+		}],
+	);
+
+	let mut link = WaitLink::default();
+	future::poll_fn(|cx| {
+		import_queue.poll_actions(cx, &mut link);
+		match link.result.take() {
+			Some(result) => Poll::Ready(result),
+			None => Poll::Pending,
+		}
+	})
+	.await
+	.map_err(Error::Other)?;
+
+	info!("🎉 Imported state snapshot. Best: #{}", client.info().best_number);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use substrate_test_runtime_client::{prelude::*, runtime::Block};
+
+	fn export(child_info: &ChildInfo) -> (Vec<u8>, <Block as BlockT>::Hash) {
+		let client = TestClientBuilder::new()
+			.add_extra_storage(b":mock".to_vec(), b"value".to_vec())
+			.add_extra_child_storage(child_info, b"key".to_vec(), b"child value".to_vec())
+			.build();
+		let genesis_hash = client.info().genesis_hash;
+		let mut snapshot = Vec::new();
+		export_state_snapshot(Arc::new(client), genesis_hash, &mut snapshot).unwrap();
+		(snapshot, genesis_hash)
+	}
+
+	#[test]
+	fn exported_snapshot_is_read_back() {
+		let child_info = ChildInfo::new_default(b"child");
+		let (snapshot, genesis_hash) = export(&child_info);
+
+		let Snapshot { header, state, .. } = read_snapshot::<Block>(&snapshot[..]).unwrap();
+		assert_eq!(header.hash(), genesis_hash);
+		let top = &state.0[0];
+		assert!(top.key_values.iter().any(|(k, v)| k == b":mock" && v == b"value"));
+		assert!(top.key_values.iter().any(|(k, _)| k == well_known_keys::CODE));
+		assert_eq!(state.0.len(), 2);
+		let child = &state.0[1];
+		assert_eq!(child.parent_storage_keys, vec![child_info.prefixed_storage_key().into_inner()]);
+		assert_eq!(child.key_values, vec![(b"key".to_vec(), b"child value".to_vec())]);
+	}
+
+	#[test]
+	fn split_child_trie_is_read_into_one_level() {
+		let child_info = ChildInfo::new_default(b"child");
+		let (mut snapshot, _) = export(&child_info);
+
+		// Replace the terminating `None` with an additional chunk of the child trie.
+		assert_eq!(snapshot.pop(), Some(0));
+		let chunk = Chunk {
+			child: Some(b"child".to_vec()),
+			key_values: vec![(b"other".to_vec(), b"value".to_vec())],
+		};
+		snapshot.extend(Some(chunk).encode());
+		snapshot.extend(None::<Chunk>.encode());
+
+		let Snapshot { state, .. } = read_snapshot::<Block>(&snapshot[..]).unwrap();
+		assert_eq!(state.0.len(), 2);
+		assert_eq!(
+			state.0[1].key_values,
+			vec![
+				(b"key".to_vec(), b"child value".to_vec()),
+				(b"other".to_vec(), b"value".to_vec())
+			]
+		);
+	}
+
+	#[test]
+	fn child_root_in_top_trie_is_rejected() {
+		let child_info = ChildInfo::new_default(b"child");
+		let (mut snapshot, _) = export(&child_info);
+
+		assert_eq!(snapshot.pop(), Some(0));
+		let chunk = Chunk {
+			child: None,
+			key_values: vec![(child_info.prefixed_storage_key().into_inner(), vec![0; 32])],
+		};
+		snapshot.extend(Some(chunk).encode());
+		snapshot.extend(None::<Chunk>.encode());
+
+		let error = read_snapshot::<Block>(&snapshot[..]).err().unwrap();
+		assert!(error.to_string().contains("child root in top trie"));
+	}
+
+	#[test]
+	fn truncated_snapshot_is_rejected() {
+		let (snapshot, _) = export(&ChildInfo::new_default(b"child"));
+		assert!(read_snapshot::<Block>(&snapshot[..snapshot.len() - 1]).is_err());
+		assert!(read_snapshot::<Block>(&b"not a snapshot"[..]).is_err());
+	}
+}
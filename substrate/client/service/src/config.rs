@@ -34,7 +34,9 @@ pub use sc_network::{
 use prometheus_endpoint::Registry;
 use sc_chain_spec::ChainSpec;
 pub use sc_telemetry::TelemetryEndpoints;
-pub use sc_transaction_pool::Options as TransactionPoolOptions;
+pub use sc_transaction_pool::{
+	Options as TransactionPoolOptions, PoolSenderLimit as TransactionPoolSenderLimit,
+};
 use sp_core::crypto::SecretString;
 use std::{
 	io, iter,
@@ -68,6 +70,8 @@ pub struct Configuration {
 	pub trie_cache_maximum_size: Option<usize>,
 	/// State pruning settings.
 	pub state_pruning: Option<PruningMode>,
+	/// Retain the state of every block with a number divisible by this interval.
+	pub state_checkpoint_interval: Option<u32>,
 	/// Number of blocks to keep in the db.
 	///
 	/// NOTE: only finalized blocks are subject for removal!
@@ -231,7 +235,10 @@ impl Configuration {
 	/// Returns true if the genesis state writting will be skipped while initializing the genesis
 	/// block.
 	pub fn no_genesis(&self) -> bool {
-		matches!(self.network.sync_mode, SyncMode::LightState { .. } | SyncMode::Warp { .. })
+		matches!(
+			self.network.sync_mode,
+			SyncMode::LightState { .. } | SyncMode::Warp { .. } | SyncMode::Checkpoint(_)
+		)
 	}
 
 	/// Returns the database config for creating the backend.
@@ -239,6 +246,7 @@ impl Configuration {
 		sc_client_db::DatabaseSettings {
 			trie_cache_maximum_size: self.trie_cache_maximum_size,
 			state_pruning: self.state_pruning.clone(),
+			state_checkpoint_interval: self.state_checkpoint_interval,
 			source: self.database.clone(),
 			blocks_pruning: self.blocks_pruning,
 		}
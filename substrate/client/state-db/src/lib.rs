@@ -39,7 +39,11 @@
 //!
 //! # Pruning.
 //! See `RefWindow` for pruning algorithm details. `StateDb` prunes on each canonicalization until
-//! pruning constraints are satisfied.
+//! pruning constraints are satisfied. The window may be constrained by the number of blocks, by
+//! the time that passed since a block was canonicalized, and by the size of the database.
+//! Optionally, the state of every `n`-th block is retained as a checkpoint after it leaves the
+//! window, so that historical state can be recomputed by re-executing blocks from the nearest
+//! checkpoint.
 
 mod noncanonical;
 mod pruning;
@@ -54,6 +58,7 @@ use pruning::{HaveBlock, RefWindow};
 use std::{
 	collections::{hash_map::Entry, HashMap},
 	fmt,
+	time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 const LOG_TARGET: &str = "state-db";
@@ -63,6 +68,23 @@ const PRUNING_MODE_ARCHIVE: &[u8] = b"archive";
 const PRUNING_MODE_ARCHIVE_CANON: &[u8] = b"archive_canonical";
 const PRUNING_MODE_CONSTRAINED: &[u8] = b"constrained";
 pub(crate) const DEFAULT_MAX_BLOCK_CONSTRAINT: u32 = 256;
+/// Number of blocks pruned on each canonicalization while the database exceeds its target size.
+///
+/// One more than the single block added by the canonicalization, so the window shrinks by one
+/// block each time.
+const SIZE_PRUNING_STEP: u32 = 2;
+/// Number of blocks the window never shrinks below because the database exceeds its target size,
+/// unless [`Constraints::max_blocks`] or [`Constraints::max_age`] cap it lower.
+pub const MIN_SIZE_PRUNING_WINDOW: u32 = DEFAULT_MAX_BLOCK_CONSTRAINT;
+/// Shortest block time assumed when pruning by age.
+///
+/// The age of a block is measured from its canonicalization, so blocks canonicalized during a
+/// major sync never get old enough to be pruned. The window is therefore also capped at the
+/// number of blocks produced within [`Constraints::max_age`] at this block time.
+pub const MIN_AGE_PRUNING_BLOCK_TIME: Duration = Duration::from_secs(1);
+/// Large pruning windows need to be loaded into memory when the database does not support
+/// reference counting.
+const LARGE_PRUNING_WINDOW: u32 = 1000;
 
 /// Database value type.
 pub type DBValue = Vec<u8>;
@@ -145,6 +167,8 @@ pub enum StateDbError {
 	BlockUnavailable,
 	/// Block record is missing from the pruning window
 	BlockMissing,
+	/// State checkpoints were requested for a pruning mode that is not constrained.
+	CheckpointsRequireConstrainedPruning { mode: PruningMode },
 }
 
 impl<E> From<StateDbError> for Error<E> {
@@ -196,6 +220,8 @@ impl fmt::Debug for StateDbError {
 				write!(f, "Trying to get a block record from db while it is not commit to db yet")
 			},
 			Self::BlockMissing => write!(f, "Block record is missing from the pruning window"),
+			Self::CheckpointsRequireConstrainedPruning { mode } =>
+				write!(f, "State checkpoints require a constrained pruning mode [mode: {mode:?}]"),
 		}
 	}
 }
@@ -218,12 +244,49 @@ pub struct CommitSet<H: Hash> {
 	pub meta: ChangeSet<Vec<u8>>,
 }
 
-/// Pruning constraints. If none are specified pruning is
+/// Pruning constraints. A block is pruned as soon as any of the specified constraints is
+/// exceeded.
+///
+/// Create it with the [`PruningMode`] constructors or from [`Constraints::default`], more
+/// constraints may be added in the future.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
 pub struct Constraints {
-	/// Maximum blocks. Defaults to 0 when unspecified, effectively keeping only non-canonical
-	/// states.
+	/// Maximum blocks. Defaults to 0 when no other constraint is specified, effectively keeping
+	/// only non-canonical states.
 	pub max_blocks: Option<u32>,
+	/// Maximum time a block is kept after it was canonicalized.
+	///
+	/// The window never holds more blocks than are produced within this time at
+	/// [`MIN_AGE_PRUNING_BLOCK_TIME`].
+	pub max_age: Option<Duration>,
+	/// Target database size in bytes.
+	///
+	/// The size is reported by the database backend through [`StateDb::note_database_size`].
+	/// While it is above the target the window shrinks gradually, down to
+	/// [`MIN_SIZE_PRUNING_WINDOW`] blocks.
+	pub max_size: Option<u64>,
+	/// Retain the state of every block with a number divisible by this interval.
+	pub checkpoint_interval: Option<u32>,
+}
+
+impl Constraints {
+	/// Maximum number of blocks in the pruning window, if any.
+	fn max_blocks(&self) -> Option<u32> {
+		match self {
+			Constraints { max_blocks: None, max_age: None, max_size: None, .. } => Some(0),
+			Constraints { max_blocks, max_age, .. } => {
+				let max_age_blocks = max_age.map(|age| {
+					let blocks = age.as_millis() / MIN_AGE_PRUNING_BLOCK_TIME.as_millis();
+					blocks.try_into().unwrap_or(u32::MAX)
+				});
+				match (*max_blocks, max_age_blocks) {
+					(Some(blocks), Some(age_blocks)) => Some(blocks.min(age_blocks)),
+					(blocks, age_blocks) => blocks.or(age_blocks),
+				}
+			},
+		}
+	}
 }
 
 /// Pruning mode.
@@ -240,7 +303,32 @@ pub enum PruningMode {
 impl PruningMode {
 	/// Create a mode that keeps given number of blocks.
 	pub fn blocks_pruning(n: u32) -> PruningMode {
-		PruningMode::Constrained(Constraints { max_blocks: Some(n) })
+		PruningMode::Constrained(Constraints {
+			max_blocks: Some(n),
+			max_age: None,
+			max_size: None,
+			checkpoint_interval: None,
+		})
+	}
+
+	/// Create a mode that keeps blocks for the given time after they were canonicalized.
+	pub fn age_pruning(age: Duration) -> PruningMode {
+		PruningMode::Constrained(Constraints {
+			max_blocks: None,
+			max_age: Some(age),
+			max_size: None,
+			checkpoint_interval: None,
+		})
+	}
+
+	/// Create a mode that keeps as many blocks as fit into the given database size in bytes.
+	pub fn size_pruning(size: u64) -> PruningMode {
+		PruningMode::Constrained(Constraints {
+			max_blocks: None,
+			max_age: None,
+			max_size: Some(size),
+			checkpoint_interval: None,
+		})
 	}
 
 	/// Is this an archive (either ArchiveAll or ArchiveCanonical) pruning mode?
@@ -278,10 +366,23 @@ impl Default for PruningMode {
 
 impl Default for Constraints {
 	fn default() -> Self {
-		Self { max_blocks: Some(DEFAULT_MAX_BLOCK_CONSTRAINT) }
+		Self {
+			max_blocks: Some(DEFAULT_MAX_BLOCK_CONSTRAINT),
+			max_age: None,
+			max_size: None,
+			checkpoint_interval: None,
+		}
 	}
 }
 
+/// Current unix time in milliseconds.
+pub(crate) fn unix_time_millis() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_millis() as u64)
+		.unwrap_or_default()
+}
+
 fn to_meta_key<S: Codec>(suffix: &[u8], data: &S) -> Vec<u8> {
 	let mut buffer = data.encode();
 	buffer.extend(suffix);
@@ -305,6 +406,8 @@ pub struct StateDbSync<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	pruning: Option<RefWindow<BlockHash, Key, D>>,
 	pinned: HashMap<BlockHash, u32>,
 	ref_counting: bool,
+	/// Last database size reported by the backend.
+	database_size: Option<u64>,
 }
 
 impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
@@ -316,13 +419,44 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		trace!(target: LOG_TARGET, "StateDb settings: {:?}. Ref-counting: {}", mode, ref_counting);
 
 		let non_canonical: NonCanonicalOverlay<BlockHash, Key> = NonCanonicalOverlay::new(&db)?;
-		let pruning: Option<RefWindow<BlockHash, Key, D>> = match mode {
-			PruningMode::Constrained(Constraints { max_blocks }) =>
-				Some(RefWindow::new(db, max_blocks.unwrap_or(0), ref_counting)?),
+		let pruning: Option<RefWindow<BlockHash, Key, D>> = match &mode {
+			PruningMode::Constrained(constraints) => {
+				let max_blocks = constraints.max_blocks();
+				// Without reference counting the entire pruning window is loaded into memory.
+				if ref_counting {
+					match max_blocks {
+						Some(window_size) if window_size > LARGE_PRUNING_WINDOW => log::warn!(
+							target: LOG_TARGET,
+							"Large pruning window of {window_size} detected! THIS CAN LEAD TO HIGH MEMORY USAGE AND CRASHES. \
+							Reduce the pruning window or switch your database to paritydb."
+						),
+						Some(_) => {},
+						None => log::warn!(
+							target: LOG_TARGET,
+							"Pruning window is only bounded by the database size! THIS CAN LEAD TO HIGH MEMORY USAGE AND CRASHES. \
+							Add a block or age limit to the pruning window or switch your database to paritydb."
+						),
+					}
+				}
+				// The window size is unknown upfront when pruning by size only.
+				Some(RefWindow::new(
+					db,
+					max_blocks.unwrap_or(DEFAULT_MAX_BLOCK_CONSTRAINT),
+					ref_counting,
+					constraints.checkpoint_interval,
+				)?)
+			},
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
 		};
 
-		Ok(StateDbSync { mode, non_canonical, pruning, pinned: Default::default(), ref_counting })
+		Ok(StateDbSync {
+			mode,
+			non_canonical,
+			pruning,
+			pinned: Default::default(),
+			ref_counting,
+			database_size: None,
+		})
 	}
 
 	fn insert_block(
@@ -345,7 +479,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		}
 	}
 
-	fn canonicalize_block(&mut self, hash: &BlockHash) -> Result<CommitSet<Key>, Error<D::Error>> {
+	fn canonicalize_block(
+		&mut self,
+		hash: &BlockHash,
+		now: u64,
+	) -> Result<CommitSet<Key>, Error<D::Error>> {
 		// NOTE: it is important that the change to `LAST_CANONICAL` (emit from
 		// `non_canonical.canonicalize`) and the insert of the new pruning journal (emit from
 		// `pruning.note_canonical`) are collected into the same `CommitSet` and are committed to
@@ -359,9 +497,9 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 			commit.data.deleted.clear();
 		}
 		if let Some(ref mut pruning) = self.pruning {
-			pruning.note_canonical(hash, number, &mut commit)?;
+			pruning.note_canonical(hash, number, now, &mut commit)?;
 		}
-		self.prune(&mut commit)?;
+		self.prune(&mut commit, now)?;
 		Ok(commit)
 	}
 
@@ -407,23 +545,43 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		}
 	}
 
-	fn prune(&mut self, commit: &mut CommitSet<Key>) -> Result<(), Error<D::Error>> {
+	fn prune(&mut self, commit: &mut CommitSet<Key>, now: u64) -> Result<(), Error<D::Error>> {
 		if let (&mut Some(ref mut pruning), PruningMode::Constrained(constraints)) =
 			(&mut self.pruning, &self.mode)
 		{
+			let max_blocks = constraints.max_blocks();
+			let max_age = constraints.max_age.map(|age| age.as_millis() as u64);
+			// Blocks that may still be pruned because the database is too large.
+			let mut size_budget = match (constraints.max_size, self.database_size) {
+				(Some(max_size), Some(size)) if size > max_size => SIZE_PRUNING_STEP,
+				_ => 0,
+			};
+			let size_floor =
+				max_blocks.map_or(MIN_SIZE_PRUNING_WINDOW, |max| max.min(MIN_SIZE_PRUNING_WINDOW));
 			loop {
-				if pruning.window_size() <= constraints.max_blocks.unwrap_or(0) as u64 {
+				let exceeds_blocks =
+					max_blocks.map_or(false, |max| pruning.window_size() > max as u64);
+				if !exceeds_blocks && max_age.is_none() && size_budget == 0 {
 					break
 				}
 
 				let pinned = &self.pinned;
-				match pruning.next_hash() {
+				let canonicalized_at = match pruning.next_block() {
 					// the block record is temporary unavailable, break and try next time
 					Err(Error::StateDb(StateDbError::BlockUnavailable)) => break,
-					res =>
-						if res?.map_or(false, |h| pinned.contains_key(&h)) {
-							break
-						},
+					res => match res? {
+						Some((hash, _)) if pinned.contains_key(&hash) => break,
+						Some((_, canonicalized_at)) => canonicalized_at,
+						None => break,
+					},
+				};
+				let expired =
+					max_age.map_or(false, |age| now.saturating_sub(canonicalized_at) >= age);
+				if !exceeds_blocks && !expired {
+					if size_budget == 0 || pruning.window_size() <= size_floor as u64 {
+						break
+					}
+					size_budget -= 1;
 				}
 				match pruning.prune_one(commit) {
 					// this branch should not reach as previous `next_hash` don't return error
@@ -536,6 +694,21 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 		requested_mode: Option<PruningMode>,
 		ref_counting: bool,
 		should_init: bool,
+	) -> Result<(CommitSet<Key>, StateDb<BlockHash, Key, D>), Error<D::Error>> {
+		Self::open_with_checkpoint_interval(db, requested_mode, None, ref_counting, should_init)
+	}
+
+	/// Create an instance of [`StateDb`] that retains the state of every block with a number
+	/// divisible by `checkpoint_interval`.
+	///
+	/// The interval is applied to the stored or requested pruning mode, which must be
+	/// constrained.
+	pub fn open_with_checkpoint_interval(
+		db: D,
+		requested_mode: Option<PruningMode>,
+		checkpoint_interval: Option<u32>,
+		ref_counting: bool,
+		should_init: bool,
 	) -> Result<(CommitSet<Key>, StateDb<BlockHash, Key, D>), Error<D::Error>> {
 		let stored_mode = fetch_stored_pruning_mode(&db)?;
 
@@ -555,6 +728,15 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 
 			(false, Some(stored), Some(requested)) => choose_pruning_mode(stored, requested)?,
 		};
+		let selected_mode = match (selected_mode, checkpoint_interval) {
+			(selected_mode, None) => selected_mode,
+			(PruningMode::Constrained(mut constraints), Some(interval)) => {
+				constraints.checkpoint_interval = Some(interval);
+				PruningMode::Constrained(constraints)
+			},
+			(mode, Some(_)) =>
+				return Err(StateDbError::CheckpointsRequireConstrainedPruning { mode }.into()),
+		};
 
 		let db_init_commit_set = if should_init {
 			let mut cs: CommitSet<Key> = Default::default();
@@ -592,7 +774,24 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 
 	/// Finalize a previously inserted block.
 	pub fn canonicalize_block(&self, hash: &BlockHash) -> Result<CommitSet<Key>, Error<D::Error>> {
-		self.db.write().canonicalize_block(hash)
+		self.db.write().canonicalize_block(hash, unix_time_millis())
+	}
+
+	/// Returns the blocks before the pruning window whose state is retained as a checkpoint,
+	/// ordered by block number.
+	pub fn checkpoints(&self) -> Vec<(u64, BlockHash)> {
+		self.db
+			.read()
+			.pruning
+			.as_ref()
+			.map_or_else(Vec::new, |pruning| pruning.checkpoints())
+	}
+
+	/// Report the current size of the database in bytes.
+	///
+	/// Used to enforce [`Constraints::max_size`] on subsequent canonicalizations.
+	pub fn note_database_size(&self, size: u64) {
+		self.db.write().database_size = Some(size);
 	}
 
 	/// Prevents pruning of specified block and its descendants.
@@ -655,7 +854,9 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 	/// Reset in-memory changes to the last disk-backed state.
 	pub fn reset(&self, db: D) -> Result<(), Error<D::Error>> {
 		let mut state_db = self.db.write();
+		let database_size = state_db.database_size;
 		*state_db = StateDbSync::new(state_db.mode.clone(), state_db.ref_counting, db)?;
+		state_db.database_size = database_size;
 		Ok(())
 	}
 }
@@ -671,6 +872,56 @@ pub enum IsPruned {
 	MaybePruned,
 }
 
+/// Summary of the journals of a database, as returned by [`check_journals`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalSummary<BlockHash> {
+	/// Stored pruning mode.
+	pub mode: PruningMode,
+	/// Last canonicalized block.
+	pub last_canonicalized: Option<(BlockHash, u64)>,
+	/// Numbers and hashes of the canonicalized blocks that were not pruned yet.
+	pub pruning_window: Vec<(u64, BlockHash)>,
+	/// Number of journaled non-canonical blocks.
+	pub non_canonical: usize,
+}
+
+/// Decode and validate the journals of `db` without loading them.
+///
+/// Checks that every non-canonical block descends from the last canonicalized block and, for
+/// constrained pruning, that the pruning journal covers all canonicalized blocks that were not
+/// pruned yet.
+pub fn check_journals<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+) -> Result<JournalSummary<BlockHash>, Error<D::Error>> {
+	let mode = fetch_stored_pruning_mode(db)?
+		.ok_or_else(|| StateDbError::Metadata("PRUNING_MODE is not stored".into()))?;
+	let (last_canonicalized, non_canonical) = noncanonical::check_journal::<BlockHash, Key, D>(db)?;
+	let pruning_window = match mode {
+		PruningMode::Constrained(_) => pruning::check_journal::<BlockHash, Key, D>(
+			db,
+			last_canonicalized.as_ref().map(|(_, number)| *number),
+		)?,
+		PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => Vec::new(),
+	};
+	Ok(JournalSummary { mode, last_canonicalized, pruning_window, non_canonical })
+}
+
+/// Rewrite the canonicalization and pruning journals of a database that does not support
+/// reference counting, so that they may be loaded from a database that does.
+///
+/// Journaled keys are mapped with `sanitize`. Pending deletions of keys that are re-inserted by
+/// a later block in the pruning window are dropped, the same way they are when the journal is
+/// loaded into memory. The returned changes overwrite the existing journal records.
+pub fn ref_counted_journals<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	sanitize: impl Fn(Key) -> Key,
+) -> Result<ChangeSet<Vec<u8>>, Error<D::Error>> {
+	let mut changes = ChangeSet::default();
+	noncanonical::ref_counted_journal::<BlockHash, Key, D>(db, &sanitize, &mut changes)?;
+	pruning::ref_counted_journal::<BlockHash, Key, D>(db, &sanitize, &mut changes)?;
+	Ok(changes)
+}
+
 fn fetch_stored_pruning_mode<D: MetaDb>(db: &D) -> Result<Option<PruningMode>, Error<D::Error>> {
 	let meta_key_mode = to_meta_key(PRUNING_MODE, &());
 	if let Some(stored_mode) = db.get_meta(&meta_key_mode).map_err(Error::Db)? {
@@ -705,10 +956,13 @@ fn choose_pruning_mode(
 #[cfg(test)]
 mod tests {
 	use crate::{
+		check_journals, ref_counted_journals,
 		test::{make_changeset, make_db, TestDb},
-		Constraints, Error, IsPruned, PruningMode, StateDb, StateDbError,
+		to_meta_key, CommitSet, Constraints, Error, IsPruned, PruningMode, StateDb, StateDbError,
+		MIN_AGE_PRUNING_BLOCK_TIME, MIN_SIZE_PRUNING_WINDOW,
 	};
 	use sp_core::H256;
+	use std::time::Duration;
 
 	fn make_test_db(settings: PruningMode) -> (TestDb, StateDb<H256, H256, TestDb>) {
 		let mut db = make_db(&[91, 921, 922, 93, 94]);
@@ -788,8 +1042,10 @@ mod tests {
 
 	#[test]
 	fn block_record_unavailable() {
-		let (mut db, state_db) =
-			make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(1) }));
+		let (mut db, state_db) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(1),
+			..Default::default()
+		}));
 		// import 2 blocks
 		for i in &[5, 6] {
 			db.commit(
@@ -823,13 +1079,19 @@ mod tests {
 
 	#[test]
 	fn prune_window_0() {
-		let (db, _) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(0) }));
+		let (db, _) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(0),
+			..Default::default()
+		}));
 		assert!(db.data_eq(&make_db(&[21, 3, 922, 94])));
 	}
 
 	#[test]
 	fn prune_window_1() {
-		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(1) }));
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(1),
+			..Default::default()
+		}));
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(0), 0), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::Pruned);
@@ -839,7 +1101,10 @@ mod tests {
 
 	#[test]
 	fn prune_window_2() {
-		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(2) }));
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(2),
+			..Default::default()
+		}));
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(0), 0), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::NotPruned);
@@ -847,6 +1112,136 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
 	}
 
+	fn import_and_canonicalize(
+		db: &mut TestDb,
+		state_db: &StateDb<H256, H256, TestDb>,
+		number: u64,
+		now: u64,
+	) {
+		let hash = H256::from_low_u64_be(number);
+		let parent_hash = H256::from_low_u64_be(number - 1);
+		db.commit(
+			&state_db
+				.insert_block(&hash, number, &parent_hash, make_changeset(&[], &[]))
+				.unwrap(),
+		);
+		db.commit(&state_db.db.write().canonicalize_block(&hash, now).unwrap());
+	}
+
+	#[test]
+	fn prune_window_by_age() {
+		let mut db = make_db(&[]);
+		let mode = PruningMode::age_pruning(Duration::from_secs(10));
+		let (state_db_init, state_db) = StateDb::open(db.clone(), Some(mode), false, true).unwrap();
+		db.commit(&state_db_init);
+
+		import_and_canonicalize(&mut db, &state_db, 1, 1_000);
+		import_and_canonicalize(&mut db, &state_db, 2, 5_000);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::NotPruned);
+
+		// block 1 is now 11 seconds old, block 2 only 7
+		import_and_canonicalize(&mut db, &state_db, 3, 12_000);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(2), 2), IsPruned::NotPruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::NotPruned);
+	}
+
+	#[test]
+	fn prune_window_by_age_is_capped_by_blocks() {
+		let mut db = make_db(&[]);
+		let mode = PruningMode::age_pruning(MIN_AGE_PRUNING_BLOCK_TIME * 10);
+		let (state_db_init, state_db) = StateDb::open(db.clone(), Some(mode), false, true).unwrap();
+		db.commit(&state_db_init);
+
+		// blocks canonicalized at once, like during a major sync, are still pruned
+		for number in 1..=12 {
+			import_and_canonicalize(&mut db, &state_db, number, 0);
+		}
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(2), 2), IsPruned::Pruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::NotPruned);
+	}
+
+	#[test]
+	fn prune_window_by_size() {
+		let mut db = make_db(&[]);
+		let mode = PruningMode::size_pruning(100);
+		let (state_db_init, state_db) = StateDb::open(db.clone(), Some(mode), false, true).unwrap();
+		db.commit(&state_db_init);
+
+		// nothing is pruned as long as no size was reported
+		let window = MIN_SIZE_PRUNING_WINDOW as u64;
+		for number in 1..=window + 4 {
+			import_and_canonicalize(&mut db, &state_db, number, 0);
+		}
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::NotPruned);
+
+		// the window shrinks by one block per canonicalization while above the target
+		state_db.note_database_size(200);
+		import_and_canonicalize(&mut db, &state_db, window + 5, 0);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(2), 2), IsPruned::Pruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::NotPruned);
+
+		state_db.note_database_size(50);
+		import_and_canonicalize(&mut db, &state_db, window + 6, 0);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::NotPruned);
+
+		// but never below the minimum window
+		state_db.note_database_size(200);
+		for number in window + 7..=window + 11 {
+			import_and_canonicalize(&mut db, &state_db, number, 0);
+		}
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(11), 11), IsPruned::Pruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(12), 12), IsPruned::NotPruned);
+	}
+
+	#[test]
+	fn check_journals_detects_missing_pruning_record() {
+		let (mut db, _) = make_test_db(PruningMode::blocks_pruning(2));
+		let summary = check_journals::<H256, H256, _>(&db).unwrap();
+		assert_eq!(summary.last_canonicalized, Some((H256::from_low_u64_be(3), 3)));
+		assert_eq!(
+			summary.pruning_window,
+			vec![(2, H256::from_low_u64_be(21)), (3, H256::from_low_u64_be(3))]
+		);
+		assert_eq!(summary.non_canonical, 1);
+
+		let mut commit = CommitSet::default();
+		commit.meta.deleted.push(to_meta_key(b"pruning_journal", &3u64));
+		db.commit(&commit);
+		assert!(matches!(
+			check_journals::<H256, H256, _>(&db),
+			Err(Error::StateDb(StateDbError::Metadata(_)))
+		));
+	}
+	#[test]
+	fn ref_counted_journals_keep_reinserted_keys() {
+		let mode = PruningMode::blocks_pruning(2);
+		let mut db = make_db(&[91]);
+		let (state_db_init, state_db) =
+			StateDb::<H256, H256, TestDb>::open(db.clone(), Some(mode.clone()), true, true)
+				.unwrap();
+		db.commit(&state_db_init);
+		for (number, inserted, deleted) in [(1, &[1][..], &[91][..]), (2, &[91], &[])] {
+			let hash = H256::from_low_u64_be(number);
+			let parent_hash = H256::from_low_u64_be(number - 1);
+			db.commit(
+				&state_db
+					.insert_block(&hash, number, &parent_hash, make_changeset(inserted, deleted))
+					.unwrap(),
+			);
+			db.commit(&state_db.canonicalize_block(&hash).unwrap());
+		}
+
+		let meta = ref_counted_journals::<H256, H256, _>(&db, |k| k).unwrap();
+		db.commit(&CommitSet { data: Default::default(), meta });
+		let (_, state_db) = StateDb::open(db.clone(), Some(mode), false, false).unwrap();
+		import_and_canonicalize(&mut db, &state_db, 3, 0);
+		import_and_canonicalize(&mut db, &state_db, 4, 0);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(2), 2), IsPruned::Pruned);
+		assert!(db.data_eq(&make_db(&[1, 91])));
+	}
+
 	#[test]
 	fn detects_incompatible_mode() {
 		let mut db = make_db(&[]);
@@ -863,7 +1258,8 @@ mod tests {
 				)
 				.unwrap(),
 		);
-		let new_mode = PruningMode::Constrained(Constraints { max_blocks: Some(2) });
+		let new_mode =
+			PruningMode::Constrained(Constraints { max_blocks: Some(2), ..Default::default() });
 		let state_db_open_result: Result<(_, StateDb<H256, H256, TestDb>), _> =
 			StateDb::open(db.clone(), Some(new_mode), false, false);
 		assert!(state_db_open_result.is_err());
@@ -946,4 +1342,48 @@ mod tests {
 			check_stored_and_requested_mode_compatibility(created, reopened, expected);
 		}
 	}
+
+	#[test]
+	fn checkpoint_interval_applies_to_selected_mode() {
+		let open = |db: &TestDb, requested_mode, should_init| {
+			StateDb::<H256, H256, TestDb>::open_with_checkpoint_interval(
+				db.clone(),
+				requested_mode,
+				Some(1000),
+				false,
+				should_init,
+			)
+		};
+		let mut with_checkpoints = PruningMode::blocks_pruning(256);
+		if let PruningMode::Constrained(constraints) = &mut with_checkpoints {
+			constraints.checkpoint_interval = Some(1000);
+		}
+
+		// the stored mode is kept when no mode is requested
+		let mut db = make_db(&[]);
+		let (state_db_init, _) =
+			StateDb::<H256, H256, TestDb>::open(db.clone(), None, false, true).unwrap();
+		db.commit(&state_db_init);
+		let (_, state_db) = open(&db, None, false).unwrap();
+		assert_eq!(state_db.pruning_mode(), with_checkpoints);
+
+		// archive databases can't retain checkpoints
+		let mut db = make_db(&[]);
+		let (state_db_init, _) = StateDb::<H256, H256, TestDb>::open(
+			db.clone(),
+			Some(PruningMode::ArchiveAll),
+			false,
+			true,
+		)
+		.unwrap();
+		db.commit(&state_db_init);
+		assert!(matches!(
+			open(&db, None, false),
+			Err(Error::StateDb(StateDbError::CheckpointsRequireConstrainedPruning { .. }))
+		));
+		assert!(matches!(
+			open(&make_db(&[]), Some(PruningMode::ArchiveCanonical), true),
+			Err(Error::StateDb(StateDbError::CheckpointsRequireConstrainedPruning { .. }))
+		));
+	}
 }
@@ -568,6 +568,79 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 	}
 }
 
+/// Decode the non-canonical journal and check that every block descends from the last
+/// canonicalized block. Returns the last canonicalized block and the number of journaled blocks.
+pub(crate) fn check_journal<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+) -> Result<(Option<(BlockHash, u64)>, usize), Error<D::Error>> {
+	let last_canonicalized =
+		match db.get_meta(&to_meta_key(LAST_CANONICAL, &())).map_err(Error::Db)? {
+			Some(buffer) => <(BlockHash, u64)>::decode(&mut buffer.as_slice())?,
+			None => return Ok((None, 0)),
+		};
+	let mut parents = vec![last_canonicalized.0.clone()];
+	let mut total = 0;
+	let mut block = last_canonicalized.1 + 1;
+	loop {
+		let mut level = Vec::new();
+		for index in 0..MAX_BLOCKS_PER_LEVEL {
+			if let Some(record) = db.get_meta(&to_journal_key(block, index)).map_err(Error::Db)? {
+				let record: JournalRecord<BlockHash, Key> = Decode::decode(&mut record.as_slice())?;
+				if !parents.contains(&record.parent_hash) {
+					return Err(StateDbError::Metadata(format!(
+						"Non-canonical block {:?} (#{}) has an unknown parent {:?}",
+						record.hash, block, record.parent_hash
+					))
+					.into())
+				}
+				level.push(record.hash);
+			}
+		}
+		if level.is_empty() {
+			break
+		}
+		total += level.len();
+		parents = level;
+		block += 1;
+	}
+	Ok((Some(last_canonicalized), total))
+}
+
+/// Rewrite the keys of all non-canonical journal records with `sanitize`.
+pub(crate) fn ref_counted_journal<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	sanitize: &impl Fn(Key) -> Key,
+	changes: &mut ChangeSet<Vec<u8>>,
+) -> Result<(), Error<D::Error>> {
+	let last_canonicalized =
+		match db.get_meta(&to_meta_key(LAST_CANONICAL, &())).map_err(Error::Db)? {
+			Some(buffer) => <(BlockHash, u64)>::decode(&mut buffer.as_slice())?,
+			None => return Ok(()),
+		};
+	let mut block = last_canonicalized.1 + 1;
+	loop {
+		let mut found = false;
+		for index in 0..MAX_BLOCKS_PER_LEVEL {
+			let journal_key = to_journal_key(block, index);
+			if let Some(record) = db.get_meta(&journal_key).map_err(Error::Db)? {
+				let record: JournalRecord<BlockHash, Key> = Decode::decode(&mut record.as_slice())?;
+				let record = JournalRecord {
+					hash: record.hash,
+					parent_hash: record.parent_hash,
+					inserted: record.inserted.into_iter().map(|(k, v)| (sanitize(k), v)).collect(),
+					deleted: record.deleted.into_iter().map(sanitize).collect(),
+				};
+				changes.inserted.push((journal_key, record.encode()));
+				found = true;
+			}
+		}
+		if !found {
+			break
+		}
+		block += 1;
+	}
+	Ok(())
+}
 #[cfg(test)]
 mod tests {
 	use super::{to_journal_key, NonCanonicalOverlay};
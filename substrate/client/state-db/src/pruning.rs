@@ -22,18 +22,26 @@
 //! There is also a global index of node key to block number.
 //! If a node is re-inserted into the window it gets removed from
 //! the death list.
-//! The changes are journaled in the DB.
+//! The changes are journaled in the DB, together with the time at which
+//! each block was canonicalized.
+//!
+//! Optionally the full state of every `n`-th block is retained as a checkpoint. While pruning
+//! the blocks following a checkpoint, only nodes inserted after the checkpoint are deleted.
+//! Deletions of all other nodes are suppressed, as they still belong to the checkpoint state.
 
 use crate::{
-	noncanonical::LAST_CANONICAL, to_meta_key, CommitSet, Error, Hash, MetaDb, StateDbError,
-	DEFAULT_MAX_BLOCK_CONSTRAINT, LOG_TARGET,
+	noncanonical::LAST_CANONICAL, to_meta_key, unix_time_millis, CommitSet, Error, Hash, MetaDb,
+	StateDbError, DEFAULT_MAX_BLOCK_CONSTRAINT, LOG_TARGET,
 };
 use codec::{Decode, Encode};
 use log::trace;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
 pub(crate) const LAST_PRUNED: &[u8] = b"last_pruned";
 const PRUNING_JOURNAL: &[u8] = b"pruning_journal";
+const PRUNING_JOURNAL_TIME: &[u8] = b"pruning_journal_time";
+const CHECKPOINTS: &[u8] = b"pruning_checkpoints";
+const CHECKPOINT_JOURNAL: &[u8] = b"pruning_checkpoint_journal";
 
 /// See module documentation.
 pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
@@ -42,6 +50,111 @@ pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	queue: DeathRowQueue<BlockHash, Key, D>,
 	/// Block number that is next to be pruned.
 	base: u64,
+	/// Blocks whose state is retained after they leave the window.
+	checkpoints: Option<Checkpoints<BlockHash, Key>>,
+}
+
+/// Tracks the retained checkpoints and the nodes that may be deleted without affecting the
+/// latest of them.
+struct Checkpoints<BlockHash: Hash, Key: Hash> {
+	/// Every block with a number divisible by `interval` becomes a checkpoint.
+	interval: u64,
+	/// Retained checkpoints by block number.
+	retained: BTreeMap<u64, BlockHash>,
+	/// Nodes inserted by the blocks pruned since the last checkpoint.
+	inserted: HashSet<Key>,
+	/// Nodes whose deletion was suppressed since the last checkpoint.
+	protected: HashSet<Key>,
+}
+
+/// Nodes tracked for a block pruned after a checkpoint, journaled until the next checkpoint.
+#[derive(Encode, Decode, Default)]
+struct CheckpointRecord<Key: Hash> {
+	inserted: Vec<Key>,
+	protected: Vec<Key>,
+}
+
+impl<BlockHash: Hash, Key: Hash> Checkpoints<BlockHash, Key> {
+	fn new<D: MetaDb>(db: &D, interval: u32, base: u64) -> Result<Self, Error<D::Error>> {
+		let interval = interval.max(1) as u64;
+		let retained: Vec<(u64, BlockHash)> =
+			match db.get_meta(&to_meta_key(CHECKPOINTS, &())).map_err(Error::Db)? {
+				Some(buffer) => Decode::decode(&mut buffer.as_slice())?,
+				None => Vec::new(),
+			};
+		let mut checkpoints = Checkpoints {
+			interval,
+			retained: retained.into_iter().collect(),
+			inserted: Default::default(),
+			protected: Default::default(),
+		};
+		// restore the nodes tracked for the blocks pruned since the last checkpoint
+		if base > 0 {
+			for block in checkpoints.last_checkpoint(base - 1) + 1..base {
+				if let Some(record) =
+					db.get_meta(&to_checkpoint_journal_key(block)).map_err(Error::Db)?
+				{
+					let record: CheckpointRecord<Key> = Decode::decode(&mut record.as_slice())?;
+					checkpoints.inserted.extend(record.inserted);
+					checkpoints.protected.extend(record.protected);
+				}
+			}
+		}
+		Ok(checkpoints)
+	}
+
+	/// Number of the last checkpoint at or before `block`.
+	fn last_checkpoint(&self, block: u64) -> u64 {
+		block / self.interval * self.interval
+	}
+
+	/// Prune `block`, returning the nodes that may actually be deleted from `deleted`.
+	///
+	/// `reinserted` are the deletions of the block that were dropped from its death row because
+	/// the node was inserted again later in the window.
+	fn prune(
+		&mut self,
+		hash: BlockHash,
+		block: u64,
+		inserted: Vec<Key>,
+		deleted: HashSet<Key>,
+		reinserted: Vec<Key>,
+		commit: &mut CommitSet<Key>,
+	) -> HashSet<Key> {
+		// Deletions are only restricted while the preceding checkpoint is retained. It is not
+		// retained when checkpoints were enabled after it was pruned.
+		let previous = block.checked_sub(1).map(|b| self.last_checkpoint(b));
+		let (deleted, protected): (HashSet<Key>, HashSet<Key>) = match previous {
+			Some(previous) if self.retained.contains_key(&previous) => {
+				let (deleted, mut protected): (HashSet<Key>, HashSet<Key>) = deleted
+					.into_iter()
+					.partition(|k| self.inserted.contains(k) && !self.protected.contains(k));
+				// The node still belongs to the checkpoint state, so its re-insertion must not
+				// make it deletable again.
+				protected.extend(reinserted.into_iter().filter(|k| !self.inserted.contains(k)));
+				(deleted, protected)
+			},
+			_ => (deleted, HashSet::new()),
+		};
+
+		if block % self.interval == 0 {
+			trace!(target: LOG_TARGET, "Retaining checkpoint {:?} (#{})", hash, block);
+			self.retained.insert(block, hash);
+			let retained: Vec<_> = self.retained.iter().collect();
+			commit.meta.inserted.push((to_meta_key(CHECKPOINTS, &()), retained.encode()));
+			for pruned in previous.map_or(block, |p| p + 1)..block {
+				commit.meta.deleted.push(to_checkpoint_journal_key(pruned));
+			}
+			self.inserted.clear();
+			self.protected.clear();
+		} else {
+			let record = CheckpointRecord { inserted, protected: protected.into_iter().collect() };
+			commit.meta.inserted.push((to_checkpoint_journal_key(block), record.encode()));
+			self.inserted.extend(record.inserted);
+			self.protected.extend(record.protected);
+		}
+		deleted
+	}
 }
 
 /// `DeathRowQueue` used to keep track of blocks in the pruning window, there are two flavors:
@@ -67,12 +180,19 @@ enum DeathRowQueue<BlockHash: Hash, Key: Hash, D: MetaDb> {
 		cache_capacity: usize,
 		/// Last block number added to the window
 		last: Option<u64>,
+		/// Time at which the queue was opened, used for records without a canonicalization time
+		opened_at: u64,
 	},
 }
 
 impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 	/// Return a `DeathRowQueue` that all blocks are keep in memory
-	fn new_mem(db: &D, base: u64) -> Result<DeathRowQueue<BlockHash, Key, D>, Error<D::Error>> {
+	fn new_mem(
+		db: &D,
+		base: u64,
+		opened_at: u64,
+		keep_inserted: bool,
+	) -> Result<DeathRowQueue<BlockHash, Key, D>, Error<D::Error>> {
 		let mut block = base;
 		let mut queue = DeathRowQueue::<BlockHash, Key, D>::Mem {
 			death_rows: VecDeque::new(),
@@ -97,7 +217,8 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 						record.inserted.len(),
 						record.deleted.len(),
 					);
-					queue.import(base, block, record);
+					let canonicalized_at = load_canonicalized_at(db, block, opened_at)?;
+					queue.import(base, block, record, canonicalized_at, keep_inserted);
 				},
 				None => break,
 			}
@@ -113,6 +234,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 		base: u64,
 		last: Option<u64>,
 		window_size: u32,
+		opened_at: u64,
 	) -> Result<DeathRowQueue<BlockHash, Key, D>, Error<D::Error>> {
 		// limit the cache capacity from 1 to `DEFAULT_MAX_BLOCK_CONSTRAINT`
 		let cache_capacity = window_size.clamp(1, DEFAULT_MAX_BLOCK_CONSTRAINT) as usize;
@@ -122,12 +244,20 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 			"Reading pruning journal for the database-backed queue. Pending #{}",
 			base
 		);
-		DeathRowQueue::load_batch_from_db(&db, &mut cache, base, cache_capacity)?;
-		Ok(DeathRowQueue::DbBacked { db, cache, cache_capacity, last })
+		DeathRowQueue::load_batch_from_db(&db, &mut cache, base, cache_capacity, opened_at)?;
+		Ok(DeathRowQueue::DbBacked { db, cache, cache_capacity, last, opened_at })
 	}
 
-	/// import a new block to the back of the queue
-	fn import(&mut self, base: u64, num: u64, journal_record: JournalRecord<BlockHash, Key>) {
+	/// import a new block to the back of the queue, `keep_inserted` retains the inserted keys in
+	/// the death row of an in-memory queue
+	fn import(
+		&mut self,
+		base: u64,
+		num: u64,
+		journal_record: JournalRecord<BlockHash, Key>,
+		canonicalized_at: u64,
+		keep_inserted: bool,
+	) {
 		let JournalRecord { hash, inserted, deleted } = journal_record;
 		trace!(target: LOG_TARGET, "Importing {}, base={}", num, base);
 		match self {
@@ -136,15 +266,26 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				// cache.
 				if num == base + cache.len() as u64 && cache.len() < *cache_capacity {
 					trace!(target: LOG_TARGET, "Adding to DB backed cache {:?} (#{})", hash, num);
-					cache.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+					cache.push_back(DeathRow {
+						hash,
+						deleted: deleted.into_iter().collect(),
+						inserted,
+						reinserted: Vec::new(),
+						canonicalized_at,
+					});
 				}
 				*last = Some(num);
 			},
 			DeathRowQueue::Mem { death_rows, death_index } => {
 				// remove all re-inserted keys from death rows
-				for k in inserted {
-					if let Some(block) = death_index.remove(&k) {
-						death_rows[(block - base) as usize].deleted.remove(&k);
+				for k in inserted.iter() {
+					if let Some(block) = death_index.remove(k) {
+						let row = &mut death_rows[(block - base) as usize];
+						row.deleted.remove(k);
+						// the deletion may still remove the node from a retained checkpoint
+						if keep_inserted {
+							row.reinserted.push(k.clone());
+						}
 					}
 				}
 				// add new keys
@@ -152,7 +293,13 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				for k in deleted.iter() {
 					death_index.insert(k.clone(), imported_block);
 				}
-				death_rows.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+				death_rows.push_back(DeathRow {
+					hash,
+					deleted: deleted.into_iter().collect(),
+					inserted: if keep_inserted { inserted } else { Vec::new() },
+					reinserted: Vec::new(),
+					canonicalized_at,
+				});
 			},
 		}
 	}
@@ -164,9 +311,15 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 		base: u64,
 	) -> Result<Option<DeathRow<BlockHash, Key>>, Error<D::Error>> {
 		match self {
-			DeathRowQueue::DbBacked { db, cache, cache_capacity, .. } => {
+			DeathRowQueue::DbBacked { db, cache, cache_capacity, opened_at, .. } => {
 				if cache.is_empty() {
-					DeathRowQueue::load_batch_from_db(db, cache, base, *cache_capacity)?;
+					DeathRowQueue::load_batch_from_db(
+						db,
+						cache,
+						base,
+						*cache_capacity,
+						*opened_at,
+					)?;
 				}
 				Ok(cache.pop_front())
 			},
@@ -189,11 +342,12 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 		cache: &mut VecDeque<DeathRow<BlockHash, Key>>,
 		base: u64,
 		cache_capacity: usize,
+		opened_at: u64,
 	) -> Result<(), Error<D::Error>> {
 		let start = base + cache.len() as u64;
 		let batch_size = cache_capacity;
 		for i in 0..batch_size as u64 {
-			match load_death_row_from_db::<BlockHash, Key, D>(db, start + i)? {
+			match load_death_row_from_db::<BlockHash, Key, D>(db, start + i, opened_at)? {
 				Some(row) => {
 					cache.push_back(row);
 				},
@@ -251,21 +405,49 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 fn load_death_row_from_db<BlockHash: Hash, Key: Hash, D: MetaDb>(
 	db: &D,
 	block: u64,
+	opened_at: u64,
 ) -> Result<Option<DeathRow<BlockHash, Key>>, Error<D::Error>> {
 	let journal_key = to_journal_key(block);
 	match db.get_meta(&journal_key).map_err(Error::Db)? {
 		Some(record) => {
-			let JournalRecord { hash, deleted, .. } = Decode::decode(&mut record.as_slice())?;
-			Ok(Some(DeathRow { hash, deleted: deleted.into_iter().collect() }))
+			let JournalRecord { hash, inserted, deleted } = Decode::decode(&mut record.as_slice())?;
+			let canonicalized_at = load_canonicalized_at(db, block, opened_at)?;
+			Ok(Some(DeathRow {
+				hash,
+				deleted: deleted.into_iter().collect(),
+				inserted,
+				reinserted: Vec::new(),
+				canonicalized_at,
+			}))
 		},
 		None => Ok(None),
 	}
 }
 
+/// Read the time at which `block` was canonicalized. Records journaled before this time was
+/// tracked are treated as canonicalized at `opened_at`.
+fn load_canonicalized_at<D: MetaDb>(
+	db: &D,
+	block: u64,
+	opened_at: u64,
+) -> Result<u64, Error<D::Error>> {
+	match db.get_meta(&to_journal_time_key(block)).map_err(Error::Db)? {
+		Some(time) => Ok(u64::decode(&mut time.as_slice())?),
+		None => Ok(opened_at),
+	}
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct DeathRow<BlockHash: Hash, Key: Hash> {
 	hash: BlockHash,
 	deleted: HashSet<Key>,
+	/// Keys inserted by the block, only tracked when checkpoints are retained.
+	inserted: Vec<Key>,
+	/// Keys deleted by the block but inserted again later in the window, only tracked by the
+	/// in-memory queue when checkpoints are retained.
+	reinserted: Vec<Key>,
+	/// Unix time in milliseconds at which the block was canonicalized.
+	canonicalized_at: u64,
 }
 
 #[derive(Encode, Decode, Default)]
@@ -279,6 +461,14 @@ fn to_journal_key(block: u64) -> Vec<u8> {
 	to_meta_key(PRUNING_JOURNAL, &block)
 }
 
+fn to_journal_time_key(block: u64) -> Vec<u8> {
+	to_meta_key(PRUNING_JOURNAL_TIME, &block)
+}
+
+fn to_checkpoint_journal_key(block: u64) -> Vec<u8> {
+	to_meta_key(CHECKPOINT_JOURNAL, &block)
+}
+
 /// The result return by `RefWindow::have_block`
 #[derive(Debug, PartialEq, Eq)]
 pub enum HaveBlock {
@@ -305,6 +495,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 		db: D,
 		window_size: u32,
 		count_insertions: bool,
+		checkpoint_interval: Option<u32>,
 	) -> Result<RefWindow<BlockHash, Key, D>, Error<D::Error>> {
 		// the block number of the first block in the queue or the next block number if the queue is
 		// empty
@@ -318,21 +509,13 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 				Some(buffer) => Some(<(BlockHash, u64)>::decode(&mut buffer.as_slice())?.1),
 				None => None,
 			};
+		let opened_at = unix_time_millis();
+		let checkpoints = checkpoint_interval
+			.map(|interval| Checkpoints::new(&db, interval, base))
+			.transpose()?;
 
 		let queue = if count_insertions {
-			// Highly scientific crafted number for deciding when to print the warning!
-			//
-			// Rocksdb doesn't support refcounting and requires that we load the entire pruning
-			// window into the memory.
-			if window_size > 1000 {
-				log::warn!(
-					target: LOG_TARGET,
-					"Large pruning window of {window_size} detected! THIS CAN LEAD TO HIGH MEMORY USAGE AND CRASHES. \
-					Reduce the pruning window or switch your database to paritydb."
-				);
-			}
-
-			DeathRowQueue::new_mem(&db, base)?
+			DeathRowQueue::new_mem(&db, base, opened_at, checkpoints.is_some())?
 		} else {
 			let last = match last_canonicalized_number {
 				Some(last_canonicalized_number) => {
@@ -344,10 +527,10 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 				// ever been committed to the db, thus set `unload` to zero
 				None => None,
 			};
-			DeathRowQueue::new_db_backed(db, base, last, window_size)?
+			DeathRowQueue::new_db_backed(db, base, last, window_size, opened_at)?
 		};
 
-		Ok(RefWindow { queue, base })
+		Ok(RefWindow { queue, base, checkpoints })
 	}
 
 	pub fn window_size(&self) -> u64 {
@@ -356,14 +539,27 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 
 	/// Get the hash of the next pruning block
 	pub fn next_hash(&mut self) -> Result<Option<BlockHash>, Error<D::Error>> {
+		Ok(self.next_block()?.map(|(hash, _)| hash))
+	}
+
+	/// Get the hash of the next pruning block together with the unix time in milliseconds at
+	/// which it was canonicalized
+	pub fn next_block(&mut self) -> Result<Option<(BlockHash, u64)>, Error<D::Error>> {
 		let res = match &mut self.queue {
-			DeathRowQueue::DbBacked { db, cache, cache_capacity, .. } => {
+			DeathRowQueue::DbBacked { db, cache, cache_capacity, opened_at, .. } => {
 				if cache.is_empty() {
-					DeathRowQueue::load_batch_from_db(db, cache, self.base, *cache_capacity)?;
+					DeathRowQueue::load_batch_from_db(
+						db,
+						cache,
+						self.base,
+						*cache_capacity,
+						*opened_at,
+					)?;
 				}
-				cache.front().map(|r| r.hash.clone())
+				cache.front().map(|r| (r.hash.clone(), r.canonicalized_at))
 			},
-			DeathRowQueue::Mem { death_rows, .. } => death_rows.front().map(|r| r.hash.clone()),
+			DeathRowQueue::Mem { death_rows, .. } =>
+				death_rows.front().map(|r| (r.hash.clone(), r.canonicalized_at)),
 		};
 		Ok(res)
 	}
@@ -372,11 +568,22 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 		self.window_size() == 0
 	}
 
+	/// Blocks before the window whose state is retained, by block number.
+	pub fn checkpoints(&self) -> Vec<(u64, BlockHash)> {
+		self.checkpoints.as_ref().map_or_else(Vec::new, |c| {
+			c.retained.iter().map(|(number, hash)| (*number, hash.clone())).collect()
+		})
+	}
+
 	// Check if a block is in the pruning window and not be pruned yet
 	pub fn have_block(&self, hash: &BlockHash, number: u64) -> HaveBlock {
 		// if the queue is empty or the block number exceed the pruning window, we definitely
 		// do not have this block
-		if self.is_empty() || number < self.base || number >= self.base + self.window_size() {
+		if number < self.base {
+			let retained = self.checkpoints.as_ref().and_then(|c| c.retained.get(&number));
+			return (retained == Some(hash)).into()
+		}
+		if self.is_empty() || number >= self.base + self.window_size() {
 			return HaveBlock::No
 		}
 		self.queue.have_block(hash, (number - self.base) as usize)
@@ -387,9 +594,21 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 		if let Some(pruned) = self.queue.pop_front(self.base)? {
 			trace!(target: "state-db", "Pruning {:?} ({} deleted)", pruned.hash, pruned.deleted.len());
 			let index = self.base;
-			commit.data.deleted.extend(pruned.deleted.into_iter());
+			let deleted = match &mut self.checkpoints {
+				Some(checkpoints) => checkpoints.prune(
+					pruned.hash,
+					index,
+					pruned.inserted,
+					pruned.deleted,
+					pruned.reinserted,
+					commit,
+				),
+				None => pruned.deleted,
+			};
+			commit.data.deleted.extend(deleted.into_iter());
 			commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), index.encode()));
 			commit.meta.deleted.push(to_journal_key(self.base));
+			commit.meta.deleted.push(to_journal_time_key(self.base));
 			self.base += 1;
 			Ok(())
 		} else {
@@ -398,11 +617,13 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 		}
 	}
 
-	/// Add a change set to the window. Creates a journal record and pushes it to `commit`
+	/// Add a change set to the window. Creates a journal record and pushes it to `commit`.
+	/// `now` is the unix time in milliseconds at which the block is canonicalized.
 	pub fn note_canonical(
 		&mut self,
 		hash: &BlockHash,
 		number: u64,
+		now: u64,
 		commit: &mut CommitSet<Key>,
 	) -> Result<(), Error<D::Error>> {
 		if self.base == 0 && self.is_empty() && number > 0 {
@@ -424,22 +645,103 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			commit.data.inserted.len(),
 			commit.data.deleted.len(),
 		);
-		let inserted = if matches!(self.queue, DeathRowQueue::Mem { .. }) {
-			commit.data.inserted.iter().map(|(k, _)| k.clone()).collect()
-		} else {
-			Default::default()
-		};
+		let inserted =
+			if matches!(self.queue, DeathRowQueue::Mem { .. }) || self.checkpoints.is_some() {
+				commit.data.inserted.iter().map(|(k, _)| k.clone()).collect()
+			} else {
+				Default::default()
+			};
 		let deleted = std::mem::take(&mut commit.data.deleted);
 		let journal_record = JournalRecord { hash: hash.clone(), inserted, deleted };
 		commit.meta.inserted.push((to_journal_key(number), journal_record.encode()));
-		self.queue.import(self.base, number, journal_record);
+		commit.meta.inserted.push((to_journal_time_key(number), now.encode()));
+		self.queue
+			.import(self.base, number, journal_record, now, self.checkpoints.is_some());
 		Ok(())
 	}
 }
 
+/// Decode the pruning journal and check that it has a record for every canonicalized block that
+/// was not pruned yet. Returns the numbers and hashes of the blocks in the window.
+pub(crate) fn check_journal<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	last_canonicalized: Option<u64>,
+) -> Result<Vec<(u64, BlockHash)>, Error<D::Error>> {
+	let Some(last_canonicalized) = last_canonicalized else { return Ok(Vec::new()) };
+	let base = match db.get_meta(&to_meta_key(LAST_PRUNED, &())).map_err(Error::Db)? {
+		Some(buffer) => u64::decode(&mut buffer.as_slice())? + 1,
+		None => 0,
+	};
+	let mut window = Vec::new();
+	for block in base..=last_canonicalized {
+		let record = db.get_meta(&to_journal_key(block)).map_err(Error::Db)?.ok_or_else(|| {
+			StateDbError::Metadata(format!("Pruning journal record of #{} is missing", block))
+		})?;
+		let record: JournalRecord<BlockHash, Key> = Decode::decode(&mut record.as_slice())?;
+		load_canonicalized_at(db, block, 0)?;
+		window.push((block, record.hash));
+	}
+	Ok(window)
+}
+
+/// Rewrite the keys of all pruning and checkpoint journal records with `sanitize`, dropping
+/// deletions of keys that are re-inserted later in the window.
+pub(crate) fn ref_counted_journal<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	sanitize: &impl Fn(Key) -> Key,
+	changes: &mut ChangeSet<Vec<u8>>,
+) -> Result<(), Error<D::Error>> {
+	let base = match db.get_meta(&to_meta_key(LAST_PRUNED, &())).map_err(Error::Db)? {
+		Some(buffer) => u64::decode(&mut buffer.as_slice())? + 1,
+		None => 0,
+	};
+	let mut records: Vec<JournalRecord<BlockHash, Key>> = Vec::new();
+	let mut death_index: HashMap<Key, usize> = HashMap::new();
+	while let Some(record) =
+		db.get_meta(&to_journal_key(base + records.len() as u64)).map_err(Error::Db)?
+	{
+		let record: JournalRecord<BlockHash, Key> = Decode::decode(&mut record.as_slice())?;
+		for k in record.inserted.iter() {
+			if let Some(index) = death_index.remove(k) {
+				records[index].deleted.retain(|deleted| deleted != k);
+			}
+		}
+		for k in record.deleted.iter() {
+			death_index.insert(k.clone(), records.len());
+		}
+		records.push(record);
+	}
+	for (block, record) in (base..).zip(records) {
+		let record = JournalRecord {
+			hash: record.hash,
+			inserted: record.inserted.into_iter().map(sanitize).collect(),
+			deleted: record.deleted.into_iter().map(sanitize).collect(),
+		};
+		changes.inserted.push((to_journal_key(block), record.encode()));
+	}
+
+	// checkpoint records are contiguous up to the last pruned block
+	let mut block = base;
+	while block > 0 {
+		block -= 1;
+		let journal_key = to_checkpoint_journal_key(block);
+		let Some(record) = db.get_meta(&journal_key).map_err(Error::Db)? else { break };
+		let record: CheckpointRecord<Key> = Decode::decode(&mut record.as_slice())?;
+		let record = CheckpointRecord {
+			inserted: record.inserted.into_iter().map(sanitize).collect(),
+			protected: record.protected.into_iter().map(sanitize).collect(),
+		};
+		changes.inserted.push((journal_key, record.encode()));
+	}
+	Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-	use super::{to_journal_key, DeathRowQueue, HaveBlock, JournalRecord, RefWindow, LAST_PRUNED};
+	use super::{
+		to_journal_key, to_journal_time_key, DeathRowQueue, HaveBlock, JournalRecord, RefWindow,
+		LAST_PRUNED,
+	};
 	use crate::{
 		noncanonical::LAST_CANONICAL,
 		test::{make_commit, make_db, TestDb},
@@ -451,7 +753,8 @@ mod tests {
 	fn check_journal(pruning: &RefWindow<H256, H256, TestDb>, db: &TestDb) {
 		let count_insertions = matches!(pruning.queue, DeathRowQueue::Mem { .. });
 		let restored: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions).unwrap();
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions, None)
+				.unwrap();
 		assert_eq!(pruning.base, restored.base);
 		assert_eq!(pruning.queue.get_mem_queue_state(), restored.queue.get_mem_queue_state());
 	}
@@ -460,7 +763,7 @@ mod tests {
 	fn created_from_empty_db() {
 		let db = make_db(&[]);
 		let pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, true, None).unwrap();
 		assert_eq!(pruning.base, 0);
 		let (death_rows, death_index) = pruning.queue.get_mem_queue_state().unwrap();
 		assert!(death_rows.is_empty());
@@ -471,7 +774,7 @@ mod tests {
 	fn prune_empty() {
 		let db = make_db(&[]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, true, None).unwrap();
 		let mut commit = CommitSet::default();
 		assert_eq!(
 			Err(Error::StateDb(StateDbError::BlockUnavailable)),
@@ -487,10 +790,10 @@ mod tests {
 	fn prune_one() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true, None).unwrap();
 		let mut commit = make_commit(&[4, 5], &[1, 3]);
 		let hash = H256::random();
-		pruning.note_canonical(&hash, 0, &mut commit).unwrap();
+		pruning.note_canonical(&hash, 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert_eq!(pruning.have_block(&hash, 0), HaveBlock::Yes);
 		assert_eq!(pruning.have_block(&hash, 0), HaveBlock::Yes);
@@ -517,12 +820,12 @@ mod tests {
 	fn prune_two() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true, None).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[5], &[2]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5])));
 
@@ -543,12 +846,12 @@ mod tests {
 	fn prune_two_pending() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true, None).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[5], &[2]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5])));
 		let mut commit = CommitSet::default();
@@ -566,15 +869,15 @@ mod tests {
 	fn reinserted_survives() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true, None).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 2, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 2, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 
@@ -598,15 +901,15 @@ mod tests {
 	fn reinserted_survive_pending() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true, None).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 2, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 2, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 
@@ -628,15 +931,15 @@ mod tests {
 	fn reinserted_ignores() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false, None).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 2, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 2, 0, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 
@@ -666,7 +969,7 @@ mod tests {
 
 		fn load_pruning_from_db(db: TestDb) -> (usize, u64) {
 			let pruning: RefWindow<u64, H256, TestDb> =
-				RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+				RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, false, None).unwrap();
 			let (cache, _) = pruning.queue.get_db_backed_queue_state().unwrap();
 			(cache.len(), pruning.base)
 		}
@@ -720,7 +1023,7 @@ mod tests {
 	fn db_backed_queue() {
 		let mut db = make_db(&[]);
 		let mut pruning: RefWindow<u64, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false, None).unwrap();
 		let cache_capacity = DEFAULT_MAX_BLOCK_CONSTRAINT as usize;
 
 		// start as an empty queue
@@ -732,7 +1035,7 @@ mod tests {
 		// queue size and content should match
 		for i in 0..(cache_capacity + 10) {
 			let mut commit = make_commit(&[], &[]);
-			pruning.note_canonical(&(i as u64), i as u64, &mut commit).unwrap();
+			pruning.note_canonical(&(i as u64), i as u64, 0, &mut commit).unwrap();
 			push_last_canonicalized(i as u64, &mut commit);
 			db.commit(&commit);
 			// blocks will fill the cache first
@@ -756,7 +1059,12 @@ mod tests {
 		// won't keep the new block in memory
 		let mut commit = CommitSet::default();
 		pruning
-			.note_canonical(&(cache_capacity as u64 + 10), cache_capacity as u64 + 10, &mut commit)
+			.note_canonical(
+				&(cache_capacity as u64 + 10),
+				cache_capacity as u64 + 10,
+				0,
+				&mut commit,
+			)
 			.unwrap();
 		assert_eq!(pruning.window_size(), cache_capacity as u64 + 11);
 		let (cache, _) = pruning.queue.get_db_backed_queue_state().unwrap();
@@ -764,7 +1072,7 @@ mod tests {
 
 		// revert the last add that no apply yet
 		// NOTE: do not commit the previous `CommitSet` to db
-		pruning = RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+		pruning = RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false, None).unwrap();
 		let cache_capacity = DEFAULT_MAX_BLOCK_CONSTRAINT as usize;
 		assert_eq!(pruning.window_size(), cache_capacity as u64 + 10);
 		let (cache, _) = pruning.queue.get_db_backed_queue_state().unwrap();
@@ -785,7 +1093,7 @@ mod tests {
 		// load a new queue from db
 		// `cache` is full again but the content of the queue should be the same
 		let pruning: RefWindow<u64, H256, TestDb> =
-			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, false, None).unwrap();
 		assert_eq!(pruning.window_size(), cache_capacity as u64 + 9);
 		let (cache, _) = pruning.queue.get_db_backed_queue_state().unwrap();
 		assert_eq!(cache.len(), cache_capacity);
@@ -798,13 +1106,13 @@ mod tests {
 	fn load_block_from_db() {
 		let mut db = make_db(&[]);
 		let mut pruning: RefWindow<u64, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false, None).unwrap();
 		let cache_capacity = DEFAULT_MAX_BLOCK_CONSTRAINT as usize;
 
 		// import blocks
 		for i in 0..(cache_capacity as u64 * 2 + 10) {
 			let mut commit = make_commit(&[], &[]);
-			pruning.note_canonical(&i, i, &mut commit).unwrap();
+			pruning.note_canonical(&i, i, 0, &mut commit).unwrap();
 			push_last_canonicalized(i as u64, &mut commit);
 			db.commit(&commit);
 		}
@@ -835,7 +1143,7 @@ mod tests {
 		// load a new queue from db
 		// `cache` should be the same
 		let pruning: RefWindow<u64, H256, TestDb> =
-			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, false, None).unwrap();
 		assert_eq!(pruning.window_size(), 10);
 		let (cache, _) = pruning.queue.get_db_backed_queue_state().unwrap();
 		assert_eq!(cache.len(), 10);
@@ -848,20 +1156,20 @@ mod tests {
 	fn get_block_from_queue() {
 		let mut db = make_db(&[]);
 		let mut pruning: RefWindow<u64, H256, TestDb> =
-			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false, None).unwrap();
 		let cache_capacity = DEFAULT_MAX_BLOCK_CONSTRAINT as u64;
 
 		// import blocks and commit to db
 		let mut commit = make_commit(&[], &[]);
 		for i in 0..(cache_capacity + 10) {
-			pruning.note_canonical(&i, i, &mut commit).unwrap();
+			pruning.note_canonical(&i, i, 0, &mut commit).unwrap();
 		}
 		db.commit(&commit);
 
 		// import a block but not commit to db yet
 		let mut pending_commit = make_commit(&[], &[]);
 		let index = cache_capacity + 10;
-		pruning.note_canonical(&index, index, &mut pending_commit).unwrap();
+		pruning.note_canonical(&index, index, 0, &mut pending_commit).unwrap();
 
 		let mut commit = make_commit(&[], &[]);
 		// prune blocks that had committed to db
@@ -890,21 +1198,119 @@ mod tests {
 		for count_insertions in [true, false] {
 			let mut db = make_db(&[]);
 			let mut pruning: RefWindow<u64, H256, TestDb> =
-				RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions).unwrap();
+				RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions, None)
+					.unwrap();
 			let block = 10000;
 
 			// import blocks
 			let mut commit = make_commit(&[], &[]);
-			pruning.note_canonical(&block, block, &mut commit).unwrap();
+			pruning.note_canonical(&block, block, 0, &mut commit).unwrap();
 			push_last_canonicalized(block, &mut commit);
 			db.commit(&commit);
 
 			// load a new queue from db
 			// `cache` should be the same
 			let pruning: RefWindow<u64, H256, TestDb> =
-				RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions).unwrap();
+				RefWindow::new(db, DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions, None).unwrap();
 
 			assert_eq!(HaveBlock::Yes, pruning.have_block(&block, block));
 		}
 	}
+
+	#[test]
+	fn canonicalization_time_is_journaled() {
+		for count_insertions in [true, false] {
+			let mut db = make_db(&[]);
+			let mut pruning: RefWindow<u64, H256, TestDb> =
+				RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions, None)
+					.unwrap();
+			for (block, time) in [(0, 1_000), (1, 2_000)] {
+				let mut commit = make_commit(&[], &[]);
+				pruning.note_canonical(&block, block, time, &mut commit).unwrap();
+				push_last_canonicalized(block, &mut commit);
+				db.commit(&commit);
+			}
+			assert_eq!(pruning.next_block().unwrap(), Some((0, 1_000)));
+
+			// the time survives a restart
+			let mut pruning: RefWindow<u64, H256, TestDb> =
+				RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions, None)
+					.unwrap();
+			assert_eq!(pruning.next_block().unwrap(), Some((0, 1_000)));
+
+			// and is removed together with the journal record
+			let mut commit = CommitSet::default();
+			pruning.prune_one(&mut commit).unwrap();
+			assert!(commit.meta.deleted.contains(&to_journal_time_key(0)));
+			db.commit(&commit);
+			assert_eq!(pruning.next_block().unwrap(), Some((1, 2_000)));
+		}
+	}
+
+	#[test]
+	fn retains_checkpoint_state() {
+		for count_insertions in [true, false] {
+			let mut db = make_db(&[1]);
+			let mut pruning: RefWindow<u64, H256, TestDb> =
+				RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions, Some(2))
+					.unwrap();
+			let blocks: [(u64, &[u64], &[u64]); 4] =
+				[(0, &[2], &[]), (1, &[3], &[1]), (2, &[4], &[3]), (3, &[], &[2, 4])];
+			for (block, inserted, deleted) in blocks {
+				let mut commit = make_commit(inserted, deleted);
+				pruning.note_canonical(&block, block, 0, &mut commit).unwrap();
+				push_last_canonicalized(block, &mut commit);
+				db.commit(&commit);
+			}
+			assert!(db.data_eq(&make_db(&[1, 2, 3, 4])));
+
+			for _ in 0..2 {
+				let mut commit = CommitSet::default();
+				pruning.prune_one(&mut commit).unwrap();
+				db.commit(&commit);
+			}
+			// the nodes tracked since the last checkpoint survive a restart
+			let mut pruning: RefWindow<u64, H256, TestDb> =
+				RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, count_insertions, Some(2))
+					.unwrap();
+			for _ in 0..2 {
+				let mut commit = CommitSet::default();
+				pruning.prune_one(&mut commit).unwrap();
+				db.commit(&commit);
+			}
+
+			// node 3 only existed between the checkpoints
+			assert!(db.data_eq(&make_db(&[1, 2, 4])));
+			assert_eq!(pruning.checkpoints(), vec![(0, 0), (2, 2)]);
+			assert_eq!(pruning.have_block(&2, 2), HaveBlock::Yes);
+			assert_eq!(pruning.have_block(&1, 1), HaveBlock::No);
+			assert_eq!(pruning.have_block(&3, 3), HaveBlock::No);
+		}
+	}
+
+	#[test]
+	fn retains_checkpoint_state_of_deleted_and_reinserted_node() {
+		// the in-memory queue drops the first deletion of node 1 once it is re-inserted
+		let mut db = make_db(&[1]);
+		let mut pruning: RefWindow<u64, H256, TestDb> =
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true, Some(4)).unwrap();
+		let blocks: [(u64, &[u64], &[u64]); 5] =
+			[(0, &[2], &[]), (1, &[], &[1]), (2, &[1], &[]), (3, &[], &[1]), (4, &[], &[])];
+		for (block, inserted, deleted) in blocks {
+			let mut commit = make_commit(inserted, deleted);
+			pruning.note_canonical(&block, block, 0, &mut commit).unwrap();
+			push_last_canonicalized(block, &mut commit);
+			db.commit(&commit);
+		}
+
+		for _ in 0..4 {
+			let mut commit = CommitSet::default();
+			pruning.prune_one(&mut commit).unwrap();
+			db.commit(&commit);
+		}
+
+		// node 1 still belongs to checkpoint 0
+		assert!(db.data_eq(&make_db(&[1, 2])));
+		assert_eq!(pruning.checkpoints(), vec![(0, 0)]);
+	}
 }
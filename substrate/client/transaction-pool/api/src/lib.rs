@@ -131,6 +131,37 @@ pub enum TransactionStatus<Hash, BlockHash> {
 	Invalid,
 }
 
+impl<Hash, BlockHash> TransactionStatus<Hash, BlockHash> {
+	/// Returns true if this is the last event emitted by [`TransactionStatusStream`].
+	pub fn is_final(&self) -> bool {
+		matches!(
+			self,
+			Self::Usurped(_) |
+				Self::Finalized(_) |
+				Self::FinalityTimeout(_) |
+				Self::Invalid |
+				Self::Dropped
+		)
+	}
+
+	/// Returns true if the transaction could be re-submitted to the pool in the future.
+	///
+	/// For example, [`TransactionStatus::Dropped`] is reported when the transaction
+	/// is removed because of the pool limits and can be accepted again later.
+	pub fn is_retriable(&self) -> bool {
+		matches!(
+			self,
+			// The number of finality watchers has been reached.
+			Self::FinalityTimeout(_) |
+				// An invalid transaction might be valid at a later time.
+				Self::Invalid |
+				// The transaction was dropped because of the limits of the pool.
+				// It can reenter the pool when other transactions are removed / finalized.
+				Self::Dropped
+		)
+	}
+}
+
 /// The stream of transaction events.
 pub type TransactionStatusStream<Hash, BlockHash> =
 	dyn Stream<Item = TransactionStatus<Hash, BlockHash>> + Send;
@@ -237,6 +268,25 @@ pub trait TransactionPool: Send + Sync {
 		>,
 	>;
 
+	/// Get an iterator for ready transactions ordered by priority, to be included in a block
+	/// built on top of the given block.
+	///
+	/// Pools keeping separate transactions for every fork return the ones of the fork of the
+	/// given block. Behaves like [`Self::ready_at`] otherwise.
+	fn ready_at_with_hash(
+		&self,
+		_hash: <Self::Block as BlockT>::Hash,
+		number: NumberFor<Self::Block>,
+	) -> Pin<
+		Box<
+			dyn Future<
+					Output = Box<dyn ReadyTransactions<Item = Arc<Self::InPoolTransaction>> + Send>,
+				> + Send,
+		>,
+	> {
+		self.ready_at(number)
+	}
+
 	/// Get an iterator for ready transactions ordered by priority.
 	fn ready(&self) -> Box<dyn ReadyTransactions<Item = Arc<Self::InPoolTransaction>> + Send>;
 
@@ -298,6 +348,11 @@ pub enum ChainEvent<B: BlockT> {
 		/// If `None`, no re-org happened on import.
 		tree_route: Option<Arc<sp_blockchain::TreeRoute<B>>>,
 	},
+	/// New block, which is not the best one, has been added to the chain.
+	NewBlock {
+		/// Hash of the block.
+		hash: B::Hash,
+	},
 	/// An existing block has been finalized.
 	Finalized {
 		/// Hash of just finalized block.
@@ -311,7 +366,9 @@ impl<B: BlockT> ChainEvent<B> {
 	/// Returns the block hash associated to the event.
 	pub fn hash(&self) -> B::Hash {
 		match self {
-			Self::NewBestBlock { hash, .. } | Self::Finalized { hash, .. } => *hash,
+			Self::NewBestBlock { hash, .. } |
+			Self::NewBlock { hash } |
+			Self::Finalized { hash, .. } => *hash,
 		}
 	}
 
@@ -87,6 +87,11 @@ where
 		self.recent_finalized_block
 	}
 
+	/// Returns the recent best block.
+	pub fn recent_best_block(&self) -> Block::Hash {
+		self.recent_best_block
+	}
+
 	/// Updates the state according to the given `ChainEvent`, returning
 	/// `Some(tree_route)` with a tree route including the blocks that need to
 	/// be enacted/retracted. If no enactment is needed then `None` is returned.
@@ -100,6 +105,11 @@ where
 		TreeRouteF: Fn(Block::Hash, Block::Hash) -> Result<TreeRoute<Block>, String>,
 		BlockNumberF: Fn(Block::Hash) -> Result<Option<NumberFor<Block>>, String>,
 	{
+		// blocks that are not the best one do not affect the enactment state.
+		if let ChainEvent::NewBlock { .. } = event {
+			return Ok(EnactmentAction::Skip)
+		}
+
 		let new_hash = event.hash();
 		let finalized = event.is_finalized();
 
@@ -179,6 +189,7 @@ where
 		match event {
 			ChainEvent::NewBestBlock { hash, .. } => self.recent_best_block = *hash,
 			ChainEvent::Finalized { hash, .. } => self.recent_finalized_block = *hash,
+			ChainEvent::NewBlock { .. } => {},
 		};
 		log::debug!(
 			target: LOG_TARGET,
@@ -20,7 +20,12 @@
 //!
 //! For a more full-featured pool, have a look at the `pool` module.
 
-use std::{cmp::Ordering, collections::HashSet, fmt, hash, sync::Arc};
+use std::{
+	cmp::Ordering,
+	collections::{HashMap, HashSet},
+	fmt, hash,
+	sync::Arc,
+};
 
 use crate::LOG_TARGET;
 use log::{debug, trace, warn};
@@ -386,14 +391,36 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	/// them. Technically the worst transaction should be evaluated by computing the entire pending
 	/// set. We use a simplified approach to remove transactions with the lowest priority first or
 	/// those that occupy the pool for the longest time in case priority is the same.
+	///
+	/// If `sender` is given, ties in priority are broken in favour of evicting transactions of
+	/// the sender that has the most transactions in the ready queue, so that a single sender
+	/// with a long chain of low-priority transactions can't push everyone else out of the pool.
 	pub fn enforce_limits(
 		&mut self,
 		ready: &Limit,
 		future: &Limit,
+		sender: Option<&SenderLimit>,
 	) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		let mut removed = vec![];
+		let mut ready_per_sender = HashMap::<Vec<u8>, usize>::new();
+		if let Some(sender) = sender {
+			if ready.is_exceeded(self.ready.len(), self.ready.bytes()) {
+				self.ready.fold::<(), _>(|_, current| {
+					if let Some(id) = sender.sender_of(&current.transaction.transaction) {
+						*ready_per_sender.entry(id.to_vec()).or_default() += 1;
+					}
+					None
+				});
+			}
+		}
 
 		while ready.is_exceeded(self.ready.len(), self.ready.bytes()) {
+			let ready_count = |transaction: &Transaction<Hash, Ex>| {
+				sender
+					.and_then(|sender| sender.sender_of(transaction))
+					.and_then(|id| ready_per_sender.get(id).copied())
+					.unwrap_or(1)
+			};
 			// find the worst transaction
 			let worst = self.ready.fold::<TransactionRef<Hash, Ex>, _>(|worst, current| {
 				let transaction = &current.transaction;
@@ -403,22 +430,50 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 						// while it prefers priority like need here, it also prefers older
 						// transactions for inclusion purposes and limit enforcement needs to prefer
 						// newer transactions instead and drop the older ones.
-						match worst.transaction.priority.cmp(&transaction.transaction.priority) {
-							Ordering::Less => worst,
-							Ordering::Equal =>
-								if worst.insertion_id > transaction.insertion_id {
-									transaction.clone()
+						//
+						// Within a single sender we drop the newest transaction though, to cut
+						// its chain from the end instead of removing it as a whole.
+						let same_sender = sender.map_or(false, |sender| {
+							sender.sender_of(&worst.transaction).map_or(false, |id| {
+								sender.sender_of(&transaction.transaction) == Some(id)
+							})
+						});
+						let ordering = worst
+							.transaction
+							.priority
+							.cmp(&transaction.transaction.priority)
+							.then_with(|| {
+								ready_count(&transaction.transaction)
+									.cmp(&ready_count(&worst.transaction))
+							})
+							.then_with(|| {
+								if same_sender {
+									transaction.insertion_id.cmp(&worst.insertion_id)
 								} else {
-									worst
-								},
-							Ordering::Greater => transaction.clone(),
+									worst.insertion_id.cmp(&transaction.insertion_id)
+								}
+							});
+						match ordering {
+							Ordering::Less => worst,
+							Ordering::Equal | Ordering::Greater => transaction.clone(),
 						}
 					})
 					.or_else(|| Some(transaction.clone()))
 			});
 
 			if let Some(worst) = worst {
-				removed.append(&mut self.remove_subtree(&[worst.transaction.hash.clone()]))
+				let mut subtree = self.remove_subtree(&[worst.transaction.hash.clone()]);
+				if let Some(sender) = sender {
+					for transaction in &subtree {
+						if let Some(count) = sender
+							.sender_of(transaction)
+							.and_then(|id| ready_per_sender.get_mut(id))
+						{
+							*count = count.saturating_sub(1);
+						}
+					}
+				}
+				removed.append(&mut subtree)
 			} else {
 				break
 			}
@@ -442,6 +497,91 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		removed
 	}
 
+	/// Makes sure that no sender has more transactions in the queues than allowed by `limit`.
+	///
+	/// Transactions of a sender over the limit are removed starting with the ones from the future
+	/// queue (newest first) and then from the ready queue, lowest priority and newest first.
+	/// Transactions that depend on a removed ready transaction are removed as well.
+	/// Returns all removed transactions.
+	pub fn enforce_sender_limits(
+		&mut self,
+		limit: &SenderLimit,
+	) -> Vec<Arc<Transaction<Hash, Ex>>> {
+		let mut per_sender = HashMap::<Vec<u8>, usize>::new();
+		for transaction in self.future.all() {
+			if let Some(id) = limit.sender_of(transaction) {
+				*per_sender.entry(id.to_vec()).or_default() += 1;
+			}
+		}
+		self.ready.fold::<(), _>(|_, current| {
+			if let Some(id) = limit.sender_of(&current.transaction.transaction) {
+				*per_sender.entry(id.to_vec()).or_default() += 1;
+			}
+			None
+		});
+		per_sender.retain(|_, count| *count > limit.count);
+		if per_sender.is_empty() {
+			return vec![]
+		}
+
+		// collect the transactions of offending senders, worst first
+		let mut future = Vec::new();
+		self.future.fold::<(), _>(|_, current| {
+			if limit
+				.sender_of(&current.transaction)
+				.map_or(false, |id| per_sender.contains_key(id))
+			{
+				future.push((current.imported_at, current.transaction.clone()));
+			}
+			None
+		});
+		future.sort_by(|a, b| b.0.cmp(&a.0));
+		let mut ready = Vec::new();
+		self.ready.fold::<(), _>(|_, current| {
+			let transaction = &current.transaction;
+			if limit
+				.sender_of(&transaction.transaction)
+				.map_or(false, |id| per_sender.contains_key(id))
+			{
+				ready.push(transaction.clone());
+			}
+			None
+		});
+		ready.sort_by(|a, b| {
+			a.transaction
+				.priority
+				.cmp(&b.transaction.priority)
+				.then_with(|| b.insertion_id.cmp(&a.insertion_id))
+		});
+		let candidates = future
+			.into_iter()
+			.map(|(_, transaction)| transaction)
+			.chain(ready.into_iter().map(|transaction| transaction.transaction));
+
+		let mut removed = vec![];
+		for candidate in candidates {
+			let exceeded = limit
+				.sender_of(&candidate)
+				.and_then(|id| per_sender.get(id))
+				.map_or(false, |count| *count > limit.count);
+			if !exceeded {
+				continue
+			}
+
+			let mut subtree = self.remove_subtree(&[candidate.hash.clone()]);
+			for transaction in &subtree {
+				if let Some(count) =
+					limit.sender_of(transaction).and_then(|id| per_sender.get_mut(id))
+				{
+					*count = count.saturating_sub(1);
+				}
+			}
+			removed.append(&mut subtree);
+		}
+
+		removed
+	}
+
 	/// Removes all transactions represented by the hashes and all other transactions
 	/// that depend on them.
 	///
@@ -529,6 +669,30 @@ impl Limit {
 	}
 }
 
+/// Limits applied to the transactions of a single sender.
+///
+/// Senders are identified by the leading `tag_prefix_len` bytes of the first tag a transaction
+/// provides. For FRAME-based runtimes the tag is the SCALE-encoded `(AccountId, Nonce)` pair, so
+/// the prefix length is the encoded length of the account id. Transactions whose first tag is
+/// shorter than the prefix are not attributed to any sender.
+#[derive(Debug, Clone)]
+pub struct SenderLimit {
+	/// Number of leading bytes of the first provided tag that identify the sender.
+	pub tag_prefix_len: usize,
+	/// Maximal number of transactions of a single sender in both queues.
+	pub count: usize,
+}
+
+impl SenderLimit {
+	/// Returns the sender identifier of the given transaction, if any.
+	pub fn sender_of<'a, Hash, Ex>(
+		&self,
+		transaction: &'a Transaction<Hash, Ex>,
+	) -> Option<&'a [u8]> {
+		transaction.provides.first().and_then(|tag| tag.get(..self.tag_prefix_len))
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -1018,6 +1182,117 @@ source: TransactionSource::External, requires: [03, 02], provides: [04], data: [
 		assert_eq!(pool.future.len(), 0);
 	}
 
+	#[test]
+	fn should_enforce_sender_limits() {
+		// given
+		let mut pool = pool();
+		let limit = SenderLimit { tag_prefix_len: 1, count: 2 };
+		pool.import(Transaction { hash: 1, provides: vec![vec![1, 0]], ..DEFAULT_TX.clone() })
+			.unwrap();
+		pool.import(Transaction {
+			hash: 2,
+			requires: vec![vec![1, 0]],
+			provides: vec![vec![1, 1]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			hash: 3,
+			requires: vec![vec![1, 1]],
+			provides: vec![vec![1, 2]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			hash: 4,
+			requires: vec![vec![1, 5]],
+			provides: vec![vec![1, 6]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction { hash: 5, provides: vec![vec![2, 0]], ..DEFAULT_TX.clone() })
+			.unwrap();
+		assert_eq!(pool.ready.len(), 4);
+		assert_eq!(pool.future.len(), 1);
+
+		// when
+		let removed = pool.enforce_sender_limits(&limit);
+
+		// then
+		assert_eq!(removed.into_iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![4, 3]);
+		assert_eq!(pool.ready.len(), 3);
+		assert!(pool.ready.contains(&5));
+		assert_eq!(pool.future.len(), 0);
+	}
+
+	#[test]
+	fn should_prefer_evicting_sender_with_most_transactions() {
+		// given
+		let mut pool = pool();
+		let ready = Limit { count: 3, total_bytes: 1000 };
+		let future = Limit { count: 10, total_bytes: 1000 };
+		let sender = SenderLimit { tag_prefix_len: 1, count: 10 };
+		pool.import(Transaction { hash: 1, provides: vec![vec![2, 0]], ..DEFAULT_TX.clone() })
+			.unwrap();
+		pool.import(Transaction { hash: 2, provides: vec![vec![1, 0]], ..DEFAULT_TX.clone() })
+			.unwrap();
+		pool.import(Transaction {
+			hash: 3,
+			requires: vec![vec![1, 0]],
+			provides: vec![vec![1, 1]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			hash: 4,
+			requires: vec![vec![1, 1]],
+			provides: vec![vec![1, 2]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+
+		// when
+		let removed = pool.enforce_limits(&ready, &future, Some(&sender));
+
+		// then
+		assert_eq!(removed.into_iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![4]);
+		assert_eq!(pool.ready.len(), 3);
+		assert!(pool.ready.contains(&1));
+	}
+
+	#[test]
+	fn should_evict_oldest_transaction_without_sender_limit() {
+		// given
+		let mut pool = pool();
+		let ready = Limit { count: 3, total_bytes: 1000 };
+		let future = Limit { count: 10, total_bytes: 1000 };
+		pool.import(Transaction { hash: 1, provides: vec![vec![2, 0]], ..DEFAULT_TX.clone() })
+			.unwrap();
+		pool.import(Transaction { hash: 2, provides: vec![vec![1, 0]], ..DEFAULT_TX.clone() })
+			.unwrap();
+		pool.import(Transaction {
+			hash: 3,
+			requires: vec![vec![1, 0]],
+			provides: vec![vec![1, 1]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			hash: 4,
+			requires: vec![vec![1, 1]],
+			provides: vec![vec![1, 2]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+
+		// when
+		let removed = pool.enforce_limits(&ready, &future, None);
+
+		// then
+		assert_eq!(removed.into_iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![1]);
+		assert_eq!(pool.ready.len(), 3);
+	}
+
 	#[test]
 	fn should_accept_future_transactions_when_explicitly_asked_to() {
 		// given
@@ -107,6 +107,11 @@ impl<H: hash::Hash + traits::Member + Serialize, C: ChainApi> Listener<H, C> {
 		debug!(target: LOG_TARGET, "[{:?}] Pruned at {:?}", tx, block_hash);
 		// Get the transactions included in the given block hash.
 		let txs = self.finality_watchers.entry(block_hash).or_insert(vec![]);
+		// The inclusion of a transaction in a fork may be reported before the fork becomes the
+		// best one and the transaction is pruned again.
+		if txs.contains(tx) {
+			return
+		}
 		txs.push(tx.clone());
 		// Current transaction is the last one included.
 		let tx_index = txs.len() - 1;
@@ -16,9 +16,9 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
 
-use crate::LOG_TARGET;
+use crate::{metrics::MetricsLink, LOG_TARGET};
 use futures::{channel::mpsc::Receiver, Future};
 use sc_transaction_pool_api::error;
 use sp_blockchain::TreeRoute;
@@ -115,10 +115,24 @@ pub struct Options {
 	pub ready: base::Limit,
 	/// Future queue limits.
 	pub future: base::Limit,
+	/// Per-sender limits.
+	///
+	/// If set, no sender may have more transactions in the pool than allowed, and evictions
+	/// caused by exceeding the `ready` limits prefer senders with the most transactions.
+	pub sender: Option<base::SenderLimit>,
 	/// Reject future transactions.
 	pub reject_future_transactions: bool,
+	/// Keep a separate view of the pool for every unfinalized fork.
+	///
+	/// Only used by [`crate::BasicPool`], the graph pool itself always holds a single set.
+	pub fork_aware: bool,
 	/// How long the extrinsic is banned for.
 	pub ban_time: Duration,
+	/// Path of the journal the pool is persisted to.
+	///
+	/// If set, ready and future transactions and banned transactions are restored from the
+	/// journal when the pool is created. Only used by [`crate::BasicPool`].
+	pub journal: Option<PathBuf>,
 }
 
 impl Default for Options {
@@ -126,8 +140,11 @@ impl Default for Options {
 		Self {
 			ready: base::Limit { count: 8192, total_bytes: 20 * 1024 * 1024 },
 			future: base::Limit { count: 512, total_bytes: 1 * 1024 * 1024 },
+			sender: None,
 			reject_future_transactions: false,
+			fork_aware: false,
 			ban_time: Duration::from_secs(60 * 30),
+			journal: None,
 		}
 	}
 }
@@ -151,6 +168,23 @@ impl<B: ChainApi> Pool<B> {
 		Self { validated_pool: Arc::new(ValidatedPool::new(options, is_validator, api)) }
 	}
 
+	/// Create a new transaction pool reporting to the given metrics.
+	pub(crate) fn new_with_metrics(
+		options: Options,
+		is_validator: IsValidator,
+		api: Arc<B>,
+		metrics: MetricsLink,
+	) -> Self {
+		Self {
+			validated_pool: Arc::new(ValidatedPool::new_with_metrics(
+				options,
+				is_validator,
+				api,
+				metrics,
+			)),
+		}
+	}
+
 	/// Imports a bunch of unverified extrinsics to the pool
 	pub async fn submit_at(
 		&self,
@@ -455,7 +489,10 @@ impl<B: ChainApi> Clone for Pool<B> {
 
 #[cfg(test)]
 mod tests {
-	use super::{super::base_pool::Limit, *};
+	use super::{
+		super::base_pool::{Limit, SenderLimit},
+		*,
+	};
 	use crate::tests::{pool, uxt, TestApi, INVALID_NONCE};
 	use assert_matches::assert_matches;
 	use codec::Encode;
@@ -707,6 +744,40 @@ mod tests {
 		assert!(!pool.validated_pool.is_banned(&hash2));
 	}
 
+	#[test]
+	fn should_not_ban_transactions_over_sender_limit() {
+		sp_tracing::try_init_simple();
+
+		// given
+		let sender = SenderLimit { tag_prefix_len: 0, count: 1 };
+		let options = Options { sender: Some(sender), ..Default::default() };
+
+		let api = Arc::new(TestApi::default());
+		let pool = Pool::new(options, true.into(), api.clone());
+
+		// when
+		let xts = (0..2).map(|nonce| {
+			uxt(Transfer {
+				from: Alice.into(),
+				to: AccountId::from_h256(H256::from_low_u64_be(2)),
+				amount: 5,
+				nonce,
+			})
+		});
+		let hashes = xts
+			.map(|xt| {
+				let hash = api.hash_and_length(&xt).0;
+				let _ = block_on(pool.submit_one(api.expect_hash_from_number(0), SOURCE, xt));
+				hash
+			})
+			.collect::<Vec<_>>();
+
+		// then
+		let status = pool.validated_pool().status();
+		assert_eq!(status.ready + status.future, 1);
+		assert!(hashes.iter().all(|hash| !pool.validated_pool.is_banned(hash)));
+	}
+
 	#[test]
 	fn should_error_if_reject_immediately() {
 		// given
@@ -63,10 +63,15 @@ impl<Hash: hash::Hash + Eq + Clone> PoolRotator<Hash> {
 
 	/// Bans given set of hashes.
 	pub fn ban(&self, now: &Instant, hashes: impl IntoIterator<Item = Hash>) {
+		self.ban_for(now, hashes.into_iter().map(|hash| (hash, self.ban_time)))
+	}
+
+	/// Bans given set of hashes, each for the given amount of time.
+	pub fn ban_for(&self, now: &Instant, bans: impl IntoIterator<Item = (Hash, Duration)>) {
 		let mut banned = self.banned_until.write();
 
-		for hash in hashes {
-			banned.insert(hash, *now + self.ban_time);
+		for (hash, duration) in bans {
+			banned.insert(hash, *now + duration);
 		}
 
 		if banned.len() > 2 * EXPECTED_SIZE {
@@ -95,6 +100,16 @@ impl<Hash: hash::Hash + Eq + Clone> PoolRotator<Hash> {
 		true
 	}
 
+	/// Returns currently banned hashes together with the remaining ban time.
+	pub fn banned(&self, now: &Instant) -> Vec<(Hash, Duration)> {
+		self.banned_until
+			.read()
+			.iter()
+			.filter(|(_, until)| **until > *now)
+			.map(|(hash, until)| (hash.clone(), *until - *now))
+			.collect()
+	}
+
 	/// Removes timed bans.
 	pub fn clear_timeouts(&self, now: &Instant) {
 		let mut banned = self.banned_until.write();
@@ -178,6 +193,25 @@ mod tests {
 		assert!(!rotator.is_banned(&hash));
 	}
 
+	#[test]
+	fn should_report_remaining_ban_time() {
+		// given
+		let rotator = rotator();
+		let now = Instant::now();
+		rotator.ban_for(&now, vec![(1, Duration::from_secs(10)), (2, Duration::from_secs(20))]);
+
+		// when
+		let mut banned = rotator.banned(&(now + Duration::from_secs(5)));
+		banned.sort();
+
+		// then
+		assert_eq!(banned, vec![(1, Duration::from_secs(5)), (2, Duration::from_secs(15))]);
+		assert_eq!(
+			rotator.banned(&(now + Duration::from_secs(15))),
+			vec![(2, Duration::from_secs(5))]
+		);
+	}
+
 	#[test]
 	fn should_garbage_collect() {
 		// given
@@ -22,7 +22,7 @@ use std::{
 	sync::Arc,
 };
 
-use crate::LOG_TARGET;
+use crate::{metrics::MetricsLink, LOG_TARGET};
 use futures::channel::mpsc::{channel, Sender};
 use parking_lot::{Mutex, RwLock};
 use sc_transaction_pool_api::{error, PoolStatus, ReadyTransactions};
@@ -32,7 +32,7 @@ use sp_runtime::{
 	traits::{self, SaturatedConversion},
 	transaction_validity::{TransactionSource, TransactionTag as Tag, ValidTransaction},
 };
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use super::{
 	base_pool::{self as base, PruneStatus},
@@ -109,11 +109,22 @@ pub struct ValidatedPool<B: ChainApi> {
 	pub(crate) pool: RwLock<base::BasePool<ExtrinsicHash<B>, ExtrinsicFor<B>>>,
 	import_notification_sinks: Mutex<Vec<Sender<ExtrinsicHash<B>>>>,
 	rotator: PoolRotator<ExtrinsicHash<B>>,
+	metrics: MetricsLink,
 }
 
 impl<B: ChainApi> ValidatedPool<B> {
 	/// Create a new transaction pool.
 	pub fn new(options: Options, is_validator: IsValidator, api: Arc<B>) -> Self {
+		Self::new_with_metrics(options, is_validator, api, Default::default())
+	}
+
+	/// Create a new transaction pool reporting to the given metrics.
+	pub(crate) fn new_with_metrics(
+		options: Options,
+		is_validator: IsValidator,
+		api: Arc<B>,
+		metrics: MetricsLink,
+	) -> Self {
 		let base_pool = base::BasePool::new(options.reject_future_transactions);
 		let ban_time = options.ban_time;
 		Self {
@@ -124,6 +135,7 @@ impl<B: ChainApi> ValidatedPool<B> {
 			pool: RwLock::new(base_pool),
 			import_notification_sinks: Default::default(),
 			rotator: PoolRotator::new(ban_time),
+			metrics,
 		}
 	}
 
@@ -132,6 +144,20 @@ impl<B: ChainApi> ValidatedPool<B> {
 		self.rotator.ban(now, hashes)
 	}
 
+	/// Bans given set of hashes, each for the given amount of time.
+	pub fn ban_for(
+		&self,
+		now: &Instant,
+		bans: impl IntoIterator<Item = (ExtrinsicHash<B>, Duration)>,
+	) {
+		self.rotator.ban_for(now, bans)
+	}
+
+	/// Returns currently banned hashes together with the remaining ban time.
+	pub fn banned(&self, now: &Instant) -> Vec<(ExtrinsicHash<B>, Duration)> {
+		self.rotator.banned(now)
+	}
+
 	/// Returns true if transaction with given hash is currently banned from the pool.
 	pub fn is_banned(&self, hash: &ExtrinsicHash<B>) -> bool {
 		self.rotator.is_banned(hash)
@@ -230,8 +256,30 @@ impl<B: ChainApi> ValidatedPool<B> {
 		let status = self.pool.read().status();
 		let ready_limit = &self.options.ready;
 		let future_limit = &self.options.future;
+		let sender_limit = self.options.sender.as_ref();
 
 		log::debug!(target: LOG_TARGET, "Pool Status: {:?}", status);
+		let mut removed = HashSet::new();
+		// Transactions over their sender's limit are not banned, they may be resubmitted once
+		// the sender's other transactions are included.
+		let mut banned = Vec::new();
+		if let Some(sender_limit) = sender_limit {
+			let dropped = self.pool.write().enforce_sender_limits(sender_limit);
+			if !dropped.is_empty() {
+				log::debug!(
+					target: LOG_TARGET,
+					"Enforcing sender limits ({} per sender): {} dropped",
+					sender_limit.count,
+					dropped.len(),
+				);
+				self.metrics.report(|metrics| {
+					metrics.sender_limit_evicted_transactions.inc_by(dropped.len() as u64)
+				});
+			}
+			removed.extend(dropped.into_iter().map(|x| x.hash));
+		}
+
+		let status = if removed.is_empty() { status } else { self.pool.read().status() };
 		if ready_limit.is_exceeded(status.ready, status.ready_bytes) ||
 			future_limit.is_exceeded(status.future, status.future_bytes)
 		{
@@ -245,31 +293,29 @@ impl<B: ChainApi> ValidatedPool<B> {
 			);
 
 			// clean up the pool
-			let removed = {
-				let mut pool = self.pool.write();
-				let removed = pool
-					.enforce_limits(ready_limit, future_limit)
-					.into_iter()
-					.map(|x| x.hash)
-					.collect::<HashSet<_>>();
-				// ban all removed transactions
-				self.rotator.ban(&Instant::now(), removed.iter().copied());
-				removed
-			};
-			if !removed.is_empty() {
-				log::debug!(target: LOG_TARGET, "Enforcing limits: {} dropped", removed.len());
+			let dropped = self.pool.write().enforce_limits(ready_limit, future_limit, sender_limit);
+			if !dropped.is_empty() {
+				log::debug!(target: LOG_TARGET, "Enforcing limits: {} dropped", dropped.len());
+				self.metrics.report(|metrics| {
+					metrics.limit_evicted_transactions.inc_by(dropped.len() as u64)
+				});
 			}
+			banned.extend(dropped.into_iter().map(|x| x.hash));
+			removed.extend(banned.iter().copied());
+		}
+
+		if !removed.is_empty() {
+			// ban the transactions removed because of the pool limits
+			self.rotator.ban(&Instant::now(), banned);
 
 			// run notifications
 			let mut listener = self.listener.write();
 			for h in &removed {
 				listener.dropped(h, None);
 			}
-
-			removed
-		} else {
-			Default::default()
 		}
+
+		removed
 	}
 
 	/// Import a single extrinsic and starts to watch their progress in the pool.
@@ -0,0 +1,269 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Journal of the transaction pool.
+//!
+//! The journal is a side file holding the SCALE-encoded ready and future transactions of the pool
+//! together with the currently banned transactions. It is written periodically and when the pool
+//! is dropped, and replayed when the pool is created, so that pending transactions survive node
+//! restarts and known-bad transactions are not re-admitted.
+
+use std::{
+	fs, io,
+	path::PathBuf,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+	thread,
+	time::{Duration, Instant},
+};
+
+use crate::LOG_TARGET;
+use codec::{Decode, Encode};
+use parking_lot::Mutex;
+use sp_runtime::transaction_validity::TransactionSource;
+
+/// Version of the journal format, bumped on incompatible changes.
+const JOURNAL_VERSION: u32 = 1;
+
+/// Minimal time between two periodic writes of the journal.
+const JOURNAL_WRITE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Content of the journal.
+#[derive(Debug, PartialEq, Encode, Decode)]
+pub(crate) struct JournalContent<Hash, Ex> {
+	/// Ready and future transactions together with the source they were submitted from.
+	pub transactions: Vec<(TransactionSource, Ex)>,
+	/// Banned transactions together with the remaining ban time in milliseconds.
+	pub banned: Vec<(Hash, u64)>,
+}
+
+/// Journal stored in a side file.
+pub(crate) struct Journal {
+	path: PathBuf,
+	last_write: Mutex<Instant>,
+	/// Sequence number of the last write requested with [`Journal::write_in_background`] or
+	/// [`Journal::write_last`].
+	requested: AtomicU64,
+	/// Sequence number of the last sequenced write, locked while writing.
+	written: Mutex<u64>,
+}
+
+impl Journal {
+	/// Creates a journal stored at the given path.
+	pub(crate) fn new(path: PathBuf) -> Self {
+		Self {
+			path,
+			last_write: Mutex::new(Instant::now()),
+			requested: AtomicU64::new(0),
+			written: Mutex::new(0),
+		}
+	}
+
+	/// Returns `true` if the journal should be written periodically again.
+	pub(crate) fn should_write(&self) -> bool {
+		self.last_write.lock().elapsed() >= JOURNAL_WRITE_INTERVAL
+	}
+
+	/// Reads the journal.
+	///
+	/// Returns `None` if there is no journal or it can't be decoded.
+	pub(crate) fn read<Hash: Decode, Ex: Decode>(&self) -> Option<JournalContent<Hash, Ex>> {
+		let bytes = match fs::read(&self.path) {
+			Ok(bytes) => bytes,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+			Err(e) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Failed to read transaction pool journal {}: {}",
+					self.path.display(),
+					e,
+				);
+				return None
+			},
+		};
+
+		match <(u32, JournalContent<Hash, Ex>)>::decode(&mut &bytes[..]) {
+			Ok((JOURNAL_VERSION, content)) => Some(content),
+			Ok((version, _)) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Ignoring transaction pool journal {} with unsupported version {}",
+					self.path.display(),
+					version,
+				);
+				None
+			},
+			Err(e) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Failed to decode transaction pool journal {}: {}",
+					self.path.display(),
+					e,
+				);
+				None
+			},
+		}
+	}
+
+	/// Writes the journal, replacing the previous one.
+	pub(crate) fn write<Hash: Encode, Ex: Encode>(
+		&self,
+		content: &JournalContent<Hash, Ex>,
+	) -> io::Result<()> {
+		*self.last_write.lock() = Instant::now();
+
+		if let Some(parent) = self.path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		// Write to a temporary file first, so that a crash doesn't leave a truncated journal.
+		let tmp_path = self.path.with_extension("tmp");
+		fs::write(&tmp_path, (JOURNAL_VERSION, content).encode())?;
+		fs::rename(&tmp_path, &self.path)
+	}
+
+	/// Writes the journal on a separate thread, so that the caller doesn't block on the IO.
+	///
+	/// Used for the periodic writes. A plain thread is used rather than the task spawner, so
+	/// that the write doesn't occupy a blocking task slot. A write is skipped if a more recent
+	/// one has completed already.
+	pub(crate) fn write_in_background<Hash, Ex>(
+		self: &Arc<Self>,
+		content: JournalContent<Hash, Ex>,
+	) -> io::Result<thread::JoinHandle<()>>
+	where
+		Hash: Encode + Send + 'static,
+		Ex: Encode + Send + 'static,
+	{
+		// Don't request another periodic write while this one is in progress.
+		*self.last_write.lock() = Instant::now();
+		let sequence = self.requested.fetch_add(1, Ordering::Relaxed) + 1;
+		let journal = self.clone();
+
+		thread::Builder::new()
+			.name("txpool-journal".into())
+			.spawn(move || journal.write_sequenced(sequence, &content))
+	}
+
+	/// Writes the journal on the calling thread, once the background write in progress (if any)
+	/// is done. Background writes requested before are skipped.
+	///
+	/// Used when the pool is dropped, so that the journal is on disk before the node exits.
+	pub(crate) fn write_last<Hash: Encode, Ex: Encode>(&self, content: &JournalContent<Hash, Ex>) {
+		let sequence = self.requested.fetch_add(1, Ordering::Relaxed) + 1;
+		self.write_sequenced(sequence, content);
+	}
+
+	/// Writes the journal unless a more recently requested write has completed already.
+	fn write_sequenced<Hash: Encode, Ex: Encode>(
+		&self,
+		sequence: u64,
+		content: &JournalContent<Hash, Ex>,
+	) {
+		let mut written = self.written.lock();
+		if *written > sequence {
+			return
+		}
+		*written = sequence;
+
+		match self.write(content) {
+			Ok(()) => log::debug!(
+				target: LOG_TARGET,
+				"Written {} transactions and {} bans to the journal",
+				content.transactions.len(),
+				content.banned.len(),
+			),
+			Err(e) => log::warn!(target: LOG_TARGET, "Failed to write the journal: {}", e),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn content() -> JournalContent<u64, Vec<u8>> {
+		JournalContent {
+			transactions: vec![
+				(TransactionSource::External, vec![1, 2, 3]),
+				(TransactionSource::Local, vec![4]),
+			],
+			banned: vec![(5, 1000)],
+		}
+	}
+
+	#[test]
+	fn should_write_and_read_journal() {
+		let dir = tempfile::tempdir().unwrap();
+		let journal = Journal::new(dir.path().join("txpool").join("journal"));
+		assert_eq!(journal.read::<u64, Vec<u8>>(), None);
+
+		journal.write(&content()).unwrap();
+
+		assert_eq!(journal.read(), Some(content()));
+		assert!(!journal.should_write());
+	}
+
+	#[test]
+	fn should_write_journal_in_background() {
+		let dir = tempfile::tempdir().unwrap();
+		let journal = Arc::new(Journal::new(dir.path().join("journal")));
+
+		journal.write_in_background(content()).unwrap().join().unwrap();
+		assert_eq!(journal.read(), Some(content()));
+
+		// the most recent content is kept, whichever write completes first.
+		let written = journal.written.lock();
+		let outdated =
+			JournalContent::<u64, Vec<u8>> { transactions: Vec::new(), banned: vec![(1, 1)] };
+		let handles = [
+			journal.write_in_background(outdated).unwrap(),
+			journal.write_in_background(content()).unwrap(),
+		];
+		drop(written);
+		handles.into_iter().for_each(|handle| handle.join().unwrap());
+		assert_eq!(journal.read(), Some(content()));
+	}
+
+	#[test]
+	fn should_write_last_journal_over_pending_background_writes() {
+		let dir = tempfile::tempdir().unwrap();
+		let journal = Arc::new(Journal::new(dir.path().join("journal")));
+
+		let written = journal.written.lock();
+		let outdated =
+			JournalContent::<u64, Vec<u8>> { transactions: Vec::new(), banned: vec![(1, 1)] };
+		let handle = journal.write_in_background(outdated).unwrap();
+		drop(written);
+		journal.write_last(&content());
+		assert_eq!(journal.read(), Some(content()));
+
+		handle.join().unwrap();
+		assert_eq!(journal.read(), Some(content()));
+	}
+
+	#[test]
+	fn should_ignore_journal_with_unknown_version() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("journal");
+		fs::write(&path, (JOURNAL_VERSION + 1, content()).encode()).unwrap();
+
+		assert_eq!(Journal::new(path).read::<u64, Vec<u8>>(), None);
+	}
+}
@@ -26,21 +26,20 @@ mod api;
 mod enactment_state;
 pub mod error;
 mod graph;
+mod journal;
 mod metrics;
 mod revalidation;
 #[cfg(test)]
 mod tests;
+mod view;
 
 pub use crate::api::FullChainApi;
 use async_trait::async_trait;
 use enactment_state::{EnactmentAction, EnactmentState};
-use futures::{
-	channel::oneshot,
-	future::{self, ready},
-	prelude::*,
-};
+use futures::{channel::oneshot, future, prelude::*};
 pub use graph::{
-	base_pool::Limit as PoolLimit, ChainApi, Options, Pool, Transaction, ValidatedTransaction,
+	base_pool::{Limit as PoolLimit, SenderLimit as PoolSenderLimit},
+	ChainApi, Options, Pool, Transaction, ValidatedTransaction,
 };
 use parking_lot::Mutex;
 use std::{
@@ -93,6 +92,8 @@ where
 	ready_poll: Arc<Mutex<ReadyPoll<ReadyIteratorFor<PoolApi>, Block>>>,
 	metrics: PrometheusMetrics,
 	enactment_state: Arc<Mutex<EnactmentState<Block>>>,
+	views: Option<Arc<view::ViewStore<PoolApi>>>,
+	journal: Option<Arc<journal::Journal>>,
 }
 
 struct ReadyPoll<T, Block: BlockT> {
@@ -183,6 +184,8 @@ where
 					best_block_hash,
 					finalized_hash,
 				))),
+				views: None,
+				journal: None,
 			},
 			background_task,
 		)
@@ -201,7 +204,17 @@ where
 		best_block_hash: Block::Hash,
 		finalized_hash: Block::Hash,
 	) -> Self {
-		let pool = Arc::new(graph::Pool::new(options, is_validator, pool_api.clone()));
+		let metrics = PrometheusMetrics::new(prometheus);
+		let views = options
+			.fork_aware
+			.then(|| Arc::new(view::ViewStore::new(pool_api.clone(), options.clone())));
+		let journal = options.journal.clone().map(|path| Arc::new(journal::Journal::new(path)));
+		let pool = Arc::new(graph::Pool::new_with_metrics(
+			options,
+			is_validator,
+			pool_api.clone(),
+			metrics.clone(),
+		));
 		let (revalidation_queue, background_task) = match revalidation_type {
 			RevalidationType::Light =>
 				(revalidation::RevalidationQueue::new(pool_api.clone(), pool.clone()), None),
@@ -229,11 +242,13 @@ where
 				RevalidationType::Full => RevalidationStrategy::Always,
 			})),
 			ready_poll: Arc::new(Mutex::new(ReadyPoll::new(best_block_number))),
-			metrics: PrometheusMetrics::new(prometheus),
+			metrics,
 			enactment_state: Arc::new(Mutex::new(EnactmentState::new(
 				best_block_hash,
 				finalized_hash,
 			))),
+			views,
+			journal,
 		}
 	}
 
@@ -246,6 +261,25 @@ where
 	pub fn api(&self) -> &PoolApi {
 		&self.api
 	}
+
+	/// Get an iterator for ready transactions of the fork ending at the given leaf.
+	///
+	/// Only available in fork-aware mode and for leaves the pool has been notified about,
+	/// returns `None` otherwise.
+	pub fn ready_at_leaf(&self, at: Block::Hash) -> Option<ReadyIteratorFor<PoolApi>> {
+		let view = self.views.as_ref()?.get(&at)?;
+		Some(Box::new(view.pool.validated_pool().ready()))
+	}
+
+	/// Returns the pool ready transactions are served from.
+	///
+	/// In fork-aware mode this is the view of the best block, otherwise the main pool.
+	fn ready_pool(&self) -> graph::Pool<PoolApi> {
+		self.views
+			.as_ref()
+			.and_then(|views| views.best_view())
+			.map_or_else(|| (*self.pool).clone(), |view| view.pool.clone())
+	}
 }
 
 impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
@@ -265,11 +299,24 @@ where
 		xts: Vec<TransactionFor<Self>>,
 	) -> PoolFuture<Vec<Result<TxHash<Self>, Self::Error>>, Self::Error> {
 		let pool = self.pool.clone();
+		let views = self.views.clone().map(|views| (views, xts.clone()));
 
 		self.metrics
 			.report(|metrics| metrics.submitted_transactions.inc_by(xts.len() as u64));
 
-		async move { pool.submit_at(at, source, xts).await }.boxed()
+		async move {
+			let results = pool.submit_at(at, source, xts).await?;
+			if let Some((views, xts)) = views {
+				let accepted = xts
+					.into_iter()
+					.zip(&results)
+					.filter_map(|(xt, result)| result.is_ok().then_some(xt))
+					.collect();
+				views.submit(source, accepted).await;
+			}
+			Ok(results)
+		}
+		.boxed()
 	}
 
 	fn submit_one(
@@ -279,10 +326,18 @@ where
 		xt: TransactionFor<Self>,
 	) -> PoolFuture<TxHash<Self>, Self::Error> {
 		let pool = self.pool.clone();
+		let views = self.views.clone().map(|views| (views, xt.clone()));
 
 		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
 
-		async move { pool.submit_one(at, source, xt).await }.boxed()
+		async move {
+			let hash = pool.submit_one(at, source, xt).await?;
+			if let Some((views, xt)) = views {
+				views.submit(source, vec![xt]).await;
+			}
+			Ok(hash)
+		}
+		.boxed()
 	}
 
 	fn submit_and_watch(
@@ -292,11 +347,15 @@ where
 		xt: TransactionFor<Self>,
 	) -> PoolFuture<Pin<Box<TransactionStatusStreamFor<Self>>>, Self::Error> {
 		let pool = self.pool.clone();
+		let views = self.views.clone().map(|views| (views, xt.clone()));
 
 		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
 
 		async move {
 			let watcher = pool.submit_and_watch(at, source, xt).await?;
+			if let Some((views, xt)) = views {
+				views.submit(source, vec![xt]).await;
+			}
 
 			Ok(watcher.into_stream().boxed())
 		}
@@ -305,13 +364,16 @@ where
 
 	fn remove_invalid(&self, hashes: &[TxHash<Self>]) -> Vec<Arc<Self::InPoolTransaction>> {
 		let removed = self.pool.validated_pool().remove_invalid(hashes);
+		if let Some(views) = &self.views {
+			views.remove_invalid(hashes);
+		}
 		self.metrics
 			.report(|metrics| metrics.validations_invalid.inc_by(removed.len() as u64));
 		removed
 	}
 
 	fn status(&self) -> PoolStatus {
-		self.pool.validated_pool().status()
+		self.ready_pool().validated_pool().status()
 	}
 
 	fn import_notification_stream(&self) -> ImportNotificationStream<TxHash<Self>> {
@@ -327,7 +389,7 @@ where
 	}
 
 	fn ready_transaction(&self, hash: &TxHash<Self>) -> Option<Arc<Self::InPoolTransaction>> {
-		self.pool.validated_pool().ready_by_hash(hash)
+		self.ready_pool().validated_pool().ready_by_hash(hash)
 	}
 
 	fn ready_at(&self, at: NumberFor<Self::Block>) -> PolledIterator<PoolApi> {
@@ -342,7 +404,7 @@ where
 
 		if self.ready_poll.lock().updated_at() >= at {
 			log::trace!(target: LOG_TARGET, "Transaction pool already processed block  #{}", at);
-			let iterator: ReadyIteratorFor<PoolApi> = Box::new(self.pool.validated_pool().ready());
+			let iterator: ReadyIteratorFor<PoolApi> = self.ready();
 			return async move { iterator }.boxed()
 		}
 
@@ -358,12 +420,38 @@ where
 			.boxed()
 	}
 
+	fn ready_at_with_hash(
+		&self,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+	) -> PolledIterator<PoolApi> {
+		let views = match &self.views {
+			Some(views) => views.clone(),
+			None => return self.ready_at(number),
+		};
+		if let Some(iterator) = self.ready_at_leaf(hash) {
+			return async move { iterator }.boxed()
+		}
+
+		// The view of the block is built once the pool has processed it.
+		let ready_at = self.ready_at(number);
+		async move {
+			let iterator = ready_at.await;
+			match views.get(&hash) {
+				Some(view) => Box::new(view.pool.validated_pool().ready()),
+				None => iterator,
+			}
+		}
+		.boxed()
+	}
+
 	fn ready(&self) -> ReadyIteratorFor<PoolApi> {
-		Box::new(self.pool.validated_pool().ready())
+		Box::new(self.ready_pool().validated_pool().ready())
 	}
 
 	fn futures(&self) -> Vec<Self::InPoolTransaction> {
-		let pool = self.pool.validated_pool().pool.read();
+		let ready_pool = self.ready_pool();
+		let pool = ready_pool.validated_pool().pool.read();
 
 		pool.futures().cloned().collect::<Vec<_>>()
 	}
@@ -404,9 +492,79 @@ where
 			client.usage_info().chain.best_hash,
 			client.usage_info().chain.finalized_hash,
 		));
+		pool.restore_journal(client.usage_info().chain.best_hash);
 
 		pool
 	}
+
+	/// Restores the transactions and bans stored in the journal.
+	///
+	/// Transactions are revalidated at the given block and only the valid ones that are not
+	/// banned are imported.
+	fn restore_journal(&self, at: Block::Hash) {
+		use sp_runtime::traits::SaturatedConversion;
+
+		let Some(content) = self
+			.journal
+			.as_ref()
+			.and_then(|journal| journal.read::<Block::Hash, Block::Extrinsic>())
+		else {
+			return
+		};
+
+		let validated_pool = self.pool.validated_pool();
+		validated_pool.ban_for(
+			&Instant::now(),
+			content
+				.banned
+				.iter()
+				.map(|(hash, remaining)| (*hash, std::time::Duration::from_millis(*remaining))),
+		);
+
+		let block_number = match self.api.block_id_to_number(&BlockId::hash(at)) {
+			Ok(Some(number)) => number.saturated_into::<u64>(),
+			other => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Can't restore the journal, failed to resolve block {:?}: {:?}",
+					at,
+					other,
+				);
+				return
+			},
+		};
+
+		let total = content.transactions.len();
+		let validated = content
+			.transactions
+			.into_iter()
+			.filter_map(|(source, xt)| {
+				let (hash, bytes) = validated_pool.api().hash_and_length(&xt);
+				if validated_pool.is_banned(&hash) {
+					return None
+				}
+				let validity =
+					self.api.validate_transaction_blocking(at, source, xt.clone()).ok()?.ok()?;
+				Some(ValidatedTransaction::valid_at(
+					block_number,
+					hash,
+					source,
+					xt,
+					bytes,
+					validity,
+				))
+			})
+			.collect::<Vec<_>>();
+		let restored = validated_pool.submit(validated).into_iter().filter(Result::is_ok).count();
+
+		log::info!(
+			target: LOG_TARGET,
+			"Restored {} of {} transactions and {} bans from the journal",
+			restored,
+			total,
+			content.banned.len(),
+		);
+	}
 }
 
 impl<Block, Client> sc_transaction_pool_api::LocalTransactionPool
@@ -588,6 +746,25 @@ where
 	Block: BlockT,
 	PoolApi: 'static + graph::ChainApi<Block = Block>,
 {
+	/// Builds the view of a new block which is not the best one, in fork-aware mode.
+	async fn handle_new_block(&self, hash: Block::Hash) {
+		let views = match &self.views {
+			Some(views) => views,
+			None => return,
+		};
+		let number = match self.api.block_id_to_number(&BlockId::Hash(hash)) {
+			Ok(Some(number)) => number,
+			Ok(None) => return,
+			Err(e) => {
+				log::debug!(target: LOG_TARGET, "Error retrieving number of {:?}: {}", hash, e);
+				return
+			},
+		};
+		let main_at = self.enactment_state.lock().recent_best_block();
+		let at = HashAndNumber { hash, number };
+		views.insert_view(at, &self.pool, view::NewView::Leaf(main_at)).await;
+	}
+
 	/// Handles enactment and retraction of blocks, prunes stale transactions
 	/// (that have already been enacted) and resubmits transactions that were
 	/// retracted.
@@ -643,9 +820,10 @@ where
 		self.metrics
 			.report(|metrics| metrics.block_transactions_pruned.inc_by(pruned_log.len() as u64));
 
-		if next_action.resubmit {
-			let mut resubmit_transactions = Vec::new();
-
+		// In fork-aware mode the transactions of retracted blocks are also needed to build the
+		// view of the new best block.
+		let mut resubmit_transactions = Vec::new();
+		if next_action.resubmit || self.views.is_some() {
 			for retracted in tree_route.retracted() {
 				let hash = retracted.hash;
 
@@ -684,7 +862,14 @@ where
 					metrics.block_transactions_resubmitted.inc_by(resubmitted_to_report)
 				});
 			}
+		}
 
+		if next_action.resubmit {
+			let resubmit_transactions = if self.views.is_some() {
+				resubmit_transactions.clone()
+			} else {
+				std::mem::take(&mut resubmit_transactions)
+			};
 			if let Err(e) = pool
 				.resubmit_at(
 					*hash,
@@ -704,7 +889,14 @@ where
 			}
 		}
 
-		let extra_pool = pool.clone();
+		let extra_pool = match &self.views {
+			Some(views) => {
+				let at = HashAndNumber { hash: *hash, number: *block_number };
+				views.insert_view(at, &pool, view::NewView::Best(resubmit_transactions)).await;
+				views.get(hash).map_or_else(|| (*pool).clone(), |view| view.pool.clone())
+			},
+			None => (*pool).clone(),
+		};
 		// After #5200 lands, this arguably might be moved to the
 		// handler of "all blocks notification".
 		self.ready_poll
@@ -720,6 +912,54 @@ where
 	}
 }
 
+impl<PoolApi, Block> BasicPool<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: graph::ChainApi<Block = Block>,
+{
+	/// Writes the ready and future transactions and the banned transactions to the journal in
+	/// the background, see [`journal::Journal::write_in_background`].
+	fn write_journal(&self) {
+		let Some(journal) = &self.journal else { return };
+
+		if let Err(e) = journal.write_in_background(self.journal_content()) {
+			log::warn!(target: LOG_TARGET, "Failed to spawn the journal writer: {}", e);
+		}
+	}
+
+	/// Collects the ready and future transactions and the banned transactions to be journaled.
+	fn journal_content(
+		&self,
+	) -> journal::JournalContent<ExtrinsicHash<PoolApi>, graph::ExtrinsicFor<PoolApi>> {
+		let validated_pool = self.pool.validated_pool();
+		let mut transactions = validated_pool
+			.ready()
+			.map(|tx| (tx.source, tx.data.clone()))
+			.collect::<Vec<_>>();
+		transactions
+			.extend(validated_pool.pool.read().futures().map(|tx| (tx.source, tx.data.clone())));
+		let banned = validated_pool
+			.banned(&Instant::now())
+			.into_iter()
+			.map(|(hash, remaining)| (hash, remaining.as_millis().try_into().unwrap_or(u64::MAX)))
+			.collect();
+
+		journal::JournalContent { transactions, banned }
+	}
+}
+
+impl<PoolApi, Block> Drop for BasicPool<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: graph::ChainApi<Block = Block>,
+{
+	fn drop(&mut self) {
+		if let Some(journal) = &self.journal {
+			journal.write_last(&self.journal_content());
+		}
+	}
+}
+
 #[async_trait]
 impl<PoolApi, Block> MaintainedTransactionPool for BasicPool<PoolApi, Block>
 where
@@ -727,6 +967,11 @@ where
 	PoolApi: 'static + graph::ChainApi<Block = Block>,
 {
 	async fn maintain(&self, event: ChainEvent<Self::Block>) {
+		if let ChainEvent::NewBlock { hash } = event {
+			self.handle_new_block(hash).await;
+			return
+		}
+
 		let prev_finalized_block = self.enactment_state.lock().recent_finalized_block();
 		let compute_tree_route = |from, to| -> Result<TreeRoute<Block>, String> {
 			match self.api.tree_route(from, to) {
@@ -773,6 +1018,14 @@ where
 					)
 				}
 			}
+
+			if let Some(views) = &self.views {
+				views.handle_finalized(hash, &self.pool);
+			}
+		}
+
+		if self.journal.as_ref().map_or(false, |journal| journal.should_write()) {
+			self.write_journal();
 		}
 	}
 }
@@ -784,10 +1037,7 @@ where
 	Client: sc_client_api::BlockchainEvents<Block>,
 	Pool: MaintainedTransactionPool<Block = Block>,
 {
-	let import_stream = client
-		.import_notification_stream()
-		.filter_map(|n| ready(n.try_into().ok()))
-		.fuse();
+	let import_stream = client.import_notification_stream().map(Into::into).fuse();
 	let finality_stream = client.finality_notification_stream().map(Into::into).fuse();
 
 	futures::stream::select(import_stream, finality_stream)
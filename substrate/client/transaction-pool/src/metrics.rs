@@ -49,6 +49,8 @@ pub struct Metrics {
 	pub validations_invalid: Counter<U64>,
 	pub block_transactions_pruned: Counter<U64>,
 	pub block_transactions_resubmitted: Counter<U64>,
+	pub limit_evicted_transactions: Counter<U64>,
+	pub sender_limit_evicted_transactions: Counter<U64>,
 }
 
 impl Metrics {
@@ -82,6 +84,20 @@ impl Metrics {
 				)?,
 				registry,
 			)?,
+			limit_evicted_transactions: register(
+				Counter::new(
+					"substrate_sub_txpool_limit_evicted_transactions",
+					"Total number of transactions evicted from the pool because the pool limits were exceeded",
+				)?,
+				registry,
+			)?,
+			sender_limit_evicted_transactions: register(
+				Counter::new(
+					"substrate_sub_txpool_sender_limit_evicted_transactions",
+					"Total number of transactions evicted from the pool because their sender exceeded the per-sender limit",
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
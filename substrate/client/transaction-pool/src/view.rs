@@ -0,0 +1,307 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Fork-aware views of the transaction pool.
+//!
+//! In fork-aware mode the pool keeps, next to the main pool, a [`View`] for every unfinalized
+//! leaf. A view holds the transactions that are valid on top of its leaf, so the ready set served
+//! for a block is the one of its own fork and not the one of whatever fork happened to be the
+//! best one most recently.
+//!
+//! Transactions are watched through the main pool, which only follows the best fork. The views
+//! notify its watchers about inclusions in the other forks, and about the retraction of these
+//! forks once they are discarded by finality.
+
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+};
+
+use crate::{
+	graph::{self, BlockHash, ChainApi, ExtrinsicFor, ExtrinsicHash},
+	LOG_TARGET,
+};
+use futures::future;
+use parking_lot::Mutex;
+use sp_blockchain::HashAndNumber;
+use sp_runtime::{
+	generic::BlockId, traits::Header as HeaderT, transaction_validity::TransactionSource,
+};
+
+/// Transactions valid at a single leaf.
+pub(crate) struct View<PoolApi: ChainApi> {
+	/// The leaf the view is built at.
+	pub(crate) at: HashAndNumber<PoolApi::Block>,
+	/// Transactions that are valid at `at`.
+	pub(crate) pool: graph::Pool<PoolApi>,
+}
+
+impl<PoolApi: ChainApi> View<PoolApi> {
+	fn new(api: Arc<PoolApi>, options: graph::Options, at: HashAndNumber<PoolApi::Block>) -> Self {
+		// Transactions only end up in a view after the main pool accepted them, so there is no
+		// need to filter unactionable transactions again.
+		Self { at, pool: graph::Pool::new(options, true.into(), api) }
+	}
+}
+
+/// The block a new view is built at.
+pub(crate) enum NewView<PoolApi: ChainApi> {
+	/// The new best block, carrying the transactions of the blocks retracted by it. The main
+	/// pool has already been updated to this block.
+	Best(Vec<ExtrinsicFor<PoolApi>>),
+	/// A new leaf which is not the best block. Carries the block the main pool is at.
+	Leaf(BlockHash<PoolApi>),
+}
+
+/// The set of views, one per unfinalized leaf.
+pub(crate) struct ViewStore<PoolApi: ChainApi> {
+	api: Arc<PoolApi>,
+	options: graph::Options,
+	views: Mutex<HashMap<BlockHash<PoolApi>, Arc<View<PoolApi>>>>,
+	best: Mutex<Option<BlockHash<PoolApi>>>,
+}
+
+impl<PoolApi: ChainApi + 'static> ViewStore<PoolApi> {
+	/// Creates an empty view store. Views are built with the given `options`.
+	pub(crate) fn new(api: Arc<PoolApi>, options: graph::Options) -> Self {
+		Self { api, options, views: Default::default(), best: Default::default() }
+	}
+
+	/// Returns the view at the given leaf.
+	pub(crate) fn get(&self, at: &BlockHash<PoolApi>) -> Option<Arc<View<PoolApi>>> {
+		self.views.lock().get(at).cloned()
+	}
+
+	/// Returns the view at the most recent best block.
+	pub(crate) fn best_view(&self) -> Option<Arc<View<PoolApi>>> {
+		let best = (*self.best.lock())?;
+		self.get(&best)
+	}
+
+	/// Submits transactions that were accepted by the main pool to all views.
+	pub(crate) async fn submit(&self, source: TransactionSource, xts: Vec<ExtrinsicFor<PoolApi>>) {
+		if xts.is_empty() {
+			return
+		}
+
+		let views = self.views.lock().values().cloned().collect::<Vec<_>>();
+		let results = future::join_all(
+			views.iter().map(|view| view.pool.submit_at(view.at.hash, source, xts.clone())),
+		)
+		.await;
+		for (view, result) in views.iter().zip(results) {
+			if let Err(e) = result {
+				log::debug!(
+					target: LOG_TARGET,
+					"[{:?}] Error submitting transactions to view: {}",
+					view.at.hash,
+					e,
+				);
+			}
+		}
+	}
+
+	/// Removes the given transactions and everything that depends on them from all views.
+	pub(crate) fn remove_invalid(&self, hashes: &[ExtrinsicHash<PoolApi>]) {
+		for view in self.views.lock().values() {
+			view.pool.validated_pool().remove_invalid(hashes);
+		}
+	}
+
+	/// Builds the view for a new block.
+	///
+	/// The view is derived from the view of the parent block if there is one, otherwise from
+	/// the `main` pool, updated to the fork of the new block. The view of the parent block is
+	/// replaced, since the parent is no longer a leaf, unless it is kept as the best view.
+	///
+	/// The watchers of the `main` pool are notified about the inclusion of transactions in a
+	/// new block that is not the best one, for the `main` pool only follows the best fork.
+	pub(crate) async fn insert_view(
+		&self,
+		at: HashAndNumber<PoolApi::Block>,
+		main: &graph::Pool<PoolApi>,
+		new: NewView<PoolApi>,
+	) {
+		let is_best = matches!(new, NewView::Best(_));
+		if self.views.lock().contains_key(&at.hash) {
+			if is_best {
+				*self.best.lock() = Some(at.hash);
+			}
+			return
+		}
+
+		let parent = match self.api.block_header(at.hash) {
+			Ok(Some(header)) => Some(*header.parent_hash()),
+			Ok(None) => None,
+			Err(e) => {
+				log::debug!(target: LOG_TARGET, "Error retrieving header for {:?}: {}", at.hash, e);
+				None
+			},
+		};
+		let origin = parent.and_then(|parent| self.get(&parent));
+
+		// Blocks whose transactions have to be pruned from the new view, with their parents.
+		let mut enacted = Vec::new();
+		let mut xts = transactions_of(origin.as_ref().map_or(main, |view| &view.pool));
+		match (origin.as_ref(), new) {
+			(Some(_), _) => enacted.extend(parent.map(|parent| (at.hash, parent))),
+			(None, NewView::Best(retracted)) =>
+				xts.extend(retracted.into_iter().map(|xt| (TransactionSource::External, xt))),
+			(None, NewView::Leaf(main_at)) => match self.api.tree_route(main_at, at.hash) {
+				Ok(route) => {
+					for block in route.retracted() {
+						let body = self.block_body(block.hash).await;
+						xts.extend(body.into_iter().map(|xt| (TransactionSource::External, xt)));
+					}
+					let mut parent = route.common_block().hash;
+					for block in route.enacted() {
+						enacted.push((block.hash, parent));
+						parent = block.hash;
+					}
+				},
+				Err(e) => log::debug!(
+					target: LOG_TARGET,
+					"Error computing tree route from {:?} to {:?}: {}",
+					main_at,
+					at.hash,
+					e,
+				),
+			},
+		}
+
+		let view = View::new(self.api.clone(), self.options.clone(), at.clone());
+		for source in
+			[TransactionSource::InBlock, TransactionSource::Local, TransactionSource::External]
+		{
+			let xts = xts.iter().filter(|(s, _)| *s == source).map(|(_, xt)| xt.clone());
+			if let Err(e) = view.pool.resubmit_at(at.hash, source, xts).await {
+				log::debug!(
+					target: LOG_TARGET,
+					"[{:?}] Error building view: {}",
+					at.hash,
+					e,
+				);
+			}
+		}
+
+		// Transactions that got included in the fork of the new block need to be pruned. The main
+		// pool was already pruned during enactment of the best block.
+		for (block, parent) in enacted {
+			let extrinsics = self.block_body(block).await;
+			if let Err(e) = view.pool.prune(block, parent, &extrinsics).await {
+				log::debug!(target: LOG_TARGET, "[{:?}] Error pruning view: {}", at.hash, e);
+			}
+			if !is_best && block == at.hash {
+				let hashes = extrinsics.iter().map(|xt| main.hash_of(xt));
+				if let Err(e) = main.validated_pool().fire_pruned(&BlockId::Hash(block), hashes) {
+					log::debug!(target: LOG_TARGET, "[{:?}] Error notifying watchers: {}", block, e);
+				}
+			}
+		}
+
+		log::debug!(
+			target: LOG_TARGET,
+			"Built view at {:?} from {:?}: {:?}",
+			at,
+			origin.as_ref().map(|view| view.at.hash),
+			view.pool.validated_pool().status(),
+		);
+
+		let mut views = self.views.lock();
+		let mut best = self.best.lock();
+		if let (Some(parent), Some(_)) = (parent, origin) {
+			if is_best || *best != Some(parent) {
+				views.remove(&parent);
+			}
+		}
+		views.insert(at.hash, Arc::new(view));
+		if is_best {
+			*best = Some(at.hash);
+		}
+	}
+
+	/// Returns the extrinsics of the given block, or none if the body is not available.
+	async fn block_body(&self, at: BlockHash<PoolApi>) -> Vec<ExtrinsicFor<PoolApi>> {
+		self.api
+			.block_body(at)
+			.await
+			.unwrap_or_else(|e| {
+				log::debug!(target: LOG_TARGET, "Failed to fetch block body: {}", e);
+				None
+			})
+			.unwrap_or_default()
+	}
+
+	/// Drops all views that are not descendants of the given finalized block.
+	///
+	/// The watchers of the `main` pool are notified about the retraction of the blocks of the
+	/// dropped forks.
+	pub(crate) fn handle_finalized(
+		&self,
+		finalized: BlockHash<PoolApi>,
+		main: &graph::Pool<PoolApi>,
+	) {
+		let views = self.views.lock().values().cloned().collect::<Vec<_>>();
+		let mut stale = Vec::new();
+		// Stale forks may share blocks, each of them is retracted once.
+		let mut retracted = HashSet::new();
+		for view in views {
+			if view.at.hash == finalized {
+				continue
+			}
+			match self.api.tree_route(finalized, view.at.hash) {
+				Ok(route) if route.common_block().hash == finalized => continue,
+				Ok(route) =>
+					for block in route.enacted() {
+						if retracted.insert(block.hash) {
+							main.validated_pool().on_block_retracted(block.hash);
+						}
+					},
+				Err(_) => {},
+			}
+			stale.push(view.at.hash);
+		}
+
+		if stale.is_empty() {
+			return
+		}
+
+		log::debug!(target: LOG_TARGET, "Dropping views of stale forks: {:?}", stale);
+		let mut views = self.views.lock();
+		let mut best = self.best.lock();
+		for hash in stale {
+			views.remove(&hash);
+			if *best == Some(hash) {
+				*best = None;
+			}
+		}
+	}
+}
+
+/// Returns all ready and future transactions of the given pool.
+fn transactions_of<PoolApi: ChainApi>(
+	pool: &graph::Pool<PoolApi>,
+) -> Vec<(TransactionSource, ExtrinsicFor<PoolApi>)> {
+	let validated_pool = pool.validated_pool();
+	let mut xts = validated_pool
+		.ready()
+		.map(|tx| (tx.source, tx.data.clone()))
+		.collect::<Vec<_>>();
+	xts.extend(validated_pool.pool.read().futures().map(|tx| (tx.source, tx.data.clone())));
+	xts
+}
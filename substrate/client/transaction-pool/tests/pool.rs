@@ -80,6 +80,22 @@ fn create_basic_pool(test_api: TestApi) -> BasicPool<TestApi, Block> {
 	create_basic_pool_with_genesis(Arc::from(test_api)).0
 }
 
+fn create_fork_aware_pool(test_api: TestApi) -> BasicPool<TestApi, Block> {
+	let genesis_hash = test_api.expect_hash_from_number(0);
+	let options = Options { fork_aware: true, ..Default::default() };
+	BasicPool::with_revalidation_type(
+		options,
+		true.into(),
+		Arc::new(test_api),
+		None,
+		RevalidationType::Light,
+		sp_core::testing::TaskExecutor::new(),
+		0,
+		genesis_hash,
+		genesis_hash,
+	)
+}
+
 const SOURCE: TransactionSource = TransactionSource::External;
 
 #[test]
@@ -923,6 +939,123 @@ fn resubmit_from_retracted_fork() {
 	assert_eq!(expected_ready, ready);
 }
 
+#[test]
+fn fork_aware_pool_should_keep_view_per_fork() {
+	let xt = uxt(Alice, 209);
+	let pool = create_fork_aware_pool(TestApi::with_alice_nonce(209));
+	let api = pool.api();
+
+	block_on(pool.submit_one(api.expect_hash_from_number(0), SOURCE, xt.clone()))
+		.expect("1. Imported");
+
+	// the transaction gets included on the first fork
+	let a1 = api.push_block(1, vec![xt.clone()], true);
+	block_on(pool.maintain(block_event(a1.clone())));
+	assert_eq!(pool.ready_at_leaf(a1.hash()).expect("view at a1 exists").count(), 0);
+	assert_eq!(pool.ready().count(), 0);
+
+	// the second fork becomes the best one, the transaction is ready there
+	let b1 = api.push_block(1, vec![], true);
+	block_on(pool.maintain(block_event_with_retracted(b1.clone(), a1.hash(), api)));
+	let ready = pool.ready_at_leaf(b1.hash()).expect("view at b1 exists");
+	assert_eq!(ready.map(|tx| tx.data.encode()).collect::<Vec<_>>(), vec![xt.encode()]);
+	assert_eq!(pool.ready().count(), 1);
+
+	// the view of the first fork is untouched
+	assert_eq!(pool.ready_at_leaf(a1.hash()).expect("view at a1 exists").count(), 0);
+
+	// building on top of the second fork replaces its view
+	let b2 = api.push_block_with_parent(b1.hash(), vec![xt.clone()], true);
+	block_on(pool.maintain(block_event(b2.clone())));
+	assert!(pool.ready_at_leaf(b1.hash()).is_none());
+	assert_eq!(pool.ready_at_leaf(b2.hash()).expect("view at b2 exists").count(), 0);
+
+	// finalizing the second fork drops the view of the first one
+	let event = ChainEvent::Finalized { hash: b2.hash(), tree_route: Arc::from(vec![b1.hash()]) };
+	block_on(pool.maintain(event));
+	assert!(pool.ready_at_leaf(a1.hash()).is_none());
+	assert!(pool.ready_at_leaf(b2.hash()).is_some());
+}
+
+#[test]
+fn fork_aware_pool_should_notify_watchers_per_fork() {
+	let xt = uxt(Alice, 209);
+	let pool = create_fork_aware_pool(TestApi::with_alice_nonce(209));
+	let api = pool.api();
+	let genesis = api.expect_hash_from_number(0);
+
+	let watcher =
+		block_on(pool.submit_and_watch(genesis, SOURCE, xt.clone())).expect("1. Imported");
+
+	// the transaction gets included on the best fork
+	let a1 = api.push_block(1, vec![xt.clone()], true);
+	block_on(pool.maintain(block_event(a1.clone())));
+
+	// and on a second fork, which is not the best one
+	let b1 = api.push_block_with_parent(genesis, vec![xt.clone()], false);
+	block_on(pool.maintain(ChainEvent::NewBlock { hash: b1.hash() }));
+	assert_eq!(pool.ready_at_leaf(b1.hash()).expect("view at b1 exists").count(), 0);
+
+	// blocks built on a third fork without the transaction get it from the view of that fork
+	let c1 = api.push_block_with_parent(genesis, vec![], false);
+	block_on(pool.maintain(ChainEvent::NewBlock { hash: c1.hash() }));
+	let ready = block_on(pool.ready_at_with_hash(c1.hash(), 1));
+	assert_eq!(ready.map(|tx| tx.data.encode()).collect::<Vec<_>>(), vec![xt.encode()]);
+	assert_eq!(block_on(pool.ready_at_with_hash(a1.hash(), 1)).count(), 0);
+
+	// the second fork becomes the best one and gets finalized
+	let b2 = api.push_block_with_parent(b1.hash(), vec![], true);
+	block_on(pool.maintain(block_event_with_retracted(b2.clone(), a1.hash(), api)));
+	let event = ChainEvent::Finalized { hash: b2.hash(), tree_route: Arc::from(vec![b1.hash()]) };
+	block_on(pool.maintain(event));
+	assert!(pool.ready_at_leaf(c1.hash()).is_none());
+
+	let mut stream = futures::executor::block_on_stream(watcher);
+	assert_eq!(stream.next(), Some(TransactionStatus::Ready));
+	assert_eq!(stream.next(), Some(TransactionStatus::InBlock((a1.hash(), 0))));
+	assert_eq!(stream.next(), Some(TransactionStatus::InBlock((b1.hash(), 0))));
+	assert_eq!(stream.next(), Some(TransactionStatus::Retracted(a1.hash())));
+	assert_eq!(stream.next(), Some(TransactionStatus::Finalized((b1.hash(), 0))));
+	assert_eq!(stream.next(), None);
+}
+
+#[test]
+fn fork_aware_pool_should_retract_forks_dropped_by_finality() {
+	let xt = uxt(Alice, 209);
+	let pool = create_fork_aware_pool(TestApi::with_alice_nonce(209));
+	let api = pool.api();
+	let genesis = api.expect_hash_from_number(0);
+
+	let watcher =
+		block_on(pool.submit_and_watch(genesis, SOURCE, xt.clone())).expect("1. Imported");
+
+	// the transaction only gets included on a fork which is not the best one
+	let a1 = api.push_block(1, vec![], true);
+	block_on(pool.maintain(block_event(a1.clone())));
+	let b1 = api.push_block_with_parent(genesis, vec![xt.clone()], false);
+	block_on(pool.maintain(ChainEvent::NewBlock { hash: b1.hash() }));
+	assert_eq!(pool.ready().count(), 1);
+
+	// the other fork splits in two leaves sharing the block with the transaction
+	let b2 = api.push_block_with_parent(b1.hash(), vec![], false);
+	block_on(pool.maintain(ChainEvent::NewBlock { hash: b2.hash() }));
+	let c2 = api.push_block_with_parent(b1.hash(), vec![], false);
+	block_on(pool.maintain(ChainEvent::NewBlock { hash: c2.hash() }));
+
+	// finalizing the best fork drops the other one
+	let event = ChainEvent::Finalized { hash: a1.hash(), tree_route: Arc::from(vec![]) };
+	block_on(pool.maintain(event));
+	assert!(pool.ready_at_leaf(b2.hash()).is_none());
+	assert!(pool.ready_at_leaf(c2.hash()).is_none());
+
+	// the shared block is retracted once
+	let mut stream = futures::executor::block_on_stream(watcher);
+	assert_eq!(stream.next(), Some(TransactionStatus::Ready));
+	assert_eq!(stream.next(), Some(TransactionStatus::InBlock((b1.hash(), 0))));
+	assert_eq!(stream.next(), Some(TransactionStatus::Retracted(b1.hash())));
+	assert!(stream.into_inner().next().now_or_never().is_none());
+}
+
 #[test]
 fn ready_set_should_not_resolve_before_block_update() {
 	let (pool, api, _guard) = maintained_pool();
@@ -1655,3 +1788,44 @@ fn best_block_after_finalization_does_not_retract() {
 		assert_eq!(stream.next(), None);
 	}
 }
+
+#[test]
+fn journal_is_restored_with_revalidation_and_bans() {
+	sp_tracing::try_init_simple();
+	let dir = tempfile::tempdir().unwrap();
+	let journal = dir.path().join("txpool").join("journal");
+	let options = Options { journal: Some(journal.clone()), ..Default::default() };
+	let mut client = Arc::new(substrate_test_runtime_client::new());
+	let spawner = sp_core::testing::TaskExecutor::new();
+	let genesis_hash = client.info().best_hash;
+
+	let pool =
+		BasicPool::new_full(options.clone(), true.into(), None, spawner.clone(), client.clone());
+	let (xt0, xt1, banned) = (uxt(Alice, 0), uxt(Alice, 1), uxt(Bob, 0));
+	for xt in [&xt0, &xt1, &banned] {
+		block_on(pool.submit_one(genesis_hash, SOURCE, xt.clone())).unwrap();
+	}
+	let banned_hash = pool.api().hash_and_length(&banned).0;
+	pool.pool().validated_pool().ban(&std::time::Instant::now(), [banned_hash]);
+	assert_eq!(pool.status().ready, 3);
+
+	// The journal is written when the pool is dropped.
+	drop(pool);
+	assert!(journal.exists());
+
+	// `xt0` is included in the new best block, so it is not valid anymore.
+	let mut block_builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	block_builder.push(xt0.clone()).unwrap();
+	let block = block_builder.build().unwrap().block;
+	block_on(client.import(BlockOrigin::Own, block)).unwrap();
+
+	let pool = BasicPool::new_full(options, true.into(), None, spawner, client.clone());
+	assert_eq!(pool.status().ready, 1);
+	let ready = pool.ready().map(|tx| tx.data.clone()).collect::<Vec<_>>();
+	assert_eq!(ready, vec![xt1]);
+	assert!(pool.pool().validated_pool().is_banned(&banned_hash));
+}
@@ -334,5 +334,130 @@ benchmarks! {
 		}
 	}
 
+	stable_swap_hop {
+		// A hop evaluates the curve of its pool twice, to price it and to check the invariant,
+		// each evaluation running two Newton iterations. Solving the invariant of this pool runs
+		// one of them to `MAX_ITERATIONS`.
+		let curve = PoolCurve::StableSwap { amplification: 1 };
+		let reserve_in: T::AssetBalance = 1_000_000_000_000.into();
+		let reserve_out: T::AssetBalance = 1000.into();
+	}: {
+		for _ in 0..4 {
+			let _ = AssetConversion::<T>::get_amount_out_for_curve(&curve, &1.into(), &reserve_in, &reserve_out);
+		}
+	}
+	verify {
+		assert!(AssetConversion::<T>::get_amount_out_for_curve(&curve, &1.into(), &reserve_in, &reserve_out).is_err());
+	}
+
+	weighted_swap_hop {
+		// The powers of these weights take the most multiplications, and taking almost all of
+		// the reserve out leaves the roots the widest range to search. Buying an exact amount
+		// finds three roots, to price the hop, to check the price and to check the invariant.
+		let curve = PoolCurve::Weighted { weight1: 95, weight2: 94 };
+		let reserve: T::AssetBalance = 1_000_000_000_000.into();
+		let amount_out: T::AssetBalance = 999_999_999_000.into();
+	}: {
+		let amount_in = AssetConversion::<T>::get_amount_in_for_curve(&curve, &amount_out, &reserve, &reserve).unwrap();
+		assert_ok!(AssetConversion::<T>::ensure_invariant(&curve, &amount_in, &amount_out, &reserve, &reserve));
+	}
+	verify {
+		let amount_in = AssetConversion::<T>::get_amount_in_for_curve(&curve, &amount_out, &reserve, &reserve).unwrap();
+		assert!(amount_in > reserve);
+	}
+
+	place_limit_order {
+		let native = T::MultiAssetIdConverter::get_native();
+		let asset1 = T::BenchmarkHelper::multiasset_id(1);
+		let (_, caller, _) = create_asset_and_pool::<T>(&native, &asset1);
+		let ed: u128 = T::Currency::minimum_balance().into();
+		let order_id = NextOrderId::<T>::get();
+	}: _(SystemOrigin::Signed(caller.clone()), asset1.clone(), native.clone(), 1000.into(), (1000 * ed).into(), false)
+	verify {
+		assert_last_event::<T>(Event::LimitOrderPlaced {
+			order_id,
+			owner: caller,
+			asset_in: asset1,
+			asset_out: native,
+			amount_in: 1000.into(),
+			amount_out: (1000 * ed).into(),
+		}.into());
+	}
+
+	cancel_limit_order {
+		let native = T::MultiAssetIdConverter::get_native();
+		let asset1 = T::BenchmarkHelper::multiasset_id(1);
+		let (_, caller, _) = create_asset_and_pool::<T>(&native, &asset1);
+		let ed: u128 = T::Currency::minimum_balance().into();
+		let order_id = NextOrderId::<T>::get();
+		AssetConversion::<T>::place_limit_order(
+			SystemOrigin::Signed(caller.clone()).into(),
+			asset1.clone(),
+			native.clone(),
+			1000.into(),
+			(1000 * ed).into(),
+			false,
+		)?;
+	}: _(SystemOrigin::Signed(caller.clone()), order_id)
+	verify {
+		assert!(!LimitOrders::<T>::contains_key(order_id));
+		assert_last_event::<T>(Event::LimitOrderCancelled {
+			order_id,
+			owner: caller,
+			refunded: 1000.into(),
+		}.into());
+	}
+
+	match_limit_order {
+		// The worst case is a partial fill, searching for the largest amount the pool can fill
+		// with a step per bit of the remaining amount of the order, each solving the curve of
+		// the pool. The weighted curve with these weights is the most expensive to solve, see
+		// `weighted_swap_hop`.
+		let native = T::MultiAssetIdConverter::get_native();
+		let asset1 = T::BenchmarkHelper::multiasset_id(1);
+		let (caller, _) = create_asset::<T>(&asset1);
+		let ed: u128 = T::Currency::minimum_balance().into();
+		let remaining: u128 = u128::MAX / 2;
+		let reserve: u128 = 1 << 64;
+		assert_ok!(T::Assets::mint_into(
+			T::BenchmarkHelper::asset_id(1),
+			&caller,
+			(remaining + reserve).into(),
+		));
+
+		AssetConversion::<T>::create_pool_with_curve(
+			SystemOrigin::Signed(caller.clone()).into(),
+			native.clone(),
+			asset1.clone(),
+			PoolCurve::Weighted { weight1: 95, weight2: 94 },
+		)?;
+		AssetConversion::<T>::add_liquidity(
+			SystemOrigin::Signed(caller.clone()).into(),
+			native.clone(),
+			asset1.clone(),
+			(1000 * ed).into(),
+			reserve.into(),
+			0.into(),
+			0.into(),
+			caller.clone(),
+		)?;
+		// priced at half of the pool, which fills about as much as the reserve of the pool.
+		let order_id = NextOrderId::<T>::get();
+		AssetConversion::<T>::place_limit_order(
+			SystemOrigin::Signed(caller.clone()).into(),
+			asset1.clone(),
+			native.clone(),
+			remaining.into(),
+			((remaining / reserve) * 500 * ed).into(),
+			false,
+		)?;
+	}: {
+		AssetConversion::<T>::match_limit_order(order_id);
+	}
+	verify {
+		let left = LimitOrders::<T>::get(order_id).unwrap().remaining;
+		assert!(left < remaining.into() && left > 0.into());
+	}
+
 	impl_benchmark_test_suite!(AssetConversion, crate::mock::new_test_ext(), crate::mock::Test);
 }
@@ -0,0 +1,278 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pricing formulas of the pool curves other than the constant product one.
+//!
+//! All functions work on fee-less amounts and return `None` if the result can't be computed,
+//! rounding against the trader so that a pool never gives out more than its invariant allows.
+
+use sp_core::U256;
+
+/// The maximum number of Newton iterations used to solve the StableSwap invariant.
+const MAX_ITERATIONS: u32 = 255;
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+	if a > b {
+		a - b
+	} else {
+		b - a
+	}
+}
+
+/// The StableSwap invariant `D` of a pool with reserves `x` and `y`, solving
+/// `4A(x + y) + D = 4AD + D^3 / 4xy` with Newton's method.
+fn stable_swap_invariant(amplification: u32, x: U256, y: U256) -> Option<U256> {
+	let sum = x.checked_add(y)?;
+	if sum.is_zero() {
+		return Some(sum)
+	}
+	let ann = U256::from(amplification).checked_mul(U256::from(4u32))?;
+	let mut d = sum;
+	for _ in 0..MAX_ITERATIONS {
+		// d_p = D^3 / 4xy
+		let d_p = d
+			.checked_mul(d)?
+			.checked_div(x.checked_mul(U256::from(2u32))?)?
+			.checked_mul(d)?
+			.checked_div(y.checked_mul(U256::from(2u32))?)?;
+		// D = (4A(x + y) + 2 d_p) D / ((4A - 1) D + 3 d_p)
+		let numerator = ann
+			.checked_mul(sum)?
+			.checked_add(d_p.checked_mul(U256::from(2u32))?)?
+			.checked_mul(d)?;
+		let denominator = ann
+			.checked_sub(U256::one())?
+			.checked_mul(d)?
+			.checked_add(d_p.checked_mul(U256::from(3u32))?)?;
+		let previous = d;
+		d = numerator.checked_div(denominator)?;
+		if abs_diff(d, previous) <= U256::one() {
+			return Some(d)
+		}
+	}
+	None
+}
+
+/// The reserve of one asset of a StableSwap pool with invariant `d`, given the reserve `x` of
+/// the other asset.
+fn stable_swap_reserve(amplification: u32, x: U256, d: U256) -> Option<U256> {
+	let ann = U256::from(amplification).checked_mul(U256::from(4u32))?;
+	// c = D^3 / 4x(4A), b = x + D / 4A
+	let c = d
+		.checked_mul(d)?
+		.checked_div(x.checked_mul(U256::from(2u32))?)?
+		.checked_mul(d)?
+		.checked_div(ann.checked_mul(U256::from(2u32))?)?;
+	let b = x.checked_add(d.checked_div(ann)?)?;
+	let mut y = d;
+	for _ in 0..MAX_ITERATIONS {
+		// y = (y^2 + c) / (2y + b - D)
+		let previous = y;
+		y = y
+			.checked_mul(y)?
+			.checked_add(c)?
+			.checked_div(y.checked_mul(U256::from(2u32))?.checked_add(b)?.checked_sub(d)?)?;
+		if abs_diff(y, previous) <= U256::one() {
+			return Some(y)
+		}
+	}
+	None
+}
+
+/// The amount received from a StableSwap pool for `amount_in`.
+pub(crate) fn stable_swap_amount_out(
+	amplification: u32,
+	amount_in: u128,
+	reserve_in: u128,
+	reserve_out: u128,
+) -> Option<u128> {
+	let (reserve_in, reserve_out) = (U256::from(reserve_in), U256::from(reserve_out));
+	let d = stable_swap_invariant(amplification, reserve_in, reserve_out)?;
+	let new_reserve_in = reserve_in.checked_add(U256::from(amount_in))?;
+	let new_reserve_out = stable_swap_reserve(amplification, new_reserve_in, d)?;
+	// Round down by one unit to cover the precision of the Newton iterations.
+	let amount_out = reserve_out.saturating_sub(new_reserve_out).saturating_sub(U256::one());
+	Some(amount_out.low_u128())
+}
+
+/// The amount needed by a StableSwap pool to give out exactly `amount_out`.
+pub(crate) fn stable_swap_amount_in(
+	amplification: u32,
+	amount_out: u128,
+	reserve_in: u128,
+	reserve_out: u128,
+) -> Option<u128> {
+	let (reserve_in, reserve_out) = (U256::from(reserve_in), U256::from(reserve_out));
+	let d = stable_swap_invariant(amplification, reserve_in, reserve_out)?;
+	let new_reserve_out = reserve_out.checked_sub(U256::from(amount_out))?;
+	let new_reserve_in = stable_swap_reserve(amplification, new_reserve_out, d)?;
+	// Round up by one unit to cover the precision of the Newton iterations.
+	let amount_in = new_reserve_in.checked_sub(reserve_in)?.checked_add(U256::one())?;
+	u128::try_from(amount_in).ok()
+}
+
+/// The number of fractional bits of the fixed point numbers the weighted curve is computed with.
+///
+/// Reserves are at most `u128::MAX`, so any product of a reserve or a fixed point number with a
+/// fixed point number fits in a `U256`.
+const FRACTIONAL_BITS: usize = 120;
+
+/// The maximum number of bisection steps used to find a root on `[0, 1]`, enough to narrow the
+/// range down to one unit of the weighted curve precision.
+pub(crate) const MAX_ROOT_ITERATIONS: u32 = FRACTIONAL_BITS as u32;
+
+/// One, as a fixed point number of the weighted curve.
+fn weighted_one() -> U256 {
+	U256::one() << FRACTIONAL_BITS
+}
+
+/// `a * b` of a fixed point `b`, rounded down if `round_up` is not set and up otherwise.
+fn mul(a: U256, b: U256, round_up: bool) -> Option<U256> {
+	let product = a.checked_mul(b)?;
+	let result = product >> FRACTIONAL_BITS;
+	if round_up && !(product - (result << FRACTIONAL_BITS)).is_zero() {
+		result.checked_add(U256::one())
+	} else {
+		Some(result)
+	}
+}
+
+/// `a / b`, rounded down if `round_up` is not set and up otherwise.
+fn div(a: U256, b: U256, round_up: bool) -> Option<U256> {
+	let (quotient, remainder) = a.div_mod(b);
+	if round_up && !remainder.is_zero() {
+		quotient.checked_add(U256::one())
+	} else {
+		Some(quotient)
+	}
+}
+
+/// `base ^ exp` of a fixed point `base` of at most one, rounding every step down if `round_up`
+/// is not set and up otherwise.
+fn pow(base: U256, exp: u8, round_up: bool) -> Option<U256> {
+	let (mut result, mut base, mut exp) = (weighted_one(), base, exp);
+	while exp > 0 {
+		if exp & 1 == 1 {
+			result = mul(result, base, round_up)?;
+		}
+		exp >>= 1;
+		if exp > 0 {
+			base = mul(base, base, round_up)?;
+		}
+	}
+	Some(result)
+}
+
+/// The `n`th root of a fixed point `base` of at most one, found by bisection.
+///
+/// If `round_up` is set, the result is the smallest number whose power rounded down is at least
+/// `base`, so it is never below the exact root. Otherwise it is the largest number whose power
+/// rounded up is at most `base`, so it is never above the exact root.
+fn root(base: U256, n: u8, round_up: bool) -> Option<U256> {
+	if n == 1 {
+		return Some(base)
+	}
+	// The root of a number of at most one is at least that number.
+	let (mut low, mut high) = (base, weighted_one());
+	for _ in 0..MAX_ROOT_ITERATIONS {
+		if low >= high {
+			break
+		}
+		if round_up {
+			let mid = low + (high - low) / 2;
+			if pow(mid, n, false)? >= base {
+				high = mid;
+			} else {
+				low = mid + 1;
+			}
+		} else {
+			let mid = low + (high - low + 1) / 2;
+			if pow(mid, n, true)? <= base {
+				low = mid;
+			} else {
+				high = mid - 1;
+			}
+		}
+	}
+	Some(low)
+}
+
+/// `base ^ (numerator / denominator)` of a fixed point `base` of at most one, never below the
+/// exact result if `round_up` is set and never above it otherwise.
+///
+/// The root is taken first so that it is computed on a number close to one, where the fixed
+/// point numbers are the most precise.
+fn pow_ratio(base: U256, numerator: u8, denominator: u8, round_up: bool) -> Option<U256> {
+	let divisor = gcd(numerator, denominator);
+	pow(root(base, denominator / divisor, round_up)?, numerator / divisor, round_up)
+}
+
+fn gcd(mut a: u8, mut b: u8) -> u8 {
+	while b != 0 {
+		(a, b) = (b, a % b);
+	}
+	a
+}
+
+/// The amount received from a weighted pool for `amount_in`:
+/// `reserve_out * (1 - (reserve_in / (reserve_in + amount_in)) ^ (weight_in / weight_out))`.
+///
+/// Every step is rounded so that the reserve left in the pool is never below the one the
+/// invariant asks for.
+pub(crate) fn weighted_amount_out(
+	weight_in: u8,
+	weight_out: u8,
+	amount_in: u128,
+	reserve_in: u128,
+	reserve_out: u128,
+) -> Option<u128> {
+	let (reserve_in, reserve_out) = (U256::from(reserve_in), U256::from(reserve_out));
+	let new_reserve_in = reserve_in.checked_add(U256::from(amount_in))?;
+	let base = div(reserve_in.checked_mul(weighted_one())?, new_reserve_in, true)?;
+	let ratio = pow_ratio(base, weight_in, weight_out, true)?;
+	let new_reserve_out = mul(reserve_out, ratio, true)?;
+	let amount_out = reserve_out.checked_sub(new_reserve_out)?;
+	u128::try_from(amount_out).ok()
+}
+
+/// The amount needed by a weighted pool to give out exactly `amount_out`:
+/// `reserve_in * ((reserve_out / (reserve_out - amount_out)) ^ (weight_out / weight_in) - 1)`.
+///
+/// Every step is rounded so that the reserve added to the pool is never below the one the
+/// invariant asks for, and the result is checked against [`weighted_amount_out`] so that the
+/// amount in always buys at least `amount_out`.
+pub(crate) fn weighted_amount_in(
+	weight_in: u8,
+	weight_out: u8,
+	amount_out: u128,
+	reserve_in: u128,
+	reserve_out: u128,
+) -> Option<u128> {
+	let (reserve_in_u256, reserve_out_u256) = (U256::from(reserve_in), U256::from(reserve_out));
+	let new_reserve_out = reserve_out_u256.checked_sub(U256::from(amount_out))?;
+	let base = div(new_reserve_out.checked_mul(weighted_one())?, reserve_out_u256, false)?;
+	let ratio = pow_ratio(base, weight_out, weight_in, false)?;
+	if ratio.is_zero() {
+		return None
+	}
+	let new_reserve_in = div(reserve_in_u256.checked_mul(weighted_one())?, ratio, true)?;
+	let amount_in = u128::try_from(new_reserve_in.checked_sub(reserve_in_u256)?).ok()?;
+	// Cover the precision lost by the fixed point numbers in the reverse direction.
+	let amount_in = amount_in.checked_add(amount_in >> 40)?.checked_add(1)?;
+	(weighted_amount_out(weight_in, weight_out, amount_in, reserve_in, reserve_out)? >= amount_out)
+		.then_some(amount_in)
+}
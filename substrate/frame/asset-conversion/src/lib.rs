@@ -24,12 +24,16 @@
 //! This pallet allows you to:
 //!
 //!  - [create a liquidity pool](`Pallet::create_pool()`) for 2 assets
+//!  - [create a liquidity pool with a different curve](`Pallet::create_pool_with_curve()`), either
+//!    a stable swap curve for assets of similar value or a weighted curve
 //!  - [provide the liquidity](`Pallet::add_liquidity()`) and receive back an LP token
 //!  - [exchange the LP token back to assets](`Pallet::remove_liquidity()`)
 //!  - [swap a specific amount of assets for another](`Pallet::swap_exact_tokens_for_tokens()`) if
 //!    there is a pool created, or
 //!  - [swap some assets for a specific amount of
 //!    another](`Pallet::swap_tokens_for_exact_tokens()`).
+//!  - [place a limit order](`Pallet::place_limit_order()`) which is filled against the pool of its
+//!    assets once its price is reached, and [cancel it](`Pallet::cancel_limit_order()`)
 //!  - [query for an exchange price](`AssetConversionApi::quote_price_exact_tokens_for_tokens`) via
 //!    a runtime call endpoint
 //!  - [query the size of a liquidity pool](`AssetConversionApi::get_reserves`) via a runtime api
@@ -53,11 +57,12 @@
 //! (This can be run against the kitchen sync node in the `node` folder of this repo.)
 #![deny(missing_docs)]
 #![cfg_attr(not(feature = "std"), no_std)]
-use frame_support::traits::{DefensiveOption, Incrementable};
+use frame_support::traits::{Defensive, DefensiveOption, Incrementable};
 
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+mod curves;
 mod types;
 pub mod weights;
 
@@ -80,7 +85,8 @@ pub use pallet::*;
 use sp_arithmetic::traits::Unsigned;
 use sp_runtime::{
 	traits::{
-		CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Ensure, MaybeDisplay, TrailingZeroInput,
+		AccountIdConversion, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Ensure, MaybeDisplay,
+		TrailingZeroInput,
 	},
 	DispatchError,
 };
@@ -93,8 +99,12 @@ pub mod pallet {
 	use super::*;
 	use frame_support::{
 		pallet_prelude::*,
+		storage::with_storage_layer,
 		traits::{
-			fungible::{Inspect as InspectFungible, Mutate as MutateFungible},
+			fungible::{
+				Inspect as InspectFungible, Mutate as MutateFungible,
+				MutateHold as MutateHoldFungible,
+			},
 			fungibles::{Create, Inspect, Mutate},
 			tokens::{
 				Fortitude::Polite,
@@ -121,7 +131,11 @@ pub mod pallet {
 
 		/// Currency type that this works on.
 		type Currency: InspectFungible<Self::AccountId, Balance = Self::Balance>
-			+ MutateFungible<Self::AccountId>;
+			+ MutateFungible<Self::AccountId>
+			+ MutateHoldFungible<Self::AccountId, Reason = Self::RuntimeHoldReason>;
+
+		/// Overarching hold reason.
+		type RuntimeHoldReason: From<HoldReason>;
 
 		/// The `Currency::Balance` type of the native currency.
 		type Balance: Balance;
@@ -202,6 +216,15 @@ pub mod pallet {
 		#[pallet::constant]
 		type AllowMultiAssetPools: Get<bool>;
 
+		/// The max number of open limit orders per account.
+		#[pallet::constant]
+		type MaxLimitOrders: Get<u32>;
+
+		/// The amount of the native currency taken for the storage of a limit order, until it is
+		/// filled or cancelled. Should be more or equal to the existential deposit.
+		#[pallet::constant]
+		type LimitOrderDeposit: Get<Self::Balance>;
+
 		/// Weight information for extrinsics in this pallet.
 		type WeightInfo: WeightInfo;
 
@@ -210,17 +233,53 @@ pub mod pallet {
 		type BenchmarkHelper: BenchmarkHelper<Self::AssetId, Self::MultiAssetId>;
 	}
 
+	/// A reason for the pallet placing a hold on funds.
+	#[pallet::composite_enum]
+	pub enum HoldReason {
+		/// The native currency sold by a limit order and the deposit of the order.
+		LimitOrder,
+	}
+
 	/// Map from `PoolAssetId` to `PoolInfo`. This establishes whether a pool has been officially
 	/// created rather than people sending tokens directly to a pool's public account.
 	#[pallet::storage]
 	pub type Pools<T: Config> =
 		StorageMap<_, Blake2_128Concat, PoolIdOf<T>, PoolInfo<T::PoolAssetId>, OptionQuery>;
 
+	/// Map from `PoolAssetId` to the `PoolCurve` of the pool, for any pool not using the constant
+	/// product curve.
+	#[pallet::storage]
+	pub type PoolCurves<T: Config> =
+		StorageMap<_, Blake2_128Concat, PoolIdOf<T>, PoolCurve, ValueQuery>;
+
 	/// Stores the `PoolAssetId` that is going to be used for the next lp token.
 	/// This gets incremented whenever a new lp pool is created.
 	#[pallet::storage]
 	pub type NextPoolAssetId<T: Config> = StorageValue<_, T::PoolAssetId, OptionQuery>;
 
+	/// Stores the `OrderId` that is going to be used for the next limit order.
+	#[pallet::storage]
+	pub type NextOrderId<T: Config> = StorageValue<_, OrderId, ValueQuery>;
+
+	/// Map from `OrderId` to the open limit order.
+	#[pallet::storage]
+	pub type LimitOrders<T: Config> =
+		StorageMap<_, Twox64Concat, OrderId, LimitOrderOf<T>, OptionQuery>;
+
+	/// The ids of the open limit orders of an account.
+	#[pallet::storage]
+	pub type AccountOrders<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<OrderId, T::MaxLimitOrders>,
+		ValueQuery,
+	>;
+
+	/// The last limit order matched in `on_idle`, from which matching resumes in the next block.
+	#[pallet::storage]
+	pub type OrderMatchCursor<T: Config> = StorageValue<_, OrderId, OptionQuery>;
+
 	// Pallet's events.
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -291,6 +350,45 @@ pub mod pallet {
 			/// The amount of the second asset that was received.
 			amount_out: T::AssetBalance,
 		},
+		/// A limit order has been placed.
+		LimitOrderPlaced {
+			/// The id of the order.
+			order_id: OrderId,
+			/// The account that placed the order.
+			owner: T::AccountId,
+			/// The asset sold by the order.
+			asset_in: T::MultiAssetId,
+			/// The asset bought by the order.
+			asset_out: T::MultiAssetId,
+			/// The amount of `asset_in` sold by the order.
+			amount_in: T::AssetBalance,
+			/// The minimum amount of `asset_out` to receive for `amount_in`.
+			amount_out: T::AssetBalance,
+		},
+		/// A limit order has been filled, either partially or fully, against the pool of its
+		/// assets.
+		LimitOrderFilled {
+			/// The id of the order.
+			order_id: OrderId,
+			/// The account that placed the order.
+			owner: T::AccountId,
+			/// The amount of the sold asset that was swapped.
+			amount_in: T::AssetBalance,
+			/// The amount of the bought asset that was received.
+			amount_out: T::AssetBalance,
+			/// The amount of the sold asset left in the order. The order is closed when this is
+			/// zero.
+			remaining: T::AssetBalance,
+		},
+		/// A limit order has been cancelled.
+		LimitOrderCancelled {
+			/// The id of the order.
+			order_id: OrderId,
+			/// The account that placed the order.
+			owner: T::AccountId,
+			/// The amount of the sold asset that was returned to the owner.
+			refunded: T::AssetBalance,
+		},
 		/// An amount has been transferred from one account to another.
 		Transfer {
 			/// The account that the assets were transferred from.
@@ -365,6 +463,18 @@ pub mod pallet {
 		/// with another. For example, an array of assets constituting a `path` should have a
 		/// corresponding array of `amounts` along the path.
 		CorrespondenceError,
+		/// The parameters of the pool curve are out of bounds.
+		InvalidCurve,
+		/// The pool curve couldn't be solved for the given amounts.
+		CurveError,
+		/// The swap would leave the pool with less than its curve allows.
+		InvariantViolated,
+		/// The account has too many open limit orders.
+		TooManyOrders,
+		/// The limit order doesn't exist.
+		OrderNotFound,
+		/// Only the owner of a limit order can cancel it.
+		NotOrderOwner,
 	}
 
 	#[pallet::hooks]
@@ -375,6 +485,10 @@ pub mod pallet {
 				"the `MaxSwapPathLength` should be greater than 1",
 			);
 		}
+
+		fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::match_limit_orders(remaining_weight)
+		}
 	}
 
 	/// Pallet's callable functions.
@@ -392,65 +506,7 @@ pub mod pallet {
 			asset2: T::MultiAssetId,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
-			ensure!(asset1 != asset2, Error::<T>::EqualAssets);
-
-			// prepare pool_id
-			let pool_id = Self::get_pool_id(asset1, asset2);
-			ensure!(!Pools::<T>::contains_key(&pool_id), Error::<T>::PoolExists);
-			let (asset1, asset2) = &pool_id;
-			if !T::AllowMultiAssetPools::get() && !T::MultiAssetIdConverter::is_native(asset1) {
-				Err(Error::<T>::PoolMustContainNativeCurrency)?;
-			}
-
-			let pool_account = Self::get_pool_account(&pool_id);
-			frame_system::Pallet::<T>::inc_providers(&pool_account);
-
-			// pay the setup fee
-			T::Currency::transfer(
-				&sender,
-				&T::PoolSetupFeeReceiver::get(),
-				T::PoolSetupFee::get(),
-				Preserve,
-			)?;
-
-			// try to convert both assets
-			match T::MultiAssetIdConverter::try_convert(asset1) {
-				MultiAssetIdConversionResult::Converted(asset) =>
-					if !T::Assets::contains(&asset, &pool_account) {
-						T::Assets::touch(asset, pool_account.clone(), sender.clone())?
-					},
-				MultiAssetIdConversionResult::Unsupported(_) => Err(Error::<T>::UnsupportedAsset)?,
-				MultiAssetIdConversionResult::Native => (),
-			}
-			match T::MultiAssetIdConverter::try_convert(asset2) {
-				MultiAssetIdConversionResult::Converted(asset) =>
-					if !T::Assets::contains(&asset, &pool_account) {
-						T::Assets::touch(asset, pool_account.clone(), sender.clone())?
-					},
-				MultiAssetIdConversionResult::Unsupported(_) => Err(Error::<T>::UnsupportedAsset)?,
-				MultiAssetIdConversionResult::Native => (),
-			}
-
-			let lp_token = NextPoolAssetId::<T>::get()
-				.or(T::PoolAssetId::initial_value())
-				.ok_or(Error::<T>::IncorrectPoolAssetId)?;
-			let next_lp_token_id = lp_token.increment().ok_or(Error::<T>::IncorrectPoolAssetId)?;
-			NextPoolAssetId::<T>::set(Some(next_lp_token_id));
-
-			T::PoolAssets::create(lp_token.clone(), pool_account.clone(), false, 1u32.into())?;
-			T::PoolAssets::touch(lp_token.clone(), pool_account.clone(), sender.clone())?;
-
-			let pool_info = PoolInfo { lp_token: lp_token.clone() };
-			Pools::<T>::insert(pool_id.clone(), pool_info);
-
-			Self::deposit_event(Event::PoolCreated {
-				creator: sender,
-				pool_id,
-				pool_account,
-				lp_token,
-			});
-
-			Ok(())
+			Self::do_create_pool(sender, asset1, asset2, PoolCurve::ConstantProduct)
 		}
 
 		/// Provide liquidity into the pool of `asset1` and `asset2`.
@@ -654,7 +710,10 @@ pub mod pallet {
 		/// [`AssetConversionApi::quote_price_exact_tokens_for_tokens`] runtime call can be called
 		/// for a quote.
 		#[pallet::call_index(3)]
-		#[pallet::weight(T::WeightInfo::swap_exact_tokens_for_tokens())]
+		#[pallet::weight(
+			T::WeightInfo::swap_exact_tokens_for_tokens()
+				.saturating_add(Pallet::<T>::max_curves_weight(path.len()))
+		)]
 		pub fn swap_exact_tokens_for_tokens(
 			origin: OriginFor<T>,
 			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
@@ -662,8 +721,9 @@ pub mod pallet {
 			amount_out_min: T::AssetBalance,
 			send_to: T::AccountId,
 			keep_alive: bool,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let sender = ensure_signed(origin)?;
+			let curves_weight = Self::curves_weight(&path);
 			Self::do_swap_exact_tokens_for_tokens(
 				sender,
 				path,
@@ -672,7 +732,8 @@ pub mod pallet {
 				send_to,
 				keep_alive,
 			)?;
-			Ok(())
+			Ok(Some(T::WeightInfo::swap_exact_tokens_for_tokens().saturating_add(curves_weight))
+				.into())
 		}
 
 		/// Swap any amount of `asset1` to get the exact amount of `asset2`.
@@ -682,7 +743,10 @@ pub mod pallet {
 		/// [`AssetConversionApi::quote_price_tokens_for_exact_tokens`] runtime call can be called
 		/// for a quote.
 		#[pallet::call_index(4)]
-		#[pallet::weight(T::WeightInfo::swap_tokens_for_exact_tokens())]
+		#[pallet::weight(
+			T::WeightInfo::swap_tokens_for_exact_tokens()
+				.saturating_add(Pallet::<T>::max_curves_weight(path.len()))
+		)]
 		pub fn swap_tokens_for_exact_tokens(
 			origin: OriginFor<T>,
 			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
@@ -690,8 +754,9 @@ pub mod pallet {
 			amount_in_max: T::AssetBalance,
 			send_to: T::AccountId,
 			keep_alive: bool,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let sender = ensure_signed(origin)?;
+			let curves_weight = Self::curves_weight(&path);
 			Self::do_swap_tokens_for_exact_tokens(
 				sender,
 				path,
@@ -700,11 +765,191 @@ pub mod pallet {
 				send_to,
 				keep_alive,
 			)?;
+			Ok(Some(T::WeightInfo::swap_tokens_for_exact_tokens().saturating_add(curves_weight))
+				.into())
+		}
+
+		/// Creates an empty liquidity pool pricing its swaps along `curve`, and an associated new
+		/// `lp_token` asset (the id of which is returned in the `Event::PoolCreated` event).
+		///
+		/// The parameters of `curve` are given in the order of `asset1` and `asset2`, e.g. a
+		/// `PoolCurve::Weighted` curve with `weight1` set to `80` gives `asset1` a weight of 80.
+		///
+		/// Once a pool is created, someone may [`Pallet::add_liquidity`] to it.
+		#[pallet::call_index(5)]
+		#[pallet::weight(
+			T::WeightInfo::create_pool().saturating_add(T::DbWeight::get().writes(1))
+		)]
+		pub fn create_pool_with_curve(
+			origin: OriginFor<T>,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			curve: PoolCurve,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::do_create_pool(sender, asset1, asset2, curve)
+		}
+
+		/// Place a limit order selling `amount_in` of `asset_in` for at least `amount_out` of
+		/// `asset_out`, i.e. at a price of at least `amount_out / amount_in`.
+		///
+		/// The `amount_in` is put aside until it's sold or the order is cancelled, together with a
+		/// deposit of `T::LimitOrderDeposit` in the native currency, returned once the order is
+		/// closed. Both are held on the sender's account, except for a non-native `amount_in`,
+		/// which is moved to [`Pallet::get_orders_account`] respecting `keep_alive`. The
+		/// order is matched against the pool of both assets with the weight left at the end of a
+		/// block, and may be filled in several parts. The bought asset is sent to the sender.
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::WeightInfo::place_limit_order())]
+		pub fn place_limit_order(
+			origin: OriginFor<T>,
+			asset_in: T::MultiAssetId,
+			asset_out: T::MultiAssetId,
+			amount_in: T::AssetBalance,
+			amount_out: T::AssetBalance,
+			keep_alive: bool,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(asset_in != asset_out, Error::<T>::EqualAssets);
+			ensure!(!amount_in.is_zero() && !amount_out.is_zero(), Error::<T>::ZeroAmount);
+
+			let pool_id = Self::get_pool_id(asset_in.clone(), asset_out.clone());
+			ensure!(Pools::<T>::contains_key(&pool_id), Error::<T>::PoolNotFound);
+			Self::validate_minimal_amount(amount_in, &asset_in)
+				.map_err(|_| Error::<T>::AmountOneLessThanMinimal)?;
+
+			let order_id = NextOrderId::<T>::get();
+			NextOrderId::<T>::put(order_id.checked_add(1).ok_or(Error::<T>::Overflow)?);
+			AccountOrders::<T>::try_mutate(&sender, |orders| orders.try_push(order_id))
+				.map_err(|_| Error::<T>::TooManyOrders)?;
+
+			Self::hold_order_asset(&asset_in, &sender, amount_in, keep_alive)?;
+			let deposit = T::LimitOrderDeposit::get();
+			T::Currency::hold(&HoldReason::LimitOrder.into(), &sender, deposit)?;
+
+			LimitOrders::<T>::insert(
+				order_id,
+				LimitOrder {
+					owner: sender.clone(),
+					asset_in: asset_in.clone(),
+					asset_out: asset_out.clone(),
+					amount_in,
+					amount_out,
+					remaining: amount_in,
+					deposit,
+				},
+			);
+
+			Self::deposit_event(Event::LimitOrderPlaced {
+				order_id,
+				owner: sender,
+				asset_in,
+				asset_out,
+				amount_in,
+				amount_out,
+			});
+			Ok(())
+		}
+
+		/// Cancel the limit order `order_id` of the sender, returning the part of the sold asset
+		/// which is not yet filled and the deposit of the order.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::cancel_limit_order())]
+		pub fn cancel_limit_order(origin: OriginFor<T>, order_id: OrderId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let order = LimitOrders::<T>::get(order_id).ok_or(Error::<T>::OrderNotFound)?;
+			ensure!(order.owner == sender, Error::<T>::NotOrderOwner);
+
+			Self::release_order_asset(&order.asset_in, &sender, order.remaining)?;
+			Self::remove_order(order_id, &order)?;
+
+			Self::deposit_event(Event::LimitOrderCancelled {
+				order_id,
+				owner: sender,
+				refunded: order.remaining,
+			});
 			Ok(())
 		}
 	}
 
 	impl<T: Config> Pallet<T> {
+		/// Create a pool of `asset1` and `asset2` pricing its swaps along `curve`, paying the
+		/// setup fee from `sender`.
+		///
+		/// The parameters of `curve` are given in the order of `asset1` and `asset2`.
+		pub(crate) fn do_create_pool(
+			sender: T::AccountId,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			curve: PoolCurve,
+		) -> DispatchResult {
+			ensure!(asset1 != asset2, Error::<T>::EqualAssets);
+			ensure!(curve.is_valid(), Error::<T>::InvalidCurve);
+
+			// prepare pool_id
+			let pool_id = Self::get_pool_id(asset1.clone(), asset2);
+			// the curve parameters follow the pool's asset order
+			let curve = if pool_id.0 == asset1 { curve } else { curve.reversed() };
+			ensure!(!Pools::<T>::contains_key(&pool_id), Error::<T>::PoolExists);
+			let (asset1, asset2) = &pool_id;
+			if !T::AllowMultiAssetPools::get() && !T::MultiAssetIdConverter::is_native(asset1) {
+				Err(Error::<T>::PoolMustContainNativeCurrency)?;
+			}
+
+			let pool_account = Self::get_pool_account(&pool_id);
+			frame_system::Pallet::<T>::inc_providers(&pool_account);
+
+			// pay the setup fee
+			T::Currency::transfer(
+				&sender,
+				&T::PoolSetupFeeReceiver::get(),
+				T::PoolSetupFee::get(),
+				Preserve,
+			)?;
+
+			// try to convert both assets
+			match T::MultiAssetIdConverter::try_convert(asset1) {
+				MultiAssetIdConversionResult::Converted(asset) =>
+					if !T::Assets::contains(&asset, &pool_account) {
+						T::Assets::touch(asset, pool_account.clone(), sender.clone())?
+					},
+				MultiAssetIdConversionResult::Unsupported(_) => Err(Error::<T>::UnsupportedAsset)?,
+				MultiAssetIdConversionResult::Native => (),
+			}
+			match T::MultiAssetIdConverter::try_convert(asset2) {
+				MultiAssetIdConversionResult::Converted(asset) =>
+					if !T::Assets::contains(&asset, &pool_account) {
+						T::Assets::touch(asset, pool_account.clone(), sender.clone())?
+					},
+				MultiAssetIdConversionResult::Unsupported(_) => Err(Error::<T>::UnsupportedAsset)?,
+				MultiAssetIdConversionResult::Native => (),
+			}
+
+			let lp_token = NextPoolAssetId::<T>::get()
+				.or(T::PoolAssetId::initial_value())
+				.ok_or(Error::<T>::IncorrectPoolAssetId)?;
+			let next_lp_token_id = lp_token.increment().ok_or(Error::<T>::IncorrectPoolAssetId)?;
+			NextPoolAssetId::<T>::set(Some(next_lp_token_id));
+
+			T::PoolAssets::create(lp_token.clone(), pool_account.clone(), false, 1u32.into())?;
+			T::PoolAssets::touch(lp_token.clone(), pool_account.clone(), sender.clone())?;
+
+			let pool_info = PoolInfo { lp_token: lp_token.clone() };
+			Pools::<T>::insert(pool_id.clone(), pool_info);
+			if curve != PoolCurve::ConstantProduct {
+				PoolCurves::<T>::insert(&pool_id, curve);
+			}
+
+			Self::deposit_event(Event::PoolCreated {
+				creator: sender,
+				pool_id,
+				pool_account,
+				lp_token,
+			});
+
+			Ok(())
+		}
+
 		/// Swap exactly `amount_in` of asset `path[0]` for asset `path[1]`.
 		/// If an `amount_out_min` is specified, it will return an error if it is unable to acquire
 		/// the amount desired.
@@ -887,6 +1132,20 @@ pub mod pallet {
 						Self::validate_minimal_amount(reserve_left, asset2)
 							.map_err(|_| Error::<T>::ReserveLeftLessThanMinimal)?;
 
+						// The amount in has already been transferred to the pool.
+						let amount_in =
+							amounts.get(i as usize).ok_or(Error::<T>::CorrespondenceError)?;
+						let reserve_in = Self::get_balance(&pool_account, asset1)?
+							.checked_sub(amount_in)
+							.ok_or(Error::<T>::Overflow)?;
+						Self::ensure_invariant(
+							&Self::get_curve(asset1, asset2),
+							amount_in,
+							amount_out,
+							&reserve_in,
+							&reserve,
+						)?;
+
 						Self::transfer(asset2, &pool_account, &to, *amount_out, true)?;
 					}
 					i.saturating_inc();
@@ -915,6 +1174,14 @@ pub mod pallet {
 				.expect("infinite length input; no invalid inputs for type; qed")
 		}
 
+		/// The account ID holding the unsold non-native assets of the limit orders.
+		///
+		/// The assets don't support holds, so the part of this account's balance belonging to
+		/// each order is tracked by the `remaining` amount of the order.
+		pub fn get_orders_account() -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(b"orders")
+		}
+
 		/// Get the `owner`'s balance of `asset`, which could be the chain's native asset or another
 		/// fungible. Returns a value in the form of an `AssetBalance`.
 		fn get_balance(
@@ -987,8 +1254,14 @@ pub mod pallet {
 			for assets_pair in path.windows(2).rev() {
 				if let [asset1, asset2] = assets_pair {
 					let (reserve_in, reserve_out) = Self::get_reserves(asset1, asset2)?;
+					let curve = Self::get_curve(asset1, asset2);
 					let prev_amount = amounts.last().expect("Always has at least one element");
-					let amount_in = Self::get_amount_in(prev_amount, &reserve_in, &reserve_out)?;
+					let amount_in = Self::get_amount_in_for_curve(
+						&curve,
+						prev_amount,
+						&reserve_in,
+						&reserve_out,
+					)?;
 					amounts.push(amount_in);
 				}
 			}
@@ -1007,8 +1280,14 @@ pub mod pallet {
 			for assets_pair in path.windows(2) {
 				if let [asset1, asset2] = assets_pair {
 					let (reserve_in, reserve_out) = Self::get_reserves(asset1, asset2)?;
+					let curve = Self::get_curve(asset1, asset2);
 					let prev_amount = amounts.last().expect("Always has at least one element");
-					let amount_out = Self::get_amount_out(prev_amount, &reserve_in, &reserve_out)?;
+					let amount_out = Self::get_amount_out_for_curve(
+						&curve,
+						prev_amount,
+						&reserve_in,
+						&reserve_out,
+					)?;
 					amounts.push(amount_out);
 				}
 			}
@@ -1029,10 +1308,13 @@ pub mod pallet {
 			let balance1 = Self::get_balance(&pool_account, &asset1).ok()?;
 			let balance2 = Self::get_balance(&pool_account, &asset2).ok()?;
 			if !balance1.is_zero() {
-				if include_fee {
-					Self::get_amount_out(&amount, &balance1, &balance2).ok()
-				} else {
-					Self::quote(&amount, &balance1, &balance2).ok()
+				match (Self::get_curve(&asset1, &asset2), include_fee) {
+					(curve, true) =>
+						Self::get_amount_out_for_curve(&curve, &amount, &balance1, &balance2).ok(),
+					(PoolCurve::ConstantProduct, false) =>
+						Self::quote(&amount, &balance1, &balance2).ok(),
+					(curve, false) =>
+						Self::curve_amount_out(&curve, &amount, &balance1, &balance2, 0).ok(),
 				}
 			} else {
 				None
@@ -1052,16 +1334,69 @@ pub mod pallet {
 			let balance1 = Self::get_balance(&pool_account, &asset1).ok()?;
 			let balance2 = Self::get_balance(&pool_account, &asset2).ok()?;
 			if !balance1.is_zero() {
-				if include_fee {
-					Self::get_amount_in(&amount, &balance1, &balance2).ok()
-				} else {
-					Self::quote(&amount, &balance2, &balance1).ok()
+				match (Self::get_curve(&asset1, &asset2), include_fee) {
+					(curve, true) =>
+						Self::get_amount_in_for_curve(&curve, &amount, &balance1, &balance2).ok(),
+					(PoolCurve::ConstantProduct, false) =>
+						Self::quote(&amount, &balance2, &balance1).ok(),
+					(curve, false) =>
+						Self::curve_amount_in(&curve, &amount, &balance1, &balance2, 0).ok(),
 				}
 			} else {
 				None
 			}
 		}
 
+		/// The weight of solving the `curve` of a pool for one hop of a swap, on top of the
+		/// constant product one the swap weights are benchmarked with.
+		pub fn curve_weight(curve: &PoolCurve) -> Weight {
+			match curve {
+				PoolCurve::ConstantProduct => Weight::zero(),
+				PoolCurve::StableSwap { .. } => T::WeightInfo::stable_swap_hop(),
+				PoolCurve::Weighted { .. } => T::WeightInfo::weighted_swap_hop(),
+			}
+		}
+
+		/// The weight of solving the curves of the pools along a path of `path_len` assets, if
+		/// each pool has the most expensive curve.
+		pub fn max_curves_weight(path_len: usize) -> Weight {
+			let hops = path_len.saturating_sub(1) as u64;
+			T::WeightInfo::stable_swap_hop()
+				.max(T::WeightInfo::weighted_swap_hop())
+				.saturating_mul(hops)
+		}
+
+		/// The weight of solving the curves of the pools along `path`.
+		fn curves_weight(path: &BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>) -> Weight {
+			path.windows(2)
+				.filter_map(|pair| match pair {
+					[asset1, asset2] => Some(Self::curve_weight(&Self::get_curve(asset1, asset2))),
+					_ => None,
+				})
+				.fold(Weight::zero(), |total, weight| total.saturating_add(weight))
+		}
+
+		/// Returns the curve of the pool of `asset1` and `asset2`, with its parameters in the order
+		/// requested (not necessarily the same as pool order).
+		pub fn get_curve(asset1: &T::MultiAssetId, asset2: &T::MultiAssetId) -> PoolCurve {
+			let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone());
+			let curve = PoolCurves::<T>::get(&pool_id);
+			if pool_id.0 == *asset1 {
+				curve
+			} else {
+				curve.reversed()
+			}
+		}
+
+		/// Used by the RPC service to provide the curve of a pool.
+		pub fn get_pool_curve(
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+		) -> Option<PoolCurve> {
+			let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone());
+			Pools::<T>::contains_key(&pool_id).then(|| Self::get_curve(&asset1, &asset2))
+		}
+
 		/// Calculates the optimal amount from the reserves.
 		pub fn quote(
 			amount: &T::AssetBalance,
@@ -1184,6 +1519,434 @@ pub mod pallet {
 			result.try_into().map_err(|_| Error::<T>::Overflow)
 		}
 
+		/// Calculates amount out along the `curve` of a pool.
+		///
+		/// Given an input amount of an asset and pair reserves, returns the maximum output amount
+		/// of the other asset. The parameters of `curve` are in the order of the input and output
+		/// assets.
+		pub fn get_amount_out_for_curve(
+			curve: &PoolCurve,
+			amount_in: &T::AssetBalance,
+			reserve_in: &T::AssetBalance,
+			reserve_out: &T::AssetBalance,
+		) -> Result<T::AssetBalance, Error<T>> {
+			match curve {
+				PoolCurve::ConstantProduct =>
+					Self::get_amount_out(amount_in, reserve_in, reserve_out),
+				curve => Self::curve_amount_out(
+					curve,
+					amount_in,
+					reserve_in,
+					reserve_out,
+					T::LPFee::get(),
+				),
+			}
+		}
+
+		/// Calculates amount in along the `curve` of a pool.
+		///
+		/// Given an output amount of an asset and pair reserves, returns a required input amount
+		/// of the other asset. The parameters of `curve` are in the order of the input and output
+		/// assets.
+		pub fn get_amount_in_for_curve(
+			curve: &PoolCurve,
+			amount_out: &T::AssetBalance,
+			reserve_in: &T::AssetBalance,
+			reserve_out: &T::AssetBalance,
+		) -> Result<T::AssetBalance, Error<T>> {
+			match curve {
+				PoolCurve::ConstantProduct =>
+					Self::get_amount_in(amount_out, reserve_in, reserve_out),
+				curve => Self::curve_amount_in(
+					curve,
+					amount_out,
+					reserve_in,
+					reserve_out,
+					T::LPFee::get(),
+				),
+			}
+		}
+
+		/// Ensure that swapping `amount_in` for `amount_out` in a pool with the reserves
+		/// `reserve_in` and `reserve_out` keeps the invariant of its `curve`.
+		///
+		/// The constant product invariant is checked exactly, with the fee taken from `amount_in`.
+		/// The other curves can't be evaluated exactly, so `amount_out` must not exceed the amount
+		/// their formulas, rounded in favour of the pool, give for `amount_in`.
+		pub(crate) fn ensure_invariant(
+			curve: &PoolCurve,
+			amount_in: &T::AssetBalance,
+			amount_out: &T::AssetBalance,
+			reserve_in: &T::AssetBalance,
+			reserve_out: &T::AssetBalance,
+		) -> Result<(), Error<T>> {
+			let holds = match curve {
+				PoolCurve::ConstantProduct => {
+					let amount_in = T::HigherPrecisionBalance::from(*amount_in);
+					let amount_out = T::HigherPrecisionBalance::from(*amount_out);
+					let reserve_in = T::HigherPrecisionBalance::from(*reserve_in);
+					let reserve_out = T::HigherPrecisionBalance::from(*reserve_out);
+					let fee_multiplier =
+						T::HigherPrecisionBalance::from(1000u32) - T::LPFee::get().into();
+
+					// (reserve_in * 1000 + amount_in * (1000 - fee)) * (reserve_out - amount_out)
+					//     >= reserve_in * 1000 * reserve_out
+					let new_k = reserve_in
+						.checked_mul(&1000u32.into())
+						.and_then(|r| r.checked_add(&amount_in.checked_mul(&fee_multiplier)?))
+						.and_then(|r| r.checked_mul(&reserve_out.checked_sub(&amount_out)?))
+						.ok_or(Error::<T>::Overflow)?;
+					let k = reserve_in
+						.checked_mul(&1000u32.into())
+						.and_then(|r| r.checked_mul(&reserve_out))
+						.ok_or(Error::<T>::Overflow)?;
+					new_k >= k
+				},
+				curve =>
+					Self::curve_amount_out(
+						curve,
+						amount_in,
+						reserve_in,
+						reserve_out,
+						T::LPFee::get(),
+					)? >= *amount_out,
+			};
+			ensure!(holds, Error::<T>::InvariantViolated);
+			Ok(())
+		}
+
+		/// Amount out of a non constant product `curve`, taking a fee of `fee` 10ths of a percent
+		/// from `amount_in`.
+		fn curve_amount_out(
+			curve: &PoolCurve,
+			amount_in: &T::AssetBalance,
+			reserve_in: &T::AssetBalance,
+			reserve_out: &T::AssetBalance,
+			fee: u32,
+		) -> Result<T::AssetBalance, Error<T>> {
+			if reserve_in.is_zero() || reserve_out.is_zero() {
+				return Err(Error::<T>::ZeroLiquidity)
+			}
+			let amount_in: u128 = (*amount_in).try_into().map_err(|_| Error::<T>::Overflow)?;
+			let reserve_in: u128 = (*reserve_in).try_into().map_err(|_| Error::<T>::Overflow)?;
+			let reserve_out: u128 = (*reserve_out).try_into().map_err(|_| Error::<T>::Overflow)?;
+
+			let amount_in_without_fee =
+				Permill::from_rational(1000u32.saturating_sub(fee), 1000).mul_floor(amount_in);
+			let amount_out = match *curve {
+				PoolCurve::ConstantProduct => None,
+				PoolCurve::StableSwap { amplification } => curves::stable_swap_amount_out(
+					amplification,
+					amount_in_without_fee,
+					reserve_in,
+					reserve_out,
+				),
+				PoolCurve::Weighted { weight1, weight2 } => curves::weighted_amount_out(
+					weight1,
+					weight2,
+					amount_in_without_fee,
+					reserve_in,
+					reserve_out,
+				),
+			}
+			.ok_or(Error::<T>::CurveError)?;
+
+			Self::convert_u128_to_asset_balance(amount_out)
+		}
+
+		/// Amount in of a non constant product `curve`, adding a fee of `fee` 10ths of a percent
+		/// on top of the amount the pool needs.
+		fn curve_amount_in(
+			curve: &PoolCurve,
+			amount_out: &T::AssetBalance,
+			reserve_in: &T::AssetBalance,
+			reserve_out: &T::AssetBalance,
+			fee: u32,
+		) -> Result<T::AssetBalance, Error<T>> {
+			if reserve_in.is_zero() || reserve_out.is_zero() {
+				return Err(Error::<T>::ZeroLiquidity)
+			}
+			if amount_out >= reserve_out {
+				return Err(Error::<T>::AmountOutTooHigh)
+			}
+			let amount_out: u128 = (*amount_out).try_into().map_err(|_| Error::<T>::Overflow)?;
+			let reserve_in: u128 = (*reserve_in).try_into().map_err(|_| Error::<T>::Overflow)?;
+			let reserve_out: u128 = (*reserve_out).try_into().map_err(|_| Error::<T>::Overflow)?;
+
+			let amount_in_without_fee = match *curve {
+				PoolCurve::ConstantProduct => None,
+				PoolCurve::StableSwap { amplification } => curves::stable_swap_amount_in(
+					amplification,
+					amount_out,
+					reserve_in,
+					reserve_out,
+				),
+				PoolCurve::Weighted { weight1, weight2 } => curves::weighted_amount_in(
+					weight1,
+					weight2,
+					amount_out,
+					reserve_in,
+					reserve_out,
+				),
+			}
+			.ok_or(Error::<T>::CurveError)?;
+
+			// amount_in_without_fee * 1000 / (1000 - fee), rounded up
+			let amount_in = Self::mul_div(
+				&Self::convert_u128_to_asset_balance(amount_in_without_fee)?,
+				&1000u32.into(),
+				&1000u32.checked_sub(fee).ok_or(Error::<T>::Overflow)?.into(),
+			)?
+			.checked_add(&One::one())
+			.ok_or(Error::<T>::Overflow)?;
+
+			Ok(amount_in)
+		}
+
+		/// Convert a `u128` to an `AssetBalance`.
+		fn convert_u128_to_asset_balance(amount: u128) -> Result<T::AssetBalance, Error<T>> {
+			amount.try_into().map_err(|_| Error::<T>::Overflow)
+		}
+
+		/// Used by the RPC service to provide the open limit orders of `who` selling either of
+		/// `asset1` and `asset2` for the other.
+		pub fn open_orders(
+			who: T::AccountId,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+		) -> Vec<(OrderId, LimitOrderOf<T>)> {
+			AccountOrders::<T>::get(&who)
+				.into_iter()
+				.filter_map(|order_id| {
+					LimitOrders::<T>::get(order_id).map(|order| (order_id, order))
+				})
+				.filter(|(_, order)| {
+					(order.asset_in == asset1 && order.asset_out == asset2) ||
+						(order.asset_in == asset2 && order.asset_out == asset1)
+				})
+				.collect()
+		}
+
+		/// Match the open limit orders against the pools within the `limit` weight, resuming from
+		/// where the previous call stopped. Returns the weight consumed.
+		pub(crate) fn match_limit_orders(limit: Weight) -> Weight {
+			let base_weight = T::DbWeight::get().reads_writes(1, 1);
+			let order_weight = T::WeightInfo::match_limit_order();
+			if !limit.all_gte(base_weight) {
+				return Weight::zero()
+			}
+			let max_orders = limit
+				.saturating_sub(base_weight)
+				.checked_div_per_component(&order_weight)
+				.unwrap_or_default();
+			if max_orders.is_zero() {
+				return Weight::zero()
+			}
+
+			let order_ids = match OrderMatchCursor::<T>::get() {
+				Some(last) =>
+					LimitOrders::<T>::iter_keys_from(LimitOrders::<T>::hashed_key_for(last)),
+				None => LimitOrders::<T>::iter_keys(),
+			}
+			.take(max_orders as usize)
+			.collect::<Vec<_>>();
+
+			// start over from the first order once all the orders have been visited
+			match order_ids.last() {
+				Some(last) if order_ids.len() as u64 == max_orders =>
+					OrderMatchCursor::<T>::put(last),
+				_ => OrderMatchCursor::<T>::kill(),
+			}
+
+			for order_id in order_ids.iter() {
+				Self::match_limit_order(*order_id);
+			}
+
+			base_weight.saturating_add(order_weight.saturating_mul(order_ids.len() as u64))
+		}
+
+		/// Fill as much of the limit order `order_id` as the pool of its assets allows at the limit
+		/// price of the order.
+		pub(crate) fn match_limit_order(order_id: OrderId) {
+			let Some(mut order) = LimitOrders::<T>::get(order_id) else { return };
+			let Some(amount_in) = Self::limit_order_fill(&order) else { return };
+			let Ok(amount_out_min) = Self::limit_amount_out(&order, amount_in) else { return };
+
+			let path =
+				BoundedVec::truncate_from(vec![order.asset_in.clone(), order.asset_out.clone()]);
+			// a failing swap must not leave any change behind, the order is simply not filled.
+			let Ok(amount_out) = with_storage_layer(|| {
+				// the native currency is released to be swapped from the owner's account, who
+				// is kept alive by the hold.
+				let (sender, keep_alive) = if T::MultiAssetIdConverter::is_native(&order.asset_in) {
+					T::Currency::release(
+						&HoldReason::LimitOrder.into(),
+						&order.owner,
+						Self::convert_asset_balance_to_native_balance(amount_in)?,
+						Exact,
+					)?;
+					(order.owner.clone(), true)
+				} else {
+					(Self::get_orders_account(), false)
+				};
+				Self::do_swap_exact_tokens_for_tokens(
+					sender,
+					path,
+					amount_in,
+					Some(amount_out_min),
+					order.owner.clone(),
+					keep_alive,
+				)
+			}) else {
+				return
+			};
+
+			order.remaining = order.remaining.saturating_sub(amount_in);
+			Self::deposit_event(Event::LimitOrderFilled {
+				order_id,
+				owner: order.owner.clone(),
+				amount_in,
+				amount_out,
+				remaining: order.remaining,
+			});
+			if order.remaining.is_zero() {
+				let _ = Self::remove_order(order_id, &order).defensive();
+			} else {
+				LimitOrders::<T>::insert(order_id, order);
+			}
+		}
+
+		/// The largest amount of the remainder of `order` the pool of its assets can fill at the
+		/// limit price of the order, if any.
+		///
+		/// The amount left in the order is never below the minimum balance of the sold asset.
+		fn limit_order_fill(order: &LimitOrderOf<T>) -> Option<T::AssetBalance> {
+			let (reserve_in, reserve_out) =
+				Self::get_reserves(&order.asset_in, &order.asset_out).ok()?;
+			let curve = Self::get_curve(&order.asset_in, &order.asset_out);
+			let fills = |amount_in: T::AssetBalance| -> bool {
+				match (
+					Self::get_amount_out_for_curve(&curve, &amount_in, &reserve_in, &reserve_out),
+					Self::limit_amount_out(order, amount_in),
+				) {
+					(Ok(amount_out), Ok(amount_out_min)) =>
+						!amount_out.is_zero() && amount_out >= amount_out_min,
+					_ => false,
+				}
+			};
+
+			let mut amount_in = order.remaining;
+			if !fills(amount_in) {
+				// the price gets worse as the amount grows, search for the largest amount which
+				// still fills.
+				let two: T::AssetBalance = 2u32.into();
+				let (mut low, mut high) = (Zero::zero(), order.remaining);
+				while high.saturating_sub(low) > One::one() {
+					let middle = low.saturating_add(high.saturating_sub(low) / two);
+					if fills(middle) {
+						low = middle;
+					} else {
+						high = middle;
+					}
+				}
+				amount_in = low;
+			}
+
+			let left = order.remaining.saturating_sub(amount_in);
+			if !left.is_zero() && Self::validate_minimal_amount(left, &order.asset_in).is_err() {
+				amount_in = order.remaining.saturating_sub(Self::minimal_amount(&order.asset_in)?);
+				if !fills(amount_in) {
+					return None
+				}
+			}
+
+			(!amount_in.is_zero()).then_some(amount_in)
+		}
+
+		/// The minimum amount of the bought asset to receive for selling `amount_in` of `order`,
+		/// rounded up.
+		fn limit_amount_out(
+			order: &LimitOrderOf<T>,
+			amount_in: T::AssetBalance,
+		) -> Result<T::AssetBalance, Error<T>> {
+			let denominator = T::HigherPrecisionBalance::from(order.amount_in);
+			let result = T::HigherPrecisionBalance::from(amount_in)
+				.checked_mul(&T::HigherPrecisionBalance::from(order.amount_out))
+				.ok_or(Error::<T>::Overflow)?
+				.checked_add(&denominator)
+				.ok_or(Error::<T>::Overflow)?
+				.checked_sub(&One::one())
+				.ok_or(Error::<T>::Overflow)?
+				.checked_div(&denominator)
+				.ok_or(Error::<T>::Overflow)?;
+
+			result.try_into().map_err(|_| Error::<T>::Overflow)
+		}
+
+		/// Put `amount` of `asset` sold by a limit order of `who` aside: held if it's the native
+		/// currency, otherwise moved to the orders account respecting `keep_alive`.
+		fn hold_order_asset(
+			asset: &T::MultiAssetId,
+			who: &T::AccountId,
+			amount: T::AssetBalance,
+			keep_alive: bool,
+		) -> DispatchResult {
+			if T::MultiAssetIdConverter::is_native(asset) {
+				let amount = Self::convert_asset_balance_to_native_balance(amount)?;
+				return T::Currency::hold(&HoldReason::LimitOrder.into(), who, amount)
+			}
+			let orders_account = Self::get_orders_account();
+			// a provider per order with a non-native asset, released by `remove_order`.
+			frame_system::Pallet::<T>::inc_providers(&orders_account);
+			Self::transfer(asset, who, &orders_account, amount, keep_alive)?;
+			Ok(())
+		}
+
+		/// Give `amount` of `asset` put aside by a limit order back to `who`.
+		fn release_order_asset(
+			asset: &T::MultiAssetId,
+			who: &T::AccountId,
+			amount: T::AssetBalance,
+		) -> DispatchResult {
+			if T::MultiAssetIdConverter::is_native(asset) {
+				let amount = Self::convert_asset_balance_to_native_balance(amount)?;
+				T::Currency::release(&HoldReason::LimitOrder.into(), who, amount, Exact)?;
+			} else {
+				Self::transfer(asset, &Self::get_orders_account(), who, amount, false)?;
+			}
+			Ok(())
+		}
+
+		/// Remove the limit order `order_id`, once its sold asset is released, releasing its
+		/// deposit to the owner.
+		fn remove_order(order_id: OrderId, order: &LimitOrderOf<T>) -> DispatchResult {
+			T::Currency::release(
+				&HoldReason::LimitOrder.into(),
+				&order.owner,
+				order.deposit,
+				Exact,
+			)?;
+			LimitOrders::<T>::remove(order_id);
+			AccountOrders::<T>::mutate(&order.owner, |orders| orders.retain(|id| *id != order_id));
+			if !T::MultiAssetIdConverter::is_native(&order.asset_in) {
+				// the account may only be kept alive by assets sent to it outside of the orders.
+				let _ = frame_system::Pallet::<T>::dec_providers(&Self::get_orders_account());
+			}
+			Ok(())
+		}
+
+		/// The minimum balance of an `asset` class.
+		fn minimal_amount(asset: &T::MultiAssetId) -> Option<T::AssetBalance> {
+			match T::MultiAssetIdConverter::try_convert(asset) {
+				MultiAssetIdConversionResult::Converted(asset_id) =>
+					Some(T::Assets::minimum_balance(asset_id)),
+				MultiAssetIdConversionResult::Native =>
+					Self::convert_native_balance_to_asset_balance(T::Currency::minimum_balance())
+						.ok(),
+				MultiAssetIdConversionResult::Unsupported(_) => None,
+			}
+		}
+
 		/// Ensure that a `value` meets the minimum balance requirements of an `asset` class.
 		fn validate_minimal_amount(
 			value: T::AssetBalance,
@@ -1304,6 +2067,23 @@ sp_api::decl_runtime_apis! {
 
 		/// Returns the size of the liquidity pool for the given asset pair.
 		fn get_reserves(asset1: AssetId, asset2: AssetId) -> Option<(Balance, Balance)>;
+
+		/// Returns the curve of the liquidity pool for the given asset pair, with its parameters
+		/// in the order of `asset1` and `asset2`.
+		#[api_version(2)]
+		fn get_pool_curve(asset1: AssetId, asset2: AssetId) -> Option<PoolCurve>;
+	}
+
+	/// This runtime api allows people to query the open limit orders of an account.
+	pub trait AssetConversionLimitOrdersApi<AccountId, Balance, AssetBalance, AssetId> where
+		AccountId: Codec,
+		Balance: Codec,
+		AssetBalance: Codec,
+		AssetId: Codec
+	{
+		/// Returns the open limit orders of `who` selling either of `asset1` and `asset2` for the
+		/// other.
+		fn open_orders(who: AccountId, asset1: AssetId, asset2: AssetId) -> Vec<(OrderId, LimitOrder<AccountId, AssetId, AssetBalance, Balance>)>;
 	}
 }
 
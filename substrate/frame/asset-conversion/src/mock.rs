@@ -86,9 +86,9 @@ impl pallet_balances::Config for Test {
 	type ReserveIdentifier = [u8; 8];
 	type FreezeIdentifier = ();
 	type MaxFreezes = ();
-	type RuntimeHoldReason = ();
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type RuntimeFreezeReason = ();
-	type MaxHolds = ();
+	type MaxHolds = ConstU32<1>;
 }
 
 impl pallet_assets::Config<Instance1> for Test {
@@ -152,6 +152,7 @@ ord_parameter_types! {
 
 impl Config for Test {
 	type RuntimeEvent = RuntimeEvent;
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type Currency = Balances;
 	type AssetBalance = <Self as pallet_balances::Config>::Balance;
 	type AssetId = u32;
@@ -166,6 +167,8 @@ impl Config for Test {
 	type LiquidityWithdrawalFee = LiquidityWithdrawalFee;
 	type AllowMultiAssetPools = AllowMultiAssetPools;
 	type MaxSwapPathLength = ConstU32<4>;
+	type MaxLimitOrders = ConstU32<4>;
+	type LimitOrderDeposit = ConstU128<100>; // should be more or equal to the existential deposit
 	type MintMinLiquidity = ConstU128<100>; // 100 is good enough when the main currency has 12 decimals.
 
 	type Balance = u128;
@@ -19,7 +19,12 @@ use crate::{mock::*, *};
 use frame_support::{
 	assert_noop, assert_ok,
 	instances::Instance1,
-	traits::{fungible::Inspect, fungibles::InspectEnumerable, Get},
+	traits::{
+		fungible::{Inspect, InspectHold},
+		fungibles::InspectEnumerable,
+		Get, Hooks,
+	},
+	weights::Weight,
 };
 use sp_arithmetic::Permill;
 use sp_runtime::{DispatchError, TokenError};
@@ -86,6 +91,10 @@ fn pool_balance(owner: u128, token_id: u32) -> u128 {
 	<<Test as Config>::PoolAssets>::balance(token_id, owner)
 }
 
+fn held_balance(owner: u128) -> u128 {
+	<<Test as Config>::Currency>::balance_on_hold(&HoldReason::LimitOrder.into(), &owner)
+}
+
 fn get_ed() -> u128 {
 	<<Test as Config>::Currency>::minimum_balance()
 }
@@ -1685,3 +1694,696 @@ fn cannot_block_pool_creation() {
 		));
 	});
 }
+
+fn setup_pool_with_curve(
+	user: u128,
+	token_1: NativeOrAssetId<u32>,
+	token_2: NativeOrAssetId<u32>,
+	curve: PoolCurve,
+) {
+	assert_ok!(AssetConversion::create_pool_with_curve(
+		RuntimeOrigin::signed(user),
+		token_1,
+		token_2,
+		curve
+	));
+	assert_ok!(AssetConversion::add_liquidity(
+		RuntimeOrigin::signed(user),
+		token_1,
+		token_2,
+		10000,
+		10000,
+		1,
+		1,
+		user,
+	));
+}
+
+#[test]
+fn can_create_pool_with_curve() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+		let token_4 = NativeOrAssetId::Asset(4);
+
+		create_tokens(user, vec![token_2, token_3, token_4]);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 1000));
+
+		assert_noop!(
+			AssetConversion::create_pool_with_curve(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				PoolCurve::StableSwap { amplification: 0 }
+			),
+			Error::<Test>::InvalidCurve
+		);
+		assert_noop!(
+			AssetConversion::create_pool_with_curve(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				PoolCurve::Weighted { weight1: 0, weight2: 50 }
+			),
+			Error::<Test>::InvalidCurve
+		);
+
+		let stable = PoolCurve::StableSwap { amplification: 100 };
+		assert_ok!(AssetConversion::create_pool_with_curve(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			stable
+		));
+		assert_eq!(PoolCurves::<Test>::get((token_1, token_2)), stable);
+
+		// The weights are stored in the order of the pool assets.
+		assert_ok!(AssetConversion::create_pool_with_curve(
+			RuntimeOrigin::signed(user),
+			token_3,
+			token_1,
+			PoolCurve::Weighted { weight1: 80, weight2: 20 }
+		));
+		assert_eq!(
+			PoolCurves::<Test>::get((token_1, token_3)),
+			PoolCurve::Weighted { weight1: 20, weight2: 80 }
+		);
+		assert_eq!(
+			AssetConversion::get_pool_curve(token_3, token_1),
+			Some(PoolCurve::Weighted { weight1: 80, weight2: 20 })
+		);
+
+		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_4));
+		assert!(!PoolCurves::<Test>::contains_key((token_1, token_4)));
+		assert_eq!(
+			AssetConversion::get_pool_curve(token_1, token_4),
+			Some(PoolCurve::ConstantProduct)
+		);
+		assert_eq!(AssetConversion::get_pool_curve(token_2, token_3), None);
+	});
+}
+
+#[test]
+fn stable_swap_pool_has_less_slippage() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+
+		create_tokens(user, vec![token_2, token_3]);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 20000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 3, user, 20000));
+
+		setup_pool_with_curve(user, token_1, token_2, PoolCurve::ConstantProduct);
+		setup_pool_with_curve(user, token_1, token_3, PoolCurve::StableSwap { amplification: 100 });
+
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_2, token_1, 1000, true),
+			Some(906)
+		);
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_3, token_1, 1000, true),
+			Some(996)
+		);
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_3, token_1, 1000, false),
+			Some(999)
+		);
+
+		let native_before = balance(user, token_1);
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_3, token_1],
+			1000,
+			1,
+			user,
+			false,
+		));
+		assert_eq!(balance(user, token_1), native_before + 996);
+
+		let pool_account = AssetConversion::get_pool_account(&(token_1, token_3));
+		assert_eq!(balance(pool_account, token_1), 10000 - 996);
+		assert_eq!(balance(pool_account, token_3), 10000 + 1000);
+	});
+}
+
+#[test]
+fn stable_swap_tokens_for_exact_tokens_works() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 20000));
+		setup_pool_with_curve(user, token_1, token_2, PoolCurve::StableSwap { amplification: 100 });
+
+		let amount_in =
+			AssetConversion::quote_price_tokens_for_exact_tokens(token_2, token_1, 996, true)
+				.unwrap();
+		assert_eq!(amount_in, 1001);
+		// Selling the quoted amount gives at least the amount asked for.
+		assert!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_2, token_1, amount_in, true)
+				.unwrap() >= 996
+		);
+
+		let native_before = balance(user, token_1);
+		assert_ok!(AssetConversion::swap_tokens_for_exact_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_2, token_1],
+			996,
+			1001,
+			user,
+			false,
+		));
+		assert_eq!(balance(user, token_1), native_before + 996);
+		assert_eq!(balance(user, token_2), 20000 - 10000 - 1001);
+	});
+}
+
+#[test]
+fn weighted_pool_swaps_work() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 20000));
+		setup_pool_with_curve(
+			user,
+			token_2,
+			token_1,
+			PoolCurve::Weighted { weight1: 80, weight2: 20 },
+		);
+
+		// 10000 * (1 - (10000 / (10000 + 99)) ^ (80 / 20)), where 99 is the amount in after fee.
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_2, token_1, 100, true),
+			Some(386)
+		);
+		assert_eq!(
+			AssetConversion::quote_price_tokens_for_exact_tokens(token_2, token_1, 386, true),
+			Some(100)
+		);
+
+		let native_before = balance(user, token_1);
+		let post_info = AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_2, token_1],
+			100,
+			386,
+			user,
+			false,
+		)
+		.unwrap();
+		assert_eq!(balance(user, token_1), native_before + 386);
+		// Only the weight of the curve of the pool swapped through is charged.
+		assert_eq!(
+			post_info.actual_weight,
+			Some(
+				<() as WeightInfo>::swap_exact_tokens_for_tokens()
+					.saturating_add(<() as WeightInfo>::weighted_swap_hop())
+			)
+		);
+	});
+}
+
+#[test]
+fn weighted_pool_rounds_tiny_swaps_in_favour_of_the_pool() {
+	let weights = [(50, 50), (80, 20), (20, 80), (33, 67), (99, 98), (1, 100)];
+	for reserve in [10u128.pow(20), 10u128.pow(24), 10u128.pow(30)] {
+		for (weight_in, weight_out) in weights {
+			for amount in [1, 2, 1000, 1_000_000] {
+				// At most the spot price, `weight_in / weight_out` with equal reserves.
+				let amount_out =
+					curves::weighted_amount_out(weight_in, weight_out, amount, reserve, reserve)
+						.unwrap();
+				assert!(amount_out * weight_out as u128 <= amount * weight_in as u128);
+
+				// The amount in asked for an amount out buys at least as much.
+				let amount_out = amount_out.max(1);
+				let amount_in =
+					curves::weighted_amount_in(weight_in, weight_out, amount_out, reserve, reserve)
+						.unwrap();
+				assert!(
+					curves::weighted_amount_out(weight_in, weight_out, amount_in, reserve, reserve)
+						.unwrap() >= amount_out
+				);
+			}
+		}
+	}
+}
+
+#[test]
+fn weighted_pool_with_large_reserves_cannot_be_drained() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let reserve = 10u128.pow(24);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 2 * reserve));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 2 * reserve));
+		let curve = PoolCurve::Weighted { weight1: 50, weight2: 50 };
+		assert_ok!(AssetConversion::create_pool_with_curve(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			curve
+		));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			reserve,
+			reserve,
+			1,
+			1,
+			user,
+		));
+
+		// A single unit in buys nothing.
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_2, token_1, 1, false),
+			Some(0)
+		);
+		assert_noop!(
+			AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_2, token_1],
+				1,
+				1,
+				user,
+				false,
+			),
+			Error::<Test>::ProvidedMinimumNotSufficientForSwap
+		);
+
+		// 997_000 in after fee, rounded down by one unit.
+		let native_before = balance(user, token_1);
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_2, token_1],
+			1_000_000,
+			1,
+			user,
+			false,
+		));
+		assert_eq!(balance(user, token_1), native_before + 996_999);
+
+		// Paying out more than the curve allows is rejected.
+		assert_eq!(
+			AssetConversion::ensure_invariant(&curve, &1, &1_000_000, &reserve, &reserve),
+			Err(Error::<Test>::InvariantViolated)
+		);
+		assert_eq!(
+			AssetConversion::ensure_invariant(&curve, &1_000_000, &997_000, &reserve, &reserve),
+			Err(Error::<Test>::InvariantViolated)
+		);
+		assert_ok!(AssetConversion::ensure_invariant(
+			&curve, &1_000_000, &996_999, &reserve, &reserve
+		));
+		assert_eq!(
+			AssetConversion::ensure_invariant(
+				&PoolCurve::ConstantProduct,
+				&1000,
+				&998,
+				&10000,
+				&10000
+			),
+			Err(Error::<Test>::InvariantViolated)
+		);
+	});
+}
+
+#[test]
+fn swap_in_multi_hops_across_curves() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+
+		create_tokens(user, vec![token_2, token_3]);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 20000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 3, user, 20000));
+
+		setup_pool_with_curve(user, token_1, token_2, PoolCurve::ConstantProduct);
+		setup_pool_with_curve(user, token_1, token_3, PoolCurve::StableSwap { amplification: 100 });
+
+		// 906 out of the constant product pool, of which 903 after fee go into the stable pool.
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_2, token_1, token_3],
+			1000,
+			1,
+			user,
+			false,
+		));
+		assert_eq!(balance(user, token_2), 20000 - 10000 - 1000);
+		assert_eq!(balance(user, token_3), 20000 - 10000 + 902);
+
+		let pool_account = AssetConversion::get_pool_account(&(token_1, token_3));
+		assert_eq!(balance(pool_account, token_1), 10000 + 906);
+		assert_eq!(balance(pool_account, token_3), 10000 - 902);
+	});
+}
+
+fn setup_limit_order_pool(user: u128) {
+	let token_1 = NativeOrAssetId::Native;
+	let token_2 = NativeOrAssetId::Asset(2);
+
+	create_tokens(user, vec![token_2]);
+	assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+	assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 20000));
+	setup_pool_with_curve(user, token_1, token_2, PoolCurve::ConstantProduct);
+}
+
+#[test]
+fn can_place_and_cancel_limit_order() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+		setup_limit_order_pool(user);
+
+		assert_noop!(
+			AssetConversion::place_limit_order(
+				RuntimeOrigin::signed(user),
+				token_2,
+				token_2,
+				1000,
+				2000,
+				false
+			),
+			Error::<Test>::EqualAssets
+		);
+		assert_noop!(
+			AssetConversion::place_limit_order(
+				RuntimeOrigin::signed(user),
+				token_2,
+				token_1,
+				0,
+				2000,
+				false
+			),
+			Error::<Test>::ZeroAmount
+		);
+		assert_noop!(
+			AssetConversion::place_limit_order(
+				RuntimeOrigin::signed(user),
+				token_3,
+				token_1,
+				1000,
+				2000,
+				false
+			),
+			Error::<Test>::PoolNotFound
+		);
+
+		// priced well above the pool, so never filled
+		let native_before = balance(user, token_1);
+		assert_ok!(AssetConversion::place_limit_order(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			1000,
+			2000,
+			false
+		));
+		let orders_account = AssetConversion::get_orders_account();
+		assert_eq!(balance(user, token_2), 20000 - 10000 - 1000);
+		assert_eq!(balance(orders_account, token_2), 1000);
+		assert_eq!(balance(user, token_1), native_before - 100);
+		assert_eq!(held_balance(user), 100);
+		assert_eq!(AccountOrders::<Test>::get(user).into_inner(), vec![0]);
+		let order = LimitOrder {
+			owner: user,
+			asset_in: token_2,
+			asset_out: token_1,
+			amount_in: 1000,
+			amount_out: 2000,
+			remaining: 1000,
+			deposit: 100,
+		};
+		assert_eq!(AssetConversion::open_orders(user, token_1, token_2), vec![(0, order)]);
+		assert_eq!(AssetConversion::open_orders(user, token_1, token_3), vec![]);
+		assert!(events().contains(&Event::<Test>::LimitOrderPlaced {
+			order_id: 0,
+			owner: user,
+			asset_in: token_2,
+			asset_out: token_1,
+			amount_in: 1000,
+			amount_out: 2000,
+		}));
+
+		AssetConversion::on_idle(1, Weight::MAX);
+		assert_eq!(LimitOrders::<Test>::get(0).unwrap().remaining, 1000);
+
+		assert_noop!(
+			AssetConversion::cancel_limit_order(RuntimeOrigin::signed(2), 0),
+			Error::<Test>::NotOrderOwner
+		);
+		assert_ok!(AssetConversion::cancel_limit_order(RuntimeOrigin::signed(user), 0));
+		assert_noop!(
+			AssetConversion::cancel_limit_order(RuntimeOrigin::signed(user), 0),
+			Error::<Test>::OrderNotFound
+		);
+		assert_eq!(balance(user, token_2), 20000 - 10000);
+		assert_eq!(balance(orders_account, token_2), 0);
+		assert_eq!(balance(user, token_1), native_before);
+		assert_eq!(held_balance(user), 0);
+		assert!(AccountOrders::<Test>::get(user).is_empty());
+		assert!(!System::account_exists(&orders_account));
+		assert_eq!(
+			events().last(),
+			Some(&Event::<Test>::LimitOrderCancelled { order_id: 0, owner: user, refunded: 1000 })
+		);
+	});
+}
+
+#[test]
+fn limit_orders_per_account_are_bounded() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		setup_limit_order_pool(user);
+
+		for _ in 0..4 {
+			assert_ok!(AssetConversion::place_limit_order(
+				RuntimeOrigin::signed(user),
+				token_2,
+				token_1,
+				100,
+				200,
+				false
+			));
+		}
+		assert_noop!(
+			AssetConversion::place_limit_order(
+				RuntimeOrigin::signed(user),
+				token_2,
+				token_1,
+				100,
+				200,
+				false
+			),
+			Error::<Test>::TooManyOrders
+		);
+
+		assert_ok!(AssetConversion::cancel_limit_order(RuntimeOrigin::signed(user), 1));
+		assert_eq!(AccountOrders::<Test>::get(user).into_inner(), vec![0, 2, 3]);
+		assert_ok!(AssetConversion::place_limit_order(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			100,
+			200,
+			false
+		));
+		assert_eq!(AccountOrders::<Test>::get(user).into_inner(), vec![0, 2, 3, 4]);
+	});
+}
+
+#[test]
+fn limit_order_is_filled_in_on_idle() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		setup_limit_order_pool(user);
+
+		assert_ok!(AssetConversion::place_limit_order(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			1000,
+			900,
+			false
+		));
+		let native_before = balance(user, token_1);
+
+		AssetConversion::on_idle(1, Weight::MAX);
+
+		// the same as swapping 1000 with the pool, and the deposit is returned.
+		assert_eq!(balance(user, token_1), native_before + 906 + 100);
+		assert!(!LimitOrders::<Test>::contains_key(0));
+		assert!(AccountOrders::<Test>::get(user).is_empty());
+		assert_eq!(held_balance(user), 0);
+		assert!(!System::account_exists(&AssetConversion::get_orders_account()));
+		assert_eq!(
+			events().last(),
+			Some(&Event::<Test>::LimitOrderFilled {
+				order_id: 0,
+				owner: user,
+				amount_in: 1000,
+				amount_out: 906,
+				remaining: 0,
+			})
+		);
+	});
+}
+
+#[test]
+fn limit_order_selling_native_currency_is_held() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		setup_limit_order_pool(user);
+		let native_before = balance(user, token_1);
+
+		// priced well above the pool, so never filled
+		assert_ok!(AssetConversion::place_limit_order(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			1000,
+			2000,
+			false
+		));
+		assert_eq!(balance(user, token_1), native_before - 1000 - 100);
+		assert_eq!(held_balance(user), 1000 + 100);
+		assert_ok!(AssetConversion::cancel_limit_order(RuntimeOrigin::signed(user), 0));
+		assert_eq!(balance(user, token_1), native_before);
+		assert_eq!(held_balance(user), 0);
+
+		assert_ok!(AssetConversion::place_limit_order(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			1000,
+			900,
+			false
+		));
+		let asset_before = balance(user, token_2);
+
+		// the same as swapping 1000 with the pool, and the hold is released.
+		AssetConversion::on_idle(1, Weight::MAX);
+		assert_eq!(balance(user, token_2), asset_before + 906);
+		assert_eq!(balance(user, token_1), native_before - 1000);
+		assert_eq!(held_balance(user), 0);
+		assert!(!LimitOrders::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn limit_order_is_filled_partially() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let user2 = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		setup_limit_order_pool(user);
+
+		// at least 0.9 native per asset
+		assert_ok!(AssetConversion::place_limit_order(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			3000,
+			2700,
+			false
+		));
+		let native_before = balance(user, token_1);
+
+		// only the largest part not moving the price of the pool below the limit is filled.
+		AssetConversion::on_idle(1, Weight::MAX);
+		assert_eq!(balance(user, token_1), native_before + 968);
+		assert_eq!(LimitOrders::<Test>::get(0).unwrap().remaining, 3000 - 1075);
+		assert_eq!(
+			events().last(),
+			Some(&Event::<Test>::LimitOrderFilled {
+				order_id: 0,
+				owner: user,
+				amount_in: 1075,
+				amount_out: 968,
+				remaining: 3000 - 1075,
+			})
+		);
+
+		// nothing more to fill at the same price.
+		AssetConversion::on_idle(2, Weight::MAX);
+		assert_eq!(LimitOrders::<Test>::get(0).unwrap().remaining, 3000 - 1075);
+
+		// buying the asset from the pool lifts its price back above the limit.
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user2),
+			bvec![token_1, token_2],
+			2000,
+			1,
+			user2,
+			false,
+		));
+		AssetConversion::on_idle(3, Weight::MAX);
+		assert_eq!(balance(user, token_1), native_before + 968 + 1926 + 100);
+		assert!(!LimitOrders::<Test>::contains_key(0));
+	});
+}
+
+#[test]
+fn limit_order_matching_is_bounded_by_weight() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		setup_limit_order_pool(user);
+
+		for _ in 0..2 {
+			assert_ok!(AssetConversion::place_limit_order(
+				RuntimeOrigin::signed(user),
+				token_2,
+				token_1,
+				100,
+				50,
+				false
+			));
+		}
+
+		let base_weight = <Test as frame_system::Config>::DbWeight::get().reads_writes(1, 1);
+		let order_weight = <Test as Config>::WeightInfo::match_limit_order();
+		assert_eq!(AssetConversion::on_idle(1, base_weight), Weight::zero());
+		assert_eq!(LimitOrders::<Test>::iter().count(), 2);
+
+		let limit = base_weight.saturating_add(order_weight);
+		assert_eq!(AssetConversion::on_idle(1, limit), limit);
+		assert_eq!(LimitOrders::<Test>::iter().count(), 1);
+		assert!(OrderMatchCursor::<Test>::get().is_some());
+
+		assert_eq!(AssetConversion::on_idle(2, limit), limit);
+		assert_eq!(LimitOrders::<Test>::iter().count(), 0);
+		assert!(AccountOrders::<Test>::get(user).is_empty());
+	});
+}
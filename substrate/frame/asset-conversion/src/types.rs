@@ -19,6 +19,7 @@ use super::*;
 
 use codec::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
 use sp_std::{cmp::Ordering, marker::PhantomData};
 
 /// Pool ID.
@@ -27,6 +28,14 @@ use sp_std::{cmp::Ordering, marker::PhantomData};
 /// migration.
 pub(super) type PoolIdOf<T> = (<T as Config>::MultiAssetId, <T as Config>::MultiAssetId);
 
+/// Limit order of a runtime.
+pub type LimitOrderOf<T> = LimitOrder<
+	<T as frame_system::Config>::AccountId,
+	<T as Config>::MultiAssetId,
+	<T as Config>::AssetBalance,
+	<T as Config>::Balance,
+>;
+
 /// Stores the lp_token asset id a particular pool has been assigned.
 #[derive(Decode, Encode, Default, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
 pub struct PoolInfo<PoolAssetId> {
@@ -34,6 +43,90 @@ pub struct PoolInfo<PoolAssetId> {
 	pub lp_token: PoolAssetId,
 }
 
+/// The curve a pool prices its swaps along.
+///
+/// Any per-asset parameter is given in the order of the assets of the pool.
+#[derive(
+	Decode, Encode, Default, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo,
+)]
+pub enum PoolCurve {
+	/// The Uniswap V2 constant product formula, `x * y = k`.
+	#[default]
+	ConstantProduct,
+	/// The Curve StableSwap invariant, which keeps the price close to one for balanced reserves.
+	/// Suited for pegged asset pairs.
+	StableSwap {
+		/// The amplification coefficient. The higher it is, the lower the slippage around the
+		/// balanced point.
+		amplification: u32,
+	},
+	/// The Balancer weighted invariant, `x ^ weight1 * y ^ weight2 = k`.
+	Weighted {
+		/// The weight of the first asset.
+		weight1: u8,
+		/// The weight of the second asset.
+		weight2: u8,
+	},
+}
+
+impl PoolCurve {
+	/// The maximum amplification coefficient of a StableSwap pool.
+	pub const MAX_AMPLIFICATION: u32 = 1_000_000;
+
+	/// The maximum weight of an asset of a weighted pool.
+	pub const MAX_WEIGHT: u8 = 100;
+
+	/// Whether the parameters of the curve are within their bounds.
+	pub fn is_valid(&self) -> bool {
+		match *self {
+			Self::ConstantProduct => true,
+			Self::StableSwap { amplification } =>
+				amplification > 0 && amplification <= Self::MAX_AMPLIFICATION,
+			Self::Weighted { weight1, weight2 } =>
+				weight1 > 0 &&
+					weight1 <= Self::MAX_WEIGHT &&
+					weight2 > 0 && weight2 <= Self::MAX_WEIGHT,
+		}
+	}
+
+	/// The same curve, with the order of the assets swapped.
+	pub fn reversed(self) -> Self {
+		match self {
+			Self::Weighted { weight1, weight2 } =>
+				Self::Weighted { weight1: weight2, weight2: weight1 },
+			curve => curve,
+		}
+	}
+}
+
+/// Identifier of a limit order.
+pub type OrderId = u32;
+
+/// A resting order to sell `asset_in` for `asset_out` at a price of at least `amount_out` per
+/// `amount_in`.
+///
+/// The order is matched against the price of the pool of the two assets, and may be filled in
+/// several parts.
+#[derive(Decode, Encode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct LimitOrder<AccountId, MultiAssetId, AssetBalance, Balance> {
+	/// The account that placed the order and receives the bought asset.
+	pub owner: AccountId,
+	/// The asset sold by the order.
+	pub asset_in: MultiAssetId,
+	/// The asset bought by the order.
+	pub asset_out: MultiAssetId,
+	/// The amount of `asset_in` the order was placed for.
+	pub amount_in: AssetBalance,
+	/// The minimum amount of `asset_out` to receive for the whole of `amount_in`. Together with
+	/// `amount_in`, this is the limit price of the order.
+	pub amount_out: AssetBalance,
+	/// The amount of `asset_in` not yet sold.
+	pub remaining: AssetBalance,
+	/// The amount of the native currency held on the account of `owner` for the storage of the
+	/// order, released once the order is closed.
+	pub deposit: Balance,
+}
+
 /// A trait that converts between a MultiAssetId and either the native currency or an AssetId.
 pub trait MultiAssetIdConverter<MultiAssetId, AssetId> {
 	/// Returns the MultiAssetId representing the native currency of the chain.
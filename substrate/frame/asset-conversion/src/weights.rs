@@ -54,6 +54,11 @@ pub trait WeightInfo {
 	fn remove_liquidity() -> Weight;
 	fn swap_exact_tokens_for_tokens() -> Weight;
 	fn swap_tokens_for_exact_tokens() -> Weight;
+	fn place_limit_order() -> Weight;
+	fn cancel_limit_order() -> Weight;
+	fn match_limit_order() -> Weight;
+	fn stable_swap_hop() -> Weight;
+	fn weighted_swap_hop() -> Weight;
 }
 
 /// Weights for pallet_asset_conversion using the Substrate node and recommended hardware.
@@ -154,6 +159,39 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(10_u64))
 			.saturating_add(T::DbWeight::get().writes(10_u64))
 	}
+	fn place_limit_order() -> Weight {
+		// Placeholder, not benchmarked yet: `add_liquidity`, which moves an asset and the native
+		// currency from the sender to an account the same way, and the accesses of the order
+		// maps.
+		Self::add_liquidity().saturating_add(T::DbWeight::get().reads_writes(2_u64, 3_u64))
+	}
+	fn cancel_limit_order() -> Weight {
+		// Placeholder, not benchmarked yet: `remove_liquidity`, which moves an asset and the
+		// native currency from an account to the sender the same way, and the accesses of the
+		// order maps.
+		Self::remove_liquidity().saturating_add(T::DbWeight::get().reads_writes(2_u64, 2_u64))
+	}
+	fn match_limit_order() -> Weight {
+		// Placeholder, not benchmarked yet: a swap, the accesses of the order maps, and solving
+		// the curve of the pool for each of the up to 128 steps of the search for the amount to
+		// fill, four times per `stable_swap_hop` and three times per `weighted_swap_hop`.
+		let search = Self::stable_swap_hop()
+			.saturating_mul(32)
+			.max(Self::weighted_swap_hop().saturating_mul(43));
+		Self::swap_exact_tokens_for_tokens()
+			.saturating_add(search)
+			.saturating_add(T::DbWeight::get().reads_writes(2_u64, 2_u64))
+	}
+	fn stable_swap_hop() -> Weight {
+		// Placeholder, not benchmarked yet: three times the native execution time of the
+		// `stable_swap_hop` benchmark.
+		Weight::from_parts(1_300_000_000, 0)
+	}
+	fn weighted_swap_hop() -> Weight {
+		// Placeholder, not benchmarked yet: three times the native execution time of the
+		// `weighted_swap_hop` benchmark.
+		Weight::from_parts(450_000_000, 0)
+	}
 }
 
 // For backwards compatibility and tests.
@@ -253,4 +291,37 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(10_u64))
 			.saturating_add(RocksDbWeight::get().writes(10_u64))
 	}
+	fn place_limit_order() -> Weight {
+		// Placeholder, not benchmarked yet: `add_liquidity`, which moves an asset and the native
+		// currency from the sender to an account the same way, and the accesses of the order
+		// maps.
+		Self::add_liquidity().saturating_add(RocksDbWeight::get().reads_writes(2_u64, 3_u64))
+	}
+	fn cancel_limit_order() -> Weight {
+		// Placeholder, not benchmarked yet: `remove_liquidity`, which moves an asset and the
+		// native currency from an account to the sender the same way, and the accesses of the
+		// order maps.
+		Self::remove_liquidity().saturating_add(RocksDbWeight::get().reads_writes(2_u64, 2_u64))
+	}
+	fn match_limit_order() -> Weight {
+		// Placeholder, not benchmarked yet: a swap, the accesses of the order maps, and solving
+		// the curve of the pool for each of the up to 128 steps of the search for the amount to
+		// fill, four times per `stable_swap_hop` and three times per `weighted_swap_hop`.
+		let search = Self::stable_swap_hop()
+			.saturating_mul(32)
+			.max(Self::weighted_swap_hop().saturating_mul(43));
+		Self::swap_exact_tokens_for_tokens()
+			.saturating_add(search)
+			.saturating_add(RocksDbWeight::get().reads_writes(2_u64, 2_u64))
+	}
+	fn stable_swap_hop() -> Weight {
+		// Placeholder, not benchmarked yet: three times the native execution time of the
+		// `stable_swap_hop` benchmark.
+		Weight::from_parts(1_300_000_000, 0)
+	}
+	fn weighted_swap_hop() -> Weight {
+		// Placeholder, not benchmarked yet: three times the native execution time of the
+		// `weighted_swap_hop` benchmark.
+		Weight::from_parts(450_000_000, 0)
+	}
 }
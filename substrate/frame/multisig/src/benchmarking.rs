@@ -210,5 +210,75 @@ benchmarks! {
 		assert!(!Multisigs::<T>::contains_key(multi_account_id, call_hash));
 	}
 
+	create_managed {
+		// Signatories, need at least 2 people
+		let s in 2 .. T::MaxSignatories::get();
+		let (signatories, _) = setup_multi::<T>(s, 0)?;
+		let multi_account_id = Multisig::<T>::multi_account_id(&signatories, s as u16);
+		T::Currency::make_free_balance_be(&multi_account_id, BalanceOf::<T>::max_value());
+		// Whitelist multisig account from further DB operations.
+		let multi_key = frame_system::Account::<T>::hashed_key_for(&multi_account_id);
+		frame_benchmarking::benchmarking::add_to_whitelist(multi_key.into());
+	}: _(RawOrigin::Signed(multi_account_id.clone()), signatories, s as u16)
+	verify {
+		assert!(ManagedMultisigs::<T>::contains_key(multi_account_id));
+	}
+
+	set_managed_signatories {
+		// Signatories, need at least 2 people
+		let s in 2 .. T::MaxSignatories::get();
+		let (mut signatories, _) = setup_multi::<T>(T::MaxSignatories::get(), 0)?;
+		let managed = Multisig::<T>::multi_account_id(&signatories, 2);
+		T::Currency::make_free_balance_be(&managed, BalanceOf::<T>::max_value());
+		Multisig::<T>::create_managed(
+			RawOrigin::Signed(managed.clone()).into(),
+			signatories.clone(),
+			2,
+		)?;
+		// Replace the signatories with the first `s` accounts, requiring all of them to approve.
+		signatories.truncate(s as usize);
+		let multi_account_id = Multisig::<T>::multi_account_id(&signatories, s as u16);
+		// Whitelist managed account from further DB operations.
+		let managed_key = frame_system::Account::<T>::hashed_key_for(&managed);
+		frame_benchmarking::benchmarking::add_to_whitelist(managed_key.into());
+	}: _(RawOrigin::Signed(managed.clone()), signatories, s as u16)
+	verify {
+		assert_eq!(ManagedBy::<T>::get(multi_account_id), Some(managed));
+	}
+
+	clear_stale_multisig {
+		// Transaction Length, not a component
+		let z = 10_000;
+		let s = T::MaxSignatories::get();
+		let (mut signatories, call) = setup_multi::<T>(s, z)?;
+		let managed = Multisig::<T>::multi_account_id(&signatories, s as u16);
+		T::Currency::make_free_balance_be(&managed, BalanceOf::<T>::max_value());
+		Multisig::<T>::create_managed(
+			RawOrigin::Signed(managed.clone()).into(),
+			signatories.clone(),
+			s as u16,
+		)?;
+		let new_signatories = signatories.clone();
+		let caller = signatories.pop().ok_or("signatories should have len 2 or more")?;
+		let call_hash = call.using_encoded(blake2_256);
+		frame_system::Pallet::<T>::set_block_number(1u32.into());
+		// Open an operation and make it stale by changing the signatories.
+		let o = RawOrigin::Signed(caller.clone()).into();
+		Multisig::<T>::as_multi(o, s as u16, signatories, None, call, Weight::zero())?;
+		frame_system::Pallet::<T>::set_block_number(2u32.into());
+		Multisig::<T>::set_managed_signatories(
+			RawOrigin::Signed(managed.clone()).into(),
+			new_signatories,
+			2,
+		)?;
+		assert!(Multisigs::<T>::contains_key(&managed, call_hash));
+		// Whitelist caller account from further DB operations.
+		let caller_key = frame_system::Account::<T>::hashed_key_for(&caller);
+		frame_benchmarking::benchmarking::add_to_whitelist(caller_key.into());
+	}: _(RawOrigin::Signed(caller), managed.clone(), call_hash)
+	verify {
+		assert!(!Multisigs::<T>::contains_key(managed, call_hash));
+	}
+
 	impl_benchmark_test_suite!(Multisig, crate::tests::new_test_ext(), crate::tests::Test);
 }
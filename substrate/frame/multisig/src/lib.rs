@@ -39,6 +39,27 @@
 //!   number of signed origins.
 //! * `approve_as_multi` - Approve a call from a composite origin.
 //! * `cancel_as_multi` - Cancel a call from a composite origin.
+//! * `create_managed` - Turn a multisig account into one with a mutable set of signatories.
+//! * `set_managed_signatories` - Change the signatories and threshold of a managed multisig.
+//! * `clear_stale_multisig` - Remove an operation invalidated by a change of signatories.
+//!
+//! ### Managed multisigs
+//!
+//! A managed multisig is a stable account whose signatories and threshold are stored on-chain
+//! rather than being part of the account derivation. A deterministic multisig account becomes
+//! managed by dispatching `create_managed` from itself, i.e. through a threshold call of its
+//! signatories, and keeps its balance, pending operations and any other state. The managed
+//! account can then change its signatories through `set_managed_signatories`, which invalidates
+//! every operation opened before the change. While a set of signatories and threshold is
+//! assigned to a managed multisig, `as_multi`, `approve_as_multi` and `cancel_as_multi` called
+//! with that set operate on the managed account, and the previous signatories can no longer
+//! operate it.
+//!
+//! Assigning a set of signatories and threshold to a managed multisig redirects control away
+//! from their deterministic multisig account, as recorded in [`ManagedBy`]: while assigned,
+//! nobody can operate that account, so funds sent to it stay inaccessible until the managed
+//! multisig changes its signatories again. Use [`Pallet::resolve_account_id`] to find the account
+//! that a set of signatories and threshold actually operates.
 
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -118,6 +139,24 @@ where
 	approvals: BoundedVec<AccountId, MaxApprovals>,
 }
 
+/// The configuration of a managed multisig account.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+#[scale_info(skip_type_params(MaxSignatories))]
+pub struct ManagedMultisig<BlockNumber, Balance, AccountId, MaxSignatories>
+where
+	MaxSignatories: Get<u32>,
+{
+	/// The current signatories. Always sorted.
+	signatories: BoundedVec<AccountId, MaxSignatories>,
+	/// The total number of approvals needed to dispatch a call.
+	threshold: u16,
+	/// The amount held in reserve of the managed account.
+	deposit: Balance,
+	/// The extrinsic in which the signatories were last changed, if ever. Any operation opened
+	/// at or before this point is stale.
+	last_change: Option<Timepoint<BlockNumber>>,
+}
+
 type CallHash = [u8; 32];
 
 enum CallOrHash<T: Config> {
@@ -186,6 +225,21 @@ pub mod pallet {
 		Multisig<BlockNumberFor<T>, BalanceOf<T>, T::AccountId, T::MaxSignatories>,
 	>;
 
+	/// The configuration of each managed multisig account.
+	#[pallet::storage]
+	pub type ManagedMultisigs<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		ManagedMultisig<BlockNumberFor<T>, BalanceOf<T>, T::AccountId, T::MaxSignatories>,
+	>;
+
+	/// The managed multisig account that the deterministic multisig account of its current
+	/// signatories and threshold resolves to. Absent while the managed account is itself the
+	/// deterministic account of its current signatories and threshold.
+	#[pallet::storage]
+	pub type ManagedBy<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, T::AccountId>;
+
 	#[pallet::error]
 	pub enum Error<T> {
 		/// Threshold must be 2 or greater.
@@ -216,6 +270,20 @@ pub mod pallet {
 		MaxWeightTooLow,
 		/// The data to be stored is already stored.
 		AlreadyStored,
+		/// The account is not a managed multisig.
+		NotManaged,
+		/// The signatories and threshold are already assigned to a managed multisig.
+		AlreadyManaged,
+		/// The threshold is greater than the number of signatories.
+		ThresholdTooHigh,
+		/// The multisig operation was opened before the last change of signatories.
+		StaleOperation,
+		/// The multisig operation was opened after the last change of signatories.
+		NotStale,
+		/// The origin is not the multisig account of the given signatories and threshold.
+		NotMultisigAccount,
+		/// The signatories and threshold are no longer those of the managed multisig.
+		SignatoriesChanged,
 	}
 
 	#[pallet::event]
@@ -245,6 +313,10 @@ pub mod pallet {
 			multisig: T::AccountId,
 			call_hash: CallHash,
 		},
+		/// A multisig account has become a managed multisig.
+		ManagedMultisigCreated { multisig: T::AccountId, threshold: u16 },
+		/// The signatories of a managed multisig have been changed.
+		ManagedSignatoriesChanged { multisig: T::AccountId, threshold: u16 },
 	}
 
 	#[pallet::hooks]
@@ -475,7 +547,7 @@ pub mod pallet {
 			ensure!(other_signatories.len() < max_sigs, Error::<T>::TooManySignatories);
 			let signatories = Self::ensure_sorted_and_insert(other_signatories, who.clone())?;
 
-			let id = Self::multi_account_id(&signatories, threshold);
+			let id = Self::operated_account_id(&signatories, threshold)?;
 
 			let m = <Multisigs<T>>::get(&id, call_hash).ok_or(Error::<T>::NotFound)?;
 			ensure!(m.when == timepoint, Error::<T>::WrongTimepoint);
@@ -493,6 +565,142 @@ pub mod pallet {
 			});
 			Ok(())
 		}
+
+		/// Turn a deterministic multisig account into a managed multisig, whose signatories and
+		/// threshold can later be changed without moving to a new account.
+		///
+		/// The account keeps its balance and pending operations; the current signatories and
+		/// threshold keep operating it until they are changed.
+		///
+		/// Payment: `DepositBase` plus `DepositFactor` for each signatory will be reserved from
+		/// the multisig account.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the multisig account of
+		/// `signatories` and `threshold`, i.e. this call is expected to be dispatched through
+		/// `as_multi`.
+		///
+		/// - `signatories`: The current signatories of the multisig account, sorted and without
+		/// duplicates.
+		/// - `threshold`: The current total number of approvals needed to dispatch a call.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::create_managed(signatories.len() as u32))]
+		pub fn create_managed(
+			origin: OriginFor<T>,
+			signatories: Vec<T::AccountId>,
+			threshold: u16,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_valid_signatories(&signatories, threshold)?;
+			ensure!(
+				Self::multi_account_id(&signatories, threshold) == who,
+				Error::<T>::NotMultisigAccount
+			);
+			ensure!(!ManagedMultisigs::<T>::contains_key(&who), Error::<T>::AlreadyManaged);
+			// The signatories of another managed multisig operate that account instead.
+			ensure!(!ManagedBy::<T>::contains_key(&who), Error::<T>::AlreadyManaged);
+
+			let deposit = Self::managed_deposit(signatories.len());
+			T::Currency::reserve(&who, deposit)?;
+
+			let signatories = signatories.try_into().map_err(|_| Error::<T>::TooManySignatories)?;
+			ManagedMultisigs::<T>::insert(
+				&who,
+				ManagedMultisig { signatories, threshold, deposit, last_change: None },
+			);
+			Self::deposit_event(Event::ManagedMultisigCreated { multisig: who, threshold });
+			Ok(())
+		}
+
+		/// Change the signatories and threshold of a managed multisig.
+		///
+		/// Every multisig operation of the managed account opened before this change becomes
+		/// stale: it can no longer be approved or executed, and should be removed with
+		/// `clear_stale_multisig`.
+		///
+		/// Payment: `DepositBase` plus `DepositFactor` for each new signatory will be reserved
+		/// from the managed account, and the previous deposit returned.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the managed multisig account,
+		/// i.e. this call is expected to be dispatched through `as_multi`.
+		///
+		/// - `signatories`: The new set of signatories, sorted and without duplicates.
+		/// - `threshold`: The new total number of approvals needed to dispatch a call.
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::WeightInfo::set_managed_signatories(signatories.len() as u32))]
+		pub fn set_managed_signatories(
+			origin: OriginFor<T>,
+			signatories: Vec<T::AccountId>,
+			threshold: u16,
+		) -> DispatchResult {
+			let managed = ensure_signed(origin)?;
+			let mut config = ManagedMultisigs::<T>::get(&managed).ok_or(Error::<T>::NotManaged)?;
+			Self::ensure_valid_signatories(&signatories, threshold)?;
+
+			let old_id = Self::multi_account_id(&config.signatories, config.threshold);
+			let new_id = Self::multi_account_id(&signatories, threshold);
+			if new_id != managed {
+				// The new signatories may not already operate another account.
+				ensure!(
+					ManagedBy::<T>::get(&new_id).map_or(true, |other| other == managed),
+					Error::<T>::AlreadyManaged
+				);
+				ensure!(!ManagedMultisigs::<T>::contains_key(&new_id), Error::<T>::AlreadyManaged);
+			}
+
+			let deposit = Self::managed_deposit(signatories.len());
+			if deposit > config.deposit {
+				T::Currency::reserve(&managed, deposit - config.deposit)?;
+			} else {
+				T::Currency::unreserve(&managed, config.deposit - deposit);
+			}
+
+			ManagedBy::<T>::remove(&old_id);
+			if new_id != managed {
+				ManagedBy::<T>::insert(&new_id, &managed);
+			}
+			config.signatories =
+				signatories.try_into().map_err(|_| Error::<T>::TooManySignatories)?;
+			config.threshold = threshold;
+			config.deposit = deposit;
+			config.last_change = Some(Self::timepoint());
+			ManagedMultisigs::<T>::insert(&managed, config);
+
+			Self::deposit_event(Event::ManagedSignatoriesChanged { multisig: managed, threshold });
+			Ok(())
+		}
+
+		/// Remove a multisig operation of a managed multisig which was opened before the last
+		/// change of its signatories. The deposit reserved for the operation is returned to its
+		/// depositor.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `multisig`: The managed multisig account.
+		/// - `call_hash`: The hash of the call of the stale operation.
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::WeightInfo::clear_stale_multisig())]
+		pub fn clear_stale_multisig(
+			origin: OriginFor<T>,
+			multisig: T::AccountId,
+			call_hash: [u8; 32],
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let config = ManagedMultisigs::<T>::get(&multisig).ok_or(Error::<T>::NotManaged)?;
+			let m = <Multisigs<T>>::get(&multisig, call_hash).ok_or(Error::<T>::NotFound)?;
+			ensure!(Self::is_stale(&m.when, &config.last_change), Error::<T>::NotStale);
+
+			let err_amount = T::Currency::unreserve(&m.depositor, m.deposit);
+			debug_assert!(err_amount.is_zero());
+			<Multisigs<T>>::remove(&multisig, &call_hash);
+
+			Self::deposit_event(Event::MultisigCancelled {
+				cancelling: who,
+				timepoint: m.when,
+				multisig,
+				call_hash,
+			});
+			Ok(())
+		}
 	}
 }
 
@@ -507,6 +715,61 @@ impl<T: Config> Pallet<T> {
 			.expect("infinite length input; no invalid inputs for type; qed")
 	}
 
+	/// Resolve a deterministic multisig account ID to the managed multisig it is currently
+	/// assigned to, if any.
+	pub fn resolve_account_id(id: T::AccountId) -> T::AccountId {
+		ManagedBy::<T>::get(&id).unwrap_or(id)
+	}
+
+	/// The account operated by `signatories` and `threshold`.
+	///
+	/// Fails if that account is a managed multisig whose signatories have since been changed.
+	fn operated_account_id(
+		signatories: &[T::AccountId],
+		threshold: u16,
+	) -> Result<T::AccountId, DispatchError> {
+		let id = Self::resolve_account_id(Self::multi_account_id(signatories, threshold));
+		if let Some(config) = ManagedMultisigs::<T>::get(&id) {
+			ensure!(
+				config.threshold == threshold && &config.signatories[..] == signatories,
+				Error::<T>::SignatoriesChanged
+			);
+		}
+		Ok(id)
+	}
+
+	/// Check that `signatories` and `threshold` describe a valid multisig.
+	fn ensure_valid_signatories(signatories: &[T::AccountId], threshold: u16) -> DispatchResult {
+		ensure!(threshold >= 2, Error::<T>::MinimumThreshold);
+		ensure!(signatories.len() >= 2, Error::<T>::TooFewSignatories);
+		ensure!(
+			signatories.len() <= T::MaxSignatories::get() as usize,
+			Error::<T>::TooManySignatories
+		);
+		ensure!(
+			signatories.windows(2).all(|pair| pair[0] < pair[1]),
+			Error::<T>::SignatoriesOutOfOrder
+		);
+		ensure!(threshold as usize <= signatories.len(), Error::<T>::ThresholdTooHigh);
+		Ok(())
+	}
+
+	/// The deposit held for a managed multisig with `signatories` signatories.
+	fn managed_deposit(signatories: usize) -> BalanceOf<T> {
+		T::DepositBase::get() + T::DepositFactor::get() * (signatories as u32).into()
+	}
+
+	/// Whether an operation opened at `when` was invalidated by a change of signatories at
+	/// `last_change`.
+	fn is_stale(
+		when: &Timepoint<BlockNumberFor<T>>,
+		last_change: &Option<Timepoint<BlockNumberFor<T>>>,
+	) -> bool {
+		last_change.as_ref().map_or(false, |last_change| {
+			(when.height, when.index) <= (last_change.height, last_change.index)
+		})
+	}
+
 	fn operate(
 		who: T::AccountId,
 		threshold: u16,
@@ -522,7 +785,7 @@ impl<T: Config> Pallet<T> {
 		ensure!(other_signatories_len < max_sigs, Error::<T>::TooManySignatories);
 		let signatories = Self::ensure_sorted_and_insert(other_signatories, who.clone())?;
 
-		let id = Self::multi_account_id(&signatories, threshold);
+		let id = Self::operated_account_id(&signatories, threshold)?;
 
 		// Threshold > 1; this means it's a multi-step operation. We extract the `call_hash`.
 		let (call_hash, call_len, maybe_call) = match call_or_hash {
@@ -535,6 +798,11 @@ impl<T: Config> Pallet<T> {
 
 		// Branch on whether the operation has already started or not.
 		if let Some(mut m) = <Multisigs<T>>::get(&id, call_hash) {
+			// Operations of a managed multisig are invalidated by a change of its signatories.
+			if let Some(config) = ManagedMultisigs::<T>::get(&id) {
+				ensure!(!Self::is_stale(&m.when, &config.last_change), Error::<T>::StaleOperation);
+			}
+
 			// Yes; ensure that the timepoint exists and agrees.
 			let timepoint = maybe_timepoint.ok_or(Error::<T>::NoTimepoint)?;
 			ensure!(m.when == timepoint, Error::<T>::WrongTimepoint);
@@ -58,6 +58,7 @@ impl Contains<RuntimeCall> for TestBaseCallFilter {
 	fn contains(c: &RuntimeCall) -> bool {
 		match *c {
 			RuntimeCall::Balances(_) => true,
+			RuntimeCall::Multisig(_) => true,
 			// Needed for benchmarking
 			RuntimeCall::System(frame_system::Call::remark { .. }) => true,
 			_ => false,
@@ -692,3 +693,317 @@ fn multisig_handles_no_preimage_after_all_approve() {
 		assert_eq!(Balances::free_balance(6), 15);
 	});
 }
+
+fn call_set_signatories(signatories: Vec<u64>, threshold: u16) -> Box<RuntimeCall> {
+	Box::new(RuntimeCall::Multisig(Call::set_managed_signatories { signatories, threshold }))
+}
+
+/// Turn the multisig account of 1, 2 and 3 with threshold 2 into a managed multisig.
+fn create_managed() -> u64 {
+	let managed = Multisig::multi_account_id(&[1, 2, 3][..], 2);
+	assert_ok!(Balances::transfer_allow_death(RuntimeOrigin::signed(1), managed, 5));
+	assert_ok!(Balances::transfer_allow_death(RuntimeOrigin::signed(2), managed, 5));
+	let call = Box::new(RuntimeCall::Multisig(Call::create_managed {
+		signatories: vec![1, 2, 3],
+		threshold: 2,
+	}));
+	let call_weight = call.get_dispatch_info().weight;
+	assert_ok!(Multisig::as_multi(
+		RuntimeOrigin::signed(1),
+		2,
+		vec![2, 3],
+		None,
+		call.clone(),
+		Weight::zero()
+	));
+	assert_ok!(Multisig::as_multi(
+		RuntimeOrigin::signed(2),
+		2,
+		vec![1, 3],
+		Some(now()),
+		call,
+		call_weight
+	));
+	assert!(ManagedMultisigs::<Test>::contains_key(managed));
+	managed
+}
+
+#[test]
+fn managed_multisig_works() {
+	new_test_ext().execute_with(|| {
+		let managed = create_managed();
+		System::assert_has_event(
+			Event::ManagedMultisigCreated { multisig: managed, threshold: 2 }.into(),
+		);
+		assert_eq!(Balances::reserved_balance(managed), 4);
+		assert_eq!(Balances::free_balance(managed), 6);
+
+		System::set_block_number(2);
+		let call = call_transfer(6, 5);
+		let call_weight = call.get_dispatch_info().weight;
+		assert_ok!(Multisig::as_multi(
+			RuntimeOrigin::signed(1),
+			2,
+			vec![2, 3],
+			None,
+			call.clone(),
+			Weight::zero()
+		));
+		let hash = blake2_256(&call.encode());
+		assert!(Multisigs::<Test>::contains_key(managed, hash));
+		assert_ok!(Multisig::as_multi(
+			RuntimeOrigin::signed(3),
+			2,
+			vec![1, 2],
+			Some(now()),
+			call,
+			call_weight
+		));
+		assert_eq!(Balances::free_balance(managed), 1);
+		assert_eq!(Balances::free_balance(6), 5);
+	});
+}
+
+#[test]
+fn managed_multisig_keeps_pending_operations() {
+	new_test_ext().execute_with(|| {
+		let multi = Multisig::multi_account_id(&[1, 2, 3][..], 2);
+		let pending = call_transfer(6, 5);
+		let pending_weight = pending.get_dispatch_info().weight;
+		let pending_timepoint = now();
+		assert_ok!(Multisig::as_multi(
+			RuntimeOrigin::signed(3),
+			2,
+			vec![1, 2],
+			None,
+			pending.clone(),
+			Weight::zero()
+		));
+
+		System::set_block_number(2);
+		assert_eq!(create_managed(), multi);
+
+		// Creating the managed multisig changes no signatories, so nothing becomes stale.
+		assert_ok!(Multisig::as_multi(
+			RuntimeOrigin::signed(1),
+			2,
+			vec![2, 3],
+			Some(pending_timepoint),
+			pending,
+			pending_weight
+		));
+		assert_eq!(Balances::free_balance(6), 5);
+		assert_eq!(Balances::reserved_balance(3), 0);
+	});
+}
+
+#[test]
+fn signatory_cannot_take_over_multisig() {
+	new_test_ext().execute_with(|| {
+		let multi = Multisig::multi_account_id(&[1, 2, 3][..], 2);
+		assert_noop!(
+			Multisig::create_managed(RuntimeOrigin::signed(1), vec![1, 2, 3], 2),
+			Error::<Test>::NotMultisigAccount,
+		);
+		assert_noop!(
+			Multisig::create_managed(RuntimeOrigin::signed(1), vec![1, 2], 2),
+			Error::<Test>::NotMultisigAccount,
+		);
+
+		// A single approval does not dispatch the creation.
+		let call = Box::new(RuntimeCall::Multisig(Call::create_managed {
+			signatories: vec![1, 2, 3],
+			threshold: 2,
+		}));
+		assert_ok!(Multisig::as_multi(
+			RuntimeOrigin::signed(1),
+			2,
+			vec![2, 3],
+			None,
+			call,
+			Weight::zero()
+		));
+		assert!(!ManagedMultisigs::<Test>::contains_key(multi));
+		assert_eq!(Multisig::resolve_account_id(multi), multi);
+	});
+}
+
+#[test]
+fn managed_signatories_can_be_changed() {
+	new_test_ext().execute_with(|| {
+		let managed = create_managed();
+
+		System::set_block_number(2);
+		let call = call_set_signatories(vec![1, 2, 4], 3);
+		let call_weight = call.get_dispatch_info().weight;
+		assert_ok!(Multisig::as_multi(
+			RuntimeOrigin::signed(2),
+			2,
+			vec![1, 3],
+			None,
+			call.clone(),
+			Weight::zero()
+		));
+		assert_ok!(Multisig::as_multi(
+			RuntimeOrigin::signed(3),
+			2,
+			vec![1, 2],
+			Some(now()),
+			call,
+			call_weight
+		));
+		System::assert_has_event(
+			Event::ManagedSignatoriesChanged { multisig: managed, threshold: 3 }.into(),
+		);
+
+		assert_eq!(Balances::reserved_balance(managed), 4);
+		let new_id = Multisig::multi_account_id(&[1, 2, 4][..], 3);
+		assert_eq!(ManagedBy::<Test>::get(managed), None);
+		assert_eq!(ManagedBy::<Test>::get(new_id), Some(managed));
+
+		// The previous signatories can no longer operate the account.
+		System::set_block_number(3);
+		let call = call_transfer(6, 5);
+		let call_weight = call.get_dispatch_info().weight;
+		assert_noop!(
+			Multisig::as_multi(
+				RuntimeOrigin::signed(1),
+				2,
+				vec![2, 3],
+				None,
+				call.clone(),
+				Weight::zero()
+			),
+			Error::<Test>::SignatoriesChanged,
+		);
+
+		// The new signatories dispatch from the same account.
+		let hash = blake2_256(&call.encode());
+		assert_ok!(Multisig::approve_as_multi(
+			RuntimeOrigin::signed(4),
+			3,
+			vec![1, 2],
+			None,
+			hash,
+			Weight::zero()
+		));
+		assert_ok!(Multisig::approve_as_multi(
+			RuntimeOrigin::signed(1),
+			3,
+			vec![2, 4],
+			Some(now()),
+			hash,
+			Weight::zero()
+		));
+		assert_ok!(Multisig::as_multi(
+			RuntimeOrigin::signed(2),
+			3,
+			vec![1, 4],
+			Some(now()),
+			call,
+			call_weight
+		));
+		assert_eq!(Balances::free_balance(6), 5);
+		assert_eq!(Balances::free_balance(managed), 1);
+	});
+}
+
+#[test]
+fn change_of_signatories_invalidates_pending_operations() {
+	new_test_ext().execute_with(|| {
+		let managed = create_managed();
+
+		System::set_block_number(2);
+		let pending = call_transfer(6, 5);
+		let pending_weight = pending.get_dispatch_info().weight;
+		let pending_hash = blake2_256(&pending.encode());
+		let pending_timepoint = now();
+		assert_ok!(Multisig::as_multi(
+			RuntimeOrigin::signed(3),
+			2,
+			vec![1, 2],
+			None,
+			pending.clone(),
+			Weight::zero()
+		));
+		assert_eq!(Balances::reserved_balance(3), 3);
+		assert_noop!(
+			Multisig::clear_stale_multisig(RuntimeOrigin::signed(5), managed, pending_hash),
+			Error::<Test>::NotStale,
+		);
+
+		System::set_block_number(3);
+		let call = call_set_signatories(vec![1, 2], 2);
+		let call_weight = call.get_dispatch_info().weight;
+		assert_ok!(Multisig::as_multi(
+			RuntimeOrigin::signed(1),
+			2,
+			vec![2, 3],
+			None,
+			call.clone(),
+			Weight::zero()
+		));
+		assert_ok!(Multisig::as_multi(
+			RuntimeOrigin::signed(2),
+			2,
+			vec![1, 3],
+			Some(now()),
+			call,
+			call_weight
+		));
+
+		// The operation opened with the previous signatories can no longer be executed.
+		assert_noop!(
+			Multisig::as_multi(
+				RuntimeOrigin::signed(1),
+				2,
+				vec![2],
+				Some(pending_timepoint),
+				pending,
+				pending_weight
+			),
+			Error::<Test>::StaleOperation,
+		);
+
+		// Anyone can clear it, returning the deposit to its depositor.
+		assert_ok!(Multisig::clear_stale_multisig(RuntimeOrigin::signed(5), managed, pending_hash));
+		assert!(!Multisigs::<Test>::contains_key(managed, pending_hash));
+		assert_eq!(Balances::reserved_balance(3), 0);
+		assert_eq!(Balances::free_balance(6), 0);
+	});
+}
+
+#[test]
+fn managed_multisig_checks_work() {
+	new_test_ext().execute_with(|| {
+		let multi = Multisig::multi_account_id(&[1, 2, 3][..], 3);
+		assert_noop!(
+			Multisig::create_managed(RuntimeOrigin::signed(multi), vec![1, 2], 3),
+			Error::<Test>::ThresholdTooHigh,
+		);
+		assert_noop!(
+			Multisig::create_managed(RuntimeOrigin::signed(multi), vec![1, 3, 2], 3),
+			Error::<Test>::SignatoriesOutOfOrder,
+		);
+		let managed = create_managed();
+		assert_noop!(
+			Multisig::create_managed(RuntimeOrigin::signed(managed), vec![1, 2, 3], 2),
+			Error::<Test>::AlreadyManaged,
+		);
+		assert_noop!(
+			Multisig::set_managed_signatories(RuntimeOrigin::signed(1), vec![1, 2], 2),
+			Error::<Test>::NotManaged,
+		);
+		// The signatories of another multisig may not be assigned to this one.
+		assert_ok!(Balances::transfer_allow_death(RuntimeOrigin::signed(4), multi, 10));
+		assert_ok!(Multisig::create_managed(RuntimeOrigin::signed(multi), vec![1, 2, 3], 3));
+		assert_noop!(
+			Multisig::set_managed_signatories(RuntimeOrigin::signed(managed), vec![1, 2, 3], 3),
+			Error::<Test>::AlreadyManaged,
+		);
+		assert_noop!(
+			Multisig::clear_stale_multisig(RuntimeOrigin::signed(1), 1, [0; 32]),
+			Error::<Test>::NotManaged,
+		);
+	});
+}
@@ -59,6 +59,9 @@ pub trait WeightInfo {
 	fn approve_as_multi_create(s: u32, ) -> Weight;
 	fn approve_as_multi_approve(s: u32, ) -> Weight;
 	fn cancel_as_multi(s: u32, ) -> Weight;
+	fn create_managed(s: u32, ) -> Weight;
+	fn set_managed_signatories(s: u32, ) -> Weight;
+	fn clear_stale_multisig() -> Weight;
 }
 
 /// Weights for pallet_multisig using the Substrate node and recommended hardware.
@@ -74,6 +77,10 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			// Standard Error: 4
 			.saturating_add(Weight::from_parts(493, 0).saturating_mul(z.into()))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// The range of component `s` is `[2, 100]`.
@@ -90,7 +97,15 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(Weight::from_parts(1_498, 0).saturating_mul(z.into()))
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// The range of component `s` is `[3, 100]`.
@@ -107,7 +122,15 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(Weight::from_parts(1_483, 0).saturating_mul(z.into()))
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// Storage: System Account (r:1 w:1)
@@ -126,7 +149,15 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(Weight::from_parts(1_523, 0).saturating_mul(z.into()))
 			.saturating_add(T::DbWeight::get().reads(2_u64))
 			.saturating_add(T::DbWeight::get().writes(2_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// The range of component `s` is `[2, 100]`.
@@ -140,7 +171,15 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(Weight::from_parts(124_815, 0).saturating_mul(s.into()))
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// The range of component `s` is `[2, 100]`.
@@ -154,7 +193,15 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(Weight::from_parts(107_220, 0).saturating_mul(s.into()))
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// The range of component `s` is `[2, 100]`.
@@ -168,6 +215,28 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(Weight::from_parts(116_537, 0).saturating_mul(s.into()))
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+	}
+	fn create_managed(s: u32, ) -> Weight {
+		// Placeholder, not benchmarked yet: `approve_as_multi_create`, which checks the
+		// signatories and reserves a deposit the same way, and the accesses of the managed
+		// multisig maps.
+		Self::approve_as_multi_create(s).saturating_add(T::DbWeight::get().reads_writes(2_u64, 2_u64))
+	}
+	fn set_managed_signatories(s: u32, ) -> Weight {
+		// Placeholder, not benchmarked yet: `approve_as_multi_create`, which checks the
+		// signatories and reserves a deposit the same way, and the accesses of the managed
+		// multisig maps.
+		Self::approve_as_multi_create(s).saturating_add(T::DbWeight::get().reads_writes(2_u64, 3_u64))
+	}
+	fn clear_stale_multisig() -> Weight {
+		// Placeholder, not benchmarked yet: bounded by `cancel_as_multi` with the most
+		// signatories, which removes the operation and returns its deposit the same way, and the
+		// read of the managed multisig.
+		Self::cancel_as_multi(100).saturating_add(T::DbWeight::get().reads(1_u64))
 	}
 }
 
@@ -183,6 +252,10 @@ impl WeightInfo for () {
 			// Standard Error: 4
 			.saturating_add(Weight::from_parts(493, 0).saturating_mul(z.into()))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// The range of component `s` is `[2, 100]`.
@@ -199,7 +272,15 @@ impl WeightInfo for () {
 			.saturating_add(Weight::from_parts(1_498, 0).saturating_mul(z.into()))
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// The range of component `s` is `[3, 100]`.
@@ -216,7 +297,15 @@ impl WeightInfo for () {
 			.saturating_add(Weight::from_parts(1_483, 0).saturating_mul(z.into()))
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// Storage: System Account (r:1 w:1)
@@ -235,7 +324,15 @@ impl WeightInfo for () {
 			.saturating_add(Weight::from_parts(1_523, 0).saturating_mul(z.into()))
 			.saturating_add(RocksDbWeight::get().reads(2_u64))
 			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// The range of component `s` is `[2, 100]`.
@@ -249,7 +346,15 @@ impl WeightInfo for () {
 			.saturating_add(Weight::from_parts(124_815, 0).saturating_mul(s.into()))
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// The range of component `s` is `[2, 100]`.
@@ -263,7 +368,15 @@ impl WeightInfo for () {
 			.saturating_add(Weight::from_parts(107_220, 0).saturating_mul(s.into()))
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
 	}
+	/// Storage: Multisig ManagedBy (r:1 w:0)
+	/// Proof: Multisig ManagedBy (max_values: None, max_size: Some(72), added: 2547, mode: MaxEncodedLen)
+	/// Storage: Multisig ManagedMultisigs (r:1 w:0)
+	/// Proof: Multisig ManagedMultisigs (max_values: None, max_size: Some(3269), added: 5744, mode: MaxEncodedLen)
 	/// Storage: Multisig Multisigs (r:1 w:1)
 	/// Proof: Multisig Multisigs (max_values: None, max_size: Some(3346), added: 5821, mode: MaxEncodedLen)
 	/// The range of component `s` is `[2, 100]`.
@@ -277,5 +390,27 @@ impl WeightInfo for () {
 			.saturating_add(Weight::from_parts(116_537, 0).saturating_mul(s.into()))
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			// Not benchmarked yet: resolving the operated account reads `ManagedBy` and
+			// `ManagedMultisigs`.
+			.saturating_add(Weight::from_parts(0, 8291))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+	}
+	fn create_managed(s: u32, ) -> Weight {
+		// Placeholder, not benchmarked yet: `approve_as_multi_create`, which checks the
+		// signatories and reserves a deposit the same way, and the accesses of the managed
+		// multisig maps.
+		Self::approve_as_multi_create(s).saturating_add(RocksDbWeight::get().reads_writes(2_u64, 2_u64))
+	}
+	fn set_managed_signatories(s: u32, ) -> Weight {
+		// Placeholder, not benchmarked yet: `approve_as_multi_create`, which checks the
+		// signatories and reserves a deposit the same way, and the accesses of the managed
+		// multisig maps.
+		Self::approve_as_multi_create(s).saturating_add(RocksDbWeight::get().reads_writes(2_u64, 3_u64))
+	}
+	fn clear_stale_multisig() -> Weight {
+		// Placeholder, not benchmarked yet: bounded by `cancel_as_multi` with the most
+		// signatories, which removes the operation and returns its deposit the same way, and the
+		// read of the managed multisig.
+		Self::cancel_as_multi(100).saturating_add(RocksDbWeight::get().reads(1_u64))
 	}
 }
@@ -306,5 +306,85 @@ benchmarks! {
 		);
 	}
 
+	schedule_retry {
+		let s in 1 .. T::MaxScheduledPerBlock::get();
+		let when: BlockNumberFor<T> = BLOCK_NUMBER.into();
+		let period = BlockNumberFor::<T>::one();
+
+		// Fill the agenda the retry is placed in, leaving room for the retry only.
+		fill_schedule::<T>(when + period, s - 1)?;
+		let task = make_task::<T>(false, false, false, None, 0);
+		let retry_config = RetryConfig { total_retries: 10, remaining: 10, period };
+		let mut counter = WeightMeter::new();
+	}: {
+		Scheduler::<T>::schedule_retry(&mut counter, when, when, 0, &task, retry_config);
+	} verify {
+		ensure!(
+			Retries::<T>::get((when + period, s - 1)) ==
+				Some(RetryConfig { total_retries: 10, remaining: 9, period }),
+			"didn't schedule retry"
+		);
+	}
+
+	set_retry {
+		let s = T::MaxScheduledPerBlock::get();
+		let when = BLOCK_NUMBER.into();
+		let period = BlockNumberFor::<T>::one();
+
+		fill_schedule::<T>(when, s)?;
+	}: _(RawOrigin::Root, (when, s - 1), 10, period)
+	verify {
+		ensure!(
+			Retries::<T>::get((when, s - 1)) ==
+				Some(RetryConfig { total_retries: 10, remaining: 10, period }),
+			"didn't set retry"
+		);
+	}
+
+	set_retry_named {
+		let s = T::MaxScheduledPerBlock::get();
+		let when = BLOCK_NUMBER.into();
+		let period = BlockNumberFor::<T>::one();
+
+		fill_schedule::<T>(when, s)?;
+	}: _(RawOrigin::Root, u32_to_name(s - 1), 10, period)
+	verify {
+		ensure!(
+			Retries::<T>::get((when, s - 1)) ==
+				Some(RetryConfig { total_retries: 10, remaining: 10, period }),
+			"didn't set retry"
+		);
+	}
+
+	cancel_retry {
+		let s = T::MaxScheduledPerBlock::get();
+		let when = BLOCK_NUMBER.into();
+		let period = BlockNumberFor::<T>::one();
+
+		fill_schedule::<T>(when, s)?;
+		Retries::<T>::insert(
+			(when, s - 1),
+			RetryConfig { total_retries: 10, remaining: 10, period },
+		);
+	}: _(RawOrigin::Root, (when, s - 1))
+	verify {
+		ensure!(Retries::<T>::get((when, s - 1)).is_none(), "didn't cancel retry");
+	}
+
+	cancel_retry_named {
+		let s = T::MaxScheduledPerBlock::get();
+		let when = BLOCK_NUMBER.into();
+		let period = BlockNumberFor::<T>::one();
+
+		fill_schedule::<T>(when, s)?;
+		Retries::<T>::insert(
+			(when, s - 1),
+			RetryConfig { total_retries: 10, remaining: 10, period },
+		);
+	}: _(RawOrigin::Root, u32_to_name(s - 1))
+	verify {
+		ensure!(Retries::<T>::get((when, s - 1)).is_none(), "didn't cancel retry");
+	}
+
 	impl_benchmark_test_suite!(Scheduler, crate::mock::new_test_ext(), crate::mock::Test);
 }
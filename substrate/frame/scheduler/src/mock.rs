@@ -40,13 +40,19 @@ pub mod logger {
 	use super::{OriginCaller, OriginTrait};
 	use frame_support::{pallet_prelude::*, parameter_types};
 	use frame_system::pallet_prelude::*;
+	use sp_runtime::SaturatedConversion;
 
 	parameter_types! {
 		static Log: Vec<(OriginCaller, u32)> = Vec::new();
+		static Threshold: (u32, u32) = (0, u32::MAX);
 	}
 	pub fn log() -> Vec<(OriginCaller, u32)> {
 		Log::get().clone()
 	}
+	/// Set the range of blocks, inclusive, in which `timed_log` succeeds.
+	pub fn set_threshold(start: u32, end: u32) {
+		Threshold::set((start, end));
+	}
 
 	#[pallet::pallet]
 	pub struct Pallet<T>(_);
@@ -65,6 +71,14 @@ pub mod logger {
 		Logged(u32, Weight),
 	}
 
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Under the block threshold.
+		TooEarly,
+		/// Over the block threshold.
+		TooLate,
+	}
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T>
 	where
@@ -89,6 +103,20 @@ pub mod logger {
 			});
 			Ok(())
 		}
+
+		#[pallet::call_index(2)]
+		#[pallet::weight(*weight)]
+		pub fn timed_log(origin: OriginFor<T>, i: u32, weight: Weight) -> DispatchResult {
+			let now = frame_system::Pallet::<T>::block_number().saturated_into::<u32>();
+			let (start, end) = Threshold::get();
+			ensure!(now >= start, Error::<T>::TooEarly);
+			ensure!(now <= end, Error::<T>::TooLate);
+			Self::deposit_event(Event::Logged(i, weight));
+			Log::mutate(|log| {
+				log.push((origin.caller().clone(), i));
+			});
+			Ok(())
+		}
 	}
 }
 
@@ -196,6 +224,21 @@ impl WeightInfo for TestWeightInfo {
 	fn cancel_named(_s: u32) -> Weight {
 		Weight::from_parts(50, 0)
 	}
+	fn schedule_retry(_s: u32) -> Weight {
+		Weight::from_parts(100000, 0)
+	}
+	fn set_retry() -> Weight {
+		Weight::from_parts(50, 0)
+	}
+	fn set_retry_named() -> Weight {
+		Weight::from_parts(50, 0)
+	}
+	fn cancel_retry() -> Weight {
+		Weight::from_parts(50, 0)
+	}
+	fn cancel_retry_named() -> Weight {
+		Weight::from_parts(50, 0)
+	}
 }
 parameter_types! {
 	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) *
@@ -542,6 +542,183 @@ fn scheduler_handles_periodic_unavailable_preimage() {
 	});
 }
 
+#[test]
+fn retry_scheduling_works() {
+	new_test_ext().execute_with(|| {
+		// task fails until block 8 is reached
+		logger::set_threshold(8, 100);
+		// task 42 at #4
+		assert_ok!(Scheduler::do_schedule(
+			DispatchTime::At(4),
+			None,
+			127,
+			root(),
+			Preimage::bound(RuntimeCall::Logger(logger::Call::timed_log {
+				i: 42,
+				weight: Weight::from_parts(10, 0)
+			}))
+			.unwrap()
+		));
+		assert!(Agenda::<Test>::get(4)[0].is_some());
+		// retry 3 times every 3 blocks
+		assert_ok!(Scheduler::set_retry(root().into(), (4, 0), 3, 3));
+		assert_eq!(Retries::<Test>::iter().count(), 1);
+		run_to_block(3);
+		assert!(Agenda::<Test>::get(4)[0].is_some());
+		assert!(logger::log().is_empty());
+		// task fails, gets rescheduled for #7 with one retry less
+		run_to_block(4);
+		assert!(Agenda::<Test>::get(4).is_empty());
+		assert!(Agenda::<Test>::get(7)[0].is_some());
+		assert_eq!(Retries::<Test>::get((7, 0)).unwrap().remaining, 2);
+		assert!(Retries::<Test>::get((4, 0)).is_none());
+		assert!(logger::log().is_empty());
+		// task still fails, gets rescheduled for #10
+		run_to_block(7);
+		assert!(Agenda::<Test>::get(7).is_empty());
+		assert!(Agenda::<Test>::get(10)[0].is_some());
+		assert_eq!(Retries::<Test>::get((10, 0)).unwrap().remaining, 1);
+		assert!(logger::log().is_empty());
+		// task succeeds at #10 and its retry configuration is cleaned up
+		run_to_block(10);
+		assert!(Agenda::<Test>::get(10).is_empty());
+		assert_eq!(logger::log(), vec![(root(), 42u32)]);
+		assert_eq!(Retries::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn retry_scheduling_expires() {
+	new_test_ext().execute_with(|| {
+		// task will never succeed
+		logger::set_threshold(100, 200);
+		// task 42 at #4
+		assert_ok!(Scheduler::do_schedule(
+			DispatchTime::At(4),
+			None,
+			127,
+			root(),
+			Preimage::bound(RuntimeCall::Logger(logger::Call::timed_log {
+				i: 42,
+				weight: Weight::from_parts(10, 0)
+			}))
+			.unwrap()
+		));
+		// retry once after 2 blocks
+		assert_ok!(Scheduler::set_retry(root().into(), (4, 0), 1, 2));
+		// task fails, gets rescheduled for #6 with no retries left
+		run_to_block(4);
+		assert!(Agenda::<Test>::get(6)[0].is_some());
+		assert_eq!(Retries::<Test>::get((6, 0)).unwrap().remaining, 0);
+		// the last retry fails and no other one is scheduled
+		run_to_block(6);
+		assert!(Agenda::<Test>::get(6).is_empty());
+		assert_eq!(Retries::<Test>::iter().count(), 0);
+		assert!(logger::log().is_empty());
+		assert_eq!(
+			System::events().last().unwrap().event,
+			crate::Event::RetryFailed { task: (6, 0), id: None }.into(),
+		);
+	});
+}
+
+#[test]
+fn retry_periodic_task_keeps_original_config() {
+	new_test_ext().execute_with(|| {
+		// task fails until block 6 is reached
+		logger::set_threshold(6, 100);
+		// periodic task 42 at #4 every 3 blocks, twice
+		assert_ok!(Scheduler::do_schedule_named(
+			[1u8; 32],
+			DispatchTime::At(4),
+			Some((3, 2)),
+			127,
+			root(),
+			Preimage::bound(RuntimeCall::Logger(logger::Call::timed_log {
+				i: 42,
+				weight: Weight::from_parts(10, 0)
+			}))
+			.unwrap()
+		));
+		// retry 3 times every block
+		assert_ok!(Scheduler::set_retry_named(root().into(), [1u8; 32], 3, 1));
+		// the periodic task fails at #4: a retry clone is placed at #5, the task itself at #7
+		run_to_block(4);
+		assert!(Agenda::<Test>::get(5)[0].is_some());
+		assert_eq!(Agenda::<Test>::get(5)[0].as_ref().unwrap().maybe_id, None);
+		assert_eq!(Retries::<Test>::get((5, 0)).unwrap().remaining, 2);
+		assert_eq!(Lookup::<Test>::get([1u8; 32]), Some((7, 0)));
+		assert_eq!(Retries::<Test>::get((7, 0)).unwrap().remaining, 3);
+		// the clone fails again at #5 and is retried at #6, where it succeeds
+		run_to_block(5);
+		assert_eq!(Retries::<Test>::get((6, 0)).unwrap().remaining, 1);
+		run_to_block(6);
+		assert_eq!(logger::log(), vec![(root(), 42u32)]);
+		assert!(Retries::<Test>::get((6, 0)).is_none());
+		// the periodic task succeeds at #7 and is done
+		run_to_block(7);
+		assert_eq!(logger::log(), vec![(root(), 42u32), (root(), 42u32)]);
+		assert_eq!(Lookup::<Test>::get([1u8; 32]), None);
+		assert_eq!(Retries::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn cancel_clears_retry_config() {
+	new_test_ext().execute_with(|| {
+		let call =
+			RuntimeCall::Logger(LoggerCall::log { i: 42, weight: Weight::from_parts(10, 0) });
+		assert_ok!(Scheduler::do_schedule(
+			DispatchTime::At(4),
+			None,
+			127,
+			root(),
+			Preimage::bound(call.clone()).unwrap()
+		));
+		assert_ok!(Scheduler::do_schedule_named(
+			[1u8; 32],
+			DispatchTime::At(4),
+			None,
+			127,
+			root(),
+			Preimage::bound(call).unwrap()
+		));
+		assert_ok!(Scheduler::set_retry(root().into(), (4, 0), 1, 2));
+		assert_ok!(Scheduler::set_retry_named(root().into(), [1u8; 32], 1, 2));
+		assert_eq!(Retries::<Test>::iter().count(), 2);
+
+		// cancelling the retry configuration leaves the task in place
+		assert_ok!(Scheduler::cancel_retry(root().into(), (4, 0)));
+		assert!(Retries::<Test>::get((4, 0)).is_none());
+		assert!(Agenda::<Test>::get(4)[0].is_some());
+
+		// cancelling the task removes its retry configuration
+		assert_ok!(Scheduler::cancel_named(root().into(), [1u8; 32]));
+		assert_eq!(Retries::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn set_retry_bad_origin_or_task() {
+	new_test_ext().execute_with(|| {
+		let call =
+			RuntimeCall::Logger(LoggerCall::log { i: 42, weight: Weight::from_parts(10, 0) });
+		assert_ok!(Scheduler::do_schedule(
+			DispatchTime::At(4),
+			None,
+			127,
+			system::RawOrigin::Signed(1).into(),
+			Preimage::bound(call).unwrap()
+		));
+		assert_noop!(Scheduler::set_retry(root().into(), (4, 1), 1, 2), Error::<Test>::NotFound);
+		assert_noop!(
+			Scheduler::set_retry_named(root().into(), [1u8; 32], 1, 2),
+			Error::<Test>::NotFound
+		);
+		assert_noop!(Scheduler::set_retry(RuntimeOrigin::signed(2), (4, 0), 1, 2), BadOrigin);
+	});
+}
+
 #[test]
 fn scheduler_respects_priority_ordering() {
 	let max_weight: Weight = <Test as Config>::MaximumWeight::get();
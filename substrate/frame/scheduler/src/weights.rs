@@ -64,6 +64,11 @@ pub trait WeightInfo {
 	fn cancel(s: u32, ) -> Weight;
 	fn schedule_named(s: u32, ) -> Weight;
 	fn cancel_named(s: u32, ) -> Weight;
+	fn schedule_retry(s: u32, ) -> Weight;
+	fn set_retry() -> Weight;
+	fn set_retry_named() -> Weight;
+	fn cancel_retry() -> Weight;
+	fn cancel_retry_named() -> Weight;
 }
 
 /// Weights for pallet_scheduler using the Substrate node and recommended hardware.
@@ -211,6 +216,31 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(2_u64))
 			.saturating_add(T::DbWeight::get().writes(2_u64))
 	}
+	fn schedule_retry(s: u32, ) -> Weight {
+		// Placeholder, not benchmarked yet: `schedule`, which inserts the task into the agenda
+		// the same way, and the write of its retry config.
+		Self::schedule(s).saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn set_retry() -> Weight {
+		// Placeholder, not benchmarked yet: bounded by `schedule` with a full agenda, which
+		// reads the agenda the same way and writes at least as much.
+		Self::schedule(511)
+	}
+	fn set_retry_named() -> Weight {
+		// Placeholder, not benchmarked yet: bounded by `schedule_named` with a full agenda, which
+		// reads the lookup and the agenda the same way and writes at least as much.
+		Self::schedule_named(511)
+	}
+	fn cancel_retry() -> Weight {
+		// Placeholder, not benchmarked yet: bounded by `schedule` with a full agenda, which
+		// reads the agenda the same way and writes at least as much.
+		Self::schedule(511)
+	}
+	fn cancel_retry_named() -> Weight {
+		// Placeholder, not benchmarked yet: bounded by `schedule_named` with a full agenda, which
+		// reads the lookup and the agenda the same way and writes at least as much.
+		Self::schedule_named(511)
+	}
 }
 
 // For backwards compatibility and tests
@@ -357,4 +387,29 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(2_u64))
 			.saturating_add(RocksDbWeight::get().writes(2_u64))
 	}
+	fn schedule_retry(s: u32, ) -> Weight {
+		// Placeholder, not benchmarked yet: `schedule`, which inserts the task into the agenda
+		// the same way, and the write of its retry config.
+		Self::schedule(s).saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn set_retry() -> Weight {
+		// Placeholder, not benchmarked yet: bounded by `schedule` with a full agenda, which
+		// reads the agenda the same way and writes at least as much.
+		Self::schedule(511)
+	}
+	fn set_retry_named() -> Weight {
+		// Placeholder, not benchmarked yet: bounded by `schedule_named` with a full agenda, which
+		// reads the lookup and the agenda the same way and writes at least as much.
+		Self::schedule_named(511)
+	}
+	fn cancel_retry() -> Weight {
+		// Placeholder, not benchmarked yet: bounded by `schedule` with a full agenda, which
+		// reads the agenda the same way and writes at least as much.
+		Self::schedule(511)
+	}
+	fn cancel_retry_named() -> Weight {
+		// Placeholder, not benchmarked yet: bounded by `schedule_named` with a full agenda, which
+		// reads the lookup and the agenda the same way and writes at least as much.
+		Self::schedule_named(511)
+	}
 }
@@ -336,6 +336,58 @@ mod benchmarks {
 		Ok(())
 	}
 
+	#[benchmark]
+	fn spend_stream() -> Result<(), BenchmarkError> {
+		let origin =
+			T::SpendOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let (asset_kind, amount, beneficiary, beneficiary_lookup) =
+			create_spend_arguments::<T, _>(SEED);
+		T::BalanceConverter::ensure_successful(asset_kind.clone());
+		let end = frame_system::Pallet::<T>::block_number().saturating_add(10u32.into());
+
+		#[extrinsic_call]
+		_(
+			origin as T::RuntimeOrigin,
+			Box::new(asset_kind.clone()),
+			amount,
+			Box::new(beneficiary_lookup),
+			None,
+			end,
+		);
+
+		assert!(Spends::<T, I>::get(0).is_some());
+		assert_last_event::<T, I>(Event::SpendStreamApproved { index: 0, end }.into());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn cancel_stream() -> Result<(), BenchmarkError> {
+		let origin = T::SpendOrigin::try_successful_origin().map_err(|_| "No origin")?;
+		let (asset_kind, amount, _, beneficiary_lookup) = create_spend_arguments::<T, _>(SEED);
+		T::BalanceConverter::ensure_successful(asset_kind.clone());
+		let now = frame_system::Pallet::<T>::block_number();
+		Treasury::<T, _>::spend_stream(
+			origin,
+			Box::new(asset_kind.clone()),
+			amount,
+			Box::new(beneficiary_lookup),
+			None,
+			now.saturating_add(10u32.into()),
+		)?;
+		// half of the spend accrued, the other half is cancelled.
+		frame_system::Pallet::<T>::set_block_number(now.saturating_add(5u32.into()));
+		let origin =
+			T::SpendOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, 0u32);
+
+		assert_last_event::<T, I>(
+			Event::SpendStreamCancelled { index: 0, cancelled: 50u32.into() }.into(),
+		);
+		Ok(())
+	}
+
 	impl_benchmark_test_suite!(
 		Treasury,
 		crate::tests::ExtBuilder::default().build(),
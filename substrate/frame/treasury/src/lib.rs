@@ -69,6 +69,11 @@
 //! [`pallet::Config::Paymaster`]. To claim these spends, the `payout` dispatchable should be called
 //! within some temporal bounds, starting from the moment they become valid and within one
 //! [`pallet::Config::PayoutPeriod`].
+//!
+//! The `spend_stream` dispatchable creates a spend of any asset kind whose amount accrues linearly
+//! over a period of time, e.g. for grants and salaries. The beneficiary claims the accrued portion
+//! with the same `payout` and `check_status` dispatchables, and the spend origin may cancel the
+//! portion not yet accrued with `cancel_stream`.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -83,8 +88,10 @@ use codec::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
 
 use sp_runtime::{
-	traits::{AccountIdConversion, CheckedAdd, Saturating, StaticLookup, Zero},
-	Permill, RuntimeDebug,
+	traits::{
+		AccountIdConversion, CheckedAdd, SaturatedConversion, Saturating, StaticLookup, Zero,
+	},
+	Permill, Perquintill, RuntimeDebug,
 };
 use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 
@@ -182,6 +189,21 @@ pub struct SpendStatus<AssetKind, AssetBalance, Beneficiary, BlockNumber, Paymen
 	status: PaymentState<PaymentId>,
 }
 
+/// The progress of an approved treasury spend paid out over a period of time.
+///
+/// The amount of the spend accrues linearly from its `valid_from` block until the `end` block, and
+/// the beneficiary may claim the accrued portion at any time.
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub struct SpendStream<AssetBalance, BlockNumber> {
+	/// The block number at which the whole amount of the spend is accrued.
+	end: BlockNumber,
+	/// The amount paid out so far.
+	paid: AssetBalance,
+	/// The amount of the payout being attempted.
+	pending: AssetBalance,
+}
+
 /// Index of an approved treasury spend.
 pub type SpendIndex = u32;
 
@@ -339,6 +361,17 @@ pub mod pallet {
 		OptionQuery,
 	>;
 
+	/// The progress of the spends approved with `spend_stream`, paid out over a period of time.
+	// Hasher: Twox safe since `SpendIndex` is an internal count based index.
+	#[pallet::storage]
+	pub type SpendStreams<T: Config<I>, I: 'static = ()> = StorageMap<
+		_,
+		Twox64Concat,
+		SpendIndex,
+		SpendStream<AssetBalanceOf<T, I>, BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
 	#[pallet::genesis_config]
 	#[derive(frame_support::DefaultNoBound)]
 	pub struct GenesisConfig<T: Config<I>, I: 'static = ()> {
@@ -401,6 +434,13 @@ pub mod pallet {
 		/// A spend was processed and removed from the storage. It might have been successfully
 		/// paid or it may have expired.
 		SpendProcessed { index: SpendIndex },
+		/// A new asset spend proposal has been approved, to be paid out over a period of time
+		/// ending at block `end`.
+		SpendStreamApproved { index: SpendIndex, end: BlockNumberFor<T> },
+		/// A part of a streaming spend was paid. `paid` is the amount paid out so far.
+		PartialPaymentProcessed { index: SpendIndex, paid: AssetBalanceOf<T, I> },
+		/// The part of a streaming spend not yet accrued was cancelled.
+		SpendStreamCancelled { index: SpendIndex, cancelled: AssetBalanceOf<T, I> },
 	}
 
 	/// Error for the treasury pallet.
@@ -431,6 +471,14 @@ pub mod pallet {
 		NotAttempted,
 		/// The payment has neither failed nor succeeded yet.
 		Inconclusive,
+		/// The end of a streaming spend must be after its start.
+		InvalidStreamPeriod,
+		/// Nothing has accrued to be claimed from the streaming spend yet.
+		NothingToClaim,
+		/// The spend is not paid out over a period of time.
+		NotStream,
+		/// The whole amount of the streaming spend has already accrued.
+		StreamEnded,
 	}
 
 	#[pallet::hooks]
@@ -739,52 +787,8 @@ pub mod pallet {
 			let expire_at = valid_from.saturating_add(T::PayoutPeriod::get());
 			ensure!(expire_at > now, Error::<T, I>::SpendExpired);
 
-			let native_amount =
-				T::BalanceConverter::from_asset_balance(amount, *asset_kind.clone())
-					.map_err(|_| Error::<T, I>::FailedToConvertBalance)?;
-
-			ensure!(native_amount <= max_amount, Error::<T, I>::InsufficientPermission);
-
-			with_context::<SpendContext<BalanceOf<T, I>>, _>(|v| {
-				let context = v.or_default();
-				// We group based on `max_amount`, to distinguish between different kind of
-				// origins. (assumes that all origins have different `max_amount`)
-				//
-				// Worst case is that we reject some "valid" request.
-				let spend = context.spend_in_context.entry(max_amount).or_default();
-
-				// Ensure that we don't overflow nor use more than `max_amount`
-				if spend.checked_add(&native_amount).map(|s| s > max_amount).unwrap_or(true) {
-					Err(Error::<T, I>::InsufficientPermission)
-				} else {
-					*spend = spend.saturating_add(native_amount);
-					Ok(())
-				}
-			})
-			.unwrap_or(Ok(()))?;
-
-			let index = SpendCount::<T, I>::get();
-			Spends::<T, I>::insert(
-				index,
-				SpendStatus {
-					asset_kind: *asset_kind.clone(),
-					amount,
-					beneficiary: beneficiary.clone(),
-					valid_from,
-					expire_at,
-					status: PaymentState::Pending,
-				},
-			);
-			SpendCount::<T, I>::put(index + 1);
-
-			Self::deposit_event(Event::AssetSpendApproved {
-				index,
-				asset_kind: *asset_kind,
-				amount,
-				beneficiary,
-				valid_from,
-				expire_at,
-			});
+			Self::ensure_spend_permitted(max_amount, amount, &asset_kind)?;
+			Self::approve_spend(*asset_kind, amount, beneficiary, valid_from, expire_at);
 			Ok(())
 		}
 
@@ -800,6 +804,8 @@ pub mod pallet {
 		/// [`Config::PayoutPeriod`] from the `valid_from` block.
 		/// In case of a payout failure, the spend status must be updated with the `check_status`
 		/// dispatchable before retrying with the current function.
+		/// For a spend approved with `spend_stream`, only the portion accrued and not yet paid is
+		/// paid out.
 		///
 		/// ### Parameters
 		/// - `index`: The spend index.
@@ -808,7 +814,9 @@ pub mod pallet {
 		///
 		/// Emits [`Event::Paid`] if successful.
 		#[pallet::call_index(6)]
-		#[pallet::weight(T::WeightInfo::payout())]
+		#[pallet::weight(
+			T::WeightInfo::payout().saturating_add(T::DbWeight::get().reads_writes(1, 1))
+		)]
 		pub fn payout(origin: OriginFor<T>, index: SpendIndex) -> DispatchResult {
 			ensure_signed(origin)?;
 			let mut spend = Spends::<T, I>::get(index).ok_or(Error::<T, I>::InvalidIndex)?;
@@ -820,7 +828,20 @@ pub mod pallet {
 				Error::<T, I>::AlreadyAttempted
 			);
 
-			let id = T::Paymaster::pay(&spend.beneficiary, spend.asset_kind.clone(), spend.amount)
+			let amount = match SpendStreams::<T, I>::get(index) {
+				Some(mut stream) => {
+					let accrued =
+						Self::accrued_amount(spend.amount, spend.valid_from, stream.end, now);
+					let claimable = accrued.saturating_sub(stream.paid);
+					ensure!(!claimable.is_zero(), Error::<T, I>::NothingToClaim);
+					stream.pending = claimable;
+					SpendStreams::<T, I>::insert(index, stream);
+					claimable
+				},
+				None => spend.amount,
+			};
+
+			let id = T::Paymaster::pay(&spend.beneficiary, spend.asset_kind.clone(), amount)
 				.map_err(|_| Error::<T, I>::PayoutError)?;
 
 			spend.status = PaymentState::Attempted { id };
@@ -841,7 +862,8 @@ pub mod pallet {
 		///
 		/// The status check is a prerequisite for retrying a failed payout.
 		/// If a spend has either succeeded or expired, it is removed from the storage by this
-		/// function. In such instances, transaction fees are refunded.
+		/// function. In such instances, transaction fees are refunded. A streaming spend which is
+		/// not yet fully paid is kept instead, to be claimed further as it accrues.
 		///
 		/// ### Parameters
 		/// - `index`: The spend index.
@@ -850,8 +872,11 @@ pub mod pallet {
 		///
 		/// Emits [`Event::PaymentFailed`] if the spend payout has failed.
 		/// Emits [`Event::SpendProcessed`] if the spend payout has succeed.
+		/// Emits [`Event::PartialPaymentProcessed`] if a part of a streaming spend has been paid.
 		#[pallet::call_index(7)]
-		#[pallet::weight(T::WeightInfo::check_status())]
+		#[pallet::weight(
+			T::WeightInfo::check_status().saturating_add(T::DbWeight::get().reads_writes(1, 1))
+		)]
 		pub fn check_status(origin: OriginFor<T>, index: SpendIndex) -> DispatchResultWithPostInfo {
 			use PaymentState as State;
 			use PaymentStatus as Status;
@@ -863,6 +888,7 @@ pub mod pallet {
 			if now > spend.expire_at && !matches!(spend.status, State::Attempted { .. }) {
 				// spend has expired and no further status update is expected.
 				Spends::<T, I>::remove(index);
+				SpendStreams::<T, I>::remove(index);
 				Self::deposit_event(Event::<T, I>::SpendProcessed { index });
 				return Ok(Pays::No.into())
 			}
@@ -879,6 +905,23 @@ pub mod pallet {
 					Self::deposit_event(Event::<T, I>::PaymentFailed { index, payment_id });
 				},
 				Status::Success | Status::Unknown => {
+					if let Some(mut stream) = SpendStreams::<T, I>::get(index) {
+						stream.paid = stream.paid.saturating_add(stream.pending);
+						stream.pending = Zero::zero();
+						if stream.paid < spend.amount {
+							// the rest of the spend can be claimed as it accrues.
+							let paid = stream.paid;
+							spend.status = PaymentState::Pending;
+							Spends::<T, I>::insert(index, spend);
+							SpendStreams::<T, I>::insert(index, stream);
+							Self::deposit_event(Event::<T, I>::PartialPaymentProcessed {
+								index,
+								paid,
+							});
+							return Ok(Pays::No.into())
+						}
+						SpendStreams::<T, I>::remove(index);
+					}
 					Spends::<T, I>::remove(index);
 					Self::deposit_event(Event::<T, I>::SpendProcessed { index });
 					return Ok(Pays::No.into())
@@ -905,7 +948,9 @@ pub mod pallet {
 		///
 		/// Emits [`Event::AssetSpendVoided`] if successful.
 		#[pallet::call_index(8)]
-		#[pallet::weight(T::WeightInfo::void_spend())]
+		#[pallet::weight(
+			T::WeightInfo::void_spend().saturating_add(T::DbWeight::get().reads_writes(1, 1))
+		)]
 		pub fn void_spend(origin: OriginFor<T>, index: SpendIndex) -> DispatchResult {
 			T::RejectOrigin::ensure_origin(origin)?;
 			let spend = Spends::<T, I>::get(index).ok_or(Error::<T, I>::InvalidIndex)?;
@@ -915,9 +960,204 @@ pub mod pallet {
 			);
 
 			Spends::<T, I>::remove(index);
+			SpendStreams::<T, I>::remove(index);
 			Self::deposit_event(Event::<T, I>::AssetSpendVoided { index });
 			Ok(())
 		}
+
+		/// Propose and approve a spend of treasury funds, paid out over a period of time.
+		///
+		/// ## Dispatch Origin
+		///
+		/// Must be [`Config::SpendOrigin`] with the `Success` value being at least
+		/// `amount` of `asset_kind` in the native asset. The amount of `asset_kind` is converted
+		/// for assertion using the [`Config::BalanceConverter`].
+		///
+		/// ## Details
+		///
+		/// Create an approved spend for transferring a specific `amount` of `asset_kind` to a
+		/// designated beneficiary, which accrues linearly from the `start` block until the `end`
+		/// block. The accrued portion may be claimed at any time using the `payout` dispatchable,
+		/// and each payment has to be concluded with the `check_status` dispatchable before the
+		/// next one. The spend must be fully claimed within the [`Config::PayoutPeriod`] following
+		/// the `end` block.
+		///
+		/// ### Parameters
+		/// - `asset_kind`: An indicator of the specific asset class to be spent.
+		/// - `amount`: The total amount to be transferred from the treasury to the `beneficiary`.
+		/// - `beneficiary`: The beneficiary of the spend.
+		/// - `start`: The block number from which the spend accrues. If `None`, the spend accrues
+		///   from the current block.
+		/// - `end`: The block number at which the whole `amount` is accrued.
+		///
+		/// ## Events
+		///
+		/// Emits [`Event::AssetSpendApproved`] and [`Event::SpendStreamApproved`] if successful.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::spend_stream())]
+		pub fn spend_stream(
+			origin: OriginFor<T>,
+			asset_kind: Box<T::AssetKind>,
+			#[pallet::compact] amount: AssetBalanceOf<T, I>,
+			beneficiary: Box<BeneficiaryLookupOf<T, I>>,
+			start: Option<BlockNumberFor<T>>,
+			end: BlockNumberFor<T>,
+		) -> DispatchResult {
+			let max_amount = T::SpendOrigin::ensure_origin(origin)?;
+			let beneficiary = T::BeneficiaryLookup::lookup(*beneficiary)?;
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let valid_from = start.unwrap_or(now);
+			ensure!(end > valid_from, Error::<T, I>::InvalidStreamPeriod);
+			let expire_at = end.saturating_add(T::PayoutPeriod::get());
+			ensure!(expire_at > now, Error::<T, I>::SpendExpired);
+
+			Self::ensure_spend_permitted(max_amount, amount, &asset_kind)?;
+			let index =
+				Self::approve_spend(*asset_kind, amount, beneficiary, valid_from, expire_at);
+			SpendStreams::<T, I>::insert(
+				index,
+				SpendStream { end, paid: Zero::zero(), pending: Zero::zero() },
+			);
+
+			Self::deposit_event(Event::SpendStreamApproved { index, end });
+			Ok(())
+		}
+
+		/// Cancel the part of a streaming spend which has not accrued yet.
+		///
+		/// ## Dispatch Origin
+		///
+		/// Must be [`Config::SpendOrigin`] with the `Success` value being at least the amount of
+		/// the spend converted to the native asset.
+		///
+		/// ## Details
+		///
+		/// The amount of the spend is reduced to the amount accrued so far, which remains
+		/// claimable by the beneficiary. The spend is removed if all of it has already been paid.
+		///
+		/// ### Parameters
+		/// - `index`: The spend index.
+		///
+		/// ## Events
+		///
+		/// Emits [`Event::SpendStreamCancelled`] if successful.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::cancel_stream())]
+		pub fn cancel_stream(origin: OriginFor<T>, index: SpendIndex) -> DispatchResult {
+			let max_amount = T::SpendOrigin::ensure_origin(origin)?;
+			let mut spend = Spends::<T, I>::get(index).ok_or(Error::<T, I>::InvalidIndex)?;
+			let mut stream = SpendStreams::<T, I>::get(index).ok_or(Error::<T, I>::NotStream)?;
+			let native_amount =
+				T::BalanceConverter::from_asset_balance(spend.amount, spend.asset_kind.clone())
+					.map_err(|_| Error::<T, I>::FailedToConvertBalance)?;
+			ensure!(native_amount <= max_amount, Error::<T, I>::InsufficientPermission);
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(now < stream.end, Error::<T, I>::StreamEnded);
+
+			let accrued = Self::accrued_amount(spend.amount, spend.valid_from, stream.end, now);
+			let cancelled = spend.amount.saturating_sub(accrued);
+			spend.amount = accrued;
+			stream.end = now;
+
+			Self::deposit_event(Event::<T, I>::SpendStreamCancelled { index, cancelled });
+			if stream.paid >= spend.amount &&
+				!matches!(spend.status, PaymentState::Attempted { .. })
+			{
+				// nothing is left to be paid out.
+				Spends::<T, I>::remove(index);
+				SpendStreams::<T, I>::remove(index);
+				Self::deposit_event(Event::<T, I>::SpendProcessed { index });
+			} else {
+				Spends::<T, I>::insert(index, spend);
+				SpendStreams::<T, I>::insert(index, stream);
+			}
+			Ok(())
+		}
+	}
+
+	impl<T: Config<I>, I: 'static> Pallet<T, I> {
+		/// Ensure that a `max_amount` spend origin may spend `amount` of `asset_kind`, accounting
+		/// for the other spends of the same dispatch context.
+		fn ensure_spend_permitted(
+			max_amount: BalanceOf<T, I>,
+			amount: AssetBalanceOf<T, I>,
+			asset_kind: &T::AssetKind,
+		) -> DispatchResult {
+			let native_amount = T::BalanceConverter::from_asset_balance(amount, asset_kind.clone())
+				.map_err(|_| Error::<T, I>::FailedToConvertBalance)?;
+
+			ensure!(native_amount <= max_amount, Error::<T, I>::InsufficientPermission);
+
+			with_context::<SpendContext<BalanceOf<T, I>>, _>(|v| {
+				let context = v.or_default();
+				// We group based on `max_amount`, to distinguish between different kind of
+				// origins. (assumes that all origins have different `max_amount`)
+				//
+				// Worst case is that we reject some "valid" request.
+				let spend = context.spend_in_context.entry(max_amount).or_default();
+
+				// Ensure that we don't overflow nor use more than `max_amount`
+				if spend.checked_add(&native_amount).map(|s| s > max_amount).unwrap_or(true) {
+					Err(Error::<T, I>::InsufficientPermission)
+				} else {
+					*spend = spend.saturating_add(native_amount);
+					Ok(())
+				}
+			})
+			.unwrap_or(Ok(()))?;
+
+			Ok(())
+		}
+
+		/// Store an approved spend and return its index.
+		fn approve_spend(
+			asset_kind: T::AssetKind,
+			amount: AssetBalanceOf<T, I>,
+			beneficiary: T::Beneficiary,
+			valid_from: BlockNumberFor<T>,
+			expire_at: BlockNumberFor<T>,
+		) -> SpendIndex {
+			let index = SpendCount::<T, I>::get();
+			Spends::<T, I>::insert(
+				index,
+				SpendStatus {
+					asset_kind: asset_kind.clone(),
+					amount,
+					beneficiary: beneficiary.clone(),
+					valid_from,
+					expire_at,
+					status: PaymentState::Pending,
+				},
+			);
+			SpendCount::<T, I>::put(index + 1);
+
+			Self::deposit_event(Event::AssetSpendApproved {
+				index,
+				asset_kind,
+				amount,
+				beneficiary,
+				valid_from,
+				expire_at,
+			});
+			index
+		}
+
+		/// The part of `amount` accrued by `now`, accruing linearly from the `start` block until
+		/// the `end` block.
+		pub(crate) fn accrued_amount(
+			amount: AssetBalanceOf<T, I>,
+			start: BlockNumberFor<T>,
+			end: BlockNumberFor<T>,
+			now: BlockNumberFor<T>,
+		) -> AssetBalanceOf<T, I> {
+			if now >= end {
+				return amount
+			}
+			let elapsed = now.saturating_sub(start).saturated_into::<u64>();
+			let duration = end.saturating_sub(start).saturated_into::<u64>();
+			Perquintill::from_rational(elapsed, duration).mul_floor(amount)
+		}
 	}
 }
 
@@ -1082,6 +1322,8 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 	/// [`SpendCount`].
 	/// 3. For each spend entry contained in [`Spends`] we should have spend.expire_at
 	/// > spend.valid_from.
+	/// 4. Each entry in [`SpendStreams`] should have a spend in [`Spends`] under the same key,
+	/// paid out no more than its amount.
 	#[cfg(any(feature = "try-runtime", test))]
 	fn try_state_spends() -> Result<(), sp_runtime::TryRuntimeError> {
 		let current_spend_count = SpendCount::<T, I>::get();
@@ -1106,6 +1348,12 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 			Ok(())
 		})?;
 
+		SpendStreams::<T, I>::iter().try_for_each(|(index, stream)| -> DispatchResult {
+			let spend = Spends::<T, I>::get(index).ok_or("Spend stream without a spend.")?;
+			ensure!(stream.paid <= spend.amount, "Spend stream paid more than its amount.");
+			Ok(())
+		})?;
+
 		Ok(())
 	}
 }
@@ -998,6 +998,195 @@ fn check_status_works() {
 	});
 }
 
+#[test]
+fn spend_stream_payout_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(<Test as Config>::PayoutPeriod::get(), 5);
+		System::set_block_number(1);
+
+		assert_noop!(
+			Treasury::spend_stream(
+				RuntimeOrigin::signed(13),
+				Box::new(1),
+				20,
+				Box::new(6),
+				Some(2),
+				2
+			),
+			Error::<Test, _>::InvalidStreamPeriod
+		);
+		assert_noop!(
+			Treasury::spend_stream(
+				RuntimeOrigin::signed(13),
+				Box::new(1),
+				26,
+				Box::new(6),
+				Some(2),
+				12
+			),
+			Error::<Test, _>::InsufficientPermission
+		);
+
+		// a `20` coins spend of asset `1` to beneficiary `6`, accruing from block `2` to `12`.
+		assert_ok!(Treasury::spend_stream(
+			RuntimeOrigin::signed(13),
+			Box::new(1),
+			20,
+			Box::new(6),
+			Some(2),
+			12
+		));
+		System::assert_last_event(
+			Event::<Test, _>::SpendStreamApproved { index: 0, end: 12 }.into(),
+		);
+		assert_eq!(Spends::<Test, _>::get(0).unwrap().expire_at, 17);
+		assert_noop!(Treasury::payout(RuntimeOrigin::signed(1), 0), Error::<Test, _>::EarlyPayout);
+		System::set_block_number(2);
+		assert_noop!(
+			Treasury::payout(RuntimeOrigin::signed(1), 0),
+			Error::<Test, _>::NothingToClaim
+		);
+
+		// a fifth of the spend accrued.
+		System::set_block_number(4);
+		assert_ok!(Treasury::payout(RuntimeOrigin::signed(1), 0));
+		assert_eq!(paid(6, 1), 4);
+		assert_noop!(
+			Treasury::payout(RuntimeOrigin::signed(1), 0),
+			Error::<Test, _>::AlreadyAttempted
+		);
+		let payment_id = get_payment_id(0).expect("no payment attempt");
+		set_status(payment_id, PaymentStatus::Success);
+		let info = Treasury::check_status(RuntimeOrigin::signed(1), 0).unwrap();
+		assert_eq!(info.pays_fee, Pays::No);
+		System::assert_last_event(
+			Event::<Test, _>::PartialPaymentProcessed { index: 0, paid: 4 }.into(),
+		);
+
+		// half of the spend accrued.
+		System::set_block_number(7);
+		assert_ok!(Treasury::payout(RuntimeOrigin::signed(1), 0));
+		assert_eq!(paid(6, 1), 10);
+		let payment_id = get_payment_id(0).expect("no payment attempt");
+		set_status(payment_id, PaymentStatus::Success);
+		assert_ok!(Treasury::check_status(RuntimeOrigin::signed(1), 0));
+		System::assert_last_event(
+			Event::<Test, _>::PartialPaymentProcessed { index: 0, paid: 10 }.into(),
+		);
+
+		// the whole spend accrued.
+		System::set_block_number(15);
+		assert_ok!(Treasury::payout(RuntimeOrigin::signed(1), 0));
+		assert_eq!(paid(6, 1), 20);
+		let payment_id = get_payment_id(0).expect("no payment attempt");
+		set_status(payment_id, PaymentStatus::Success);
+		assert_ok!(Treasury::check_status(RuntimeOrigin::signed(1), 0));
+		System::assert_last_event(Event::<Test, _>::SpendProcessed { index: 0 }.into());
+		assert_eq!(Spends::<Test, _>::get(0), None);
+		assert_eq!(SpendStreams::<Test, _>::get(0), None);
+		assert_ok!(Treasury::do_try_state());
+	});
+}
+
+#[test]
+fn cancel_stream_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		// a `20` coins spend of asset `1`, accruing from block `1` to `11`.
+		assert_ok!(Treasury::spend_stream(
+			RuntimeOrigin::signed(13),
+			Box::new(1),
+			20,
+			Box::new(6),
+			None,
+			11
+		));
+
+		// the payment of the first half fails.
+		System::set_block_number(6);
+		assert_ok!(Treasury::payout(RuntimeOrigin::signed(1), 0));
+		let payment_id = get_payment_id(0).expect("no payment attempt");
+		set_status(payment_id, PaymentStatus::Failure);
+		unpay(6, 1, 10);
+		assert_ok!(Treasury::check_status(RuntimeOrigin::signed(1), 0));
+
+		assert_noop!(Treasury::cancel_stream(RuntimeOrigin::signed(1), 0), BadOrigin);
+		// the spend is worth `40` native coins, more than these origins may spend.
+		assert_noop!(
+			Treasury::cancel_stream(RuntimeOrigin::signed(11), 0),
+			Error::<Test, _>::InsufficientPermission
+		);
+		assert_noop!(
+			Treasury::cancel_stream(RuntimeOrigin::signed(12), 0),
+			Error::<Test, _>::InsufficientPermission
+		);
+		System::set_block_number(8);
+		assert_ok!(Treasury::cancel_stream(RuntimeOrigin::signed(13), 0));
+		System::assert_last_event(
+			Event::<Test, _>::SpendStreamCancelled { index: 0, cancelled: 6 }.into(),
+		);
+		assert_eq!(Spends::<Test, _>::get(0).unwrap().amount, 14);
+		assert_noop!(
+			Treasury::cancel_stream(RuntimeOrigin::signed(13), 0),
+			Error::<Test, _>::StreamEnded
+		);
+
+		// the accrued part is still claimable.
+		System::set_block_number(9);
+		assert_ok!(Treasury::payout(RuntimeOrigin::signed(1), 0));
+		assert_eq!(paid(6, 1), 14);
+		let payment_id = get_payment_id(0).expect("no payment attempt");
+		set_status(payment_id, PaymentStatus::Success);
+		assert_ok!(Treasury::check_status(RuntimeOrigin::signed(1), 0));
+		System::assert_last_event(Event::<Test, _>::SpendProcessed { index: 0 }.into());
+
+		// a spend which is not streamed cannot be cancelled.
+		assert_ok!(Treasury::spend(RuntimeOrigin::signed(10), Box::new(1), 2, Box::new(6), None));
+		assert_noop!(
+			Treasury::cancel_stream(RuntimeOrigin::signed(13), 1),
+			Error::<Test, _>::NotStream
+		);
+
+		// a spend cancelled before it starts accruing is removed.
+		assert_ok!(Treasury::spend_stream(
+			RuntimeOrigin::signed(13),
+			Box::new(1),
+			20,
+			Box::new(6),
+			Some(12),
+			22
+		));
+		assert_ok!(Treasury::cancel_stream(RuntimeOrigin::signed(13), 2));
+		System::assert_last_event(Event::<Test, _>::SpendProcessed { index: 2 }.into());
+		assert_eq!(Spends::<Test, _>::get(2), None);
+		assert_eq!(SpendStreams::<Test, _>::get(2), None);
+		assert_ok!(Treasury::do_try_state());
+	});
+}
+
+#[test]
+fn spend_stream_expires() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Treasury::spend_stream(
+			RuntimeOrigin::signed(13),
+			Box::new(1),
+			20,
+			Box::new(6),
+			None,
+			11
+		));
+		// the spend has to be claimed within the payout period following its end.
+		System::set_block_number(16);
+		assert_noop!(Treasury::payout(RuntimeOrigin::signed(1), 0), Error::<Test, _>::SpendExpired);
+		System::set_block_number(17);
+		assert_ok!(Treasury::check_status(RuntimeOrigin::signed(1), 0));
+		System::assert_last_event(Event::<Test, _>::SpendProcessed { index: 0 }.into());
+		assert_eq!(Spends::<Test, _>::get(0), None);
+		assert_eq!(SpendStreams::<Test, _>::get(0), None);
+	});
+}
+
 #[test]
 fn try_state_proposals_invariant_1_works() {
 	ExtBuilder::default().build().execute_with(|| {
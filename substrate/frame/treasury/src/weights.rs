@@ -57,6 +57,8 @@ pub trait WeightInfo {
 	fn payout() -> Weight;
 	fn check_status() -> Weight;
 	fn void_spend() -> Weight;
+	fn spend_stream() -> Weight;
+	fn cancel_stream() -> Weight;
 }
 
 /// Weights for pallet_treasury using the Substrate node and recommended hardware.
@@ -209,6 +211,16 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
+	fn spend_stream() -> Weight {
+		// Placeholder, not benchmarked yet: `spend`, which converts the amount and stores the
+		// spend the same way, and the write of the stream.
+		Self::spend().saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn cancel_stream() -> Weight {
+		// Placeholder, not benchmarked yet: `spend`, which converts the amount the same way, and
+		// the accesses of the spend and the stream.
+		Self::spend().saturating_add(T::DbWeight::get().reads_writes(2_u64, 2_u64))
+	}
 }
 
 // For backwards compatibility and tests
@@ -360,4 +372,14 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
+	fn spend_stream() -> Weight {
+		// Placeholder, not benchmarked yet: `spend`, which converts the amount and stores the
+		// spend the same way, and the write of the stream.
+		Self::spend().saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn cancel_stream() -> Weight {
+		// Placeholder, not benchmarked yet: `spend`, which converts the amount the same way, and
+		// the accesses of the spend and the stream.
+		Self::spend().saturating_add(RocksDbWeight::get().reads_writes(2_u64, 2_u64))
+	}
 }
@@ -628,6 +628,25 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl sp_consensus_aura::AuraEquivocationApi<Block, AuraId> for Runtime {
+		fn generate_key_ownership_proof(
+			_slot: sp_consensus_aura::Slot,
+			_authority_id: AuraId,
+		) -> Option<sp_consensus_aura::OpaqueKeyOwnershipProof> {
+			None
+		}
+
+		fn submit_report_equivocation_unsigned_extrinsic(
+			_equivocation_proof: sp_consensus_aura::EquivocationProof<
+				<Block as BlockT>::Header,
+				AuraId,
+			>,
+			_key_owner_proof: sp_consensus_aura::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			None
+		}
+	}
+
 	impl sp_consensus_babe::BabeApi<Block> for Runtime {
 		fn configuration() -> sp_consensus_babe::BabeConfiguration {
 			let epoch_config = Babe::epoch_config().unwrap_or(TEST_RUNTIME_BABE_EPOCH_CONFIGURATION);